@@ -0,0 +1,227 @@
+//! Node.js bindings of the library.
+
+use std::convert::TryFrom;
+
+use napi::{Error, Result};
+use napi_derive::napi;
+use sophya_prog_test::{BoundingRect, Point2D};
+
+/// Rectangle given by its corners.
+#[napi(object)]
+pub struct Rect {
+    pub x1: f64,
+    pub y1: f64,
+    pub x2: f64,
+    pub y2: f64,
+}
+
+/// Point on the plane.
+#[napi(object)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Displacement on the plane.
+#[napi(object)]
+pub struct Vector {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Intersection of two rectangles from the list.
+#[napi(object)]
+pub struct Intersection {
+    /// Intersection area.
+    pub area: Rect,
+    /// First rectangle index.
+    pub a_idx: u32,
+    /// Second rectangle index.
+    pub b_idx: u32,
+    /// Minimum translation vector of the first rectangle that resolves the overlap.
+    pub mtv: Vector,
+}
+
+/// Rectangle of the index closest to the queried point.
+#[napi(object)]
+pub struct Nearest {
+    /// Rectangle index.
+    pub idx: u32,
+    /// Distance from the point to the rectangle, zero if the rectangle contains it.
+    pub distance: f64,
+}
+
+impl From<&Rect> for BoundingRect {
+    fn from(rect: &Rect) -> Self {
+        BoundingRect::from_points(
+            Point2D::new(rect.x1 as f32, rect.y1 as f32),
+            Point2D::new(rect.x2 as f32, rect.y2 as f32),
+        )
+    }
+}
+
+impl From<BoundingRect> for Rect {
+    fn from(rect: BoundingRect) -> Self {
+        let (from, to) = (rect.top_left(), rect.bottom_right());
+        Self {
+            x1: from.x.into(),
+            y1: from.y.into(),
+            x2: to.x.into(),
+            y2: to.y.into(),
+        }
+    }
+}
+
+impl From<Point> for Point2D {
+    fn from(point: Point) -> Self {
+        Point2D::new(point.x as f32, point.y as f32)
+    }
+}
+
+fn to_bounding_rects(rects: &[Rect]) -> Vec<BoundingRect> {
+    rects.iter().map(BoundingRect::from).collect()
+}
+
+// Converts the index into the JavaScript one, the lists longer than `u32::MAX` are rejected.
+fn to_index(idx: usize) -> Result<u32> {
+    u32::try_from(idx)
+        .map_err(|_| Error::from_reason(format!("The index {} does not fit into u32", idx)))
+}
+
+fn to_intersections(rects: &[BoundingRect]) -> Result<Vec<Intersection>> {
+    sophya_prog_test::list_intersections(rects)
+        .into_iter()
+        .map(|x| {
+            Ok(Intersection {
+                area: x.area.into(),
+                a_idx: to_index(x.a_idx)?,
+                b_idx: to_index(x.b_idx)?,
+                mtv: Vector {
+                    x: x.mtv.x.into(),
+                    y: x.mtv.y.into(),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Searches for intersecting rectangles in the specified list.
+#[napi]
+pub fn list_intersections(rects: Vec<Rect>) -> Result<Vec<Intersection>> {
+    to_intersections(&to_bounding_rects(&rects))
+}
+
+/// Calculates the area shared by every rectangle in the specified list.
+#[napi]
+pub fn intersect_all(rects: Vec<Rect>) -> Option<Rect> {
+    sophya_prog_test::intersect_all(&to_bounding_rects(&rects)).map(Rect::from)
+}
+
+/// Rectangles of the scene answering the spatial queries, e.g. the hit tests of an editor.
+/// The rectangles are identified by their indices in the list.
+#[napi]
+pub struct SpatialIndex {
+    rects: Vec<BoundingRect>,
+}
+
+#[napi]
+impl SpatialIndex {
+    /// Creates the index of the specified rectangles.
+    #[napi(constructor)]
+    pub fn new(rects: Vec<Rect>) -> Self {
+        Self {
+            rects: to_bounding_rects(&rects),
+        }
+    }
+
+    /// Number of the rectangles.
+    #[napi(getter)]
+    pub fn length(&self) -> Result<u32> {
+        to_index(self.rects.len())
+    }
+
+    /// Appends the rectangle and returns its index.
+    #[napi]
+    pub fn push(&mut self, rect: Rect) -> Result<u32> {
+        let idx = to_index(self.rects.len())?;
+        self.rects.push(BoundingRect::from(&rect));
+        Ok(idx)
+    }
+
+    /// Replaces the rectangle with the given index, e.g. after it has been moved.
+    #[napi]
+    pub fn update(&mut self, idx: u32, rect: Rect) -> Result<()> {
+        let slot = self
+            .rects
+            .get_mut(idx as usize)
+            .ok_or_else(|| Error::from_reason(format!("There is no rectangle {}", idx)))?;
+        *slot = BoundingRect::from(&rect);
+        Ok(())
+    }
+
+    /// Returns the rectangles that contain the point, including the ones touching it by the
+    /// border.
+    #[napi]
+    pub fn objects_at(&self, point: Point) -> Result<Vec<u32>> {
+        let point = Point2D::from(point);
+        self.find(|rect| rect.contains(point))
+    }
+
+    /// Returns the rectangles intersecting the given one.
+    #[napi]
+    pub fn objects_in(&self, rect: Rect) -> Result<Vec<u32>> {
+        let rect = BoundingRect::from(&rect);
+        self.find(|other| other.intersect(&rect).is_some())
+    }
+
+    /// Returns the rectangle closest to the point, the first one wins the ties.
+    #[napi]
+    pub fn nearest(&self, point: Point) -> Result<Option<Nearest>> {
+        let point = Point2D::from(point);
+        let nearest = self
+            .rects
+            .iter()
+            .map(|rect| rect.distance_to(point))
+            .enumerate()
+            .fold(
+                None,
+                |nearest: Option<(usize, f32)>, (idx, distance)| match nearest {
+                    Some(nearest) if nearest.1 <= distance => Some(nearest),
+                    _ => Some((idx, distance)),
+                },
+            );
+        nearest
+            .map(|(idx, distance)| {
+                Ok(Nearest {
+                    idx: to_index(idx)?,
+                    distance: distance.into(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Searches for the intersecting rectangles.
+    #[napi]
+    pub fn intersections(&self) -> Result<Vec<Intersection>> {
+        to_intersections(&self.rects)
+    }
+
+    /// Returns the intersections involving the rectangle with the given index.
+    #[napi]
+    pub fn intersections_of(&self, idx: u32) -> Result<Vec<Intersection>> {
+        Ok(self
+            .intersections()?
+            .into_iter()
+            .filter(|x| x.a_idx == idx || x.b_idx == idx)
+            .collect())
+    }
+
+    fn find(&self, predicate: impl Fn(&BoundingRect) -> bool) -> Result<Vec<u32>> {
+        self.rects
+            .iter()
+            .enumerate()
+            .filter(|(_, rect)| predicate(rect))
+            .map(|(idx, _)| to_index(idx))
+            .collect()
+    }
+}
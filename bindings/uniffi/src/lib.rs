@@ -0,0 +1,150 @@
+//! UniFFI bindings of the library for Kotlin and Swift.
+
+use sophya_prog_test::{BoundingRect, Point2D};
+
+uniffi::include_scaffolding!("sophya");
+
+/// Two-dimensional point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Two-dimensional displacement vector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vector {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Rectangle given by its corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+/// Intersection of two rectangles from the list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Intersection {
+    pub area: Rect,
+    pub a_idx: u64,
+    pub b_idx: u64,
+    pub mtv: Vector,
+}
+
+/// Crossing of two polygon edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeCrossing {
+    pub a_idx: u64,
+    pub b_idx: u64,
+    pub point: Point,
+}
+
+impl From<Point> for Point2D {
+    fn from(point: Point) -> Self {
+        Point2D::new(point.x, point.y)
+    }
+}
+
+impl From<Point2D> for Point {
+    fn from(point: Point2D) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl From<Rect> for BoundingRect {
+    fn from(rect: Rect) -> Self {
+        BoundingRect::from_points(
+            Point2D::new(rect.x1, rect.y1),
+            Point2D::new(rect.x2, rect.y2),
+        )
+    }
+}
+
+impl From<BoundingRect> for Rect {
+    fn from(rect: BoundingRect) -> Self {
+        let (from, to) = (rect.top_left(), rect.bottom_right());
+        Self {
+            x1: from.x,
+            y1: from.y,
+            x2: to.x,
+            y2: to.y,
+        }
+    }
+}
+
+fn list_intersections(rects: Vec<Rect>) -> Vec<Intersection> {
+    let rects = rects
+        .into_iter()
+        .map(BoundingRect::from)
+        .collect::<Vec<_>>();
+    sophya_prog_test::list_intersections(&rects)
+        .into_iter()
+        .map(|x| Intersection {
+            area: x.area.into(),
+            a_idx: x.a_idx as u64,
+            b_idx: x.b_idx as u64,
+            mtv: Vector {
+                x: x.mtv.x,
+                y: x.mtv.y,
+            },
+        })
+        .collect()
+}
+
+fn intersect_all(rects: Vec<Rect>) -> Option<Rect> {
+    let rects = rects
+        .into_iter()
+        .map(BoundingRect::from)
+        .collect::<Vec<_>>();
+    sophya_prog_test::intersect_all(&rects).map(Rect::from)
+}
+
+fn polygon_self_intersections(vertices: Vec<Point>) -> Vec<EdgeCrossing> {
+    let polygon = sophya_prog_test::Polygon::new(vertices.into_iter().map(Point2D::from).collect());
+    polygon
+        .self_intersections()
+        .into_iter()
+        .map(|x| EdgeCrossing {
+            a_idx: x.a_idx as u64,
+            b_idx: x.b_idx as u64,
+            point: x.point.into(),
+        })
+        .collect()
+}
+
+#[test]
+fn test_list_intersections() {
+    let rects = vec![
+        Rect {
+            x1: 0.0,
+            y1: 0.0,
+            x2: 4.0,
+            y2: 4.0,
+        },
+        Rect {
+            x1: 7.0,
+            y1: 6.0,
+            x2: 3.0,
+            y2: 2.0,
+        },
+    ];
+
+    let expected = Rect {
+        x1: 3.0,
+        y1: 2.0,
+        x2: 4.0,
+        y2: 4.0,
+    };
+    let intersections = list_intersections(rects.clone());
+    assert_eq!(intersections.len(), 1);
+    assert_eq!(intersections[0].area, expected);
+    assert_eq!(intersect_all(rects), Some(expected));
+}
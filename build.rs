@@ -0,0 +1,15 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=build.rs");
+    // The gRPC service is generated by the pure Rust protobuf compiler, so `protoc` is not
+    // required to build the crate.
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/sophya.proto");
+        let descriptors = protox::compile(["proto/sophya.proto"], ["proto"])?;
+        tonic_prost_build::configure()
+            // The generated `connect` relies on the 2021 edition prelude.
+            .build_transport(false)
+            .compile_fds(descriptors)?;
+    }
+    Ok(())
+}
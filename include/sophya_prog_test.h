@@ -0,0 +1,127 @@
+#ifndef SOPHYA_PROG_TEST_H
+#define SOPHYA_PROG_TEST_H
+
+/* This file is generated by cbindgen, do not edit it manually. */
+
+#include <stddef.h>
+
+/**
+ * Intersections found in the scene.
+ */
+typedef struct SophyaIntersections SophyaIntersections;
+
+/**
+ * Scene of rectangles.
+ */
+typedef struct SophyaScene SophyaScene;
+
+/**
+ * Rectangle given by its corners.
+ */
+typedef struct SophyaRect {
+  float x1;
+  float y1;
+  float x2;
+  float y2;
+} SophyaRect;
+
+/**
+ * Intersection of two rectangles of the scene.
+ */
+typedef struct SophyaIntersection {
+  /**
+   * Intersection area.
+   */
+  struct SophyaRect area;
+  /**
+   * First rectangle index.
+   */
+  size_t a_idx;
+  /**
+   * Second rectangle index.
+   */
+  size_t b_idx;
+} SophyaIntersection;
+
+/**
+ * Creates an empty scene. The scene must be released by `sophya_scene_free`.
+ */
+struct SophyaScene *sophya_scene_new(void);
+
+/**
+ * Releases the scene.
+ *
+ * # Safety
+ *
+ * The scene must be created by `sophya_scene_new` and must not be used afterwards.
+ */
+void sophya_scene_free(struct SophyaScene *scene);
+
+/**
+ * Adds a rectangle with the specified origin and size to the scene and returns its index.
+ *
+ * # Safety
+ *
+ * The scene must be a valid pointer returned by `sophya_scene_new`.
+ */
+size_t sophya_scene_add_rect(struct SophyaScene *scene,
+                             float x,
+                             float y,
+                             float width,
+                             float height);
+
+/**
+ * Searches for intersecting rectangles of the scene. The result must be released
+ * by `sophya_intersections_free`.
+ *
+ * # Safety
+ *
+ * The scene must be a valid pointer returned by `sophya_scene_new`.
+ */
+struct SophyaIntersections *sophya_scene_intersections(const struct SophyaScene *scene);
+
+/**
+ * Returns the number of the found intersections.
+ *
+ * # Safety
+ *
+ * The intersections must be a valid pointer returned by `sophya_scene_intersections`.
+ */
+size_t sophya_intersections_len(const struct SophyaIntersections *intersections);
+
+/**
+ * Returns the pointer to the array of the found intersections, the array length is
+ * returned by `sophya_intersections_len`. The array is valid until the intersections
+ * are released.
+ *
+ * # Safety
+ *
+ * The intersections must be a valid pointer returned by `sophya_scene_intersections`.
+ */
+const struct SophyaIntersection *sophya_intersections_data(const struct SophyaIntersections *intersections);
+
+/**
+ * Copies the intersection with the given index to `out`.
+ *
+ * Returns 1 on success or 0 if the index is out of bounds.
+ *
+ * # Safety
+ *
+ * The intersections must be a valid pointer returned by `sophya_scene_intersections`,
+ * and `out` must point to a writable `SophyaIntersection`.
+ */
+int sophya_intersections_get(const struct SophyaIntersections *intersections,
+                             size_t idx,
+                             struct SophyaIntersection *out);
+
+/**
+ * Releases the intersections.
+ *
+ * # Safety
+ *
+ * The intersections must be returned by `sophya_scene_intersections` and must not be
+ * used afterwards.
+ */
+void sophya_intersections_free(struct SophyaIntersections *intersections);
+
+#endif  /* SOPHYA_PROG_TEST_H */
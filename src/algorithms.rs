@@ -0,0 +1,912 @@
+use std::{borrow::Cow, mem, str::FromStr};
+
+use rayon::prelude::*;
+use rstar::{
+    primitives::{GeomWithData, Rectangle},
+    RTree, AABB,
+};
+
+use crate::{
+    hilbert_order, morton_order, packed_rtree::PackedRTree, stats, AnalysisStats, BoundingRect,
+    Intersection, Shape,
+};
+
+/// Intersection search strategy.
+///
+/// Every algorithm finds the same intersections in the same order as
+/// [`list_intersections`](crate::list_intersections), they differ only in how the candidate
+/// pairs are chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// Tests every pair of shapes, O(n^2).
+    #[default]
+    Naive,
+    /// Sorts the shapes by the left edge and tests only the shapes overlapping along the x axis.
+    Sweep,
+    /// Buckets the shapes into a uniform grid and tests only the shapes sharing a cell.
+    Grid,
+    /// Indexes the shapes in the R-tree bulk loaded by the recursive partitioning of the space
+    /// and tests only the shapes found by it.
+    RTree,
+    /// Indexes the shapes in the R-tree packed from the runs of the shapes in the Morton order,
+    /// it is built faster than [`Algorithm::RTree`] at the cost of the looser nodes.
+    MortonRTree,
+    /// Like [`Algorithm::MortonRTree`], but packs the R-tree in the Hilbert order, which keeps
+    /// the neighbouring shapes closer, so its nodes are tighter.
+    HilbertRTree,
+}
+
+impl Algorithm {
+    /// Names of all supported algorithms.
+    pub const VARIANTS: &'static [&'static str] = &[
+        "naive",
+        "sweep",
+        "grid",
+        "rtree",
+        "rtree-morton",
+        "rtree-hilbert",
+    ];
+    /// All supported algorithms.
+    pub const ALL: &'static [Algorithm] = &[
+        Self::Naive,
+        Self::Sweep,
+        Self::Grid,
+        Self::RTree,
+        Self::MortonRTree,
+        Self::HilbertRTree,
+    ];
+
+    /// Returns the name of the algorithm.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Naive => "naive",
+            Self::Sweep => "sweep",
+            Self::Grid => "grid",
+            Self::RTree => "rtree",
+            Self::MortonRTree => "rtree-morton",
+            Self::HilbertRTree => "rtree-hilbert",
+        }
+    }
+
+    /// Chooses the algorithm by the number of the shapes: the naive search has the smallest
+    /// overhead for the small inputs, the R-tree does not degrade on the clustered or sparse
+    /// large ones unlike the sweep line and the grid.
+    pub fn auto(shapes: usize) -> Self {
+        if shapes < 64 {
+            Self::Naive
+        } else {
+            Self::RTree
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|algorithm| algorithm.name() == s)
+            .ok_or_else(|| format!("Unknown algorithm \"{}\"", s))
+    }
+}
+
+/// Searches for intersecting shapes with the given algorithm.
+///
+/// The candidate pairs are tested in parallel on the current rayon thread pool, which uses
+/// all cores by default. The results do not depend on the number of threads.
+pub fn list_intersections_using<S: Shape + Sync>(
+    objects: &[S],
+    algorithm: Algorithm,
+) -> Vec<Intersection> {
+    list_intersections_using_with_stats(objects, algorithm).0
+}
+
+/// Searches for intersecting shapes with the given algorithm and collects the search
+/// statistics, the candidate pairs are the pairs that have actually been tested.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(shapes = objects.len(), ?algorithm, threads = rayon::current_num_threads())
+    )
+)]
+pub fn list_intersections_using_with_stats<S: Shape + Sync>(
+    objects: &[S],
+    algorithm: Algorithm,
+) -> (Vec<Intersection>, AnalysisStats) {
+    let rects = objects.iter().map(Shape::bounding_rect).collect::<Vec<_>>();
+    search_with_stats(
+        &Shapes {
+            objects,
+            rects: &rects,
+        },
+        algorithm,
+    )
+}
+
+// Searches for the intersecting shapes of any storage with the given algorithm.
+pub(crate) fn search_with_stats<T: Searched>(
+    search: &T,
+    algorithm: Algorithm,
+) -> (Vec<T::Found>, AnalysisStats) {
+    let mut stats = AnalysisStats {
+        shapes: search.len(),
+        ..AnalysisStats::default()
+    };
+    let (found, index_memory) = stats.measure("search", || index(algorithm)(search));
+    finish(search, found, index_memory, stats)
+}
+
+// Searches for the intersecting shapes within and between the groups of the shapes, e.g. of
+// the same layer, so the pairs of the groups that are not tested are never even visited. The
+// shapes of every group go in the ascending order, `tests` tells which pairs of the groups,
+// including a group with itself, are tested.
+pub(crate) fn search_groups<T: Searched>(
+    search: &T,
+    groups: &[Vec<usize>],
+    tests: impl Fn(usize, usize) -> bool + Sync,
+    algorithm: Algorithm,
+) -> (Vec<T::Found>, AnalysisStats) {
+    let mut stats = AnalysisStats {
+        shapes: search.len(),
+        ..AnalysisStats::default()
+    };
+    let pairs = (0..groups.len())
+        .flat_map(|a| (a..groups.len()).map(move |b| (a, b)))
+        .filter(|&(a, b)| tests(a, b))
+        .collect::<Vec<_>>();
+    let (found, index_memory) = stats.measure("search", || {
+        pairs
+            .par_iter()
+            .map(|&(a, b)| {
+                if a == b {
+                    let subset = Subset {
+                        search,
+                        indices: &groups[a],
+                    };
+                    index(algorithm)(&subset)
+                } else {
+                    between_search(search, &groups[a], &groups[b])
+                }
+            })
+            .reduce(
+                || (Found::default(), 0),
+                |(a, a_memory), (b, b_memory)| (a.merge(b), a_memory + b_memory),
+            )
+    });
+    finish(search, found, index_memory, stats)
+}
+
+// Intersecting shapes searched block by block of the first shapes of the pairs, so the found
+// ones are passed on before the whole search ends. The groups are split like for
+// `search_groups`, but every group is indexed by the packed Hilbert R-tree whatever the
+// algorithm is, so any block can be queried on its own.
+pub(crate) struct Blocks<'a, T: Searched> {
+    search: &'a T,
+    groups: &'a [Vec<usize>],
+    // Group of every shape.
+    group_of: Vec<usize>,
+    // Groups tested against every group.
+    tested: Vec<Vec<usize>>,
+    trees: Vec<PackedRTree>,
+    // First shape of the next block.
+    next: usize,
+    size: usize,
+    stats: AnalysisStats,
+    // Largest number of the intersections of one block.
+    largest: usize,
+}
+
+impl<'a, T: Searched> Blocks<'a, T> {
+    // Indexes the groups, `tests` is called with the ordered pairs of the groups like for
+    // `search_groups`.
+    pub(crate) fn new(
+        search: &'a T,
+        groups: &'a [Vec<usize>],
+        tests: impl Fn(usize, usize) -> bool,
+        size: usize,
+    ) -> Self {
+        let mut group_of = vec![0; search.len()];
+        for (group, indices) in groups.iter().enumerate() {
+            for &idx in indices {
+                group_of[idx] = group;
+            }
+        }
+        let tested = (0..groups.len())
+            .map(|a| {
+                (0..groups.len())
+                    .filter(|&b| tests(a.min(b), a.max(b)))
+                    .collect()
+            })
+            .collect();
+        let trees = groups
+            .par_iter()
+            .map(|indices| {
+                let rects = indices
+                    .iter()
+                    .map(|&idx| search.rect(idx))
+                    .collect::<Vec<_>>();
+                PackedRTree::new(&rects, &hilbert_order(&rects))
+            })
+            .collect();
+        Self {
+            search,
+            groups,
+            group_of,
+            tested,
+            trees,
+            next: 0,
+            size: size.max(1),
+            stats: AnalysisStats {
+                shapes: search.len(),
+                ..AnalysisStats::default()
+            },
+            largest: 0,
+        }
+    }
+
+    // Returns the statistics of the searched blocks.
+    pub(crate) fn stats(mut self) -> AnalysisStats {
+        self.stats.peak_memory_estimate = self.search.memory()
+            + self.trees.iter().map(PackedRTree::memory).sum::<usize>()
+            + self.group_of.len() * mem::size_of::<usize>()
+            + self.largest * mem::size_of::<T::Found>();
+        self.stats
+    }
+}
+
+impl<T: Searched> Iterator for Blocks<'_, T> {
+    type Item = Vec<T::Found>;
+
+    // Returns the ordered intersections of the next block, so all blocks together are ordered
+    // like the results of `search_groups`.
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, end) = (self.next, (self.next + self.size).min(self.search.len()));
+        if start >= end {
+            return None;
+        }
+        self.next = end;
+
+        let found = (start..end)
+            .into_par_iter()
+            .fold(Found::default, |mut found, a| {
+                let rect = self.search.rect(a);
+                for &group in &self.tested[self.group_of[a]] {
+                    let indices = &self.groups[group];
+                    // Every pair is found twice, it is tested only from the shape with the
+                    // smaller index.
+                    self.trees[group].for_each_intersecting(&rect, |b| {
+                        if indices[b] > a {
+                            found.test(self.search, a, indices[b]);
+                        }
+                    });
+                }
+                found
+            })
+            .reduce(Found::default, Found::merge);
+        let mut intersections = found.intersections;
+        intersections.par_sort_unstable_by_key(T::pair);
+
+        self.stats.candidate_pairs += found.tested;
+        self.stats.intersections += intersections.len();
+        self.largest = self.largest.max(intersections.len());
+        Some(intersections)
+    }
+}
+
+// Searches for the intersections between the shapes of two lists, the indices of the second
+// list start from zero.
+pub(crate) fn search_between<S: Shape + Sync>(
+    first: &[S],
+    second: &[S],
+) -> (Vec<Intersection>, AnalysisStats) {
+    let search = Joined {
+        first,
+        second,
+        rects: first
+            .iter()
+            .chain(second)
+            .map(Shape::bounding_rect)
+            .collect(),
+    };
+    let groups = [
+        (0..first.len()).collect(),
+        (first.len()..search.rects.len()).collect(),
+    ];
+    // Only the first list is tested against the second one, the algorithm is not used then.
+    search_groups(&search, &groups, |a, b| a != b, Algorithm::Naive)
+}
+
+// Returns the search function of the algorithm.
+fn index<T: Searched>(algorithm: Algorithm) -> fn(&T) -> (Found<T::Found>, usize) {
+    match algorithm {
+        Algorithm::Naive => naive_search,
+        Algorithm::Sweep => sweep_search,
+        Algorithm::Grid => grid_search,
+        Algorithm::RTree => rtree_search,
+        Algorithm::MortonRTree => |search| packed_rtree_search(search, morton_order),
+        Algorithm::HilbertRTree => |search| packed_rtree_search(search, hilbert_order),
+    }
+}
+
+// Orders the found intersections and completes the statistics.
+fn finish<T: Searched>(
+    search: &T,
+    found: Found<T::Found>,
+    index_memory: usize,
+    mut stats: AnalysisStats,
+) -> (Vec<T::Found>, AnalysisStats) {
+    stats.candidate_pairs = found.tested;
+    // The threads find the intersections in an arbitrary order.
+    let mut intersections = found.intersections;
+    intersections.par_sort_unstable_by_key(T::pair);
+
+    stats.intersections = intersections.len();
+    stats.peak_memory_estimate =
+        search.memory() + intersections.len() * mem::size_of::<T::Found>() + index_memory;
+    trace_event!(
+        pairs = stats.candidate_pairs,
+        intersections = stats.intersections,
+        "Intersection search finished"
+    );
+    (intersections, stats)
+}
+
+// Storage of the shapes searched by the algorithms.
+pub(crate) trait Searched: Sync {
+    // Intersection of two shapes found by the search.
+    type Found: Send;
+
+    // Returns the number of the shapes.
+    fn len(&self) -> usize;
+
+    // Returns the bounding rect of the shape.
+    fn rect(&self, idx: usize) -> BoundingRect;
+
+    // Returns the left edge of the bounding rect.
+    fn left(&self, idx: usize) -> f32 {
+        self.rect(idx).from.x
+    }
+
+    // Returns the right edge of the bounding rect.
+    fn right(&self, idx: usize) -> f32 {
+        self.rect(idx).to.x
+    }
+
+    // Returns the bounding rects of all shapes.
+    fn rects(&self) -> Cow<'_, [BoundingRect]>;
+
+    // Returns the intersection of the shapes if it is found, `a < b`.
+    fn intersect(&self, a: usize, b: usize) -> Option<Self::Found>;
+
+    // Returns the indices of the shapes of the found intersection.
+    fn pair(found: &Self::Found) -> (usize, usize);
+
+    // Returns the memory used by the storage.
+    fn memory(&self) -> usize;
+}
+
+// Shapes and their bounding rects.
+struct Shapes<'a, S> {
+    objects: &'a [S],
+    rects: &'a [BoundingRect],
+}
+
+impl<S: Shape + Sync> Searched for Shapes<'_, S> {
+    type Found = Intersection;
+
+    fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    fn rect(&self, idx: usize) -> BoundingRect {
+        self.rects[idx]
+    }
+
+    fn rects(&self) -> Cow<'_, [BoundingRect]> {
+        Cow::Borrowed(self.rects)
+    }
+
+    fn intersect(&self, a: usize, b: usize) -> Option<Intersection> {
+        let area = self.objects[a].intersection(&self.objects[b])?;
+        Some(Intersection {
+            area,
+            a_idx: a,
+            b_idx: b,
+            mtv: self.rects[a].mtv(&self.rects[b])?,
+        })
+    }
+
+    fn pair(found: &Intersection) -> (usize, usize) {
+        (found.a_idx, found.b_idx)
+    }
+
+    fn memory(&self) -> usize {
+        stats::search_memory_estimate::<S>(self.objects.len(), 0)
+    }
+}
+
+// Shapes of one group searched by their indices in the storage.
+struct Subset<'a, T> {
+    search: &'a T,
+    indices: &'a [usize],
+}
+
+impl<T: Searched> Searched for Subset<'_, T> {
+    type Found = T::Found;
+
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    fn rect(&self, idx: usize) -> BoundingRect {
+        self.search.rect(self.indices[idx])
+    }
+
+    fn left(&self, idx: usize) -> f32 {
+        self.search.left(self.indices[idx])
+    }
+
+    fn right(&self, idx: usize) -> f32 {
+        self.search.right(self.indices[idx])
+    }
+
+    fn rects(&self) -> Cow<'_, [BoundingRect]> {
+        Cow::Owned(
+            self.indices
+                .iter()
+                .map(|&idx| self.search.rect(idx))
+                .collect(),
+        )
+    }
+
+    // The indices are ascending, so the order of the pair is kept.
+    fn intersect(&self, a: usize, b: usize) -> Option<T::Found> {
+        self.search.intersect(self.indices[a], self.indices[b])
+    }
+
+    fn pair(found: &T::Found) -> (usize, usize) {
+        T::pair(found)
+    }
+
+    fn memory(&self) -> usize {
+        0
+    }
+}
+
+// Shapes of two lists searched as one list, the second list follows the first one.
+struct Joined<'a, S> {
+    first: &'a [S],
+    second: &'a [S],
+    rects: Vec<BoundingRect>,
+}
+
+impl<S: Shape + Sync> Searched for Joined<'_, S> {
+    type Found = Intersection;
+
+    fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    fn rect(&self, idx: usize) -> BoundingRect {
+        self.rects[idx]
+    }
+
+    fn rects(&self) -> Cow<'_, [BoundingRect]> {
+        Cow::Borrowed(&self.rects)
+    }
+
+    // Only the shapes of the first list are tested against the shapes of the second one.
+    fn intersect(&self, a: usize, b: usize) -> Option<Intersection> {
+        let b_idx = b - self.first.len();
+        let area = self.first[a].intersection(&self.second[b_idx])?;
+        Some(Intersection {
+            area,
+            a_idx: a,
+            b_idx,
+            mtv: self.rects[a].mtv(&self.rects[b])?,
+        })
+    }
+
+    fn pair(found: &Intersection) -> (usize, usize) {
+        (found.a_idx, found.b_idx)
+    }
+
+    fn memory(&self) -> usize {
+        stats::search_memory_estimate::<S>(self.rects.len(), 0)
+    }
+}
+
+// Intersections found by one thread.
+struct Found<I> {
+    intersections: Vec<I>,
+    tested: usize,
+}
+
+impl<I> Default for Found<I> {
+    fn default() -> Self {
+        Self {
+            intersections: Vec::new(),
+            tested: 0,
+        }
+    }
+}
+
+impl<I> Found<I> {
+    // Tests the pair of shapes exactly like the naive search does.
+    fn test<T: Searched<Found = I> + ?Sized>(&mut self, search: &T, a: usize, b: usize) {
+        let (i, j) = if a < b { (a, b) } else { (b, a) };
+        self.tested += 1;
+        if let Some(found) = search.intersect(i, j) {
+            self.intersections.push(found);
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.intersections.extend(other.intersections);
+        self.tested += other.tested;
+        self
+    }
+}
+
+// Returns the found intersections and the memory used by the index.
+fn naive_search<T: Searched>(search: &T) -> (Found<T::Found>, usize) {
+    let count = search.len();
+    let found = (0..count)
+        .into_par_iter()
+        .fold(Found::default, |mut found, a| {
+            for b in a + 1..count {
+                found.test(search, a, b);
+            }
+            found
+        })
+        .reduce(Found::default, Found::merge);
+    (found, 0)
+}
+
+// Returns the found intersections and the memory used by the index.
+fn sweep_search<T: Searched>(search: &T) -> (Found<T::Found>, usize) {
+    let mut order = (0..search.len()).collect::<Vec<_>>();
+    order.par_sort_by(|&a, &b| search.left(a).total_cmp(&search.left(b)));
+
+    let found = order
+        .par_iter()
+        .enumerate()
+        .fold(Found::default, |mut found, (pos, &a)| {
+            // The following rects starting after the right edge of this one cannot intersect
+            // it, the touching ones are tested since the degenerate rects may intersect them.
+            let right = search.right(a);
+            for &b in order[pos + 1..]
+                .iter()
+                .take_while(|&&b| search.left(b) <= right)
+            {
+                found.test(search, a, b);
+            }
+            found
+        })
+        .reduce(Found::default, Found::merge);
+    (found, order.len() * mem::size_of::<usize>())
+}
+
+// Returns the found intersections and the memory used by the index.
+fn grid_search<T: Searched>(search: &T) -> (Found<T::Found>, usize) {
+    let rects = search.rects();
+    if rects.is_empty() {
+        return (Found::default(), 0);
+    }
+    // Unlike the union, keeps the degenerate rects.
+    let (min_x, min_y, max_x, max_y) = rects.iter().fold(
+        (
+            f32::INFINITY,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NEG_INFINITY,
+        ),
+        |(min_x, min_y, max_x, max_y), rect| {
+            (
+                min_x.min(rect.from.x),
+                min_y.min(rect.from.y),
+                max_x.max(rect.to.x),
+                max_y.max(rect.to.y),
+            )
+        },
+    );
+
+    // About one shape per cell on average.
+    let side = (rects.len() as f32).sqrt().ceil() as usize;
+    let cell_size = |min: f32, max: f32| {
+        let size = (max - min) / side as f32;
+        if size > 0.0 && size.is_finite() {
+            size
+        } else {
+            1.0
+        }
+    };
+    let (cell_width, cell_height) = (cell_size(min_x, max_x), cell_size(min_y, max_y));
+    let cell_of = |x: f32, y: f32| {
+        let column = (((x - min_x) / cell_width) as usize).min(side - 1);
+        let row = (((y - min_y) / cell_height) as usize).min(side - 1);
+        (column, row)
+    };
+
+    let mut cells = vec![Vec::new(); side * side];
+    for (idx, rect) in rects.iter().enumerate() {
+        let (first_column, first_row) = cell_of(rect.from.x, rect.from.y);
+        let (last_column, last_row) = cell_of(rect.to.x, rect.to.y);
+        for row in first_row..=last_row {
+            for column in first_column..=last_column {
+                cells[row * side + column].push(idx);
+            }
+        }
+    }
+
+    let found = cells
+        .par_iter()
+        .enumerate()
+        .fold(Found::default, |mut found, (cell_idx, cell)| {
+            for (pos, &a) in cell.iter().enumerate() {
+                for &b in &cell[pos + 1..] {
+                    // The pair shares several cells, it is tested only in the cell with the
+                    // top left corner of the overlap.
+                    let (x, y) = (
+                        rects[a].from.x.max(rects[b].from.x),
+                        rects[a].from.y.max(rects[b].from.y),
+                    );
+                    let (column, row) = cell_of(x, y);
+                    if row * side + column == cell_idx {
+                        found.test(search, a, b);
+                    }
+                }
+            }
+            found
+        })
+        .reduce(Found::default, Found::merge);
+    let entries = cells.iter().map(Vec::len).sum::<usize>();
+    let memory = entries * mem::size_of::<usize>() + cells.len() * mem::size_of::<Vec<usize>>();
+    (found, memory)
+}
+
+// Returns the found intersections and the memory used by the index.
+fn rtree_search<T: Searched>(search: &T) -> (Found<T::Found>, usize) {
+    type Entry = GeomWithData<Rectangle<[f32; 2]>, usize>;
+    let corners = |rect: &BoundingRect| ([rect.from.x, rect.from.y], [rect.to.x, rect.to.y]);
+
+    let rects = search.rects();
+    let entries = rects
+        .iter()
+        .enumerate()
+        .map(|(idx, rect)| {
+            let (from, to) = corners(rect);
+            Entry::new(Rectangle::from_corners(from, to), idx)
+        })
+        .collect::<Vec<_>>();
+    let count = entries.len();
+    let tree = RTree::bulk_load(entries);
+
+    let found = rects
+        .par_iter()
+        .enumerate()
+        .fold(Found::default, |mut found, (a, rect)| {
+            let (from, to) = corners(rect);
+            let envelope = AABB::from_corners(from, to);
+            // Every pair is found twice, it is tested only from the shape with the smaller
+            // index.
+            for entry in tree.locate_in_envelope_intersecting(envelope) {
+                if entry.data > a {
+                    found.test(search, a, entry.data);
+                }
+            }
+            found
+        })
+        .reduce(Found::default, Found::merge);
+    // The leaves hold the entries, the inner nodes add roughly the same amount.
+    (found, 2 * count * mem::size_of::<Entry>())
+}
+
+// Returns the found intersections and the memory used by the index, the R-tree is packed
+// from the shapes ordered along the given space-filling curve.
+fn packed_rtree_search<T: Searched>(
+    search: &T,
+    curve_order: fn(&[BoundingRect]) -> Vec<usize>,
+) -> (Found<T::Found>, usize) {
+    let rects = search.rects();
+    let order = curve_order(&rects);
+    let tree = PackedRTree::new(&rects, &order);
+
+    // The queries go in the same order, so the consecutive ones mostly visit the same nodes.
+    let found = order
+        .par_iter()
+        .fold(Found::default, |mut found, &a| {
+            // Every pair is found twice, it is tested only from the shape with the smaller
+            // index.
+            tree.for_each_intersecting(&rects[a], |b| {
+                if b > a {
+                    found.test(search, a, b);
+                }
+            });
+            found
+        })
+        .reduce(Found::default, Found::merge);
+    (found, tree.memory() + order.len() * mem::size_of::<usize>())
+}
+
+// Returns the intersections between the shapes of two groups found by the packed R-tree of
+// the second group and the memory used by the tree.
+fn between_search<T: Searched>(
+    search: &T,
+    first: &[usize],
+    second: &[usize],
+) -> (Found<T::Found>, usize) {
+    let rects = second
+        .iter()
+        .map(|&idx| search.rect(idx))
+        .collect::<Vec<_>>();
+    let tree = PackedRTree::new(&rects, &hilbert_order(&rects));
+
+    let found = first
+        .par_iter()
+        .fold(Found::default, |mut found, &a| {
+            tree.for_each_intersecting(&search.rect(a), |b| found.test(search, a, second[b]));
+            found
+        })
+        .reduce(Found::default, Found::merge);
+    (found, tree.memory())
+}
+
+#[test]
+fn test_algorithms() {
+    use crate::{
+        generate_scene, list_intersections_with_stats, Distribution, GenerateOptions, Point2D,
+    };
+
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+    let edge_cases = vec![
+        rect(0.0, 0.0, 4.0, 4.0),
+        // Touches the first one by the edge.
+        rect(4.0, 0.0, 6.0, 4.0),
+        // Degenerate rect inside the first one.
+        rect(2.0, 1.0, 2.0, 3.0),
+        rect(0.0, 0.0, 4.0, 4.0),
+        // Degenerate rect on the left edge of the previous one.
+        rect(0.0, 1.0, 0.0, 2.0),
+        rect(-10.0, -10.0, 10.0, 10.0),
+    ];
+
+    let mut cases = vec![
+        (edge_cases, "edge cases".to_owned()),
+        (vec![], "empty".to_owned()),
+    ];
+    for &distribution in &[
+        Distribution::Uniform,
+        Distribution::Clustered,
+        Distribution::Grid,
+    ] {
+        let input = generate_scene(&GenerateOptions {
+            count: 300,
+            seed: 7,
+            density: 0.5,
+            distribution,
+        });
+        let rects = input
+            .objects
+            .iter()
+            .map(|object| object.area().area)
+            .collect();
+        cases.push((rects, format!("{:?}", distribution)));
+    }
+
+    for case in cases {
+        let (expected, _) = list_intersections_with_stats(&case.0);
+        for &algorithm in Algorithm::ALL {
+            let (actual, stats) = list_intersections_using_with_stats(&case.0, algorithm);
+            assert_eq!(
+                actual,
+                expected,
+                "Test case \"{} with {}\" has been failed",
+                case.1,
+                algorithm.name()
+            );
+            assert_eq!(stats.intersections, expected.len());
+
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .build()
+                .unwrap();
+            let single_threaded = pool.install(|| list_intersections_using(&case.0, algorithm));
+            assert_eq!(
+                single_threaded,
+                expected,
+                "Test case \"{} with {} on one thread\" has been failed",
+                case.1,
+                algorithm.name()
+            );
+        }
+    }
+    assert_eq!("grid".parse(), Ok(Algorithm::Grid));
+    assert_eq!(Algorithm::auto(10), Algorithm::Naive);
+    assert!(Algorithm::VARIANTS
+        .iter()
+        .all(|name| name.parse::<Algorithm>().is_ok()));
+}
+
+#[test]
+fn test_algorithms_claiming_shapes() {
+    use crate::{claiming_shapes, list_intersections_between};
+
+    // The touching shapes claiming an intersection are skipped by every algorithm.
+    let shapes = claiming_shapes();
+    for &algorithm in Algorithm::ALL {
+        let mut pairs = list_intersections_using(&shapes, algorithm)
+            .iter()
+            .map(|x| (x.a_idx, x.b_idx))
+            .collect::<Vec<_>>();
+        pairs.sort_unstable();
+        assert_eq!(
+            pairs,
+            vec![(0, 2), (1, 2)],
+            "Test case \"{}\" has been failed",
+            algorithm.name()
+        );
+    }
+    let pairs = list_intersections_between(&shapes[..1], &shapes[1..])
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![(0, 1)]);
+}
+
+#[test]
+fn test_search_groups() {
+    use crate::{generate_scene, list_intersections, GenerateOptions};
+
+    let input = generate_scene(&GenerateOptions {
+        count: 300,
+        seed: 5,
+        density: 0.5,
+        ..GenerateOptions::default()
+    });
+    let rects = input
+        .objects
+        .iter()
+        .map(|object| object.area().area)
+        .collect::<Vec<_>>();
+    let search = Shapes {
+        objects: &rects,
+        rects: &rects,
+    };
+    // The first group is tested against every group, the other ones only against the first.
+    let groups = (0..3)
+        .map(|group| (group..rects.len()).step_by(3).collect())
+        .collect::<Vec<Vec<usize>>>();
+    let tests = |a: usize, b: usize| a == 0 || a != b;
+    let expected = list_intersections(&rects)
+        .into_iter()
+        .filter(|x| {
+            tests(
+                (x.a_idx % 3).min(x.b_idx % 3),
+                (x.a_idx % 3).max(x.b_idx % 3),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+
+    for &algorithm in Algorithm::ALL {
+        let (actual, stats) = search_groups(&search, &groups, tests, algorithm);
+        assert_eq!(
+            actual,
+            expected,
+            "Test case \"{}\" has been failed",
+            algorithm.name()
+        );
+        assert_eq!(stats.intersections, expected.len());
+    }
+
+    let mut blocks = Blocks::new(&search, &groups, tests, 7);
+    let actual = blocks.by_ref().flatten().collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+    assert_eq!(blocks.stats().intersections, expected.len());
+}
@@ -0,0 +1,869 @@
+use std::{borrow::Cow, collections::HashMap, mem};
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::{
+    algorithms::{search_groups, Blocks, Searched},
+    Algorithm, AnalysisStats, AnyShape, BoundingRect, CollisionMatrix, Input, IntersectionMetrics,
+    LayerRule, Object, ObjectArea, ObjectIntersection, Output, OutputRecord, Shape,
+};
+
+/// Number of the first objects of the pairs searched before their intersections are streamed.
+const STREAM_BLOCK: usize = 4096;
+
+/// Analysis settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    /// Include the analysis statistics into the output.
+    pub stats: bool,
+    /// Drop the intersections with the smaller area, e.g. the slivers of the touching objects.
+    pub min_area: f32,
+    /// Drop the intersections with the smaller intersection over union of the object areas.
+    pub min_iou: f32,
+    /// Intersection search algorithm, chosen by [`Algorithm::auto`] if not set. All algorithms
+    /// give the same results.
+    pub algorithm: Option<Algorithm>,
+    /// Number of the search threads, all cores are used if not set.
+    pub threads: Option<usize>,
+    /// Include the [`IntersectionMetrics`] into the output intersections.
+    pub metrics: bool,
+    /// Copy the properties of the input objects into their areas and attach the properties of
+    /// both objects to their intersections.
+    pub properties: bool,
+    /// Pairs of the object layers tested for the intersections, all by default.
+    pub layers: LayerRule,
+    /// Test only the objects of the same `z` level, e.g. of the same floor. The objects
+    /// without a level form one more level.
+    pub same_z: bool,
+    /// Pairs of the `z` levels tested for the intersections like the pairs of the layers, e.g.
+    /// `1:1,1:2` for the stairs of the first floor, all by default.
+    pub z_pairs: Option<CollisionMatrix>,
+}
+
+impl Options {
+    /// Returns the area of the object with its properties if they are kept.
+    fn area(&self, object: &Object) -> ObjectArea {
+        let mut area = object.area();
+        if self.properties {
+            area.properties = object.properties.clone();
+        }
+        area
+    }
+
+    /// Returns the intersection of the given objects if it passes the filters.
+    fn intersection(
+        &self,
+        a: &ObjectArea,
+        b: &ObjectArea,
+        area: BoundingRect,
+    ) -> Option<ObjectIntersection> {
+        // The rotated and non-rectangular objects are clipped exactly, the intersection is the
+        // bounding rect of their common part.
+        let (area, overlap, object_areas) = if a.shape.is_none() && b.shape.is_none() {
+            (area, area.area(), (a.area.area(), b.area.area()))
+        } else {
+            let (first, second) = (a.exact_shape(), b.exact_shape());
+            let (area, overlap) = first.overlap(&second)?;
+            // The shapes touching by an edge or a vertex have an empty common part, only the
+            // segments intersect without any area.
+            let segments = [&first, &second]
+                .iter()
+                .any(|shape| matches!(shape, AnyShape::Segment { .. }));
+            if overlap <= 0.0 && !segments {
+                return None;
+            }
+            (
+                area,
+                overlap,
+                (first.outline().area(), second.outline().area()),
+            )
+        };
+        if overlap < self.min_area {
+            return None;
+        }
+        let metrics = if self.metrics || self.min_iou > 0.0 {
+            let metrics = IntersectionMetrics::from_areas(object_areas.0, object_areas.1, overlap);
+            if metrics.iou < self.min_iou {
+                return None;
+            }
+            Some(metrics).filter(|_| self.metrics)
+        } else {
+            None
+        };
+        Some(ObjectIntersection {
+            area,
+            names: (a.name.clone(), b.name.clone()),
+            metrics,
+            properties: Some((a.properties.clone(), b.properties.clone()))
+                .filter(|_| self.properties),
+        })
+    }
+
+    /// Searches for the intersections of the areas passing the filters, only they are counted
+    /// in the statistics. The areas are split into the layers and the levels first, so only
+    /// the pairs of the tested ones are searched.
+    ///
+    /// If the index of the first area of the second input is given, only the pairs of the
+    /// areas of different inputs are searched.
+    fn search(
+        &self,
+        areas: &[ObjectArea],
+        second: Option<usize>,
+    ) -> (Vec<ObjectIntersection>, AnalysisStats) {
+        let algorithm = self
+            .algorithm
+            .unwrap_or_else(|| Algorithm::auto(areas.len()));
+        let search = Areas {
+            areas,
+            rects: areas.iter().map(Shape::bounding_rect).collect(),
+            options: self,
+        };
+        let (keys, groups) = self.groups(areas, second);
+        let tests = self.tests(keys);
+        let (found, stats) = self.install(|| search_groups(&search, &groups, tests, algorithm));
+        (found.into_iter().map(|x| x.1).collect(), stats)
+    }
+
+    /// Passes the intersections of the areas passing the filters to the `sink` block by block
+    /// as soon as they are found, in the same order as [`Options::search`] returns them.
+    fn search_each<E>(
+        &self,
+        areas: &[ObjectArea],
+        mut sink: impl FnMut(ObjectIntersection) -> Result<(), E>,
+    ) -> Result<AnalysisStats, E> {
+        let search = Areas {
+            areas,
+            rects: areas.iter().map(Shape::bounding_rect).collect(),
+            options: self,
+        };
+        let (keys, groups) = self.groups(areas, None);
+        let tests = self.tests(keys);
+        let pool = self.pool();
+
+        let mut stats = AnalysisStats::default();
+        let mut blocks = install(pool.as_ref(), || {
+            Blocks::new(&search, &groups, tests, STREAM_BLOCK)
+        });
+        stats.measure("search", || {
+            while let Some(block) = install(pool.as_ref(), || blocks.next()) {
+                block.into_iter().try_for_each(|x| sink(x.1))?;
+            }
+            Ok(())
+        })?;
+        stats.merge(blocks.stats());
+        Ok(stats)
+    }
+
+    /// Returns whether the pair of the groups with the given keys is tested.
+    fn tests<'a>(&'a self, keys: Vec<Group<'a>>) -> impl Fn(usize, usize) -> bool + Sync + 'a {
+        let levels = keys
+            .iter()
+            .map(|key| key.2.map(|z| z.to_string()))
+            .collect::<Vec<_>>();
+        move |a: usize, b: usize| {
+            (keys[a].0.is_none() || keys[a].0 != keys[b].0)
+                && self.layers.tests(keys[a].1, keys[b].1)
+                && (!self.same_z || keys[a].2 == keys[b].2)
+                && self
+                    .z_pairs
+                    .as_ref()
+                    .is_none_or(|pairs| pairs.contains(levels[a].as_deref(), levels[b].as_deref()))
+        }
+    }
+
+    /// Splits the indices of the areas into the groups of the same input, layer and level in
+    /// the input order, the layers or the levels are not split if every pair of them is tested.
+    fn groups<'a>(
+        &self,
+        areas: &'a [ObjectArea],
+        second: Option<usize>,
+    ) -> (Vec<Group<'a>>, Vec<Vec<usize>>) {
+        let by_layer = self.layers != LayerRule::All;
+        let by_level = self.same_z || self.z_pairs.is_some();
+        let mut indices = HashMap::new();
+        let (mut keys, mut groups) = (Vec::new(), Vec::<Vec<usize>>::new());
+        for (idx, area) in areas.iter().enumerate() {
+            let key = (
+                second.map(|second| idx >= second),
+                area.layer.as_deref().filter(|_| by_layer),
+                area.z.filter(|_| by_level),
+            );
+            let group = *indices.entry(key).or_insert_with(|| {
+                keys.push(key);
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(idx);
+        }
+        (keys, groups)
+    }
+
+    /// Runs the search on the pool with the configured number of threads.
+    fn install<T: Send>(&self, search: impl FnOnce() -> T + Send) -> T {
+        install(self.pool().as_ref(), search)
+    }
+
+    /// Returns the pool with the configured number of threads if it is set.
+    fn pool(&self) -> Option<ThreadPool> {
+        self.threads
+            .and_then(|threads| ThreadPoolBuilder::new().num_threads(threads).build().ok())
+    }
+}
+
+/// Runs the search on the given pool.
+fn install<T: Send>(pool: Option<&ThreadPool>, search: impl FnOnce() -> T + Send) -> T {
+    match pool {
+        Some(pool) => pool.install(search),
+        // The global pool is used if the threads cannot be spawned.
+        None => search(),
+    }
+}
+
+// Input, layer and level of the areas of one group, the input is set only for the join.
+type Group<'a> = (Option<bool>, Option<&'a str>, Option<i32>);
+
+// Object areas searched with the analysis filters applied to every found pair.
+struct Areas<'a> {
+    areas: &'a [ObjectArea],
+    rects: Vec<BoundingRect>,
+    options: &'a Options,
+}
+
+impl Searched for Areas<'_> {
+    type Found = ((usize, usize), ObjectIntersection);
+
+    fn len(&self) -> usize {
+        self.areas.len()
+    }
+
+    fn rect(&self, idx: usize) -> BoundingRect {
+        self.rects[idx]
+    }
+
+    fn rects(&self) -> Cow<'_, [BoundingRect]> {
+        Cow::Borrowed(&self.rects)
+    }
+
+    fn intersect(&self, a: usize, b: usize) -> Option<Self::Found> {
+        let (first, second) = (&self.areas[a], &self.areas[b]);
+        let area = first.intersection(second)?;
+        let intersection = self.options.intersection(first, second, area)?;
+        Some(((a, b), intersection))
+    }
+
+    fn pair(found: &Self::Found) -> (usize, usize) {
+        found.0
+    }
+
+    fn memory(&self) -> usize {
+        self.rects.len() * mem::size_of::<BoundingRect>()
+    }
+}
+
+/// Searches for intersecting objects in the given input.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(objects = input.objects.len()))
+)]
+pub fn analyze(input: Input, options: Options) -> Output {
+    let mut stats = AnalysisStats::default();
+
+    let areas = stats.measure("areas", || {
+        input
+            .objects
+            .iter()
+            .map(|x| options.area(x))
+            .collect::<Vec<_>>()
+    });
+    // The input objects are alive along with their areas.
+    let names = input.objects.iter().map(|x| x.name.len()).sum::<usize>();
+    stats.peak_memory_estimate += input.objects.len() * mem::size_of::<Object>() + names;
+    search_areas(areas, stats, &options)
+}
+
+/// Searches for intersecting objects like [`analyze`], but takes the objects one by one, e.g.
+/// from [`Input::ndjson_objects`], and keeps only their areas, so the whole input never has to
+/// fit in memory. The objects are read in the "areas" phase.
+///
+/// The first error of the objects stops the analysis and is returned.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn analyze_objects<E>(
+    objects: impl IntoIterator<Item = Result<Object, E>>,
+    options: Options,
+) -> Result<Output, E> {
+    let mut stats = AnalysisStats::default();
+
+    let areas = stats.measure("areas", || {
+        objects
+            .into_iter()
+            .map(|object| object.map(|x| options.area(&x)))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    Ok(search_areas(areas, stats, &options))
+}
+
+// Searches for the intersections of the object areas and collects the output.
+fn search_areas(areas: Vec<ObjectArea>, mut stats: AnalysisStats, options: &Options) -> Output {
+    trace_event!(areas = areas.len(), "Object areas computed");
+
+    // The filtered out intersections are dropped inside the search before their names are
+    // copied.
+    let (intersections, search_stats) = options.search(&areas, None);
+    stats.merge(search_stats);
+    trace_event!(
+        intersections = intersections.len(),
+        "Object intersections mapped"
+    );
+
+    // The areas and the output intersections are alive at the same time.
+    let names = areas.iter().map(|x| x.name.len()).sum::<usize>();
+    let intersection_names = intersections
+        .iter()
+        .map(|x| x.names.0.len() + x.names.1.len())
+        .sum::<usize>();
+    stats.peak_memory_estimate += areas.len() * mem::size_of::<ObjectArea>()
+        + names
+        + intersections.len() * mem::size_of::<ObjectIntersection>()
+        + intersection_names;
+
+    Output {
+        page: None,
+        unit: None,
+        areas,
+        intersections,
+        stats: Some(stats).filter(|_| options.stats),
+    }
+}
+
+/// Searches for intersecting objects like [`analyze`], but passes every area and then every
+/// intersection to the `sink` as soon as it is found instead of collecting them, so the
+/// results do not have to fit in memory. The statistics are passed last if enabled.
+///
+/// The intersections are searched by the packed Hilbert R-tree block by block of the objects
+/// whatever the [`Options::algorithm`] is, every block is passed on as soon as it is searched.
+///
+/// The first error of the `sink` stops the analysis and is returned.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(objects = input.objects.len()))
+)]
+pub fn analyze_each<E>(
+    input: Input,
+    options: Options,
+    mut sink: impl FnMut(OutputRecord) -> Result<(), E>,
+) -> Result<(), E> {
+    let mut stats = AnalysisStats::default();
+
+    let areas = stats.measure("areas", || {
+        input
+            .objects
+            .iter()
+            .map(|x| options.area(x))
+            .collect::<Vec<_>>()
+    });
+    for area in &areas {
+        sink(OutputRecord::Area(area.clone()))?;
+    }
+
+    let search_stats = options.search_each(&areas, |x| sink(OutputRecord::Intersection(x)))?;
+    stats.merge(search_stats);
+
+    // Only the input objects and their areas are kept.
+    let names = input.objects.iter().map(|x| x.name.len()).sum::<usize>();
+    stats.peak_memory_estimate +=
+        input.objects.len() * (mem::size_of::<Object>() + mem::size_of::<ObjectArea>()) + 2 * names;
+    if options.stats {
+        sink(OutputRecord::Stats(stats))?;
+    }
+    Ok(())
+}
+
+/// Searches for intersections between the objects of two inputs, e.g. the planned and the
+/// actual layouts. The objects of the same input are not tested against each other.
+///
+/// The first name of every intersection refers to the first input and the second one to the
+/// second input, the areas of both inputs are listed in order.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(first = first.objects.len(), second = second.objects.len())
+    )
+)]
+pub fn analyze_join(first: Input, second: Input, options: Options) -> Output {
+    let mut stats = AnalysisStats::default();
+
+    let split = first.objects.len();
+    let areas = stats.measure("areas", || {
+        first
+            .objects
+            .iter()
+            .chain(&second.objects)
+            .map(|x| options.area(x))
+            .collect::<Vec<_>>()
+    });
+    // The areas of the first input go first, so they are the first ones of the pairs.
+    let (intersections, search_stats) = options.search(&areas, Some(split));
+    stats.merge(search_stats);
+
+    Output {
+        page: None,
+        unit: None,
+        areas,
+        intersections,
+        stats: Some(stats).filter(|_| options.stats),
+    }
+}
+
+#[test]
+fn test_analyze() {
+    use crate::{BoundingRect, Point2D};
+
+    let object = |name: &str, x: f32, y: f32, width: f32, height: f32| Object {
+        name: name.to_owned(),
+        width,
+        height,
+        x,
+        y,
+        layer: None,
+        z: None,
+        rotation: None,
+        shape: None,
+        properties: Vec::new(),
+    };
+
+    let input = Input {
+        objects: vec![
+            object("A", 0.0, 0.0, 4.0, 4.0),
+            object("B", 10.0, 10.0, 1.0, 1.0),
+            object("C", 3.0, 2.0, 4.0, 4.0),
+        ],
+    };
+
+    let output = analyze(input.clone(), Options::default());
+    assert_eq!(output.areas.len(), 3);
+    assert_eq!(output.stats, None);
+    assert_eq!(
+        output.intersections,
+        vec![ObjectIntersection {
+            names: ("A".to_owned(), "C".to_owned()),
+            area: BoundingRect::from_points(Point2D { x: 3.0, y: 2.0 }, Point2D { x: 4.0, y: 4.0 }),
+            metrics: None,
+            properties: None,
+        }]
+    );
+
+    let stats = analyze(
+        input,
+        Options {
+            stats: true,
+            ..Options::default()
+        },
+    )
+    .stats
+    .unwrap();
+    assert_eq!(stats.shapes, 3);
+    assert_eq!(stats.candidate_pairs, 3);
+    assert_eq!(stats.intersections, 1);
+    assert_eq!(
+        stats
+            .phases
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["areas", "search"]
+    );
+    assert!(stats.peak_memory_estimate > 0);
+}
+
+#[test]
+fn test_analyze_join() {
+    let planned = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 3.0, 3.0, 2.0, 2.0)
+        .build();
+    let built = crate::SceneBuilder::new()
+        .object("A", 0.5, 0.0, 4.0, 4.0)
+        .object("C", 10.0, 10.0, 1.0, 1.0)
+        .build();
+
+    let output = analyze_join(
+        planned,
+        built,
+        Options {
+            stats: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(output.areas.len(), 4);
+    assert_eq!(
+        output
+            .intersections
+            .iter()
+            .map(|x| x.names.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            ("A".to_owned(), "A".to_owned()),
+            ("B".to_owned(), "A".to_owned())
+        ]
+    );
+    // Only the pairs with the overlapping bounding rects are tested.
+    let stats = output.stats.unwrap();
+    assert_eq!((stats.shapes, stats.candidate_pairs), (4, 2));
+}
+
+#[test]
+fn test_analyze_metrics() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 2.0, 0.0, 2.0, 2.0)
+        // Degenerate object on the edge of the first one.
+        .object("C", 0.0, 1.0, 0.0, 2.0)
+        .build();
+    let output = analyze(
+        input,
+        Options {
+            metrics: true,
+            ..Options::default()
+        },
+    );
+
+    let metrics = output
+        .intersections
+        .iter()
+        .map(|x| x.metrics.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        metrics,
+        vec![
+            IntersectionMetrics {
+                area: 4.0,
+                iou: 0.25,
+                coverage: (25.0, 100.0),
+            },
+            IntersectionMetrics {
+                area: 0.0,
+                iou: 0.0,
+                coverage: (0.0, 0.0),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_analyze_threads() {
+    use crate::{generate_scene, GenerateOptions};
+
+    let scene = |seed: u64| {
+        generate_scene(&GenerateOptions {
+            count: 200,
+            seed,
+            density: 0.5,
+            ..GenerateOptions::default()
+        })
+    };
+    let with_threads = |threads: usize| Options {
+        threads: Some(threads),
+        ..Options::default()
+    };
+
+    let single = analyze(scene(1), with_threads(1));
+    assert!(!single.intersections.is_empty());
+    assert_eq!(analyze(scene(1), with_threads(3)), single);
+
+    let single = analyze_join(scene(1), scene(2), with_threads(1));
+    let areas = |input: Input| input.objects.iter().map(Object::area).collect::<Vec<_>>();
+    let expected = crate::list_intersections_between(&areas(scene(1)), &areas(scene(2)));
+    assert_eq!(single.intersections.len(), expected.len());
+    assert_eq!(analyze_join(scene(1), scene(2), with_threads(3)), single);
+}
+
+#[test]
+fn test_analyze_filters() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        // Sliver overlap of the touching walls.
+        .object("B", 3.9, 0.0, 4.0, 4.0)
+        .object("C", 0.0, 0.0, 4.0, 2.0)
+        .object("D", 1.0, 1.0, 1.0, 1.0)
+        .build();
+    let pairs = |options: Options| {
+        analyze(input.clone(), options)
+            .intersections
+            .into_iter()
+            .map(|x| format!("{}{}", x.names.0, x.names.1))
+            .collect::<Vec<_>>()
+    };
+
+    let cases = vec![
+        (
+            Options::default(),
+            vec!["AB", "AC", "AD", "BC", "CD"],
+            "no filters",
+        ),
+        (
+            Options {
+                min_area: 1.0,
+                ..Options::default()
+            },
+            vec!["AC", "AD", "CD"],
+            "min area",
+        ),
+        (
+            Options {
+                min_iou: 0.1,
+                ..Options::default()
+            },
+            vec!["AC", "CD"],
+            "min iou",
+        ),
+    ];
+    for case in cases {
+        assert_eq!(
+            pairs(case.0),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+
+    // Only the kept intersections are counted.
+    let stats = analyze(
+        input,
+        Options {
+            stats: true,
+            min_area: 1.0,
+            ..Options::default()
+        },
+    )
+    .stats
+    .unwrap();
+    assert_eq!((stats.candidate_pairs, stats.intersections), (6, 3));
+}
+
+#[test]
+fn test_analyze_properties() {
+    use serde_json::json;
+
+    let mut input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 2.0, 0.0, 4.0, 4.0)
+        .build();
+    input.objects[0].properties = vec![json!({ "name": "type", "value": "wall" })];
+
+    let output = analyze(input.clone(), Options::default());
+    assert!(output.areas[0].properties.is_empty());
+    assert_eq!(output.intersections[0].properties, None);
+
+    let output = analyze(
+        input,
+        Options {
+            properties: true,
+            ..Options::default()
+        },
+    );
+    assert_eq!(
+        output.areas[0].properties,
+        vec![json!({ "name": "type", "value": "wall" })]
+    );
+    assert_eq!(
+        output.intersections[0].properties,
+        Some((vec![json!({ "name": "type", "value": "wall" })], Vec::new()))
+    );
+}
+
+#[test]
+fn test_analyze_each() {
+    let input = crate::generate_scene(&crate::GenerateOptions {
+        count: 100,
+        seed: 3,
+        density: 0.5,
+        ..crate::GenerateOptions::default()
+    });
+    let options = Options {
+        stats: true,
+        min_area: 1.0,
+        ..Options::default()
+    };
+    let output = analyze(input.clone(), options.clone());
+
+    let mut records = Vec::new();
+    analyze_each(input, options, |record| {
+        records.push(record);
+        Ok::<_, ()>(())
+    })
+    .unwrap();
+    assert!(matches!(records.pop(), Some(OutputRecord::Stats(_))));
+    let expected = output
+        .areas
+        .into_iter()
+        .map(OutputRecord::Area)
+        .chain(
+            output
+                .intersections
+                .into_iter()
+                .map(OutputRecord::Intersection),
+        )
+        .collect::<Vec<_>>();
+    assert_eq!(records, expected);
+
+    // The error of the sink stops the analysis.
+    let mut count = 0;
+    let result = analyze_each(
+        crate::Input::from_path("objects/test.json").unwrap(),
+        Options::default(),
+        |_| {
+            count += 1;
+            if count == 2 {
+                Err("stop")
+            } else {
+                Ok(())
+            }
+        },
+    );
+    assert_eq!((result, count), (Err("stop"), 2));
+}
+
+#[test]
+fn test_analyze_objects() {
+    let input = crate::generate_scene(&crate::GenerateOptions {
+        count: 100,
+        seed: 5,
+        density: 0.5,
+        ..crate::GenerateOptions::default()
+    });
+    let ndjson = input
+        .objects
+        .iter()
+        .map(|object| serde_json::to_string(object).unwrap() + "\n")
+        .collect::<String>();
+    let expected = analyze(input, Options::default());
+    let output =
+        analyze_objects(Input::ndjson_objects(ndjson.as_bytes()), Options::default()).unwrap();
+    assert!(!output.intersections.is_empty());
+    assert_eq!(output, expected);
+
+    // The first broken object stops the analysis.
+    let ndjson = format!("{}{{}}\n{}", ndjson, ndjson);
+    let err = analyze_objects(Input::ndjson_objects(ndjson.as_bytes()), Options::default());
+    assert!(err.unwrap_err().to_string().contains("line 101"));
+}
+
+#[test]
+fn test_analyze_rotation() {
+    use crate::AnyShape;
+
+    // The half diagonal of the 4x4 squares rotated by 45 degrees.
+    let d = 8.0_f32.sqrt();
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 4.0, 4.0, 4.0, 4.0)
+        .rotation(45.0)
+        .object("C", 3.0, 3.0, 4.0, 4.0)
+        .rotation(45.0)
+        .object("D", 10.0, 0.0, 4.0, 4.0)
+        .rotation(360.0)
+        .build();
+    let output = analyze(
+        input,
+        Options {
+            metrics: true,
+            ..Options::default()
+        },
+    );
+
+    let b = &output.areas[1];
+    assert!(matches!(&b.shape, Some(AnyShape::Polygon(polygon)) if polygon.vertices.len() == 4));
+    assert!((b.area.area() - 4.0 * d * d).abs() < 1e-3);
+    // The full turn keeps the exact rect.
+    assert_eq!(output.areas[3].shape, None);
+
+    // The bounding rects of A and B overlap, the diamond does not reach the corner of A.
+    let names = output
+        .intersections
+        .iter()
+        .map(|x| (x.names.0.as_str(), x.names.1.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![("A", "C"), ("B", "C")]);
+
+    // Only the corner of A is covered by the diamond, the rect is tighter than the overlap
+    // of the bounding rects.
+    let a_c = &output.intersections[0];
+    let (from, to) = (a_c.area.top_left(), a_c.area.bottom_right());
+    let corner = 6.0 - d;
+    for (value, expected) in [(from.x, corner), (from.y, corner), (to.x, 4.0), (to.y, 4.0)] {
+        assert!((value - expected).abs() < 1e-4, "{} != {}", value, expected);
+    }
+    let metrics = a_c.metrics.unwrap();
+    assert!((metrics.area - (4.0 - corner).powi(2) / 2.0).abs() < 1e-4);
+    assert!((metrics.coverage.1 - 100.0 * metrics.area / 16.0).abs() < 1e-3);
+}
+
+#[test]
+fn test_analyze_shapes() {
+    use crate::{AnyShape, Point2D, Polygon};
+
+    let point = Point2D::new;
+    let input = crate::SceneBuilder::new()
+        .shape(
+            "A",
+            AnyShape::Circle {
+                center: point(1.0, 1.0),
+                radius: 1.0,
+            },
+        )
+        .object("B", 1.8, 1.8, 2.0, 2.0)
+        .shape(
+            "C",
+            AnyShape::Polygon(Polygon::new(vec![
+                point(4.0, 0.0),
+                point(6.0, 0.0),
+                point(6.0, 3.0),
+            ])),
+        )
+        .shape(
+            "D",
+            AnyShape::Segment {
+                from: point(3.0, 3.0),
+                to: point(7.0, 1.0),
+            },
+        )
+        .shape(
+            "E",
+            AnyShape::Polygon(Polygon::new(vec![
+                point(10.0, 0.0),
+                point(12.0, 0.0),
+                point(10.0, 2.0),
+            ])),
+        )
+        .shape(
+            "F",
+            AnyShape::Polygon(Polygon::new(vec![
+                point(12.0, 0.0),
+                point(12.0, 2.0),
+                point(10.0, 2.0),
+            ])),
+        )
+        .build();
+    let output = analyze(input, Options::default());
+
+    // The corner of B is outside of the circle, the segment passes the corner of B and the
+    // triangles E and F only touch each other by the diagonal.
+    let names = output
+        .intersections
+        .iter()
+        .map(|x| (x.names.0.as_str(), x.names.1.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![("B", "D"), ("C", "D")]);
+    assert_eq!(
+        output.areas[0].shape,
+        Some(AnyShape::Circle {
+            center: point(1.0, 1.0),
+            radius: 1.0,
+        })
+    );
+    let c_d = &output.intersections[1];
+    let (from, to) = (c_d.area.top_left(), c_d.area.bottom_right());
+    assert!((from.x - 5.25).abs() < 1e-5 && (from.y - 1.5).abs() < 1e-5);
+    assert!((to.x - 6.0).abs() < 1e-5 && (to.y - 1.875).abs() < 1e-5);
+}
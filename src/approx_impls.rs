@@ -0,0 +1,68 @@
+//! Tolerant comparisons of the geometry types.
+
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+
+use crate::{BoundingRect, Point2D, Vector2D};
+
+// Implements the approx traits for the structure by comparing its fields.
+macro_rules! impl_approx {
+    ($ty:ty, $($field:ident),+) => {
+        impl AbsDiffEq for $ty {
+            type Epsilon = f32;
+
+            fn default_epsilon() -> Self::Epsilon {
+                f32::default_epsilon()
+            }
+
+            fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+                $(self.$field.abs_diff_eq(&other.$field, epsilon))&&+
+            }
+        }
+
+        impl RelativeEq for $ty {
+            fn default_max_relative() -> Self::Epsilon {
+                f32::default_max_relative()
+            }
+
+            fn relative_eq(
+                &self,
+                other: &Self,
+                epsilon: Self::Epsilon,
+                max_relative: Self::Epsilon,
+            ) -> bool {
+                $(self.$field.relative_eq(&other.$field, epsilon, max_relative))&&+
+            }
+        }
+
+        impl UlpsEq for $ty {
+            fn default_max_ulps() -> u32 {
+                f32::default_max_ulps()
+            }
+
+            fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool {
+                $(self.$field.ulps_eq(&other.$field, epsilon, max_ulps))&&+
+            }
+        }
+    };
+}
+
+impl_approx!(Point2D, x, y);
+impl_approx!(Vector2D, x, y);
+impl_approx!(BoundingRect, from, to);
+
+#[test]
+fn test_approx_rects() {
+    use approx::{assert_abs_diff_eq, assert_relative_eq, assert_relative_ne, assert_ulps_eq};
+
+    let a = BoundingRect::from_points(Point2D::new(0.1, 0.2), Point2D::new(0.3, 0.7));
+    let b = BoundingRect::from_points(
+        Point2D::new(0.05 + 0.05, 0.1 + 0.1),
+        Point2D::new(0.1 + 0.2, 0.35 + 0.35),
+    );
+
+    assert_abs_diff_eq!(a, b);
+    assert_relative_eq!(a, b);
+    assert_ulps_eq!(a, b);
+    assert_abs_diff_eq!(a, a.translate(Vector2D::new(0.01, 0.0)), epsilon = 0.02);
+    assert_relative_ne!(a, a.translate(Vector2D::new(0.01, 0.0)));
+}
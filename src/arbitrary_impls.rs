@@ -0,0 +1,133 @@
+//! Generators of valid geometry for fuzzers and property tests.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{AnyShape, BoundingRect, Point2D, Polygon, Vector2D};
+
+// Generates a finite coordinate that can be safely used in arithmetic without overflows.
+fn coordinate(u: &mut Unstructured<'_>) -> Result<f32> {
+    const RANGE: i32 = 1 << 24;
+    Ok(u.int_in_range(-RANGE..=RANGE)? as f32 / 256.0)
+}
+
+impl<'a> Arbitrary<'a> for Point2D {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: coordinate(u)?,
+            y: coordinate(u)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Vector2D {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: coordinate(u)?,
+            y: coordinate(u)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for BoundingRect {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_points(u.arbitrary()?, u.arbitrary()?))
+    }
+}
+
+// Generates a positive length up to 256.
+fn length(u: &mut Unstructured<'_>) -> Result<f32> {
+    Ok(u.int_in_range(1..=1_u32 << 16)? as f32 / 256.0)
+}
+
+impl<'a> Arbitrary<'a> for Polygon {
+    // The vertices are sorted by their angles around the center and the neighbors are less
+    // than a half turn apart, so the polygon is simple.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let center = Point2D::arbitrary(u)?;
+        let count = u.int_in_range(3..=12_usize)?;
+        let step = std::f32::consts::TAU / count as f32;
+        let vertices = (0..count)
+            .map(|i| {
+                // The vertex is shifted by less than a half step from its place.
+                let angle = (i as f32 + u.int_in_range(0..=255_u8)? as f32 / 512.0) * step;
+                let radius = u.int_in_range(16..=4096_u16)? as f32 / 16.0;
+                let (sin, cos) = angle.sin_cos();
+                Ok(Point2D::new(
+                    center.x + radius * cos,
+                    center.y + radius * sin,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self::new(vertices))
+    }
+}
+
+impl<'a> Arbitrary<'a> for AnyShape {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=3_u8)? {
+            0 => Self::Rect(u.arbitrary()?),
+            1 => Self::Circle {
+                center: u.arbitrary()?,
+                radius: length(u)?,
+            },
+            2 => Self::Polygon(u.arbitrary()?),
+            _ => {
+                let from = Point2D::arbitrary(u)?;
+                let offset = Vector2D::arbitrary(u)?;
+                let mut to = Point2D::new(from.x + offset.x, from.y + offset.y);
+                // The short offsets are lost in the rounding of the far points.
+                if to == from {
+                    to.x += 1.0;
+                }
+                Self::Segment { from, to }
+            }
+        })
+    }
+}
+
+#[test]
+fn test_arbitrary_rects_are_valid() {
+    let bytes = (0..4096_u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect::<Vec<_>>();
+    let mut u = Unstructured::new(&bytes);
+
+    while !u.is_empty() {
+        let rect = BoundingRect::arbitrary(&mut u).unwrap();
+        assert!(rect.from.x <= rect.to.x && rect.from.y <= rect.to.y);
+        assert!(rect.from.x.is_finite() && rect.to.y.is_finite());
+    }
+}
+
+#[test]
+fn test_arbitrary_shapes_are_valid() {
+    let bytes = (0..16384_u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 11) as u8)
+        .collect::<Vec<_>>();
+    let mut u = Unstructured::new(&bytes);
+
+    let mut kinds = [0; 4];
+    while !u.is_empty() {
+        match AnyShape::arbitrary(&mut u).unwrap() {
+            AnyShape::Rect(rect) => {
+                kinds[0] += 1;
+                assert!(rect.from.x <= rect.to.x && rect.from.y <= rect.to.y);
+            }
+            AnyShape::Circle { radius, .. } => {
+                kinds[1] += 1;
+                assert!(radius > 0.0);
+            }
+            AnyShape::Polygon(polygon) => {
+                kinds[2] += 1;
+                assert!(polygon.vertices.len() >= 3);
+                assert!(polygon.area() > 0.0);
+                assert_eq!(polygon.self_intersections(), Vec::new(), "{:?}", polygon);
+            }
+            AnyShape::Segment { from, to } => {
+                kinds[3] += 1;
+                assert_ne!(from, to);
+            }
+        }
+    }
+    assert!(kinds.iter().all(|&count| count > 0), "{:?}", kinds);
+}
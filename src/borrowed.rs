@@ -0,0 +1,281 @@
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+use serde::{de, Deserialize};
+use serde_json::value::RawValue;
+
+use crate::{
+    io::object_geometry, io::RawObject, AnyShape, BoundingRect, Error, Input, Object, Shape,
+};
+
+/// Input file contents borrowing the names and the layers from the parsed text, so loading a
+/// huge file does not copy every string. Only the names with the escape sequences are copied.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BorrowedInput<'a> {
+    /// Objects to analyze.
+    #[serde(borrow)]
+    pub objects: Vec<BorrowedObject<'a>>,
+}
+
+/// Object of the input file like [`Object`], its properties are kept as the raw JSON.
+#[derive(Debug, Clone)]
+pub struct BorrowedObject<'a> {
+    /// Name of the object, borrowed unless it has the escape sequences.
+    pub name: Cow<'a, str>,
+    /// Width of the bounding rect.
+    pub width: f32,
+    /// Height of the bounding rect.
+    pub height: f32,
+    /// Left side of the bounding rect.
+    pub x: f32,
+    /// Top side of the bounding rect.
+    pub y: f32,
+    /// Layer of the object like [`Object::layer`].
+    pub layer: Option<Cow<'a, str>>,
+    /// Level of the object like [`Object::z`].
+    pub z: Option<i32>,
+    /// Rotation in degrees about the center like [`Object::rotation`].
+    pub rotation: Option<f32>,
+    /// Exact shape of the object like [`Object::shape`].
+    pub shape: Option<AnyShape>,
+    /// Properties of the object as the raw JSON, parsed only by [`BorrowedObject::into_owned`].
+    pub properties: Vec<&'a RawValue>,
+}
+
+impl PartialEq for BorrowedObject<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let raw = |object: &Self| {
+            object
+                .properties
+                .iter()
+                .map(|property| property.get())
+                .collect::<Vec<_>>()
+        };
+        self.name == other.name
+            && (self.width, self.height, self.x, self.y)
+                == (other.width, other.height, other.x, other.y)
+            && self.layer == other.layer
+            && self.z == other.z
+            && self.rotation == other.rotation
+            && self.shape == other.shape
+            && raw(self) == raw(other)
+    }
+}
+
+impl<'a> BorrowedInput<'a> {
+    /// Parses the JSON input borrowing its strings, the input is the same as for
+    /// [`Input::from_reader`].
+    pub fn from_json(text: &'a str) -> Result<Self, Error> {
+        serde_json::from_str(text).map_err(|err| Error::ParseError { reason: err.into() })
+    }
+
+    /// Copies the borrowed strings and parses the properties into the owned input.
+    pub fn into_owned(self) -> Result<Input, Error> {
+        Ok(Input {
+            objects: self
+                .objects
+                .into_iter()
+                .map(BorrowedObject::into_owned)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl BorrowedObject<'_> {
+    /// Copies the borrowed strings and parses the properties into the owned object.
+    pub fn into_owned(self) -> Result<Object, Error> {
+        let properties = self
+            .properties
+            .into_iter()
+            .map(|property| serde_json::from_str(property.get()))
+            .collect::<Result<_, _>>()
+            .map_err(|err| Error::ParseError { reason: err.into() })?;
+        Ok(Object {
+            name: self.name.into_owned(),
+            width: self.width,
+            height: self.height,
+            x: self.x,
+            y: self.y,
+            layer: self.layer.map(Cow::into_owned),
+            z: self.z,
+            rotation: self.rotation,
+            shape: self.shape,
+            properties,
+        })
+    }
+}
+
+impl Shape for BorrowedObject<'_> {
+    /// Returns the area occupied by the object like [`Object::area`].
+    fn bounding_rect(&self) -> BoundingRect {
+        if self.rotation.is_none() {
+            if let Some(shape) = &self.shape {
+                return shape.bounding_rect();
+            }
+        }
+        object_geometry(
+            (self.x, self.y, self.width, self.height),
+            self.rotation,
+            self.shape.as_ref(),
+        )
+        .0
+    }
+}
+
+// String borrowed from the input unless it has the escape sequences.
+struct Name<'a>(Cow<'a, str>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for Name<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NameVisitor<'a>(PhantomData<&'a str>);
+
+        impl<'de: 'a, 'a> de::Visitor<'de> for NameVisitor<'a> {
+            type Value = Name<'a>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_borrowed_str<E: de::Error>(self, value: &'de str) -> Result<Name<'a>, E> {
+                Ok(Name(Cow::Borrowed(value)))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Name<'a>, E> {
+                Ok(Name(Cow::Owned(value.to_owned())))
+            }
+
+            fn visit_string<E: de::Error>(self, value: String) -> Result<Name<'a>, E> {
+                Ok(Name(Cow::Owned(value)))
+            }
+        }
+
+        deserializer.deserialize_str(NameVisitor(PhantomData))
+    }
+}
+
+struct BorrowedObjectVisitor<'a>(PhantomData<&'a str>);
+
+impl<'de: 'a, 'a> de::Visitor<'de> for BorrowedObjectVisitor<'a> {
+    type Value = BorrowedObject<'a>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an object")
+    }
+
+    // The conversion happens inside the visitor like for the owned objects.
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<BorrowedObject<'a>, A::Error> {
+        let raw = RawObject::<Name<'a>, &'a RawValue>::deserialize(
+            de::value::MapAccessDeserializer::new(map),
+        )?;
+        let shape = raw.shape()?;
+        let (x, y, width, height) = raw.rect(shape.as_ref())?;
+        Ok(BorrowedObject {
+            name: raw.name.0,
+            width,
+            height,
+            x,
+            y,
+            layer: raw.layer.map(|layer| layer.0),
+            z: raw.z,
+            rotation: raw.rotation,
+            shape,
+            properties: raw.properties,
+        })
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for BorrowedObject<'a> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(BorrowedObjectVisitor(PhantomData))
+    }
+}
+
+#[test]
+fn test_borrowed_input() {
+    use crate::list_intersections;
+
+    let json = r#"{
+        "objects": [
+            { "name": "A", "x": 0, "y": 0, "width": 4, "height": 4, "layer": "walls" },
+            { "name": "B \"quoted\"", "from": { "x": 3, "y": 3 }, "to": { "x": 6, "y": 5 } },
+            {
+                "name": "C",
+                "kind": "circle",
+                "center": { "x": 9, "y": 9 },
+                "radius": 1,
+                "properties": [{ "color": "red" }, 7]
+            },
+            { "name": "D", "x": 8, "y": 8, "width": 2, "height": 2, "rotation": 45 }
+        ]
+    }"#;
+    let input = BorrowedInput::from_json(json).unwrap();
+    let names = input
+        .objects
+        .iter()
+        .map(|object| {
+            (
+                object.name.as_ref(),
+                matches!(object.name, Cow::Borrowed(_)),
+            )
+        })
+        .collect::<Vec<_>>();
+    // The escaped names cannot be borrowed.
+    assert_eq!(
+        names,
+        vec![
+            ("A", true),
+            ("B \"quoted\"", false),
+            ("C", true),
+            ("D", true)
+        ]
+    );
+    assert!(matches!(
+        input.objects[0].layer,
+        Some(Cow::Borrowed("walls"))
+    ));
+    assert_eq!(
+        input.objects[2].properties[0].get(),
+        r#"{ "color": "red" }"#
+    );
+
+    // The borrowed objects are the same as the owned ones.
+    let owned = Input::from_reader(json.as_bytes()).unwrap();
+    assert_eq!(
+        input
+            .objects
+            .iter()
+            .map(Shape::bounding_rect)
+            .collect::<Vec<_>>(),
+        owned
+            .objects
+            .iter()
+            .map(|object| object.area().area)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        list_intersections(&input.objects),
+        list_intersections(&owned.objects.iter().map(Object::area).collect::<Vec<_>>())
+    );
+    assert_eq!(input.into_owned().unwrap(), owned);
+
+    let cases = vec![
+        (
+            r#"{ "objects": [{ "name": "A", "x": 0 }] }"#,
+            "missing size",
+        ),
+        (
+            r#"{ "objects": [{ "name": 1, "x": 0, "y": 0, "width": 1, "height": 1 }] }"#,
+            "number name",
+        ),
+        (
+            r#"{ "objects": [{ "name": "A", "kind": "circle", "radius": 1 }] }"#,
+            "missing center",
+        ),
+    ];
+    for case in cases {
+        assert!(
+            BorrowedInput::from_json(case.0).is_err(),
+            "Test case \"{}\" has been failed",
+            case.1
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{list_intersections, BoundingRect, Intersection, Point2D, Shape, Vector2D};
+
+/// Returns the area swept by the rectangle moving with the given velocity during the time step.
+fn swept_rect(rect: &BoundingRect, velocity: Vector2D, dt: f32) -> BoundingRect {
+    let shift = Vector2D {
+        x: velocity.x * dt,
+        y: velocity.y * dt,
+    };
+    // Unlike the union, keeps degenerate rectangles of the moving points and lines.
+    let moved = rect.translate(shift);
+    BoundingRect::from_points(
+        Point2D {
+            x: rect.from.x.min(moved.from.x),
+            y: rect.from.y.min(moved.from.y),
+        },
+        Point2D {
+            x: rect.to.x.max(moved.to.x),
+            y: rect.to.y.max(moved.to.y),
+        },
+    )
+}
+
+/// Searches for shapes that may collide while moving with the given velocities
+/// during the time step `dt`.
+///
+/// Each bounding rectangle is expanded along its motion before the intersection search,
+/// so the reported areas and translation vectors refer to the swept rectangles. The result
+/// is conservative: shapes that are reported may still miss each other within the step.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(shapes = objects.len(), dt)))]
+pub fn list_potential_collisions<S: Shape>(
+    objects: &[(S, Vector2D)],
+    dt: f32,
+) -> Vec<Intersection> {
+    let swept = objects
+        .iter()
+        .map(|(shape, velocity)| swept_rect(&shape.bounding_rect(), *velocity, dt))
+        .collect::<Vec<_>>();
+
+    list_intersections(&swept)
+}
+
+#[test]
+fn test_potential_collisions() {
+    let rect = |x: f32, y: f32| {
+        BoundingRect::from_points(
+            Point2D { x, y },
+            Point2D {
+                x: x + 1.0,
+                y: y + 1.0,
+            },
+        )
+    };
+
+    let objects = vec![
+        // Moves to the right and reaches the second rect within the step.
+        (rect(0.0, 0.0), Vector2D { x: 6.0, y: 0.0 }),
+        // Stands still.
+        (rect(3.0, 0.0), Vector2D { x: 0.0, y: 0.0 }),
+        // Moves away from both of them.
+        (rect(0.0, 3.0), Vector2D { x: 0.0, y: 2.0 }),
+        // Falls down onto the path of the first rect.
+        (rect(0.5, 5.0), Vector2D { x: 0.0, y: -10.0 }),
+    ];
+
+    let pairs = list_potential_collisions(&objects, 0.5)
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![(0, 1), (0, 3), (2, 3)]);
+
+    let pairs = list_potential_collisions(&objects, 0.0)
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx))
+        .collect::<Vec<_>>();
+    assert!(pairs.is_empty());
+}
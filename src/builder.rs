@@ -0,0 +1,244 @@
+use crate::{AnyShape, BoundingRect, Input, Object, Point2D, Shape};
+
+// Anchor point of the rectangle under construction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Anchor {
+    Origin(Point2D),
+    Center(Point2D),
+}
+
+/// Rectangle builder.
+///
+/// ```
+/// use sophya_prog_test::{BoundingRect, Point2D, RectBuilder};
+///
+/// let rect = RectBuilder::new().center(2.0, 2.0).size(2.0, 4.0).margin(1.0).build();
+/// assert_eq!(
+///     rect,
+///     BoundingRect::from_points(Point2D { x: 0.0, y: -1.0 }, Point2D { x: 4.0, y: 5.0 })
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RectBuilder {
+    anchor: Anchor,
+    width: f32,
+    height: f32,
+    margins: (f32, f32),
+}
+
+impl Default for RectBuilder {
+    fn default() -> Self {
+        Self {
+            anchor: Anchor::Origin(Point2D::default()),
+            width: 0.0,
+            height: 0.0,
+            margins: (0.0, 0.0),
+        }
+    }
+}
+
+impl RectBuilder {
+    /// Creates a builder of an empty rectangle at the origin.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the top left corner of the rectangle.
+    pub fn origin(mut self, x: f32, y: f32) -> Self {
+        self.anchor = Anchor::Origin(Point2D { x, y });
+        self
+    }
+
+    /// Sets the center of the rectangle.
+    pub fn center(mut self, x: f32, y: f32) -> Self {
+        self.anchor = Anchor::Center(Point2D { x, y });
+        self
+    }
+
+    /// Sets the size of the rectangle.
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the horizontal and vertical margins that are added on each side of the rectangle.
+    ///
+    /// Negative margins shrink the rectangle.
+    pub fn margins(mut self, horizontal: f32, vertical: f32) -> Self {
+        self.margins = (horizontal, vertical);
+        self
+    }
+
+    /// Sets the same margin on each side of the rectangle.
+    pub fn margin(self, margin: f32) -> Self {
+        self.margins(margin, margin)
+    }
+
+    /// Builds the rectangle.
+    pub fn build(self) -> BoundingRect {
+        let origin = match self.anchor {
+            Anchor::Origin(origin) => origin,
+            Anchor::Center(center) => Point2D {
+                x: center.x - self.width / 2.0,
+                y: center.y - self.height / 2.0,
+            },
+        };
+        let (dx, dy) = self.margins;
+
+        BoundingRect::from_points(
+            Point2D {
+                x: origin.x - dx,
+                y: origin.y - dy,
+            },
+            Point2D {
+                x: origin.x + self.width + dx,
+                y: origin.y + self.height + dy,
+            },
+        )
+    }
+}
+
+/// Builder of the input scene with the named objects.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SceneBuilder {
+    objects: Vec<Object>,
+}
+
+impl SceneBuilder {
+    /// Creates an empty scene builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an object occupying the specified rectangle.
+    pub fn rect(mut self, name: impl Into<String>, rect: BoundingRect) -> Self {
+        self.objects.push(Object {
+            name: name.into(),
+            width: rect.to.x - rect.from.x,
+            height: rect.to.y - rect.from.y,
+            x: rect.from.x,
+            y: rect.from.y,
+            layer: None,
+            z: None,
+            rotation: None,
+            shape: None,
+            properties: Vec::new(),
+        });
+        self
+    }
+
+    /// Adds an object with the specified position and size.
+    pub fn object(self, name: impl Into<String>, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.rect(
+            name,
+            RectBuilder::new().origin(x, y).size(width, height).build(),
+        )
+    }
+
+    /// Adds an object of the given shape, e.g. a circle.
+    pub fn shape(mut self, name: impl Into<String>, shape: AnyShape) -> Self {
+        let rect = shape.bounding_rect();
+        self = self.rect(name, rect);
+        if let Some(object) = self.objects.last_mut() {
+            object.shape = Some(shape);
+        }
+        self
+    }
+
+    /// Moves the last added object to the given layer.
+    pub fn layer(mut self, layer: impl Into<String>) -> Self {
+        if let Some(object) = self.objects.last_mut() {
+            object.layer = Some(layer.into());
+        }
+        self
+    }
+
+    /// Moves the last added object to the given z level.
+    pub fn z(mut self, level: i32) -> Self {
+        if let Some(object) = self.objects.last_mut() {
+            object.z = Some(level);
+        }
+        self
+    }
+
+    /// Rotates the last added object about its center by the given degrees.
+    pub fn rotation(mut self, degrees: f32) -> Self {
+        if let Some(object) = self.objects.last_mut() {
+            object.rotation = Some(degrees);
+        }
+        self
+    }
+
+    /// Builds the scene.
+    pub fn build(self) -> Input {
+        Input {
+            objects: self.objects,
+        }
+    }
+}
+
+#[test]
+fn test_rect_builder() {
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+
+    let cases = vec![
+        (RectBuilder::new(), rect(0.0, 0.0, 0.0, 0.0), "default"),
+        (
+            RectBuilder::new().origin(1.0, 2.0).size(3.0, 4.0),
+            rect(1.0, 2.0, 4.0, 6.0),
+            "origin and size",
+        ),
+        (
+            RectBuilder::new().center(0.0, 0.0).size(4.0, 2.0),
+            rect(-2.0, -1.0, 2.0, 1.0),
+            "center and size",
+        ),
+        (
+            RectBuilder::new()
+                .origin(1.0, 1.0)
+                .size(2.0, 2.0)
+                .margins(1.0, -0.5),
+            rect(0.0, 1.5, 4.0, 2.5),
+            "margins",
+        ),
+        (
+            RectBuilder::new().origin(1.0, 1.0).size(-1.0, -1.0),
+            rect(0.0, 0.0, 1.0, 1.0),
+            "negative size",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            case.0.build(),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+}
+
+#[test]
+fn test_scene_builder() {
+    let input = SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .rect(
+            "B",
+            RectBuilder::new().center(4.0, 4.0).size(2.0, 2.0).build(),
+        )
+        .build();
+
+    let output = crate::analyze(input, crate::Options::default());
+    assert_eq!(output.intersections.len(), 1);
+    assert_eq!(
+        output.intersections[0].names,
+        ("A".to_owned(), "B".to_owned())
+    );
+    assert_eq!(
+        output.intersections[0].area,
+        RectBuilder::new().origin(3.0, 3.0).size(1.0, 1.0).build()
+    );
+}
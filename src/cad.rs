@@ -0,0 +1,220 @@
+use std::io::Read;
+
+use ::dxf::{entities::EntityType, Drawing};
+
+use crate::{decompress, wkt::ring_shape, AnyShape, BoundingRect, Error, Input, Object, Point2D};
+
+fn parse_error(reason: impl Into<anyhow::Error>) -> Error {
+    Error::ParseError {
+        reason: reason.into(),
+    }
+}
+
+// Returns the shape of the closed ring, of the two-point line or the bounding rect of the open
+// polyline.
+fn polyline_shape(points: Vec<Point2D>, closed: bool) -> Result<Option<AnyShape>, Error> {
+    if closed || (points.len() > 3 && points.first() == points.last()) {
+        return ring_shape(points).map(Some);
+    }
+    if let [from, to] = points[..] {
+        return Ok(Some(AnyShape::Segment { from, to }));
+    }
+    let Some(&first) = points.first() else {
+        return Ok(None);
+    };
+    let (min, max) = points.iter().fold((first, first), |(min, max), point| {
+        (
+            Point2D::new(min.x.min(point.x), min.y.min(point.y)),
+            Point2D::new(max.x.max(point.x), max.y.max(point.y)),
+        )
+    });
+    Ok(Some(AnyShape::Rect(BoundingRect::from_points(min, max))))
+}
+
+// Returns the shape of the supported entity, the other entities are skipped.
+fn entity_shape(entity: &EntityType) -> Result<Option<AnyShape>, Error> {
+    let point = |x: f64, y: f64| Point2D::new(x as f32, y as f32);
+    match entity {
+        EntityType::Line(line) => Ok(Some(AnyShape::Segment {
+            from: point(line.p1.x, line.p1.y),
+            to: point(line.p2.x, line.p2.y),
+        })),
+        EntityType::LwPolyline(polyline) => polyline_shape(
+            polyline
+                .vertices
+                .iter()
+                .map(|vertex| point(vertex.x, vertex.y))
+                .collect(),
+            polyline.is_closed(),
+        ),
+        EntityType::Polyline(polyline) => polyline_shape(
+            polyline
+                .vertices()
+                .map(|vertex| point(vertex.location.x, vertex.location.y))
+                .collect(),
+            polyline.is_closed(),
+        ),
+        // The third and the fourth corners of the solids are swapped.
+        EntityType::Solid(solid) => ring_shape(
+            [
+                &solid.first_corner,
+                &solid.second_corner,
+                &solid.fourth_corner,
+                &solid.third_corner,
+            ]
+            .iter()
+            .map(|corner| point(corner.x, corner.y))
+            .collect(),
+        )
+        .map(Some),
+        EntityType::Circle(circle) => Ok(Some(AnyShape::Circle {
+            center: point(circle.center.x, circle.center.y),
+            radius: circle.radius as f32,
+        })),
+        _ => Ok(None),
+    }
+}
+
+impl Input {
+    /// Parses the entities of the DXF drawing, either the ASCII or the binary one. The closed
+    /// `LWPOLYLINE` and `POLYLINE` entities become polygons or rects, the `LINE` entities and
+    /// the two-point polylines become segments, the open polylines are read by their bounding
+    /// rects. The filled `SOLID` and the `CIRCLE` entities are read too, the other entities
+    /// are skipped. The objects are named by their handles and get the DXF layers; the
+    /// elevations, the bulges and the blocks are ignored.
+    pub fn from_dxf_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut reader = decompress::decompress(reader).map_err(parse_error)?;
+        let drawing = Drawing::load(&mut reader).map_err(parse_error)?;
+        let mut objects = Vec::new();
+        for entity in drawing.entities() {
+            let shape = entity_shape(&entity.specific).map_err(|err| {
+                parse_error(anyhow::anyhow!(
+                    "The entity {}: {}",
+                    entity.common.handle.as_string(),
+                    err
+                ))
+            })?;
+            let Some(shape) = shape else {
+                continue;
+            };
+            objects.push(Object {
+                layer: Some(entity.common.layer.clone()),
+                ..Object::from_shape(entity.common.handle.as_string(), shape)
+            });
+        }
+        Ok(Self { objects })
+    }
+}
+
+#[test]
+fn test_dxf() {
+    use ::dxf::{
+        entities::{Circle, Entity, Line, LwPolyline, Text},
+        enums::AcadVersion,
+        LwPolylineVertex, Point,
+    };
+
+    // The lightweight polylines are written since R14.
+    let mut drawing = Drawing::new();
+    drawing.header.version = AcadVersion::R2000;
+    let mut add = |layer: &str, specific: EntityType| {
+        let mut entity = Entity::new(specific);
+        entity.common.layer = layer.to_owned();
+        drawing.add_entity(entity).common.handle.as_string()
+    };
+    let polyline = |points: &[(f64, f64)], closed: bool| {
+        let mut polyline = LwPolyline {
+            vertices: points
+                .iter()
+                .map(|&(x, y)| LwPolylineVertex {
+                    x,
+                    y,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        polyline.set_is_closed(closed);
+        EntityType::LwPolyline(polyline)
+    };
+    let handles = [
+        add(
+            "walls",
+            polyline(&[(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (0.0, 3.0)], true),
+        ),
+        add(
+            "ducts",
+            polyline(&[(2.0, 2.0), (6.0, 2.0), (4.0, 5.0)], true),
+        ),
+        add(
+            "ducts",
+            polyline(&[(10.0, 0.0), (12.0, 0.0), (12.0, 2.0)], false),
+        ),
+        add(
+            "pipes",
+            EntityType::Line(Line::new(
+                Point::new(1.0, 1.0, 0.0),
+                Point::new(5.0, 1.0, 0.0),
+            )),
+        ),
+        add(
+            "columns",
+            EntityType::Circle(Circle::new(Point::new(8.0, 8.0, 0.0), 1.0)),
+        ),
+        add("notes", EntityType::Text(Text::default())),
+    ];
+    let mut bytes = Vec::new();
+    drawing.save(&mut bytes).unwrap();
+
+    let input = Input::from_dxf_reader(bytes.as_slice()).unwrap();
+    let objects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.clone(),
+                x.layer.as_deref(),
+                [x.x, x.y, x.width, x.height],
+                x.shape.is_some(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        vec![
+            (
+                handles[0].clone(),
+                Some("walls"),
+                [0.0, 0.0, 4.0, 3.0],
+                false
+            ),
+            (
+                handles[1].clone(),
+                Some("ducts"),
+                [2.0, 2.0, 4.0, 3.0],
+                true
+            ),
+            (
+                handles[2].clone(),
+                Some("ducts"),
+                [10.0, 0.0, 2.0, 2.0],
+                false
+            ),
+            (
+                handles[3].clone(),
+                Some("pipes"),
+                [1.0, 1.0, 4.0, 0.0],
+                true
+            ),
+            (
+                handles[4].clone(),
+                Some("columns"),
+                [7.0, 7.0, 2.0, 2.0],
+                true
+            ),
+        ]
+    );
+
+    assert!(Input::from_dxf_reader("not a drawing".as_bytes()).is_err());
+    assert!(polyline_shape(vec![Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0)], true).is_err());
+}
@@ -0,0 +1,158 @@
+use std::{cmp::Ordering, mem};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{precision::decimal, BoundingRect, Error, Output, OutputSection};
+
+// Orders the rects by their corners, so the objects with the same names keep one order.
+fn compare_rects(a: &BoundingRect, b: &BoundingRect) -> Ordering {
+    let corners = |rect: &BoundingRect| {
+        let (from, to) = (rect.top_left(), rect.bottom_right());
+        [from.x, from.y, to.x, to.y]
+    };
+    corners(a)
+        .iter()
+        .zip(corners(b).iter())
+        .map(|(a, b)| a.total_cmp(b))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+// Writes the floats holding an `f32` by the shortest decimal of the `f32`, so `0.1` is not
+// written as `0.10000000149011612`. The map keys are already sorted.
+fn normalize(value: &mut Value) {
+    match value {
+        Value::Number(number) => {
+            let float = number
+                .as_f64()
+                .filter(|_| number.is_f64())
+                .filter(|float| (*float as f32) as f64 == *float);
+            if let Some(number) =
+                float.and_then(|float| serde_json::Number::from_f64(decimal(float as f32)))
+            {
+                *value = Value::Number(number);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize),
+        Value::Object(map) => map.values_mut().for_each(normalize),
+        _ => {}
+    }
+}
+
+/// Serializes the value as pretty printed JSON with the keys of every object sorted.
+fn to_canonical_json<T: Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let reason = |err: serde_json::Error| Error::SerializeError { reason: err.into() };
+    let mut value = serde_json::to_value(value).map_err(reason)?;
+    normalize(&mut value);
+    serde_json::to_vec_pretty(&value).map_err(reason)
+}
+
+impl Output {
+    /// Puts the results into the order independent of the search algorithm and of the
+    /// input order: the areas are sorted by the names and the rects, the names of every
+    /// intersection are ordered together with their properties and coverages, and the
+    /// intersections are sorted by the pairs of names and the rects. The wall times of the
+    /// statistics are dropped.
+    pub fn canonicalize(&mut self) {
+        for intersection in &mut self.intersections {
+            if intersection.names.0 > intersection.names.1 {
+                let names = &mut intersection.names;
+                mem::swap(&mut names.0, &mut names.1);
+                if let Some(properties) = intersection.properties.as_mut() {
+                    mem::swap(&mut properties.0, &mut properties.1);
+                }
+                if let Some(metrics) = intersection.metrics.as_mut() {
+                    metrics.coverage = (metrics.coverage.1, metrics.coverage.0);
+                }
+            }
+        }
+        self.areas.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then_with(|| compare_rects(&a.area, &b.area))
+        });
+        self.intersections.sort_by(|a, b| {
+            a.names
+                .cmp(&b.names)
+                .then_with(|| compare_rects(&a.area, &b.area))
+        });
+        if let Some(stats) = self.stats.as_mut() {
+            stats.phases.clear();
+        }
+    }
+
+    /// Serializes the canonicalized results or their section as JSON with the sorted keys and
+    /// the shortest floats, so the same scene always gives the same bytes, e.g. to keep the
+    /// results in git.
+    pub fn to_canonical_json(&self, section: Option<OutputSection>) -> Result<Vec<u8>, Error> {
+        let mut output = self.clone();
+        output.canonicalize();
+        match section {
+            None => to_canonical_json(&output),
+            Some(OutputSection::Intersections) => {
+                to_canonical_json(&output.intersections_section())
+            }
+            Some(OutputSection::Areas) => to_canonical_json(&output.areas_section()),
+            Some(OutputSection::Summary) => to_canonical_json(&crate::summarize(&output, 0)),
+        }
+    }
+}
+
+#[test]
+fn test_output_canonical_json() {
+    use crate::{analyze, Algorithm, Options, SceneBuilder};
+
+    let scene = |reversed: bool| {
+        let mut objects = vec![
+            ("C", 0.1, 0.2, 4.0, 4.0),
+            ("A", 3.0, 3.0, 1.0, 1.0),
+            ("B", 2.0, 0.0, 4.0, 4.0),
+            ("A", 0.0, 0.0, 1.0, 1.0),
+        ];
+        if reversed {
+            objects.reverse();
+        }
+        objects
+            .into_iter()
+            .fold(SceneBuilder::new(), |builder, (name, x, y, w, h)| {
+                builder.object(name, x, y, w, h)
+            })
+            .build()
+    };
+    let options = |algorithm, stats| Options {
+        algorithm: Some(algorithm),
+        stats,
+        ..Options::default()
+    };
+
+    let expected = analyze(scene(false), options(Algorithm::Naive, false))
+        .to_canonical_json(None)
+        .unwrap();
+    let actual = analyze(scene(true), options(Algorithm::Grid, false))
+        .to_canonical_json(None)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(actual).unwrap(),
+        String::from_utf8(expected).unwrap()
+    );
+
+    let expected = analyze(scene(false), options(Algorithm::Naive, true))
+        .to_canonical_json(None)
+        .unwrap();
+    let text = String::from_utf8(expected).unwrap();
+    let value = serde_json::from_str::<Value>(&text).unwrap();
+    let names = value["areas"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|area| area["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["A", "A", "B", "C"]);
+    assert_eq!(value["areas"][0]["area"]["from"]["x"], 0.0);
+    assert_eq!(value["stats"]["phases"], Value::Array(Vec::new()));
+    // The keys are sorted and the floats are the shortest ones.
+    assert!(text.contains(r#""area": {"#), "{}", text);
+    assert!(text.find(r#""area": {"#) < text.find(r#""name": "#));
+    assert!(text.contains(r#""x": 0.1,"#) && !text.contains("0.1000000"));
+}
@@ -0,0 +1,89 @@
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+
+use crate::{Intersection, Shape};
+
+/// Runs the intersection search on the worker threads and sends the found intersections
+/// to the returned channel as soon as they are found.
+///
+/// The channel is closed after all the workers have finished, so the receiver can simply be
+/// iterated. The order of intersections is not specified. Dropping the receiver stops the search.
+pub fn spawn_intersections<S>(objects: Vec<S>, workers: usize) -> mpsc::Receiver<Intersection>
+where
+    S: Shape + Send + Sync + 'static,
+{
+    let objects = Arc::new(objects);
+    let workers = workers.max(1);
+    let (tx, rx) = mpsc::channel();
+
+    for worker in 0..workers {
+        let objects = objects.clone();
+        let tx = tx.clone();
+        thread::spawn(move || {
+            // Interleaves the rows so that every worker gets both long and short ones.
+            for i in (worker..objects.len()).step_by(workers) {
+                let a_rect = objects[i].bounding_rect();
+                for j in (i + 1)..objects.len() {
+                    let area = match objects[i].intersection(&objects[j]) {
+                        Some(area) => area,
+                        None => continue,
+                    };
+                    // The shapes claiming an intersection of the merely touching rects are
+                    // skipped like by the other searches.
+                    let mtv = match a_rect.mtv(&objects[j].bounding_rect()) {
+                        Some(mtv) => mtv,
+                        None => continue,
+                    };
+
+                    let intersection = Intersection {
+                        area,
+                        a_idx: i,
+                        b_idx: j,
+                        mtv,
+                    };
+                    if tx.send(intersection).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    rx
+}
+
+#[test]
+fn test_spawn_intersections() {
+    use crate::{list_intersections, BoundingRect, Point2D};
+
+    let objects = (0..64)
+        .map(|i| {
+            let x = (i % 8) as f32 * 3.0;
+            let y = (i / 8) as f32 * 3.0;
+            BoundingRect::from_points(
+                Point2D { x, y },
+                Point2D {
+                    x: x + 4.0,
+                    y: y + 4.0,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for workers in [0, 1, 3, 8] {
+        let mut actual = spawn_intersections(objects.clone(), workers)
+            .iter()
+            .collect::<Vec<_>>();
+        actual.sort_by_key(|x| (x.a_idx, x.b_idx));
+        assert_eq!(actual, list_intersections(&objects), "workers: {}", workers);
+    }
+
+    let mut pairs = spawn_intersections(crate::claiming_shapes(), 2)
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx))
+        .collect::<Vec<_>>();
+    pairs.sort_unstable();
+    assert_eq!(pairs, vec![(0, 2), (1, 2)]);
+}
@@ -0,0 +1,1028 @@
+//! Command line subcommands and the options shared between them.
+
+use std::{
+    convert::TryInto,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use anyhow::Context;
+use regex::Regex;
+use sophya_prog_test::{
+    open_file, Algorithm, BorrowedInput, BorrowedObject, BoundingRect, CollisionMatrix, CsvColumns,
+    DedupeMode, Error, ImageSize, Input, InputFormat, LayerRule, Object, ObjectFilter,
+    ObjectIntersection, Options, Output, OutputFormat, OutputRecord, OutputSection, Point2D,
+    PropertyPredicate, RectForm, RectSoA, SortKey, SortOrder, Unit,
+};
+use structopt::StructOpt;
+
+pub mod bench;
+pub mod completions;
+pub mod config;
+pub mod convert;
+pub mod diff;
+pub mod generate;
+pub mod intersect;
+pub mod join;
+pub mod nms;
+pub mod query;
+pub mod render;
+pub mod repl;
+pub mod report;
+pub mod serve;
+pub mod stats;
+pub mod validate;
+pub mod view;
+
+use report::InFile;
+
+// How much is logged to stderr, stdout is kept for the results.
+#[derive(StructOpt)]
+pub struct Verbosity {
+    /// Log the progress and timings to stderr, repeat for more details (-vv). Requires the
+    /// "tracing" feature, RUST_LOG overrides the level
+    #[structopt(short, long, parse(from_occurrences), global = true)]
+    pub verbose: u8,
+    /// Log only the errors
+    #[structopt(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+// Whether `--quiet` has been given, set once by `Verbosity::init`.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+impl Verbosity {
+    /// Returns `true` if only the errors are logged, so the summaries and the notes written
+    /// to stderr are skipped.
+    pub fn is_quiet() -> bool {
+        QUIET.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the argument is one of the verbosity flags, e.g. `-vv`.
+    pub fn is_flag(arg: &str) -> bool {
+        matches!(arg, "--verbose" | "--quiet" | "-q")
+            || (arg.len() > 1 && arg.starts_with('-') && arg[1..].bytes().all(|c| c == b'v'))
+    }
+
+    /// Installs the stderr logger.
+    #[cfg(feature = "tracing")]
+    pub fn init(&self) {
+        use std::io::IsTerminal;
+
+        use tracing_subscriber::{filter::LevelFilter, fmt::format::FmtSpan, EnvFilter};
+
+        QUIET.store(self.quiet, Ordering::Relaxed);
+        let level = match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::ERROR,
+            (false, 0) => LevelFilter::WARN,
+            (false, 1) => LevelFilter::DEBUG,
+            _ => LevelFilter::TRACE,
+        };
+        let filter = EnvFilter::builder()
+            .with_default_directive(level.into())
+            .from_env_lossy();
+        // The closed spans report the time spent in every phase.
+        tracing_subscriber::fmt()
+            .with_env_filter(filter)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .with_ansi(std::io::stderr().is_terminal())
+            .init();
+    }
+
+    /// Installs the stderr logger.
+    #[cfg(not(feature = "tracing"))]
+    pub fn init(&self) {
+        QUIET.store(self.quiet, Ordering::Relaxed);
+        if self.verbose > 0 && !self.quiet {
+            eprintln!("Warning: logging requires the \"tracing\" feature");
+        }
+    }
+}
+
+// Where and how the input scenes are read from, no doc comment so that it does not
+// override the subcommand descriptions.
+#[derive(StructOpt)]
+pub struct InputOptions {
+    /// Input files (*.json, *.yaml, *.csv, *.toml, *.ndjson, *.xml), directories, glob
+    /// patterns or HTTP(S) URLs, reads stdin if omitted or "-"
+    pub input_files: Vec<PathBuf>,
+    /// Input format, detected by the file extension by default
+    #[structopt(long, possible_values = InputFormat::VARIANTS)]
+    pub input_format: Option<InputFormat>,
+    /// Header names of the CSV input columns, e.g. "name=id,x=left,y=top"
+    #[structopt(long)]
+    pub csv_columns: Option<CsvColumns>,
+    /// Merge all input files into one scene instead of processing them independently
+    #[structopt(long)]
+    pub merge: bool,
+    /// Keep only the objects with the names matching the regex, can be repeated
+    #[structopt(long, number_of_values = 1)]
+    pub include: Vec<Regex>,
+    /// Drop the objects with the names matching the regex, can be repeated
+    #[structopt(long, number_of_values = 1)]
+    pub exclude: Vec<Regex>,
+    /// Keep only the objects having the property with the given value, e.g. "type=door". All
+    /// the repeated predicates must match
+    #[structopt(long = "where", number_of_values = 1)]
+    pub properties: Vec<PropertyPredicate>,
+    /// Remove the duplicate objects keeping the first one: "geometry" removes the objects with
+    /// the same rect, "name" the ones with the same name and "iou=0.9" the ones overlapping
+    /// with at least the given intersection over union
+    #[structopt(long)]
+    pub dedupe: Option<DedupeMode>,
+    /// Reject the unknown object fields and the values of the wrong types, e.g. "widht", and
+    /// report every offending field with its path
+    #[structopt(long)]
+    pub strict: bool,
+    /// Multiply the input coordinates and sizes by the factor on load, e.g. "0.001" to read
+    /// the millimeters of a CAD export as meters
+    #[structopt(long, parse(try_from_str = parse_scale))]
+    pub scale: Option<f32>,
+    /// Unit of the coordinates after the scaling, written into the output. The coordinates are
+    /// converted into it from "--from-unit"
+    #[structopt(long, possible_values = Unit::VARIANTS)]
+    pub unit: Option<Unit>,
+    /// Unit of the input coordinates, e.g. "mm" with "--unit m" to read a CAD export in meters
+    #[structopt(long, possible_values = Unit::VARIANTS, requires = "unit")]
+    pub from_unit: Option<Unit>,
+    /// Size of the images the YOLO labels are normalized by, e.g. "640x480", the labels are
+    /// read and written in the normalized coordinates if omitted
+    #[structopt(long, conflicts_with = "image-dir")]
+    pub image_size: Option<ImageSize>,
+    /// Directory of the images the YOLO labels are normalized by, the size of every label
+    /// file is read from the header of the PNG or JPEG image with the same name, e.g.
+    /// "images/" for "labels/0001.txt" and "images/0001.jpg"
+    #[structopt(long)]
+    pub image_dir: Option<PathBuf>,
+}
+
+// Where and how the results are written to.
+#[derive(StructOpt)]
+pub struct OutputOptions {
+    /// Write the results into the given file instead of stdout, or into the given directory
+    /// if several files are processed independently
+    #[structopt(short, long)]
+    pub output: Option<PathBuf>,
+    /// Output format
+    #[structopt(long, default_value = "json", possible_values = OutputFormat::VARIANTS)]
+    pub format: OutputFormat,
+    /// Round the written coordinates and areas to the given number of decimal places, the
+    /// ties are rounded to the even digit
+    #[structopt(long)]
+    pub precision: Option<u32>,
+    /// Write the byte-identical JSON for the same scene: the keys are sorted, the floats are
+    /// the shortest ones and the areas and intersections are sorted by the names, ignoring
+    /// --sort-by. The wall times of the statistics are dropped
+    #[structopt(long)]
+    pub canonical: bool,
+    /// Table of the SQL output, e.g. `public.collisions`, the default is `intersections` or
+    /// `areas`
+    #[structopt(long, value_name = "table")]
+    pub sql_table: Option<String>,
+    /// SRID of the geometries of the SQL output
+    #[structopt(long)]
+    pub srid: Option<i32>,
+}
+
+/// When the table output is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Names of all choices.
+    pub const VARIANTS: &'static [&'static str] = &["auto", "always", "never"];
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            other => Err(format!("Unknown color choice \"{}\"", other)),
+        }
+    }
+}
+
+// How the intersections are searched and filtered.
+#[derive(StructOpt)]
+pub struct AnalysisOptions {
+    /// Drop the intersections with the smaller area, e.g. the slivers of the touching objects
+    #[structopt(long, default_value = "0")]
+    pub min_area: f32,
+    /// Drop the intersections with the smaller intersection over union of the object areas
+    #[structopt(long, default_value = "0")]
+    pub min_iou: f32,
+    /// Intersection search algorithm, chosen by the input size by default. All algorithms
+    /// give the same results
+    #[structopt(long, possible_values = Algorithm::VARIANTS)]
+    pub algorithm: Option<Algorithm>,
+    /// Number of the search threads, all cores by default. The results do not depend on it
+    #[structopt(short = "j", long)]
+    pub threads: Option<usize>,
+    /// Test only the objects of the same layer
+    #[structopt(long, conflicts_with_all = &["across-layers", "collision-matrix"])]
+    pub within_layers: bool,
+    /// Test only the objects of different layers
+    #[structopt(long, conflicts_with = "collision-matrix")]
+    pub across_layers: bool,
+    /// Test only the listed pairs of layers, e.g. "wall:wall,wall:door". The "*" layer matches
+    /// any one, the objects without a layer are matched only by it
+    #[structopt(long)]
+    pub collision_matrix: Option<CollisionMatrix>,
+    /// Test only the objects of the same z level, e.g. of the same floor of a building
+    #[structopt(long, conflicts_with = "z-pairs")]
+    pub same_z: bool,
+    /// Test only the listed pairs of z levels, e.g. "1:1,1:2" for the stairs of the first
+    /// floor. The "*" level matches any one, the objects without a level are matched only by it
+    #[structopt(long)]
+    pub z_pairs: Option<CollisionMatrix>,
+}
+
+impl AnalysisOptions {
+    /// Returns the library analysis options.
+    pub fn options(&self, stats: bool) -> Options {
+        Options {
+            stats,
+            min_area: self.min_area,
+            min_iou: self.min_iou,
+            algorithm: self.algorithm,
+            threads: self.threads,
+            layers: self.layers(),
+            same_z: self.same_z,
+            z_pairs: self.z_pairs.clone(),
+            ..Options::default()
+        }
+    }
+
+    fn layers(&self) -> LayerRule {
+        if let Some(matrix) = &self.collision_matrix {
+            LayerRule::Matrix(matrix.clone())
+        } else if self.within_layers {
+            LayerRule::Within
+        } else if self.across_layers {
+            LayerRule::Across
+        } else {
+            LayerRule::All
+        }
+    }
+}
+
+// How the results are ordered and which parts of them are written.
+#[derive(StructOpt)]
+pub struct ResultOptions {
+    /// Sort the areas and the intersections of the results, the equal ones keep the input order
+    #[structopt(long, default_value = "index", possible_values = SortKey::VARIANTS)]
+    pub sort_by: SortKey,
+    /// Sort in the descending order
+    #[structopt(long)]
+    pub descending: bool,
+    /// Write only the intersections, only the areas or the compact summary of the results
+    #[structopt(long, possible_values = OutputSection::VARIANTS)]
+    pub only: Option<OutputSection>,
+    /// Skip the given number of the sorted intersections, the output gets the "page" header
+    /// with the total number of them
+    #[structopt(long, default_value = "0")]
+    pub offset: usize,
+    /// Write at most the given number of the sorted intersections
+    #[structopt(long)]
+    pub limit: Option<usize>,
+    /// Color the table format and highlight the largest intersections, by default only if
+    /// the results are written to the terminal
+    #[structopt(long, default_value = "auto", possible_values = ColorChoice::VARIANTS)]
+    pub color: ColorChoice,
+}
+
+impl ResultOptions {
+    /// Sorts and paginates the results and serializes the selected parts of them.
+    pub fn encode(&self, mut output: Output, options: &OutputOptions) -> Result<Vec<u8>, Error> {
+        if let Some(decimals) = options.precision {
+            output.round(decimals);
+        }
+        if options.canonical {
+            output.canonicalize();
+        } else {
+            self.sort(&mut output);
+        }
+        // The summary describes all the intersections.
+        if (self.offset > 0 || self.limit.is_some()) && self.only != Some(OutputSection::Summary) {
+            output.paginate(self.offset, self.limit);
+        }
+        let format = options.format;
+        if options.canonical {
+            if format != OutputFormat::Json {
+                return Err(Error::SerializeError {
+                    reason: anyhow::anyhow!("--canonical supports only the JSON format"),
+                });
+            }
+            return output.to_canonical_json(self.only);
+        }
+        if format == OutputFormat::Table && self.colored(options) {
+            return output.to_table(self.only, true).map(String::into_bytes);
+        }
+        if format == OutputFormat::Sql {
+            let table = options.sql_table.as_deref();
+            match self.only {
+                Some(OutputSection::Areas) => return Ok(output.areas_to_sql(table, options.srid)),
+                None | Some(OutputSection::Intersections) => {
+                    return Ok(output.intersections_to_sql(table, options.srid))
+                }
+                Some(OutputSection::Summary) => {}
+            }
+        }
+        match self.only {
+            Some(section) => output.section_to_vec_as(section, format),
+            None => output.to_vec_as(format),
+        }
+    }
+
+    /// Whether the NDJSON records can be written as soon as they are found, i.e. the results
+    /// are neither sorted nor paginated.
+    pub fn streams(&self, options: &OutputOptions) -> bool {
+        options.format == OutputFormat::Ndjson
+            && !options.canonical
+            && self.sort_by == SortKey::Index
+            && !self.descending
+            && self.offset == 0
+            && self.limit.is_none()
+            && self.only != Some(OutputSection::Summary)
+    }
+
+    /// Whether the streamed record belongs to the selected part of the results.
+    pub fn selects(&self, record: &OutputRecord) -> bool {
+        !matches!(
+            (self.only, record),
+            (Some(OutputSection::Intersections), OutputRecord::Area(_))
+                | (Some(OutputSection::Areas), OutputRecord::Intersection(_))
+        )
+    }
+
+    /// Whether the tables are colored, `NO_COLOR` disables the automatic colors.
+    fn colored(&self, options: &OutputOptions) -> bool {
+        use std::io::IsTerminal;
+
+        match self.color {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                options.output.is_none()
+                    && std::env::var_os("NO_COLOR").is_none()
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+
+    fn sort(&self, output: &mut Output) {
+        let order = if self.descending {
+            SortOrder::Descending
+        } else {
+            SortOrder::Ascending
+        };
+        output.sort(self.sort_by, order);
+    }
+}
+
+/// Objects and intersections found in the processed files, summarized to stderr and checked
+/// by `--fail-on-intersection`.
+#[derive(Default)]
+pub struct Collisions {
+    objects: usize,
+    count: usize,
+    first: Option<(String, String)>,
+}
+
+/// Error of the `--fail-on-intersection` run that has found the intersections.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "Found {count} intersection(s), the first one is between \"{}\" and \"{}\"",
+    first.0,
+    first.1
+)]
+pub struct IntersectionsFound {
+    pub count: usize,
+    pub first: (String, String),
+}
+
+impl Collisions {
+    /// Counts the objects and the intersections of the results.
+    pub fn add(&mut self, output: &Output) {
+        self.objects += output.areas.len();
+        for intersection in &output.intersections {
+            self.add_intersection(intersection);
+        }
+    }
+
+    /// Counts the single object, e.g. the area of the streamed one.
+    pub fn add_object(&mut self) {
+        self.objects += 1;
+    }
+
+    /// Counts the single intersection, e.g. the streamed one.
+    pub fn add_intersection(&mut self, intersection: &ObjectIntersection) {
+        self.count += 1;
+        if self.first.is_none() {
+            self.first = Some(intersection.names.clone());
+        }
+    }
+
+    /// Prints the end-of-run summary line to stderr unless `--quiet` is given.
+    pub fn summarize(&self, elapsed: Duration) {
+        if Verbosity::is_quiet() {
+            return;
+        }
+        eprintln!(
+            "{} objects, {} intersections, {} ms",
+            self.objects,
+            self.count,
+            elapsed.as_millis()
+        );
+    }
+
+    /// Fails with the one line summary if any intersection has been found.
+    pub fn check(&self) -> Result<(), IntersectionsFound> {
+        match &self.first {
+            Some(first) => Err(IntersectionsFound {
+                count: self.count,
+                first: first.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses the comma separated list of the given number of coordinates.
+fn parse_coordinates<const N: usize>(s: &str) -> Result<[f32; N], String> {
+    let values = s
+        .split(',')
+        .map(|value| value.trim().parse::<f32>().map_err(|err| err.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    values
+        .try_into()
+        .map_err(|_| format!("Expected {} comma separated numbers, got \"{}\"", N, s))
+}
+
+/// Parses the point given as `x,y`.
+pub fn parse_point(s: &str) -> Result<Point2D, String> {
+    let [x, y] = parse_coordinates(s)?;
+    Ok(Point2D::new(x, y))
+}
+
+/// Parses the rect given by its corners as `x1,y1,x2,y2`.
+pub fn parse_rect(s: &str) -> Result<BoundingRect, String> {
+    let [x1, y1, x2, y2] = parse_coordinates(s)?;
+    Ok(BoundingRect::from_points(
+        Point2D::new(x1, y1),
+        Point2D::new(x2, y2),
+    ))
+}
+
+/// Parses the positive scale factor.
+fn parse_scale(s: &str) -> Result<f32, String> {
+    let factor = s.trim().parse::<f32>().map_err(|err| err.to_string())?;
+    if factor.is_finite() && factor > 0.0 {
+        Ok(factor)
+    } else {
+        Err(format!("Expected a positive scale factor, got \"{}\"", s))
+    }
+}
+
+/// Returns `true` if the input file is an HTTP(S) URL.
+fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|path| path.starts_with("http://") || path.starts_with("https://"))
+}
+
+impl InputOptions {
+    /// Expands the directories and glob patterns into the list of input files.
+    pub fn files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for path in &self.input_files {
+            if is_url(path) {
+                files.push(path.clone());
+            } else if path.is_dir() {
+                let mut entries = std::fs::read_dir(path)?
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                // The forced format keeps its files not detected by the extension, e.g. the
+                // `.txt` YOLO labels.
+                entries.retain(|entry| {
+                    entry.is_file()
+                        && (InputFormat::from_extension(entry).is_some()
+                            || self
+                                .input_format
+                                .is_some_and(|format| format.matches_extension(entry)))
+                });
+                entries.sort();
+                files.extend(entries);
+            } else if !path.exists() && path.to_string_lossy().contains(['*', '?', '[']) {
+                let pattern = path.to_string_lossy();
+                for entry in glob::glob(&pattern)? {
+                    files.push(entry?);
+                }
+            } else {
+                files.push(path.clone());
+            }
+        }
+
+        if self.input_files.is_empty() {
+            files.push(PathBuf::from("-"));
+        }
+        Ok(files)
+    }
+
+    /// Opens the given input file, URL or stdin and detects its format.
+    pub fn open(&self, path: &Path) -> Result<(Box<dyn Read>, InputFormat), Error> {
+        let reader: Box<dyn Read> = if path.as_os_str() == "-" {
+            Box::new(std::io::stdin().lock())
+        } else if is_url(path) {
+            open_url(path)?
+        } else {
+            Box::new(open_file(path)?)
+        };
+        Ok((reader, self.format(path)))
+    }
+
+    // Returns the format the input file, URL or stdin is opened with.
+    fn format(&self, path: &Path) -> InputFormat {
+        let format = if path.as_os_str() == "-" {
+            None
+        } else if is_url(path) {
+            InputFormat::from_url(&path.to_string_lossy())
+        } else {
+            InputFormat::from_extension(path)
+        };
+        self.input_format.or(format).unwrap_or_default()
+    }
+
+    /// Reads the given input file, URL or stdin, the parse errors are attached to the file.
+    pub fn read(&self, path: &Path) -> anyhow::Result<Input> {
+        let mut input = self.parse_file(path)?;
+        self.dedupe(&mut input);
+        Ok(input)
+    }
+
+    /// Parses the already opened input, the objects not passing the filters and the
+    /// duplicates are dropped.
+    pub fn read_from(&self, reader: impl Read, format: InputFormat) -> Result<Input, Error> {
+        let mut input = self.parse(reader, format, self.image_size)?;
+        self.dedupe(&mut input);
+        Ok(input)
+    }
+
+    /// Reads the given files as one scene, the objects of several files are merged. The
+    /// duplicates are searched in the merged scene.
+    pub fn read_merged(&self, files: &[PathBuf]) -> anyhow::Result<Input> {
+        match files {
+            [path] => self.read(path),
+            files => {
+                let mut input = files
+                    .iter()
+                    .map(|path| Ok((path.display().to_string(), self.parse_file(path)?)))
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .map(Input::merge)?;
+                self.dedupe(&mut input);
+                Ok(input)
+            }
+        }
+    }
+
+    /// Lazily reads the objects of the given NDJSON file one by one with the filters and the
+    /// scale applied, so the whole file never has to fit in memory. Returns `None` for the
+    /// other inputs and for the several files or the duplicates that need the whole scene.
+    pub fn stream_objects(
+        &self,
+        files: &[PathBuf],
+    ) -> anyhow::Result<Option<impl Iterator<Item = anyhow::Result<Object>> + '_>> {
+        let [path] = files else {
+            return Ok(None);
+        };
+        if self.format(path) != InputFormat::Ndjson || self.strict || self.dedupe.is_some() {
+            return Ok(None);
+        }
+        let (reader, _) = self.open(path)?;
+        let reader = sophya_prog_test::decompress(reader)
+            .map_err(|err| Error::ParseError { reason: err.into() })?;
+        Ok(Some(
+            self.prepare_objects(path, Input::ndjson_objects(reader))?,
+        ))
+    }
+
+    /// Reads the text of the given JSON file to parse it by [`InputOptions::borrowed_objects`].
+    /// Returns `None` for the other inputs like [`InputOptions::stream_objects`].
+    pub fn read_json_text(&self, files: &[PathBuf]) -> anyhow::Result<Option<String>> {
+        let [path] = files else {
+            return Ok(None);
+        };
+        if self.format(path) != InputFormat::Json || self.strict || self.dedupe.is_some() {
+            return Ok(None);
+        }
+        let (reader, _) = self.open(path)?;
+        let mut text = String::new();
+        sophya_prog_test::decompress(reader)
+            .and_then(|mut reader| reader.read_to_string(&mut text))
+            .map_err(|err| Error::ParseError { reason: err.into() })
+            .with_context(|| InFile(path.to_owned()))?;
+        Ok(Some(text))
+    }
+
+    /// Parses the JSON text of the file as the [`BorrowedInput`] and converts its objects one by
+    /// one with the filters and the scale applied, so the names are copied only once into the
+    /// areas and the whole owned scene is never kept.
+    pub fn borrowed_objects<'a>(
+        &self,
+        path: &Path,
+        text: &'a str,
+    ) -> anyhow::Result<impl Iterator<Item = anyhow::Result<Object>> + 'a> {
+        let input = BorrowedInput::from_json(text).with_context(|| InFile(path.to_owned()))?;
+        Ok(self.prepare_objects(
+            path,
+            input.objects.into_iter().map(BorrowedObject::into_owned),
+        )?)
+    }
+
+    // Lazily drops the objects not passing the filters and scales the rest, the errors are
+    // attached to the file.
+    fn prepare_objects<'a, E: Into<anyhow::Error>>(
+        &self,
+        path: &Path,
+        objects: impl Iterator<Item = Result<Object, E>> + 'a,
+    ) -> Result<impl Iterator<Item = anyhow::Result<Object>> + 'a, Error> {
+        let factor = self.factor()?;
+        let file = path.to_owned();
+        let filter = ObjectFilter {
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            properties: self.properties.clone(),
+        };
+        Ok(objects.filter_map(move |object| match object {
+            Ok(mut object) if filter.matches(&object) => {
+                if let Some(factor) = factor {
+                    object.scale(factor);
+                }
+                Some(Ok(object))
+            }
+            Ok(_) => None,
+            Err(err) => Some(Err(err.into().context(InFile(file.clone())))),
+        }))
+    }
+
+    /// Reads the areas of the objects of the given files as one scene of the rects. The Parquet
+    /// and Arrow files are read straight into the arrays unless the objects are filtered, scaled
+    /// or deduplicated, the other inputs are read as the usual scene.
+    pub fn read_rects(&self, files: &[PathBuf]) -> anyhow::Result<RectSoA> {
+        #[cfg(feature = "arrow")]
+        if self.include.is_empty()
+            && self.exclude.is_empty()
+            && self.properties.is_empty()
+            && self.dedupe.is_none()
+            && self.scale.is_none()
+            && self.from_unit.is_none()
+            && !self.strict
+            && files
+                .iter()
+                .all(|path| matches!(self.format(path), InputFormat::Parquet | InputFormat::Arrow))
+        {
+            let columns = self.csv_columns.clone().unwrap_or_default();
+            let mut rects = RectSoA::new();
+            for path in files {
+                let (reader, format) = self.open(path)?;
+                let file_rects = if format == InputFormat::Parquet {
+                    RectSoA::from_parquet_reader(reader, &columns)
+                } else {
+                    RectSoA::from_arrow_reader(reader, &columns)
+                };
+                rects.extend(file_rects.with_context(|| InFile(path.to_owned()))?.iter());
+            }
+            return Ok(rects);
+        }
+
+        let input = self.read_merged(files)?;
+        Ok(input
+            .objects
+            .iter()
+            .map(|object| object.area().area)
+            .collect())
+    }
+
+    // Parses the given input file, URL or stdin, the shapefiles are read with the attributes
+    // of the `.dbf` files next to them.
+    fn parse_file(&self, path: &Path) -> anyhow::Result<Input> {
+        #[cfg(feature = "shapefile")]
+        if path.as_os_str() != "-"
+            && !is_url(path)
+            && self
+                .input_format
+                .or_else(|| InputFormat::from_extension(path))
+                == Some(InputFormat::Shapefile)
+        {
+            let input = Input::from_shapefile(path).with_context(|| InFile(path.to_owned()))?;
+            return Ok(self.prepare(input)?);
+        }
+        let (reader, format) = self.open(path)?;
+        let image_size = if format == InputFormat::Yolo {
+            self.image_size_of(path)?
+        } else {
+            None
+        };
+        self.parse(reader, format, image_size)
+            .with_context(|| InFile(path.to_owned()))
+    }
+
+    /// Returns the size of the image the YOLO labels of the given file are normalized by, it
+    /// is read from the image of the same name in the image directory if it is set.
+    pub fn image_size_of(&self, path: &Path) -> anyhow::Result<Option<ImageSize>> {
+        let Some(dir) = &self.image_dir else {
+            return Ok(self.image_size);
+        };
+        // The compression extension is dropped along with the label one.
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split('.').next())
+            .filter(|name| !name.is_empty() && path.as_os_str() != "-")
+            .ok_or_else(|| anyhow::anyhow!("The labels read from stdin have no image"))?;
+        let image = ["png", "jpg", "jpeg", "PNG", "JPG", "JPEG"]
+            .iter()
+            .map(|extension| dir.join(format!("{}.{}", name, extension)))
+            .find(|image| image.is_file())
+            .ok_or_else(|| {
+                anyhow::anyhow!("No PNG or JPEG image \"{}\" in \"{}\"", name, dir.display())
+            })?;
+        let size = ImageSize::from_image_reader(open_file(&image)?)
+            .with_context(|| InFile(image.clone()))?;
+        Ok(Some(size))
+    }
+
+    /// Returns the size the YOLO labels written for the given files are normalized by.
+    pub fn labels_size(
+        &self,
+        files: &[PathBuf],
+        format: OutputFormat,
+    ) -> anyhow::Result<Option<ImageSize>> {
+        match files {
+            _ if format != OutputFormat::Yolo => Ok(None),
+            [file] => self.image_size_of(file),
+            _ if self.image_dir.is_some() => Err(anyhow::anyhow!(
+                "The labels merged from several images cannot be normalized by one image size"
+            )),
+            _ => Ok(self.image_size),
+        }
+    }
+
+    fn parse(
+        &self,
+        reader: impl Read,
+        format: InputFormat,
+        image_size: Option<ImageSize>,
+    ) -> Result<Input, Error> {
+        let columns = self.csv_columns.clone().unwrap_or_default();
+        let input = match (format, self.strict) {
+            (InputFormat::Csv, false) => Input::from_csv_reader(reader, &columns)?,
+            (InputFormat::Csv, true) => Input::from_csv_reader_strict(reader, &columns)?,
+            (InputFormat::Yolo, _) => {
+                Input::from_yolo_reader(reader, image_size.unwrap_or_default())?
+            }
+            #[cfg(feature = "arrow")]
+            (InputFormat::Parquet, _) => Input::from_parquet_reader(reader, &columns)?,
+            #[cfg(feature = "arrow")]
+            (InputFormat::Arrow, _) => Input::from_arrow_reader(reader, &columns)?,
+            (format, false) => Input::from_reader_as(reader, format)?,
+            (format, true) => Input::from_reader_strict(reader, format)?,
+        };
+        self.prepare(input)
+    }
+
+    // Returns the factor of `--scale` and the conversion from `--from-unit` into `--unit`.
+    fn factor(&self) -> Result<Option<f32>, Error> {
+        let conversion = match (self.from_unit, self.unit) {
+            (Some(from), Some(to)) => {
+                Some(from.factor_to(to).ok_or_else(|| Error::ParseError {
+                    reason: anyhow::anyhow!(
+                        "The pixels cannot be converted from or into the physical units"
+                    ),
+                })?)
+            }
+            _ => None,
+        };
+        Ok(match (self.scale, conversion) {
+            (Some(scale), Some(conversion)) => Some(scale * conversion),
+            (scale, conversion) => scale.or(conversion),
+        })
+    }
+
+    // Drops the objects not passing the filters and scales the rest.
+    fn prepare(&self, mut input: Input) -> Result<Input, Error> {
+        input.filter(&ObjectFilter {
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            properties: self.properties.clone(),
+        });
+        if let Some(factor) = self.factor()? {
+            input.scale(factor);
+        }
+        Ok(input)
+    }
+
+    /// Removes the duplicates and reports them to stderr.
+    fn dedupe(&self, input: &mut Input) {
+        let Some(mode) = self.dedupe else {
+            return;
+        };
+        let duplicates = input.dedupe(mode);
+        if let (Some(first), false) = (duplicates.first(), Verbosity::is_quiet()) {
+            eprintln!(
+                "Removed {} duplicate object(s), e.g. \"{}\" of \"{}\"",
+                duplicates.len(),
+                first.name,
+                first.original
+            );
+        }
+        #[cfg(feature = "tracing")]
+        for duplicate in &duplicates {
+            tracing::debug!(name = %duplicate.name, original = %duplicate.original, "Duplicate removed");
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+fn open_url(url: &Path) -> Result<Box<dyn Read>, Error> {
+    Ok(Box::new(sophya_prog_test::open_url(
+        &url.to_string_lossy(),
+    )?))
+}
+
+#[cfg(not(feature = "net"))]
+fn open_url(url: &Path) -> Result<Box<dyn Read>, Error> {
+    Err(Error::FileReadError {
+        path: url.to_owned(),
+        reason: anyhow::anyhow!("URL inputs require the \"net\" feature"),
+    })
+}
+
+impl OutputOptions {
+    /// Rounds the objects to the precision and serializes them in the given rect form, the
+    /// YOLO labels are normalized by the image size.
+    pub fn encode_input(
+        &self,
+        mut input: Input,
+        form: RectForm,
+        image_size: Option<ImageSize>,
+    ) -> Result<Vec<u8>, Error> {
+        if let Some(decimals) = self.precision {
+            input.round(decimals);
+        }
+        match (self.format, image_size) {
+            (OutputFormat::Yolo, Some(size)) => input.to_yolo_labels(size),
+            (format, _) => input.to_vec_as(format, form),
+        }
+    }
+
+    /// Writes the encoded results into the output file or stdout.
+    pub fn write(&self, contents: Vec<u8>) -> anyhow::Result<()> {
+        self.write_to(self.output.as_deref(), contents)
+    }
+
+    /// Writes the encoded results of an input file into its path in the output directory
+    /// returned by [`OutputOptions::paths_in_dir`].
+    pub fn write_into_dir(&self, path: &Path, contents: Vec<u8>) -> anyhow::Result<()> {
+        create_parent_dir(path)?;
+        self.write_to(Some(path), contents)
+    }
+
+    /// Streams the results into the output file or stdout while they are written by the
+    /// callback, the file is replaced only after the callback succeeds.
+    pub fn stream(
+        &self,
+        write: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        match self.output.as_deref() {
+            Some(path) => write_atomically_with(path, write),
+            // Stdout is line buffered, so every written line is flushed.
+            None => write(&mut std::io::stdout().lock()),
+        }
+    }
+
+    /// Streams the results of an input file into its path in the output directory returned
+    /// by [`OutputOptions::paths_in_dir`].
+    pub fn stream_into_dir(
+        &self,
+        path: &Path,
+        write: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        create_parent_dir(path)?;
+        write_atomically_with(path, write)
+    }
+
+    /// Returns the paths of the results of the input files in the output directory. The
+    /// directories of the files are kept relative to their common root, e.g. "a/x.json" and
+    /// "b/x.json" are written into "DIR/a/x.json" and "DIR/b/x.json", and the files mapped to
+    /// the same result, e.g. "x.json" and "x.yaml", are rejected.
+    pub fn paths_in_dir(&self, files: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+        let dir = self.output.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("--output DIR is required to process several files independently")
+        })?;
+
+        // The URLs are named by their last segments only.
+        let parents = files
+            .iter()
+            .map(|file| match file.parent() {
+                Some(parent) if !is_url(file) => Ok(Some(std::path::absolute(parent)?)),
+                _ => Ok(None),
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let root = parents
+            .iter()
+            .flatten()
+            .fold(None::<PathBuf>, |root, parent| match root {
+                None => Some(parent.clone()),
+                Some(root) => Some(
+                    root.components()
+                        .zip(parent.components())
+                        .take_while(|(a, b)| a == b)
+                        .map(|(a, _)| a)
+                        .collect(),
+                ),
+            })
+            .unwrap_or_default();
+
+        let mut sources = std::collections::HashMap::new();
+        let mut paths = Vec::with_capacity(files.len());
+        for (file, parent) in files.iter().zip(&parents) {
+            let name = Path::new(file.file_stem().unwrap_or(file.as_os_str()))
+                .with_extension(self.format.extension());
+            let mut path = dir.to_owned();
+            if let Some(parent) = parent {
+                path.extend(
+                    parent
+                        .strip_prefix(&root)
+                        .unwrap_or(parent)
+                        .components()
+                        .filter(|component| matches!(component, std::path::Component::Normal(_))),
+                );
+            }
+            path.push(name);
+            if let Some(other) = sources.insert(path.clone(), file) {
+                anyhow::bail!(
+                    "The input files \"{}\" and \"{}\" are both written into \"{}\"",
+                    other.display(),
+                    file.display(),
+                    path.display()
+                );
+            }
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    fn write_to(&self, path: Option<&Path>, mut contents: Vec<u8>) -> anyhow::Result<()> {
+        if !self.format.is_binary() && !contents.ends_with(b"\n") {
+            contents.push(b'\n');
+        }
+        match path {
+            Some(path) => write_atomically(path, &contents),
+            None => Ok(std::io::stdout().lock().write_all(&contents)?),
+        }
+    }
+}
+
+// Creates the directory of the result file with its ancestors.
+fn create_parent_dir(path: &Path) -> anyhow::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => Ok(std::fs::create_dir_all(dir)?),
+        _ => Ok(()),
+    }
+}
+
+/// Writes the contents into a temporary file next to the path and renames it, so readers
+/// never observe a partially written file.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    write_atomically_with(path, |file| Ok(file.write_all(contents)?))
+}
+
+/// Like [`write_atomically`], but the contents are written by the callback.
+pub fn write_atomically_with(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    let mut writer = std::io::BufWriter::new(file.as_file_mut());
+    write(&mut writer)?;
+    writer.flush()?;
+    drop(writer);
+    file.as_file().sync_all()?;
+    file.persist(path)?;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::{io::Write, time::Instant};
+
+use sophya_prog_test::{
+    generate_scene, list_intersections_soa_using_with_stats, list_intersections_using_with_stats,
+    Algorithm, Distribution, GenerateOptions, Object, RectSoA,
+};
+use structopt::StructOpt;
+
+use super::InputOptions;
+
+/// Compares the timing, throughput and memory of the intersection search algorithms
+///
+/// The input files are used if given, otherwise a random scene is generated. The outputs
+/// of all algorithms are checked to match the first one, which is the naive by default.
+#[derive(StructOpt)]
+pub struct Bench {
+    #[structopt(flatten)]
+    input: InputOptions,
+    /// Number of the objects of the generated scene
+    #[structopt(long, default_value = "10000")]
+    count: usize,
+    /// Seed of the generated scene
+    #[structopt(long, default_value = "0")]
+    seed: u64,
+    /// Density of the generated scene
+    #[structopt(long, default_value = "0.1")]
+    density: f32,
+    /// Placement of the objects of the generated scene
+    #[structopt(long, default_value = "uniform", possible_values = Distribution::VARIANTS)]
+    distribution: Distribution,
+    /// Algorithms to compare, all of them by default
+    #[structopt(long, use_delimiter = true, possible_values = Algorithm::VARIANTS)]
+    algorithms: Vec<Algorithm>,
+    /// Number of the runs of every algorithm, the best time is reported
+    #[structopt(long, default_value = "3")]
+    runs: usize,
+    /// Number of the search threads, all cores by default
+    #[structopt(short = "j", long)]
+    threads: Option<usize>,
+    /// Search the bounding rects stored as the separate arrays of their edges instead of the
+    /// objects, the exact shapes are not tested then
+    #[structopt(long)]
+    soa: bool,
+}
+
+impl Bench {
+    pub fn run(self) -> anyhow::Result<()> {
+        let generated = || {
+            generate_scene(&GenerateOptions {
+                count: self.count,
+                seed: self.seed,
+                density: self.density,
+                distribution: self.distribution,
+            })
+        };
+        // The rects of the columnar files are read straight into the arrays.
+        let (areas, rects) = match (self.input.input_files.is_empty(), self.soa) {
+            (true, true) => {
+                let input = generated();
+                let rects = input.objects.iter().map(|object| object.area().area);
+                (Vec::new(), rects.collect())
+            }
+            (false, true) => (Vec::new(), self.input.read_rects(&self.input.files()?)?),
+            (generate, false) => {
+                let input = if generate {
+                    generated()
+                } else {
+                    self.input.read_merged(&self.input.files()?)?
+                };
+                let areas = input.objects.iter().map(Object::area).collect::<Vec<_>>();
+                (areas, RectSoA::new())
+            }
+        };
+        let shapes = if self.soa { rects.len() } else { areas.len() };
+        let algorithms = if self.algorithms.is_empty() {
+            Algorithm::ALL.to_vec()
+        } else {
+            self.algorithms.clone()
+        };
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads.unwrap_or(0))
+            .build()?;
+
+        let mut stdout = std::io::stdout().lock();
+        writeln!(
+            stdout,
+            "{:<13} {:>12} {:>14} {:>14} {:>14} {:>12}  output",
+            "algorithm", "time, ms", "shapes/s", "tested pairs", "intersections", "memory, KiB"
+        )?;
+        let mut reference = None;
+        let mut mismatches = Vec::new();
+        for algorithm in algorithms {
+            let mut best = f64::INFINITY;
+            let mut result = None;
+            for _ in 0..self.runs.max(1) {
+                let start = Instant::now();
+                let found = pool.install(|| {
+                    if self.soa {
+                        list_intersections_soa_using_with_stats(&rects, algorithm)
+                    } else {
+                        list_intersections_using_with_stats(&areas, algorithm)
+                    }
+                });
+                best = best.min(start.elapsed().as_secs_f64());
+                result = Some(found);
+            }
+            let (intersections, stats) = result.expect("at least one run is made");
+
+            let verdict = match &reference {
+                None => {
+                    reference = Some(intersections);
+                    "reference"
+                }
+                Some(expected) if *expected == intersections => "matches",
+                Some(_) => {
+                    mismatches.push(algorithm.name());
+                    "DIFFERS"
+                }
+            };
+            writeln!(
+                stdout,
+                "{:<13} {:>12.3} {:>14.0} {:>14} {:>14} {:>12.1}  {}",
+                algorithm.name(),
+                best * 1e3,
+                shapes as f64 / best,
+                stats.candidate_pairs,
+                stats.intersections,
+                stats.peak_memory_estimate as f64 / 1024.0,
+                verdict
+            )?;
+        }
+
+        if !mismatches.is_empty() {
+            anyhow::bail!(
+                "The outputs of {} differ from the reference",
+                mismatches.join(", ")
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+use structopt::{
+    clap::{App, Shell},
+    StructOpt,
+};
+
+/// Prints the completion script of the given shell
+#[derive(StructOpt)]
+pub struct Completions {
+    /// Shell to generate the script for
+    #[structopt(possible_values = &Shell::variants())]
+    shell: Shell,
+}
+
+impl Completions {
+    /// Writes the completions of the given command line definition to stdout.
+    pub fn run(self, mut app: App) -> anyhow::Result<()> {
+        let name = app.get_name().to_owned();
+        app.gen_completions_to(name, self.shell, &mut std::io::stdout().lock());
+        Ok(())
+    }
+}
@@ -0,0 +1,457 @@
+//! Defaults of the command line options read from the TOML config file and the environment.
+//!
+//! The top level keys are the defaults of every subcommand having the option with the same
+//! name, the `[subcommand]` tables are the defaults of the given subcommand only:
+//!
+//! ```toml
+//! format = "yaml"
+//! min_area = 0.5
+//! algorithm = "rtree"
+//!
+//! [render]
+//! width = 1600
+//! legend = true
+//! ```
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use structopt::clap::{ArgMatches, ErrorKind};
+
+/// Name of the project config discovered in the current directory or its parents.
+pub const FILE_NAME: &str = "sophya.toml";
+/// Prefix of the environment variables setting the options, e.g. `SHAPES_MIN_AREA`.
+pub const ENV_PREFIX: &str = "SHAPES_";
+
+/// Parses the subcommand with the single given argument and returns its matches.
+pub type Probe<'a> = dyn Fn(OsString) -> Result<ArgMatches<'static>, ErrorKind> + 'a;
+
+/// Returns the config file given by `--config` or `SHAPES_CONFIG`, or the discovered project
+/// config unless `--no-config` is given.
+pub fn find(args: &[OsString]) -> anyhow::Result<Option<PathBuf>> {
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {
+        let arg = arg.to_string_lossy();
+        if arg == "--no-config" {
+            return Ok(None);
+        } else if arg == "--config" {
+            let path = args.next().context("--config requires a path")?;
+            return Ok(Some(PathBuf::from(path)));
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            return Ok(Some(PathBuf::from(path)));
+        }
+    }
+    if let Some(path) = std::env::var_os(format!("{}CONFIG", ENV_PREFIX)) {
+        return Ok(Some(PathBuf::from(path)));
+    }
+
+    let dir = std::env::current_dir()?;
+    Ok(dir
+        .ancestors()
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|path| path.is_file()))
+}
+
+/// Reads the config and converts the defaults of the subcommand into its arguments.
+///
+/// The top level options the subcommand does not have, as reported by the `probe`, are
+/// skipped, the unknown options of its own table are an error.
+pub fn args(path: &Path, subcommand: &str, probe: &Probe) -> anyhow::Result<Vec<OsString>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Unable to read config \"{}\"", path.display()))?;
+    let config = text
+        .parse::<toml::Table>()
+        .with_context(|| format!("Unable to parse config \"{}\"", path.display()))?;
+
+    let mut args = Vec::new();
+    for (key, value) in &config {
+        if value.is_table() {
+            continue;
+        }
+        if let Some(option) = option_args(probe, key, config_value(key, value)?) {
+            args.extend(option);
+        }
+    }
+    // The later occurrences override the earlier ones, so the subcommand defaults win. The
+    // values named like the subcommand are the top level options, e.g. `stats = true`.
+    if let Some(table) = config.get(subcommand).and_then(toml::Value::as_table) {
+        for (key, value) in table {
+            let option = option_args(probe, key, config_value(key, value)?).with_context(|| {
+                format!(
+                    "The \"{}\" subcommand has no \"{}\" option set by the config",
+                    subcommand, key
+                )
+            })?;
+            args.extend(option);
+        }
+    }
+    Ok(args)
+}
+
+/// Returns the defaults of the subcommand read from the config, if any, and from the
+/// environment variables. They are inserted before the given arguments and the later
+/// occurrences override the earlier ones, so the environment overrides the config and the
+/// given arguments override both.
+pub fn defaults(
+    config: Option<&Path>,
+    subcommand: &str,
+    probe: &Probe,
+    vars: impl IntoIterator<Item = (OsString, OsString)>,
+) -> anyhow::Result<Vec<OsString>> {
+    let mut defaults = match config {
+        Some(path) => args(path, subcommand, probe)?,
+        None => Vec::new(),
+    };
+    defaults.extend(env_args(probe, vars));
+    Ok(defaults)
+}
+
+/// Converts the `SHAPES_*` environment variables into the arguments of the subcommand, e.g.
+/// `SHAPES_MIN_AREA=1` into `--min-area=1`.
+///
+/// The variables of the options the subcommand does not have are skipped.
+pub fn env_args(
+    probe: &Probe,
+    vars: impl IntoIterator<Item = (OsString, OsString)>,
+) -> Vec<OsString> {
+    let mut vars = vars
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let key = key.to_str()?.strip_prefix(ENV_PREFIX)?.to_lowercase();
+            Some((key, value))
+        })
+        .filter(|(key, _)| key != "config")
+        .collect::<Vec<_>>();
+    vars.sort();
+
+    vars.into_iter()
+        .filter_map(|(key, value)| option_args(probe, &key, value))
+        .flatten()
+        .collect()
+}
+
+/// Formats the config value like the command line one, the arrays are joined by commas.
+fn config_value(key: &str, value: &toml::Value) -> anyhow::Result<OsString> {
+    let value = match value {
+        toml::Value::String(value) => value.clone(),
+        toml::Value::Integer(_) | toml::Value::Float(_) | toml::Value::Boolean(_) => {
+            value.to_string()
+        }
+        toml::Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                toml::Value::String(value) => Ok(value.clone()),
+                toml::Value::Integer(_) | toml::Value::Float(_) => Ok(value.to_string()),
+                _ => anyhow::bail!("Unsupported value of \"{}\" in the config", key),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?
+            .join(","),
+        _ => anyhow::bail!("Unsupported value of \"{}\" in the config", key),
+    };
+    Ok(value.into())
+}
+
+/// Converts the option with the given name and value into the arguments, e.g. `min_area`
+/// into `--min-area=1`. Returns `None` if the subcommand has no such option.
+///
+/// The flags are set by `1`, `true` or `yes` and omitted otherwise.
+fn option_args(probe: &Probe, key: &str, value: OsString) -> Option<Vec<OsString>> {
+    let name = key.replace('-', "_");
+    let flag = format!("--{}", name.replace('_', "-"));
+    let mut option = OsString::from(format!("{}=", flag));
+    option.push(&value);
+
+    match probe(option.clone()) {
+        Err(
+            ErrorKind::UnknownArgument | ErrorKind::HelpDisplayed | ErrorKind::VersionDisplayed,
+        ) => None,
+        // The flags ignore the value.
+        Ok(matches) if matches.is_present(&name) && matches.value_of_os(&name).is_none() => {
+            let value = value.to_string_lossy().to_lowercase();
+            let set = matches!(value.as_str(), "1" | "true" | "yes");
+            Some(if set { vec![flag.into()] } else { Vec::new() })
+        }
+        // The invalid values are reported by the parser.
+        _ => Some(vec![option]),
+    }
+}
+
+// Subcommand with the value options `--format`, `--min-area` and `--include` and the flag
+// `--stats` probed like the real ones.
+#[cfg(test)]
+fn probe(option: OsString) -> Result<ArgMatches<'static>, ErrorKind> {
+    use structopt::clap::{App, Arg};
+
+    App::new("intersect")
+        .arg(Arg::with_name("format").long("format").takes_value(true))
+        .arg(
+            Arg::with_name("min_area")
+                .long("min-area")
+                .takes_value(true),
+        )
+        .arg(Arg::with_name("include").long("include").takes_value(true))
+        .arg(Arg::with_name("stats").long("stats"))
+        .get_matches_from_safe([OsString::from("intersect"), option])
+        .map_err(|err| err.kind)
+}
+
+#[test]
+fn test_option_args() {
+    let cases = vec![
+        (
+            "min_area",
+            "0.5",
+            Some(vec!["--min-area=0.5"]),
+            "underscores",
+        ),
+        ("min-area", "0.5", Some(vec!["--min-area=0.5"]), "dashes"),
+        ("format", "yaml", Some(vec!["--format=yaml"]), "value"),
+        ("stats", "true", Some(vec!["--stats"]), "flag set"),
+        (
+            "stats",
+            "YES",
+            Some(vec!["--stats"]),
+            "flag set in upper case",
+        ),
+        ("stats", "1", Some(vec!["--stats"]), "flag set by one"),
+        ("stats", "false", Some(vec![]), "flag unset"),
+        ("width", "1600", None, "unknown option"),
+        ("help", "true", None, "help"),
+    ];
+    for case in cases {
+        let expected = case
+            .2
+            .map(|args| args.into_iter().map(OsString::from).collect::<Vec<_>>());
+        assert_eq!(
+            option_args(&probe, case.0, case.1.into()),
+            expected,
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+    }
+}
+
+#[test]
+fn test_find() {
+    let cases = vec![
+        (vec!["--no-config", "a.json"], Some(None), "disabled"),
+        (
+            vec!["--config", "a.toml"],
+            Some(Some("a.toml")),
+            "separate path",
+        ),
+        (
+            vec!["--config=b.toml", "a.json"],
+            Some(Some("b.toml")),
+            "joined path",
+        ),
+        (
+            vec!["--no-config", "--config=b.toml"],
+            Some(None),
+            "first one",
+        ),
+        (vec!["intersect", "--config"], None, "missing path"),
+    ];
+    for case in cases {
+        let args = std::iter::once("tool")
+            .chain(case.0)
+            .map(OsString::from)
+            .collect::<Vec<_>>();
+        let found = find(&args)
+            .ok()
+            .map(|path| path.map(|path| path.display().to_string()));
+        assert_eq!(
+            found.as_ref().map(|path| path.as_deref()),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+}
+
+#[test]
+fn test_config_args() {
+    let config = |text: &str| {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, text.as_bytes()).unwrap();
+        file
+    };
+    let args = |text: &str, subcommand: &str| {
+        let file = config(text);
+        args(file.path(), subcommand, &probe).map(|args| {
+            args.into_iter()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // The top level options the subcommand has are taken first, then its own table. The
+    // tables of the other subcommands are skipped and `stats` is the option, not the table
+    // of the subcommand.
+    let text = r#"
+        format = "yaml"
+        min_area = 0.5
+        stats = true
+        width = 1600
+
+        [intersect]
+        format = "csv"
+        include = ["^a", "^b"]
+
+        [render]
+        legend = true
+    "#;
+    assert_eq!(
+        args(text, "intersect").unwrap(),
+        vec![
+            "--format=yaml",
+            "--min-area=0.5",
+            "--stats",
+            "--format=csv",
+            "--include=^a,^b"
+        ]
+    );
+    assert_eq!(
+        args(text, "stats").unwrap(),
+        vec!["--format=yaml", "--min-area=0.5", "--stats"]
+    );
+
+    let cases = vec![
+        (
+            "[intersect]\nwidth = 1",
+            "has no \"width\" option",
+            "unknown option",
+        ),
+        ("min_area = 2020-01-01", "Unsupported value", "datetime"),
+        ("include = [[1]]", "Unsupported value", "nested array"),
+        ("format = ", "Unable to parse config", "broken toml"),
+    ];
+    for case in cases {
+        let err = args(case.0, "intersect").unwrap_err();
+        assert!(
+            format!("{:#}", err).contains(case.1),
+            "Test case \"{}\" has been failed: {:#}",
+            case.2,
+            err
+        );
+    }
+    let missing = super::config::args(Path::new("missing.toml"), "intersect", &probe).unwrap_err();
+    assert!(missing.to_string().contains("Unable to read config"));
+}
+
+#[cfg(test)]
+fn vars(vars: &[(&str, &str)]) -> Vec<(OsString, OsString)> {
+    vars.iter()
+        .map(|&(key, value)| (key.into(), value.into()))
+        .collect()
+}
+
+#[test]
+fn test_env_args() {
+    let cases = vec![
+        (vec![], vec![], "no variables"),
+        (
+            vec![("SHAPES_MIN_AREA", "1")],
+            vec!["--min-area=1"],
+            "value",
+        ),
+        (vec![("SHAPES_STATS", "true")], vec!["--stats"], "flag"),
+        (vec![("SHAPES_STATS", "no")], vec![], "unset flag"),
+        (
+            vec![("SHAPES_STATS", "1"), ("SHAPES_FORMAT", "csv")],
+            vec!["--format=csv", "--stats"],
+            "sorted",
+        ),
+        (
+            vec![("SHAPES_CONFIG", "a.toml"), ("SHAPES_WIDTH", "10")],
+            vec![],
+            "config and unknown option",
+        ),
+        (
+            vec![
+                ("MIN_AREA", "1"),
+                ("SHAPESMIN_AREA", "1"),
+                ("shapes_format", "csv"),
+            ],
+            vec![],
+            "other variables",
+        ),
+    ];
+    for case in cases {
+        let expected = case.1.into_iter().map(OsString::from).collect::<Vec<_>>();
+        assert_eq!(
+            env_args(&probe, vars(&case.0)),
+            expected,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+}
+
+#[test]
+fn test_defaults() {
+    use structopt::clap::{App, AppSettings, Arg};
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, b"format = \"yaml\"\nmin_area = 2\n").unwrap();
+    // The options of the arguments given after the defaults override them.
+    let parse = |config: Option<&Path>, env: &[(&str, &str)], given: &[&str]| {
+        let defaults = defaults(config, "intersect", &probe, vars(env)).unwrap();
+        let matches = App::new("intersect")
+            .setting(AppSettings::AllArgsOverrideSelf)
+            .arg(Arg::with_name("format").long("format").takes_value(true))
+            .arg(
+                Arg::with_name("min_area")
+                    .long("min-area")
+                    .takes_value(true),
+            )
+            .get_matches_from(
+                std::iter::once(OsString::from("intersect"))
+                    .chain(defaults)
+                    .chain(given.iter().map(OsString::from)),
+            );
+        (
+            matches.value_of("format").map(str::to_owned),
+            matches.value_of("min_area").map(str::to_owned),
+        )
+    };
+    let value = |value: &str| Some(value.to_owned());
+
+    let env = [("SHAPES_FORMAT", "csv")];
+    let cases = vec![
+        (None, &[][..], &[][..], (None, None), "nothing"),
+        (
+            Some(file.path()),
+            &[][..],
+            &[][..],
+            (value("yaml"), value("2")),
+            "config",
+        ),
+        (None, &env[..], &[][..], (value("csv"), None), "environment"),
+        (
+            Some(file.path()),
+            &env[..],
+            &[][..],
+            (value("csv"), value("2")),
+            "environment over config",
+        ),
+        (
+            Some(file.path()),
+            &env[..],
+            &["--format", "toml"][..],
+            (value("toml"), value("2")),
+            "arguments over both",
+        ),
+    ];
+    for case in cases {
+        assert_eq!(
+            parse(case.0, case.1, case.2),
+            case.3,
+            "Test case \"{}\" has been failed",
+            case.4
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use sophya_prog_test::RectForm;
+use structopt::StructOpt;
+
+use super::{InputOptions, OutputOptions};
+
+/// Converts the input files between the supported formats
+#[derive(StructOpt)]
+pub struct Convert {
+    #[structopt(flatten)]
+    input: InputOptions,
+    /// Representation of the object rects, CSV tables always use the origin-size form
+    #[structopt(long, default_value = "origin-size", possible_values = RectForm::VARIANTS)]
+    rects: RectForm,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Convert {
+    pub fn run(self) -> anyhow::Result<()> {
+        let files = self.input.files()?;
+        if files.len() == 1 || self.input.merge {
+            let input = self.input.read_merged(&files)?;
+            return self.output.write(self.output.encode_input(
+                input,
+                self.rects,
+                self.input.labels_size(&files, self.output.format)?,
+            )?);
+        }
+
+        for (file, path) in files.iter().zip(self.output.paths_in_dir(&files)?) {
+            let input = self.input.read(file)?;
+            self.output.write_into_dir(
+                &path,
+                self.output.encode_input(
+                    input,
+                    self.rects,
+                    self.input
+                        .labels_size(std::slice::from_ref(file), self.output.format)?,
+                )?,
+            )?;
+        }
+        Ok(())
+    }
+}
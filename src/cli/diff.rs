@@ -0,0 +1,122 @@
+use std::{io::Read, path::Path};
+
+use anyhow::Context;
+use sophya_prog_test::{analyze, diff_outputs, InputFormat, Options, Output, OutputFormat};
+use structopt::StructOpt;
+
+use super::{report::InFile, InputOptions, OutputOptions};
+
+/// Compares two input or result files and reports the intersections that appeared,
+/// disappeared or changed their area
+#[derive(StructOpt)]
+pub struct Diff {
+    #[structopt(flatten)]
+    input: InputOptions,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Diff {
+    /// Reads the results file or analyzes the input file.
+    fn load(&self, path: &Path) -> anyhow::Result<Output> {
+        let (mut reader, format) = self.input.open(path)?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let results_format = match format {
+            InputFormat::Json => Some(OutputFormat::Json),
+            InputFormat::Yaml => Some(OutputFormat::Yaml),
+            InputFormat::Toml => Some(OutputFormat::Toml),
+            InputFormat::MessagePack => Some(OutputFormat::MessagePack),
+            InputFormat::Cbor => Some(OutputFormat::Cbor),
+            InputFormat::Ndjson => Some(OutputFormat::Ndjson),
+            InputFormat::Csv
+            | InputFormat::Coco
+            | InputFormat::Yolo
+            | InputFormat::Voc
+            | InputFormat::GeoJson
+            | InputFormat::Wkt
+            | InputFormat::Shapefile
+            | InputFormat::Svg
+            | InputFormat::Tiled
+            | InputFormat::Dxf
+            | InputFormat::Parquet
+            | InputFormat::Arrow
+            | InputFormat::Sqlite => None,
+        };
+        // The file is read as the input only if it is not the results, the errors of both are
+        // reported otherwise.
+        let results_error =
+            match results_format.map(|format| Output::from_reader_as(bytes.as_slice(), format)) {
+                Some(Ok(output)) => return Ok(output),
+                Some(Err(err)) => Some(err),
+                None => None,
+            };
+        let input = match (
+            self.input.read_from(bytes.as_slice(), format),
+            results_error,
+        ) {
+            (Ok(input), _) => input,
+            (Err(err), None) => return Err(err).with_context(|| InFile(path.to_owned())),
+            (Err(err), Some(results_error)) => {
+                return Err(anyhow::anyhow!(
+                    "Neither the results nor the input: {}; {}",
+                    results_error,
+                    err
+                ))
+                .with_context(|| InFile(path.to_owned()))
+            }
+        };
+        Ok(analyze(input, Options::default()))
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let files = self.input.files()?;
+        let (before, after) = match files.as_slice() {
+            [before, after] => (self.load(before)?, self.load(after)?),
+            _ => anyhow::bail!("Exactly two files are expected, got {}", files.len()),
+        };
+        let diff = diff_outputs(&before, &after);
+        self.output.write(diff.to_vec_as(self.output.format)?)
+    }
+}
+
+#[test]
+fn test_diff_load() {
+    let diff = Diff::from_iter(vec!["diff"]);
+    let load = |text: &str| {
+        let mut file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        std::io::Write::write_all(&mut file, text.as_bytes()).unwrap();
+        diff.load(file.path()).map_err(|err| format!("{:#}", err))
+    };
+
+    let results = r#"{ "areas": [], "intersections": [] }"#;
+    assert!(load(results).unwrap().areas.is_empty());
+    let input = r#"{ "objects": [
+        { "name": "A", "x": 0, "y": 0, "width": 2, "height": 2 },
+        { "name": "B", "x": 1, "y": 1, "width": 2, "height": 2 }
+    ] }"#;
+    assert_eq!(load(input).unwrap().intersections.len(), 1);
+
+    let cases = vec![
+        (
+            r#"{ "areas": [], "intersections": [{ "names": ["A"] }] }"#,
+            "Neither the results nor the input",
+            "broken results",
+        ),
+        (
+            r#"{ "objects": [{ "name": "A" }] }"#,
+            "missing field `x`",
+            "broken input",
+        ),
+    ];
+    for case in cases {
+        let err = load(case.0).unwrap_err();
+        assert!(
+            err.contains(case.1),
+            "Test case \"{}\" has been failed: {}",
+            case.2,
+            err
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use sophya_prog_test::{generate_scene, Distribution, GenerateOptions, RectForm};
+use structopt::StructOpt;
+
+use super::OutputOptions;
+
+/// Generates a reproducible random input file for the benchmarks and tests
+#[derive(StructOpt)]
+pub struct Generate {
+    /// Number of the objects
+    #[structopt(long, default_value = "1000")]
+    count: usize,
+    /// Seed of the random generator, the same seed always gives the same scene
+    #[structopt(long, default_value = "0")]
+    seed: u64,
+    /// Ratio of the total object area to the scene area
+    #[structopt(long, default_value = "0.1")]
+    density: f32,
+    /// Placement of the objects
+    #[structopt(long, default_value = "uniform", possible_values = Distribution::VARIANTS)]
+    distribution: Distribution,
+    /// Representation of the object rects, CSV tables always use the origin-size form
+    #[structopt(long, default_value = "origin-size", possible_values = RectForm::VARIANTS)]
+    rects: RectForm,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Generate {
+    pub fn run(self) -> anyhow::Result<()> {
+        if !self.density.is_finite() || self.density <= 0.0 {
+            anyhow::bail!("The density must be a positive number");
+        }
+        let input = generate_scene(&GenerateOptions {
+            count: self.count,
+            seed: self.seed,
+            density: self.density,
+            distribution: self.distribution,
+        });
+        self.output
+            .write(self.output.encode_input(input, self.rects, None)?)
+    }
+}
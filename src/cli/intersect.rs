@@ -0,0 +1,178 @@
+use std::{io::Write, mem, path::PathBuf, time::Instant};
+
+use sophya_prog_test::{
+    analyze, analyze_each, analyze_objects, AnalysisStats, Error, InputFormat, NdjsonWriter,
+    Options, Output, OutputRecord,
+};
+use structopt::StructOpt;
+
+use super::{AnalysisOptions, Collisions, InputOptions, OutputOptions, ResultOptions};
+
+/// Searches for intersecting objects in the given input files, the CSV output contains only
+/// the intersections
+#[derive(StructOpt)]
+pub struct Intersect {
+    #[structopt(flatten)]
+    input: InputOptions,
+    #[structopt(flatten)]
+    analysis: AnalysisOptions,
+    #[structopt(flatten)]
+    results: ResultOptions,
+    /// Include the analysis statistics into the output
+    #[structopt(long)]
+    stats: bool,
+    /// Include the intersection area, the intersection over union and the percents of both
+    /// objects covered by each intersection
+    #[structopt(long)]
+    include_metrics: bool,
+    /// Copy the properties of the input objects into their areas and attach the properties
+    /// of both objects to each intersection
+    #[structopt(long)]
+    include_properties: bool,
+    /// Exit with an error if any intersection passing the filters is found, e.g. to check in
+    /// CI that the layout has no collisions
+    #[structopt(long)]
+    fail_on_intersection: bool,
+    /// Append the scene and the results to the SQLite database as a new run, the results are
+    /// never streamed then
+    #[cfg(feature = "sqlite")]
+    #[structopt(long, value_name = "database")]
+    store: Option<PathBuf>,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Intersect {
+    fn options(&self) -> Options {
+        Options {
+            metrics: self.include_metrics,
+            properties: self.include_properties,
+            // The annotations of different images never overlap.
+            same_z: self.analysis.same_z || self.input.input_format == Some(InputFormat::Coco),
+            ..self.analysis.options(self.stats)
+        }
+    }
+
+    fn analyze(&self, files: &[PathBuf]) -> anyhow::Result<Output> {
+        // The NDJSON objects are parsed along with their areas and never kept.
+        if !self.stores() {
+            if let Some(objects) = self.input.stream_objects(files)? {
+                let mut output = analyze_objects(objects, self.options())?;
+                output.unit = self.input.unit;
+                return Ok(output);
+            }
+            // The JSON objects borrow their names from the text until their areas are found.
+            if let Some(text) = self.input.read_json_text(files)? {
+                let objects = self.input.borrowed_objects(&files[0], &text)?;
+                let mut output = analyze_objects(objects, self.options())?;
+                output.unit = self.input.unit;
+                return Ok(output);
+            }
+        }
+
+        let mut parse_stats = AnalysisStats::default();
+        let input = parse_stats.measure("parse", || self.input.read_merged(files))?;
+        #[cfg(feature = "sqlite")]
+        let scene = self.store.as_ref().map(|_| input.clone());
+
+        let mut output = analyze(input, self.options());
+        output.unit = self.input.unit;
+        if let Some(stats) = output.stats.as_mut() {
+            stats.phases.splice(0..0, parse_stats.phases);
+        }
+        #[cfg(feature = "sqlite")]
+        if let (Some(database), Some(scene)) = (&self.store, scene) {
+            let label = files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let run = sophya_prog_test::store_sqlite(database, Some(&label), &scene, &output)?;
+            eprintln!("Stored the run {} into {}", run, database.display());
+        }
+        Ok(output)
+    }
+
+    // Whether the runs are stored into the database, so the whole results are needed.
+    fn stores(&self) -> bool {
+        #[cfg(feature = "sqlite")]
+        return self.store.is_some();
+        #[cfg(not(feature = "sqlite"))]
+        false
+    }
+
+    /// Analyzes the files and writes every NDJSON record as soon as it is found.
+    fn stream(
+        &self,
+        files: &[PathBuf],
+        collisions: &mut Collisions,
+        writer: &mut dyn Write,
+    ) -> anyhow::Result<()> {
+        let mut parse_stats = AnalysisStats::default();
+        let input = parse_stats.measure("parse", || self.input.read_merged(files))?;
+
+        let mut writer = NdjsonWriter::new(writer);
+        if let Some(unit) = self.input.unit {
+            writer.write(&OutputRecord::Unit { unit })?;
+        }
+        analyze_each(input, self.options(), |mut record| {
+            match &mut record {
+                OutputRecord::Area(_) => collisions.add_object(),
+                OutputRecord::Intersection(intersection) => {
+                    collisions.add_intersection(intersection)
+                }
+                OutputRecord::Stats(stats) => {
+                    stats
+                        .phases
+                        .splice(0..0, mem::take(&mut parse_stats.phases));
+                }
+                _ => {}
+            }
+            if let Some(decimals) = self.output.precision {
+                record.round(decimals);
+            }
+            if self.results.selects(&record) {
+                writer.write(&record)?;
+            }
+            Ok::<_, Error>(())
+        })?;
+        writer.into_inner()?;
+        Ok(())
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let started = Instant::now();
+        let files = self.input.files()?;
+        let mut collisions = Collisions::default();
+        if self.results.streams(&self.output) && !self.stores() {
+            if files.len() == 1 || self.input.merge {
+                self.output
+                    .stream(|writer| self.stream(&files, &mut collisions, writer))?;
+            } else {
+                for (file, path) in files.iter().zip(self.output.paths_in_dir(&files)?) {
+                    self.output.stream_into_dir(&path, |writer| {
+                        self.stream(std::slice::from_ref(file), &mut collisions, writer)
+                    })?;
+                }
+            }
+        } else if files.len() == 1 || self.input.merge {
+            let output = self.analyze(&files)?;
+            collisions.add(&output);
+            self.output
+                .write(self.results.encode(output, &self.output)?)?;
+        } else {
+            for (file, path) in files.iter().zip(self.output.paths_in_dir(&files)?) {
+                let output = self.analyze(std::slice::from_ref(file))?;
+                collisions.add(&output);
+                self.output
+                    .write_into_dir(&path, self.results.encode(output, &self.output)?)?;
+            }
+        }
+
+        collisions.summarize(started.elapsed());
+        if self.fail_on_intersection {
+            collisions.check()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use std::time::Instant;
+
+use sophya_prog_test::{analyze_join, Options};
+use structopt::StructOpt;
+
+use super::{AnalysisOptions, Collisions, InputOptions, OutputOptions, ResultOptions};
+
+/// Reports the intersections between the objects of two files, e.g. the planned and the
+/// as-built layouts
+#[derive(StructOpt)]
+pub struct Join {
+    #[structopt(flatten)]
+    input: InputOptions,
+    #[structopt(flatten)]
+    analysis: AnalysisOptions,
+    #[structopt(flatten)]
+    results: ResultOptions,
+    /// Include the analysis statistics into the output
+    #[structopt(long)]
+    stats: bool,
+    /// Include the intersection area, the intersection over union and the percents of both
+    /// objects covered by each intersection
+    #[structopt(long)]
+    include_metrics: bool,
+    /// Copy the properties of the input objects into their areas and attach the properties
+    /// of both objects to each intersection
+    #[structopt(long)]
+    include_properties: bool,
+    /// Exit with an error if any intersection passing the filters is found
+    #[structopt(long)]
+    fail_on_intersection: bool,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Join {
+    fn options(&self) -> Options {
+        Options {
+            metrics: self.include_metrics,
+            properties: self.include_properties,
+            ..self.analysis.options(self.stats)
+        }
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let started = Instant::now();
+        let files = self.input.files()?;
+        let (first, second) = match files.as_slice() {
+            [first, second] => (self.input.read(first)?, self.input.read(second)?),
+            _ => anyhow::bail!("Exactly two files are expected, got {}", files.len()),
+        };
+        let mut output = analyze_join(first, second, self.options());
+        output.unit = self.input.unit;
+        let mut collisions = Collisions::default();
+        collisions.add(&output);
+        self.output
+            .write(self.results.encode(output, &self.output)?)?;
+
+        collisions.summarize(started.elapsed());
+        if self.fail_on_intersection {
+            collisions.check()?;
+        }
+        Ok(())
+    }
+}
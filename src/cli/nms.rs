@@ -0,0 +1,66 @@
+use sophya_prog_test::{NmsOptions, RectForm};
+use structopt::StructOpt;
+
+use super::{InputOptions, OutputOptions};
+
+/// Keeps only the detection boxes not suppressed by the overlapping boxes with the higher
+/// scores, the kept boxes are written as a scene sorted by the descending score
+#[derive(StructOpt)]
+pub struct Nms {
+    #[structopt(flatten)]
+    input: InputOptions,
+    /// Suppress the boxes overlapping a kept box with a greater intersection over union
+    #[structopt(long, default_value = "0.5")]
+    iou_threshold: f32,
+    /// Suppress only the boxes of the same class, i.e. of the same layer or the "layer" CSV
+    /// column
+    #[structopt(long)]
+    per_class: bool,
+    /// Property holding the scores of the boxes
+    #[structopt(long, default_value = "score")]
+    score: String,
+    /// Representation of the object rects, CSV tables always use the origin-size form
+    #[structopt(long, default_value = "origin-size", possible_values = RectForm::VARIANTS)]
+    rects: RectForm,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Nms {
+    pub fn run(self) -> anyhow::Result<()> {
+        if !(0.0..=1.0).contains(&self.iou_threshold) {
+            anyhow::bail!("The IoU threshold must be in [0, 1]");
+        }
+        let options = NmsOptions {
+            iou_threshold: self.iou_threshold,
+            per_class: self.per_class,
+            score: self.score.clone(),
+        };
+
+        let files = self.input.files()?;
+        if files.len() == 1 || self.input.merge {
+            let mut input = self.input.read_merged(&files)?;
+            input.non_max_suppression(&options)?;
+            return self.output.write(self.output.encode_input(
+                input,
+                self.rects,
+                self.input.labels_size(&files, self.output.format)?,
+            )?);
+        }
+
+        for (file, path) in files.iter().zip(self.output.paths_in_dir(&files)?) {
+            let mut input = self.input.read(file)?;
+            input.non_max_suppression(&options)?;
+            self.output.write_into_dir(
+                &path,
+                self.output.encode_input(
+                    input,
+                    self.rects,
+                    self.input
+                        .labels_size(std::slice::from_ref(file), self.output.format)?,
+                )?,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use sophya_prog_test::{BoundingRect, Object, Output, Point2D, QueryResult};
+use structopt::StructOpt;
+
+use super::{parse_point, parse_rect, InputOptions, OutputOptions};
+
+/// Prints the objects containing the point or overlapping the rect
+#[derive(StructOpt)]
+pub struct Query {
+    #[structopt(flatten)]
+    input: InputOptions,
+    /// Find the objects containing the point given as "x,y"
+    #[structopt(
+        long,
+        parse(try_from_str = parse_point),
+        required_unless = "rect",
+        conflicts_with = "rect"
+    )]
+    point: Option<Point2D>,
+    /// Find the objects overlapping the rect given as "x1,y1,x2,y2"
+    #[structopt(long, parse(try_from_str = parse_rect))]
+    rect: Option<BoundingRect>,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Query {
+    pub fn run(self) -> anyhow::Result<()> {
+        let files = self.input.files()?;
+        let input = self.input.read_merged(&files)?;
+        // The lookups need only the object areas, so the intersection search is skipped.
+        let output = Output {
+            page: None,
+            unit: None,
+            areas: input.objects.iter().map(Object::area).collect(),
+            intersections: Vec::new(),
+            stats: None,
+        };
+
+        let result: QueryResult = match (self.point, self.rect) {
+            (Some(point), _) => output.objects_at(point).collect(),
+            (None, Some(rect)) => output.objects_in(rect).collect(),
+            (None, None) => unreachable!("either --point or --rect is required"),
+        };
+        self.output.write(result.to_vec_as(self.output.format)?)
+    }
+}
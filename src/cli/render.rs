@@ -0,0 +1,88 @@
+use std::{io::Write, path::PathBuf};
+
+use sophya_prog_test::{analyze, render_svg, render_text, BoundingRect, Charset, RenderOptions};
+use structopt::StructOpt;
+
+use super::{parse_rect, write_atomically, AnalysisOptions, InputOptions};
+
+/// Draws the objects and highlights their intersections as an SVG or PNG image or as text
+#[derive(StructOpt)]
+pub struct Render {
+    #[structopt(flatten)]
+    input: InputOptions,
+    #[structopt(flatten)]
+    analysis: AnalysisOptions,
+    /// Write the image into the given file instead of stdout
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+    /// Write a PNG image instead of SVG, requires the "png" feature
+    #[structopt(long)]
+    png: bool,
+    /// Draw the scene as ASCII characters in the terminal, the overlaps are marked by "#"
+    #[structopt(long, conflicts_with_all = &["png", "unicode"])]
+    ascii: bool,
+    /// Draw the scene as Unicode block characters in the terminal
+    #[structopt(long, conflicts_with = "png")]
+    unicode: bool,
+    /// Width of the image in pixels or in characters for the text modes, 1024 and 80 by default
+    #[structopt(long)]
+    width: Option<u32>,
+    /// Area of the scene to draw as "x1,y1,x2,y2", the bounds of all objects by default
+    #[structopt(long, parse(try_from_str = parse_rect))]
+    viewport: Option<BoundingRect>,
+    /// Draw the grid lines with the given step in the scene units
+    #[structopt(long)]
+    grid: Option<f32>,
+    /// Draw the legend of the used colors
+    #[structopt(long)]
+    legend: bool,
+}
+
+impl Render {
+    #[cfg(feature = "png")]
+    fn render_png(
+        output: &sophya_prog_test::Output,
+        options: &RenderOptions,
+    ) -> anyhow::Result<Vec<u8>> {
+        Ok(sophya_prog_test::render_png(output, options)?)
+    }
+
+    #[cfg(not(feature = "png"))]
+    fn render_png(
+        _output: &sophya_prog_test::Output,
+        _options: &RenderOptions,
+    ) -> anyhow::Result<Vec<u8>> {
+        anyhow::bail!("PNG rendering requires the \"png\" feature")
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let files = self.input.files()?;
+        let input = self.input.read_merged(&files)?;
+        let output = analyze(input, self.analysis.options(false));
+
+        let charset = match (self.ascii, self.unicode) {
+            (true, _) => Some(Charset::Ascii),
+            (_, true) => Some(Charset::Unicode),
+            _ => None,
+        };
+        let default_width = if charset.is_some() { 80 } else { 1024 };
+        let options = RenderOptions {
+            viewport: self.viewport,
+            width: self.width.unwrap_or(default_width),
+            grid: self.grid,
+            legend: self.legend,
+        };
+        let image = if let Some(charset) = charset {
+            render_text(&output, &options, charset).into_bytes()
+        } else if self.png {
+            Self::render_png(&output, &options)?
+        } else {
+            render_svg(&output, &options).into_bytes()
+        };
+
+        match &self.output {
+            Some(path) => write_atomically(path, &image),
+            None => Ok(std::io::stdout().lock().write_all(&image)?),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use sophya_prog_test::{analyze, BoundingRect, Options, Output, Point2D};
+use structopt::StructOpt;
+
+use super::{parse_point, parse_rect, InputOptions};
+
+const HELP: &str = "\
+Commands:
+  intersections NAME     intersections involving the object
+  at X,Y                 objects containing the point
+  in X1,Y1,X2,Y2         objects overlapping the rect
+  nearest X,Y            object closest to the point
+  reload                 reads the input files again
+  help                   prints this help
+  quit                   exits";
+
+/// Loads the scene once and answers the queries read from stdin, run "help" for the list
+#[derive(StructOpt)]
+pub struct Repl {
+    #[structopt(flatten)]
+    input: InputOptions,
+}
+
+/// Command entered in the REPL.
+enum Query {
+    Intersections(String),
+    At(Point2D),
+    In(BoundingRect),
+    Nearest(Point2D),
+    Reload,
+    Help,
+    Quit,
+}
+
+impl Query {
+    fn parse(line: &str) -> Result<Self, String> {
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line, ""),
+        };
+        let query = match command {
+            "intersections" | "i" => Self::Intersections(argument.to_owned()),
+            "at" => Self::At(parse_point(argument)?),
+            "in" => Self::In(parse_rect(argument)?),
+            "nearest" => Self::Nearest(parse_point(argument)?),
+            "reload" => return Ok(Self::Reload),
+            "help" => return Ok(Self::Help),
+            "quit" | "exit" => return Ok(Self::Quit),
+            _ => return Err(format!("Unknown command \"{}\", run \"help\"", command)),
+        };
+        if argument.is_empty() {
+            return Err(format!("Command \"{}\" requires an argument", command));
+        }
+        Ok(query)
+    }
+}
+
+fn format_rect(rect: &BoundingRect) -> String {
+    let (a, b) = (rect.top_left(), rect.bottom_right());
+    format!("({}, {}) - ({}, {})", a.x, a.y, b.x, b.y)
+}
+
+impl Repl {
+    fn load(&self) -> anyhow::Result<Output> {
+        let files = self.input.files()?;
+        let input = self.input.read_merged(&files)?;
+        Ok(analyze(input, Options::default()))
+    }
+
+    /// Writes the answer to the query, returns `false` if the REPL should exit.
+    fn answer(
+        &self,
+        output: &mut Output,
+        query: Query,
+        out: &mut impl Write,
+    ) -> anyhow::Result<bool> {
+        match query {
+            Query::Intersections(name) => {
+                if output.object(&name).is_none() {
+                    anyhow::bail!("Unknown object \"{}\"", name);
+                }
+                for intersection in output.intersections_of(&name) {
+                    let (first, second) = &intersection.names;
+                    let other = if *first == name { second } else { first };
+                    writeln!(out, "{}: {}", other, format_rect(&intersection.area))?;
+                }
+            }
+            Query::At(point) => {
+                for object in output.objects_at(point) {
+                    writeln!(out, "{}: {}", object.name, format_rect(&object.area))?;
+                }
+            }
+            Query::In(rect) => {
+                for object in output.objects_in(rect) {
+                    writeln!(out, "{}: {}", object.name, format_rect(&object.area))?;
+                }
+            }
+            Query::Nearest(point) => {
+                if let Some((object, distance)) = output.nearest(point) {
+                    writeln!(
+                        out,
+                        "{}: {} at {}",
+                        object.name,
+                        format_rect(&object.area),
+                        distance
+                    )?;
+                }
+            }
+            Query::Reload => {
+                *output = self.load()?;
+                writeln!(out, "Loaded {} objects", output.areas.len())?;
+            }
+            Query::Help => writeln!(out, "{}", HELP)?,
+            Query::Quit => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        if self.input.input_files.is_empty() {
+            anyhow::bail!("The input files are required, stdin is used for the commands");
+        }
+        let mut output = self.load()?;
+
+        let stdin = io::stdin();
+        let interactive = stdin.is_terminal();
+        let mut stdout = io::stdout().lock();
+        if interactive {
+            writeln!(
+                stdout,
+                "Loaded {} objects, run \"help\" for the commands",
+                output.areas.len()
+            )?;
+        }
+        let mut lines = stdin.lock().lines();
+        loop {
+            if interactive {
+                write!(stdout, "> ")?;
+                stdout.flush()?;
+            }
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => return Ok(()),
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let result = Query::parse(line)
+                .map_err(anyhow::Error::msg)
+                .and_then(|query| self.answer(&mut output, query, &mut stdout));
+            match result {
+                Ok(true) => {}
+                Ok(false) => return Ok(()),
+                Err(err) => eprintln!("Error: {:#}", err),
+            }
+        }
+    }
+}
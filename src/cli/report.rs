@@ -0,0 +1,360 @@
+//! Machine-readable reports of the failed runs.
+
+use std::{ffi::OsString, fmt, path::PathBuf};
+
+use serde::Serialize;
+use sophya_prog_test::Error;
+
+use super::{validate::ValidationFailed, IntersectionsFound};
+
+/// How the error of the failed run is reported to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl ErrorFormat {
+    /// Names of all formats.
+    pub const VARIANTS: &'static [&'static str] = &["text", "json"];
+
+    /// Returns the format given by the last `--error-format` of the raw arguments, so the
+    /// errors of the config and of the arguments themselves are reported in it too. The
+    /// invalid values are left to the parser.
+    pub fn from_args(args: &[OsString]) -> Self {
+        let mut format = Self::default();
+        let mut args = args.iter().skip(1).map(|arg| arg.to_string_lossy());
+        while let Some(arg) = args.next() {
+            let value = match arg.strip_prefix("--error-format") {
+                Some("") => args.next(),
+                Some(value) => value.strip_prefix('=').map(|value| value.to_owned().into()),
+                None if arg == "--" => break,
+                None => None,
+            };
+            if let Some(value) = value.and_then(|value| value.parse().ok()) {
+                format = value;
+            }
+        }
+        format
+    }
+}
+
+impl std::str::FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(format!("Unknown error format \"{}\"", other)),
+        }
+    }
+}
+
+/// Input file the error has occurred in, attached to the parse errors as their context.
+#[derive(Debug)]
+pub struct InFile(pub PathBuf);
+
+impl fmt::Display for InFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.as_os_str() == "-" {
+            f.write_str("Unable to read stdin")
+        } else {
+            write!(f, "Unable to read \"{}\"", self.0.display())
+        }
+    }
+}
+
+/// What has failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Io,
+    Parse,
+    Serialize,
+    #[cfg(feature = "png")]
+    Render,
+    Validation,
+    /// Intersections found by `--fail-on-intersection`.
+    Intersections,
+    /// Invalid command line arguments.
+    Usage,
+    Other,
+}
+
+impl ErrorKind {
+    /// Exit code of the process: 1 for the IO and other errors, 2 for the parse errors, 3 for
+    /// the invalid input and 4 for the intersections found in the gate mode.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Parse => 2,
+            Self::Validation => 3,
+            Self::Intersections => 4,
+            _ => 1,
+        }
+    }
+}
+
+/// Structured error, e.g. `{"kind":"parse","path":"a.json","line":3,"column":7,...}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorReport {
+    pub kind: ErrorKind,
+    /// Input file or URL, if the error is related to one.
+    pub path: Option<String>,
+    /// 1-based position of the parse error, if reported by the format.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Message of the error and all its causes.
+    pub message: String,
+}
+
+impl ErrorReport {
+    /// Collects the details of the error from its chain of causes.
+    pub fn new(err: &anyhow::Error) -> Self {
+        let mut report = Self {
+            kind: ErrorKind::Other,
+            path: err
+                .downcast_ref::<InFile>()
+                .map(|file| file.0.display().to_string()),
+            line: None,
+            column: None,
+            message: err
+                .chain()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(": "),
+        };
+
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<Error>() {
+                report.add_error(err);
+                break;
+            } else if cause.is::<ValidationFailed>() {
+                report.kind = ErrorKind::Validation;
+                break;
+            } else if cause.is::<IntersectionsFound>() {
+                report.kind = ErrorKind::Intersections;
+                break;
+            } else if cause.is::<std::io::Error>() {
+                report.kind = ErrorKind::Io;
+                break;
+            } else if cause.is::<structopt::clap::Error>() {
+                report.kind = ErrorKind::Usage;
+                break;
+            }
+        }
+        report
+    }
+
+    fn add_error(&mut self, err: &Error) {
+        let reason = match err {
+            Error::FileReadError { path, reason } => {
+                self.kind = ErrorKind::Io;
+                self.path = Some(path.display().to_string());
+                reason
+            }
+            #[cfg(feature = "net")]
+            Error::FetchError { url, reason } => {
+                self.kind = ErrorKind::Io;
+                self.path = Some(url.clone());
+                reason
+            }
+            Error::ParseError { reason } => {
+                self.kind = ErrorKind::Parse;
+                reason
+            }
+            Error::SerializeError { reason } => {
+                self.kind = ErrorKind::Serialize;
+                reason
+            }
+            #[cfg(feature = "png")]
+            Error::RenderError { reason } => {
+                self.kind = ErrorKind::Render;
+                reason
+            }
+        };
+
+        let position = reason.chain().find_map(|cause| {
+            if let Some(err) = cause.downcast_ref::<serde_json::Error>() {
+                Some((err.line(), Some(err.column())))
+            } else if let Some(err) = cause.downcast_ref::<serde_yaml::Error>() {
+                err.location()
+                    .map(|location| (location.line(), Some(location.column())))
+            } else if let Some(err) = cause.downcast_ref::<csv::Error>() {
+                err.position()
+                    .map(|position| (position.line() as usize, None))
+            } else {
+                None
+            }
+        });
+        // The EOF errors of `serde_json` have no position.
+        if let Some((line, column)) = position.filter(|(line, _)| *line > 0) {
+            self.line = Some(line);
+            self.column = column;
+        }
+    }
+
+    /// Encodes the report as a single line JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("The error report is always serializable")
+    }
+}
+
+#[test]
+fn test_error_format_from_args() {
+    let cases = vec![
+        (vec![], ErrorFormat::Text, "default"),
+        (
+            vec!["--error-format", "json"],
+            ErrorFormat::Json,
+            "separate value",
+        ),
+        (
+            vec!["--error-format=json"],
+            ErrorFormat::Json,
+            "joined value",
+        ),
+        (
+            vec!["intersect", "a.json", "--error-format", "json"],
+            ErrorFormat::Json,
+            "after the subcommand",
+        ),
+        (
+            vec!["--error-format=json", "--error-format", "text"],
+            ErrorFormat::Text,
+            "last one",
+        ),
+        (
+            vec!["--error-format", "xml"],
+            ErrorFormat::Text,
+            "invalid value",
+        ),
+        (vec!["--error-format"], ErrorFormat::Text, "missing value"),
+        (
+            vec!["--error-formats=json"],
+            ErrorFormat::Text,
+            "other option",
+        ),
+        (
+            vec!["--", "--error-format=json"],
+            ErrorFormat::Text,
+            "positional argument",
+        ),
+    ];
+    for case in cases {
+        let args = std::iter::once("tool")
+            .chain(case.0)
+            .map(OsString::from)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ErrorFormat::from_args(&args),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+}
+
+#[test]
+fn test_error_report() {
+    use anyhow::Context;
+    use sophya_prog_test::Input;
+
+    let usage = structopt::clap::App::new("tool")
+        .get_matches_from_safe(["tool", "--bogus"])
+        .unwrap_err();
+    let cases = vec![
+        (
+            Input::from_reader("{\n  \"objects\": [1]\n}".as_bytes())
+                .context(InFile(PathBuf::from("a.json")))
+                .unwrap_err(),
+            ErrorKind::Parse,
+            Some("a.json"),
+            Some((2, 16)),
+            2,
+            "parse error",
+        ),
+        (
+            anyhow::Error::from(Input::from_reader("{".as_bytes()).unwrap_err()),
+            ErrorKind::Parse,
+            None,
+            Some((1, 1)),
+            2,
+            "unexpected end",
+        ),
+        (
+            anyhow::Error::from(Input::from_path("objects/missing.json").unwrap_err()),
+            ErrorKind::Io,
+            Some("objects/missing.json"),
+            None,
+            1,
+            "missing file",
+        ),
+        (
+            anyhow::Error::from(ValidationFailed { errors: 2 }),
+            ErrorKind::Validation,
+            None,
+            None,
+            3,
+            "validation",
+        ),
+        (
+            anyhow::Error::from(IntersectionsFound {
+                count: 1,
+                first: ("A".to_owned(), "B".to_owned()),
+            }),
+            ErrorKind::Intersections,
+            None,
+            None,
+            4,
+            "intersections",
+        ),
+        (
+            anyhow::Error::from(std::io::Error::other("closed")).context("Unable to write"),
+            ErrorKind::Io,
+            None,
+            None,
+            1,
+            "io error",
+        ),
+        (
+            anyhow::Error::from(usage),
+            ErrorKind::Usage,
+            None,
+            None,
+            1,
+            "usage",
+        ),
+        (
+            anyhow::anyhow!("failed"),
+            ErrorKind::Other,
+            None,
+            None,
+            1,
+            "other",
+        ),
+    ];
+    for case in cases {
+        let report = ErrorReport::new(&case.0);
+        assert_eq!(
+            (
+                report.kind,
+                report.path.as_deref(),
+                report.line.zip(report.column),
+                report.kind.exit_code()
+            ),
+            (case.1, case.2, case.3, case.4),
+            "Test case \"{}\" has been failed",
+            case.5
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&report.to_json()).unwrap()["message"],
+            case.0
+                .chain()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(": ")
+        );
+    }
+}
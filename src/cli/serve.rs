@@ -0,0 +1,416 @@
+use sophya_prog_test::{analyze, Options, Output};
+use structopt::StructOpt;
+
+use super::InputOptions;
+
+/// Serves the scene queries over HTTP, requires the "server" feature
+///
+/// GET /output returns the analysis results, /intersections?name=NAME the intersections
+/// involving the object, /objects?point=X,Y and /objects?rect=X1,Y1,X2,Y2 the objects
+/// containing the point or overlapping the rect and /nearest?point=X,Y the closest object.
+/// POST /scene replaces the scene by the posted JSON input and returns the results.
+///
+/// Pass --grpc to serve the gRPC API described by "proto/sophya.proto" instead, it requires
+/// the "grpc" feature.
+#[derive(StructOpt)]
+pub struct Serve {
+    // The input files are not required, the scene can be posted later.
+    #[structopt(flatten)]
+    input: InputOptions,
+    /// Address to listen on
+    #[structopt(long, default_value = "127.0.0.1:8080")]
+    listen: String,
+    /// Serve the gRPC API instead of HTTP
+    #[structopt(long)]
+    grpc: bool,
+    /// Largest accepted body of the posted scenes in bytes, the bigger ones are rejected
+    #[structopt(long, default_value = "67108864")]
+    max_body: usize,
+}
+
+impl Serve {
+    pub fn run(self) -> anyhow::Result<()> {
+        let output = if self.input.input_files.is_empty() {
+            Output {
+                page: None,
+                unit: None,
+                areas: Vec::new(),
+                intersections: Vec::new(),
+                stats: None,
+            }
+        } else {
+            let files = self.input.files()?;
+            analyze(self.input.read_merged(&files)?, Options::default())
+        };
+        if self.grpc {
+            grpc::run(&self.listen, output)
+        } else {
+            server::run(&self.listen, output, self.max_body)
+        }
+    }
+}
+
+#[cfg(not(feature = "grpc"))]
+mod grpc {
+    pub fn run(_listen: &str, _output: sophya_prog_test::Output) -> anyhow::Result<()> {
+        anyhow::bail!("The gRPC server requires the \"grpc\" feature")
+    }
+}
+
+#[cfg(feature = "grpc")]
+mod grpc {
+    use sophya_prog_test::{grpc::SceneService, Output};
+
+    pub fn run(listen: &str, output: Output) -> anyhow::Result<()> {
+        let addr = listen.parse()?;
+        let server = tonic::transport::Server::builder()
+            .add_service(SceneService::new(output).into_server())
+            .serve(addr);
+        eprintln!("Listening on {}", addr);
+        tokio::runtime::Runtime::new()?.block_on(server)?;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "server"))]
+mod server {
+    pub fn run(
+        _listen: &str,
+        _output: sophya_prog_test::Output,
+        _max_body: usize,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("The HTTP server requires the \"server\" feature")
+    }
+}
+
+#[cfg(feature = "server")]
+mod server {
+    use std::io::Read;
+
+    use sophya_prog_test::{analyze, Input, Options, Output};
+    use tiny_http::{Header, Method, Response, Server};
+
+    use super::super::{parse_point, parse_rect};
+
+    /// Error response with the status code.
+    struct HttpError(u16, String);
+
+    impl HttpError {
+        fn bad_request(message: impl ToString) -> Self {
+            Self(400, message.to_string())
+        }
+    }
+
+    /// Decodes the percent-encoded query string component.
+    fn decode(s: &str) -> String {
+        let (mut bytes, mut rest) = (Vec::with_capacity(s.len()), s.as_bytes());
+        while let Some((&byte, tail)) = rest.split_first() {
+            let escaped = tail
+                .get(..2)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            rest = match (byte, escaped) {
+                (b'%', Some(escaped)) => {
+                    bytes.push(escaped);
+                    &tail[2..]
+                }
+                (b'+', _) => {
+                    bytes.push(b' ');
+                    tail
+                }
+                (byte, _) => {
+                    bytes.push(byte);
+                    tail
+                }
+            };
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Returns the decoded value of the query parameter.
+    fn param(query: &str, name: &str) -> Option<String> {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| decode(key) == name)
+            .map(|(_, value)| decode(value))
+    }
+
+    /// Reads the request body, the bodies longer than the limit are rejected before they are
+    /// read if their length is known.
+    fn read_body(
+        reader: impl Read,
+        length: Option<usize>,
+        limit: usize,
+    ) -> Result<Vec<u8>, HttpError> {
+        let too_large = || HttpError(413, format!("The body is longer than {} bytes", limit));
+        if length.is_some_and(|length| length > limit) {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        reader
+            .take(limit as u64 + 1)
+            .read_to_end(&mut body)
+            .map_err(HttpError::bad_request)?;
+        if body.len() > limit {
+            return Err(too_large());
+        }
+        Ok(body)
+    }
+
+    fn json(value: &impl serde::Serialize) -> Result<Vec<u8>, HttpError> {
+        serde_json::to_vec(value).map_err(|err| HttpError(500, err.to_string()))
+    }
+
+    fn respond(
+        output: &mut Output,
+        method: &Method,
+        url: &str,
+        body: &[u8],
+    ) -> Result<Vec<u8>, HttpError> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let point = || {
+            let point = param(query, "point")
+                .ok_or_else(|| HttpError::bad_request("The \"point\" parameter is required"))?;
+            parse_point(&point).map_err(HttpError::bad_request)
+        };
+
+        match (method, path) {
+            (Method::Get, "/output") => json(output),
+            (Method::Get, "/intersections") => match param(query, "name") {
+                Some(name) if output.object(&name).is_none() => {
+                    Err(HttpError(404, format!("Unknown object \"{}\"", name)))
+                }
+                Some(name) => json(&output.intersections_of(&name).collect::<Vec<_>>()),
+                None => json(&output.intersections),
+            },
+            (Method::Get, "/objects") => match param(query, "rect") {
+                Some(rect) => {
+                    let rect = parse_rect(&rect).map_err(HttpError::bad_request)?;
+                    json(&output.objects_in(rect).collect::<Vec<_>>())
+                }
+                None => json(&output.objects_at(point()?).collect::<Vec<_>>()),
+            },
+            (Method::Get, "/nearest") => json(&output.nearest(point()?).map(|(object, _)| object)),
+            (Method::Post, "/scene") => {
+                let input = Input::from_reader(body).map_err(HttpError::bad_request)?;
+                *output = analyze(input, Options::default());
+                json(output)
+            }
+            _ => Err(HttpError(
+                404,
+                format!("Unknown endpoint {} {}", method, path),
+            )),
+        }
+    }
+
+    pub fn run(listen: &str, mut output: Output, max_body: usize) -> anyhow::Result<()> {
+        let server = Server::http(listen).map_err(anyhow::Error::msg)?;
+        eprintln!("Listening on http://{}", server.server_addr());
+
+        let content_type = |value: &str| Header::from_bytes("Content-Type", value).unwrap();
+        for mut request in server.incoming_requests() {
+            let length = request.body_length();
+            let response = read_body(request.as_reader(), length, max_body)
+                .and_then(|body| respond(&mut output, request.method(), request.url(), &body));
+            let response = match response {
+                Ok(body) => Response::from_data(body).with_header(content_type("application/json")),
+                Err(HttpError(status, message)) => Response::from_data(message)
+                    .with_status_code(status)
+                    .with_header(content_type("text/plain")),
+            };
+            if let Err(err) = request.respond(response) {
+                eprintln!("Unable to send the response: {}", err);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_respond() {
+        let input = sophya_prog_test::SceneBuilder::new()
+            .object("A", 0.0, 0.0, 4.0, 4.0)
+            .object("B", 2.0, 2.0, 4.0, 4.0)
+            .object("C", 10.0, 10.0, 1.0, 1.0)
+            .build();
+        let mut output = analyze(input, Options::default());
+        let scene = r#"{ "objects": [{ "name": "D", "x": 0, "y": 0, "width": 1, "height": 1 }] }"#;
+
+        let cases = vec![
+            (
+                Method::Get,
+                "/output",
+                "",
+                200,
+                "\"intersections\"",
+                "output",
+            ),
+            (
+                Method::Get,
+                "/intersections",
+                "",
+                200,
+                "\"B\"",
+                "all intersections",
+            ),
+            (
+                Method::Get,
+                "/intersections?name=A",
+                "",
+                200,
+                "\"A\"",
+                "intersections of A",
+            ),
+            (
+                Method::Get,
+                "/intersections?name=C",
+                "",
+                200,
+                "[]",
+                "no intersections",
+            ),
+            (
+                Method::Get,
+                "/intersections?name=Z",
+                "",
+                404,
+                "Unknown object \"Z\"",
+                "unknown",
+            ),
+            (Method::Get, "/objects?point=3,3", "", 200, "\"B\"", "point"),
+            (
+                Method::Get,
+                "/objects?rect=9,9,12,12",
+                "",
+                200,
+                "\"C\"",
+                "rect",
+            ),
+            (
+                Method::Get,
+                "/objects",
+                "",
+                400,
+                "\"point\" parameter",
+                "missing point",
+            ),
+            (
+                Method::Get,
+                "/objects?point=x",
+                "",
+                400,
+                "",
+                "invalid point",
+            ),
+            (
+                Method::Get,
+                "/nearest?point=12,12",
+                "",
+                200,
+                "\"C\"",
+                "nearest",
+            ),
+            (
+                Method::Get,
+                "/missing",
+                "",
+                404,
+                "Unknown endpoint GET /missing",
+                "unknown endpoint",
+            ),
+            (
+                Method::Post,
+                "/output",
+                "",
+                404,
+                "Unknown endpoint",
+                "wrong method",
+            ),
+            (Method::Post, "/scene", "{", 400, "", "invalid scene"),
+            (Method::Post, "/scene", scene, 200, "\"D\"", "posted scene"),
+            (
+                Method::Get,
+                "/objects?point=3,3",
+                "",
+                200,
+                "[]",
+                "replaced scene",
+            ),
+        ];
+        for case in cases {
+            let (status, body) = match respond(&mut output, &case.0, case.1, case.2.as_bytes()) {
+                Ok(body) => (200, String::from_utf8(body).unwrap()),
+                Err(HttpError(status, message)) => (status, message),
+            };
+            assert!(
+                status == case.3 && body.contains(case.4),
+                "Test case \"{}\" has been failed: {} {}",
+                case.5,
+                status,
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let cases = vec![
+            ("plain", "plain", "plain"),
+            ("a%20b+c", "a b c", "space"),
+            ("%C3%A9", "\u{e9}", "utf-8"),
+            ("100%", "100%", "trailing percent"),
+            ("%zz%4", "%zz%4", "invalid escapes"),
+            ("%FF", "\u{fffd}", "invalid utf-8"),
+        ];
+        for case in cases {
+            assert_eq!(
+                decode(case.0),
+                case.1,
+                "Test case \"{}\" has been failed",
+                case.2
+            );
+        }
+    }
+
+    #[test]
+    fn test_param() {
+        let cases = vec![
+            ("name=A&point=1,2", "point", Some("1,2"), "second"),
+            ("flag&name=A", "name", Some("A"), "without value"),
+            ("na%6De=A%2BB", "name", Some("A+B"), "encoded"),
+            ("name=A&name=B", "name", Some("A"), "repeated"),
+            ("name=", "name", Some(""), "empty value"),
+            ("names=A", "name", None, "other name"),
+            ("", "name", None, "empty query"),
+        ];
+        for case in cases {
+            assert_eq!(
+                param(case.0, case.1).as_deref(),
+                case.2,
+                "Test case \"{}\" has been failed",
+                case.3
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_body() {
+        let cases = vec![
+            ("body", None, Ok("body"), "unknown length"),
+            ("body", Some(4), Ok("body"), "at the limit"),
+            ("body", Some(5), Err(413), "too long length"),
+            ("long body", None, Err(413), "too long body"),
+        ];
+        for case in cases {
+            let body = read_body(case.0.as_bytes(), case.1, 4)
+                .map(|body| String::from_utf8(body).unwrap())
+                .map_err(|HttpError(status, _)| status);
+            assert_eq!(
+                body.as_deref().map_err(|status| *status),
+                case.2,
+                "Test case \"{}\" has been failed",
+                case.3
+            );
+        }
+    }
+}
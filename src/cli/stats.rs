@@ -0,0 +1,36 @@
+use sophya_prog_test::{analyze, summarize};
+use structopt::StructOpt;
+
+use super::{AnalysisOptions, InputOptions, OutputOptions};
+
+/// Prints the scene summary: object areas, overall bounds and overlaps
+#[derive(StructOpt)]
+pub struct Stats {
+    #[structopt(flatten)]
+    input: InputOptions,
+    #[structopt(flatten)]
+    analysis: AnalysisOptions,
+    /// Number of the intersection area histogram bins
+    #[structopt(long, default_value = "10")]
+    bins: usize,
+    #[structopt(flatten)]
+    output: OutputOptions,
+}
+
+impl Stats {
+    pub fn run(self) -> anyhow::Result<()> {
+        let files = self.input.files()?;
+        let summary = |input| summarize(&analyze(input, self.analysis.options(false)), self.bins);
+        if files.len() == 1 || self.input.merge {
+            let summary = summary(self.input.read_merged(&files)?);
+            return self.output.write(summary.to_vec_as(self.output.format)?);
+        }
+
+        for (file, path) in files.iter().zip(self.output.paths_in_dir(&files)?) {
+            let summary = summary(self.input.read(file)?);
+            self.output
+                .write_into_dir(&path, summary.to_vec_as(self.output.format)?)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::{fmt, io::Read, path::Path};
+
+use miette::{LabeledSpan, NamedSource, SourceCode};
+use sophya_prog_test::{
+    decompress, validate_input, validate_json, Diagnostic, Error, InputFormat, Severity,
+};
+use structopt::StructOpt;
+
+use super::InputOptions;
+
+/// Checks the input files and reports every found problem with its location
+#[derive(StructOpt)]
+pub struct Validate {
+    #[structopt(flatten)]
+    input: InputOptions,
+}
+
+/// Error of the run that has found the invalid input.
+#[derive(Debug, thiserror::Error)]
+#[error("Validation failed with {errors} error(s)")]
+pub struct ValidationFailed {
+    pub errors: usize,
+}
+
+/// Diagnostic with the source snippet for the report.
+#[derive(Debug)]
+struct Report {
+    diagnostic: Diagnostic,
+    source: Option<NamedSource<String>>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.diagnostic.message)
+    }
+}
+
+impl std::error::Error for Report {}
+
+impl miette::Diagnostic for Report {
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(match self.diagnostic.severity {
+            Severity::Error => miette::Severity::Error,
+            Severity::Warning => miette::Severity::Warning,
+        })
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.source.as_ref().map(|source| source as &dyn SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.source.as_ref()?;
+        let span = self.diagnostic.span.clone()?;
+        let related =
+            self.diagnostic.related.clone().map(|related| {
+                LabeledSpan::new_with_span(Some("first used here".to_owned()), related)
+            });
+        Some(Box::new(
+            std::iter::once(LabeledSpan::new_primary_with_span(
+                Some("here".to_owned()),
+                span,
+            ))
+            .chain(related),
+        ))
+    }
+}
+
+impl Validate {
+    fn check(&self, path: &Path) -> Result<Vec<Report>, Error> {
+        let (reader, format) = self.input.open(path)?;
+        if format != InputFormat::Json {
+            let diagnostics = match self.input.read_from(reader, format) {
+                Ok(input) => validate_input(&input),
+                Err(err) => vec![Diagnostic {
+                    severity: Severity::Error,
+                    message: err.to_string(),
+                    span: None,
+                    related: None,
+                }],
+            };
+            return Ok(diagnostics
+                .into_iter()
+                .map(|diagnostic| Report {
+                    diagnostic,
+                    source: None,
+                })
+                .collect());
+        }
+
+        let mut text = String::new();
+        decompress(reader)
+            .and_then(|mut reader| reader.read_to_string(&mut text))
+            .map_err(|err| Error::FileReadError {
+                path: path.to_owned(),
+                reason: err.into(),
+            })?;
+        let source = NamedSource::new(path.display().to_string(), text);
+        Ok(validate_json(source.inner())
+            .into_iter()
+            .map(|diagnostic| Report {
+                diagnostic,
+                source: Some(source.clone()),
+            })
+            .collect())
+    }
+
+    pub fn run(self) -> anyhow::Result<()> {
+        let (mut errors, mut warnings) = (0, 0);
+        for file in self.input.files()? {
+            for report in self.check(&file)? {
+                match report.diagnostic.severity {
+                    Severity::Error => errors += 1,
+                    Severity::Warning => warnings += 1,
+                }
+                eprintln!("{:?}", miette::Report::new(report));
+            }
+        }
+
+        eprintln!("Found {} error(s) and {} warning(s)", errors, warnings);
+        if errors > 0 {
+            return Err(ValidationFailed { errors }.into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,412 @@
+use sophya_prog_test::{analyze, Options};
+use structopt::StructOpt;
+
+use super::InputOptions;
+
+/// Shows the scene in the interactive terminal viewer, requires the "tui" feature
+///
+/// Arrows or hjkl pan the view, +/- zoom, 0 resets the view. Click an object or use Tab to
+/// select it and list its intersections, Esc clears the selection and q quits.
+#[derive(StructOpt)]
+pub struct View {
+    #[structopt(flatten)]
+    input: InputOptions,
+}
+
+impl View {
+    pub fn run(self) -> anyhow::Result<()> {
+        let files = self.input.files()?;
+        let input = self.input.read_merged(&files)?;
+        let output = analyze(input, Options::default());
+        viewer::run(output)
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+mod viewer {
+    pub fn run(_output: sophya_prog_test::Output) -> anyhow::Result<()> {
+        anyhow::bail!("The interactive viewer requires the \"tui\" feature")
+    }
+}
+
+#[cfg(feature = "tui")]
+mod viewer {
+    use std::io::stdout;
+
+    use ratatui::{
+        crossterm::{
+            event::{
+                self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind,
+                MouseButton, MouseEventKind,
+            },
+            execute,
+        },
+        layout::{Constraint, Layout, Position, Rect},
+        style::{Color, Style, Stylize},
+        symbols::Marker,
+        text::Line,
+        widgets::{
+            canvas::{Canvas, Rectangle},
+            Block, List, ListItem, Paragraph, Widget,
+        },
+        DefaultTerminal, Frame,
+    };
+    use sophya_prog_test::{BoundingRect, Output, Point2D};
+
+    /// Part of the view that is moved by one pan step.
+    const PAN_STEP: f32 = 0.1;
+    /// Scale of the view after one zoom step.
+    const ZOOM_STEP: f32 = 1.25;
+
+    struct Viewer {
+        output: Output,
+        bounds: BoundingRect,
+        viewport: BoundingRect,
+        selected: Option<usize>,
+        /// Screen area of the scene drawn by the last frame, used to map the mouse clicks.
+        scene_area: Rect,
+    }
+
+    impl Viewer {
+        fn new(output: Output) -> Self {
+            let bounds = output
+                .areas
+                .iter()
+                .map(|object| object.area)
+                .reduce(|a, b| a.union(&b))
+                .unwrap_or_else(|| {
+                    BoundingRect::from_points(Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0))
+                });
+            Self {
+                output,
+                bounds,
+                viewport: bounds,
+                selected: None,
+                scene_area: Rect::default(),
+            }
+        }
+
+        fn pan(&mut self, dx: f32, dy: f32) {
+            let (a, b) = (self.viewport.top_left(), self.viewport.bottom_right());
+            let (dx, dy) = (dx * (b.x - a.x), dy * (b.y - a.y));
+            self.viewport = BoundingRect::from_points(
+                Point2D::new(a.x + dx, a.y + dy),
+                Point2D::new(b.x + dx, b.y + dy),
+            );
+        }
+
+        fn zoom(&mut self, scale: f32) {
+            let (a, b) = (self.viewport.top_left(), self.viewport.bottom_right());
+            let center = Point2D::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+            let (half_width, half_height) = ((b.x - a.x) * scale / 2.0, (b.y - a.y) * scale / 2.0);
+            self.viewport = BoundingRect::from_points(
+                Point2D::new(center.x - half_width, center.y - half_height),
+                Point2D::new(center.x + half_width, center.y + half_height),
+            );
+        }
+
+        fn select_next(&mut self, forward: bool) {
+            let count = self.output.areas.len();
+            if count == 0 {
+                return;
+            }
+            self.selected = Some(match (self.selected, forward) {
+                (None, true) => 0,
+                (None, false) => count - 1,
+                (Some(idx), true) => (idx + 1) % count,
+                (Some(idx), false) => (idx + count - 1) % count,
+            });
+        }
+
+        /// Selects the topmost object under the clicked cell, clears the selection if there
+        /// is none.
+        fn click(&mut self, column: u16, row: u16) {
+            let area = self.scene_area;
+            if !area.contains(Position::new(column, row)) {
+                return;
+            }
+            let (a, b) = (self.viewport.top_left(), self.viewport.bottom_right());
+            let x = a.x + (column - area.x) as f32 / area.width as f32 * (b.x - a.x);
+            let y = a.y + (row - area.y) as f32 / area.height as f32 * (b.y - a.y);
+            let cell = BoundingRect::from_points(
+                Point2D::new(x, y),
+                Point2D::new(
+                    x + (b.x - a.x) / area.width as f32,
+                    y + (b.y - a.y) / area.height as f32,
+                ),
+            );
+            self.selected = self
+                .output
+                .areas
+                .iter()
+                .rposition(|object| object.area.intersect(&cell).is_some());
+        }
+
+        fn draw(&mut self, frame: &mut Frame) {
+            let [main, help] =
+                Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+            let [scene, side] =
+                Layout::horizontal([Constraint::Percentage(75), Constraint::Percentage(25)])
+                    .areas(main);
+
+            let block = Block::bordered().title(" Scene ");
+            self.scene_area = block.inner(scene);
+            frame.render_widget(self.scene(block), scene);
+            frame.render_widget(self.selection(), side);
+            frame.render_widget(
+                Paragraph::new(
+                    "arrows/hjkl: pan  +/-: zoom  0: reset  click/Tab: select  Esc: clear  q: quit",
+                )
+                .dim(),
+                help,
+            );
+        }
+
+        /// Draws the scene, the y axis of the canvas is flipped to keep the top-left origin.
+        fn scene<'a>(&'a self, block: Block<'a>) -> impl Widget + 'a {
+            let (a, b) = (self.viewport.top_left(), self.viewport.bottom_right());
+            let selected = self
+                .selected
+                .map(|idx| self.output.areas[idx].name.as_str());
+            Canvas::default()
+                .block(block)
+                .marker(Marker::Braille)
+                .x_bounds([a.x as f64, b.x as f64])
+                .y_bounds([-b.y as f64, -a.y as f64])
+                .paint(move |ctx| {
+                    let rectangle = |rect: &BoundingRect, color| {
+                        let (a, b) = (rect.top_left(), rect.bottom_right());
+                        Rectangle {
+                            x: a.x as f64,
+                            y: -b.y as f64,
+                            width: (b.x - a.x) as f64,
+                            height: (b.y - a.y) as f64,
+                            color,
+                        }
+                    };
+                    for object in &self.output.areas {
+                        let color = if Some(object.name.as_str()) == selected {
+                            Color::Yellow
+                        } else {
+                            Color::Blue
+                        };
+                        ctx.draw(&rectangle(&object.area, color));
+                    }
+                    for intersection in &self.output.intersections {
+                        let (first, second) = &intersection.names;
+                        let involved = selected.is_none()
+                            || selected == Some(first.as_str())
+                            || selected == Some(second.as_str());
+                        if involved {
+                            ctx.draw(&rectangle(&intersection.area, Color::Red));
+                        }
+                    }
+                    ctx.layer();
+                    for object in &self.output.areas {
+                        let top_left = object.area.top_left();
+                        let style = if Some(object.name.as_str()) == selected {
+                            Style::new().yellow().bold()
+                        } else {
+                            Style::new()
+                        };
+                        ctx.print(
+                            top_left.x as f64,
+                            -top_left.y as f64,
+                            Line::styled(object.name.clone(), style),
+                        );
+                    }
+                })
+        }
+
+        /// Lists the selected object and the intersections involving it.
+        fn selection(&self) -> List<'_> {
+            let block = Block::bordered().title(" Selection ");
+            let Some(object) = self.selected.map(|idx| &self.output.areas[idx]) else {
+                return List::new([ListItem::new("Nothing selected")]).block(block);
+            };
+
+            let rect = |rect: &BoundingRect| {
+                let (a, b) = (rect.top_left(), rect.bottom_right());
+                format!("({:.1}, {:.1}) - ({:.1}, {:.1})", a.x, a.y, b.x, b.y)
+            };
+            let mut items = vec![
+                ListItem::new(object.name.as_str().bold()),
+                ListItem::new(rect(&object.area)),
+                ListItem::new(""),
+                ListItem::new("Intersections:".bold()),
+            ];
+            items.extend(self.output.intersections.iter().filter_map(|intersection| {
+                let other = match &intersection.names {
+                    (first, other) | (other, first) if *first == object.name => other,
+                    _ => return None,
+                };
+                Some(ListItem::new(format!(
+                    "{}: {}",
+                    other,
+                    rect(&intersection.area)
+                )))
+            }));
+            if items.len() == 4 {
+                items.push(ListItem::new("None".dim()));
+            }
+            List::new(items).block(block)
+        }
+
+        fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+            loop {
+                terminal.draw(|frame| self.draw(frame))?;
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Left | KeyCode::Char('h') => self.pan(-PAN_STEP, 0.0),
+                        KeyCode::Right | KeyCode::Char('l') => self.pan(PAN_STEP, 0.0),
+                        KeyCode::Up | KeyCode::Char('k') => self.pan(0.0, -PAN_STEP),
+                        KeyCode::Down | KeyCode::Char('j') => self.pan(0.0, PAN_STEP),
+                        KeyCode::Char('+') | KeyCode::Char('=') => self.zoom(1.0 / ZOOM_STEP),
+                        KeyCode::Char('-') => self.zoom(ZOOM_STEP),
+                        KeyCode::Char('0') => self.viewport = self.bounds,
+                        KeyCode::Tab => self.select_next(true),
+                        KeyCode::BackTab => self.select_next(false),
+                        KeyCode::Esc => self.selected = None,
+                        _ => {}
+                    },
+                    Event::Mouse(mouse) => match mouse.kind {
+                        MouseEventKind::Down(MouseButton::Left) => {
+                            self.click(mouse.column, mouse.row)
+                        }
+                        MouseEventKind::ScrollUp => self.zoom(1.0 / ZOOM_STEP),
+                        MouseEventKind::ScrollDown => self.zoom(ZOOM_STEP),
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    pub fn run(output: Output) -> anyhow::Result<()> {
+        let mut viewer = Viewer::new(output);
+        execute!(stdout(), EnableMouseCapture)?;
+        let result = ratatui::run(|terminal| viewer.run(terminal));
+        execute!(stdout(), DisableMouseCapture)?;
+        result
+    }
+
+    #[cfg(test)]
+    fn viewer() -> Viewer {
+        let input = sophya_prog_test::SceneBuilder::new()
+            .object("A", 0.0, 0.0, 10.0, 10.0)
+            .object("B", 20.0, 0.0, 10.0, 10.0)
+            .object("C", 5.0, 5.0, 3.0, 3.0)
+            .build();
+        Viewer::new(sophya_prog_test::analyze(input, Default::default()))
+    }
+
+    #[cfg(test)]
+    fn corners(rect: &BoundingRect) -> [f32; 4] {
+        let (a, b) = (rect.top_left(), rect.bottom_right());
+        [a.x, a.y, b.x, b.y]
+    }
+
+    #[test]
+    fn test_viewer_pan_zoom() {
+        let cases = vec![
+            (vec![], [0.0, 0.0, 30.0, 10.0], "bounds"),
+            (vec![(0.1, 0.0, 1.0)], [3.0, 0.0, 33.0, 10.0], "pan right"),
+            (vec![(0.0, -0.5, 1.0)], [0.0, -5.0, 30.0, 5.0], "pan up"),
+            (vec![(0.0, 0.0, 0.5)], [7.5, 2.5, 22.5, 7.5], "zoom in"),
+            (vec![(0.0, 0.0, 2.0)], [-15.0, -5.0, 45.0, 15.0], "zoom out"),
+            (
+                vec![(0.0, 0.0, 0.5), (0.0, 0.0, 2.0)],
+                [0.0, 0.0, 30.0, 10.0],
+                "zoom back",
+            ),
+            // The pan step is the part of the zoomed view.
+            (
+                vec![(0.0, 0.0, 0.5), (-1.0, 1.0, 1.0)],
+                [-7.5, 7.5, 7.5, 12.5],
+                "pan zoomed",
+            ),
+        ];
+        for case in cases {
+            let mut viewer = viewer();
+            for (dx, dy, scale) in case.0 {
+                viewer.pan(dx, dy);
+                viewer.zoom(scale);
+            }
+            assert_eq!(
+                corners(&viewer.viewport),
+                case.1,
+                "Test case \"{}\" has been failed",
+                case.2
+            );
+            assert_eq!(corners(&viewer.bounds), [0.0, 0.0, 30.0, 10.0]);
+        }
+    }
+
+    #[test]
+    fn test_viewer_select() {
+        let cases: Vec<(&[bool], Option<usize>, &str)> = vec![
+            (&[], None, "nothing"),
+            (&[true], Some(0), "first"),
+            (&[false], Some(2), "last"),
+            (&[true, true, true], Some(2), "third"),
+            (&[true, true, true, true], Some(0), "wrap forward"),
+            (&[true, false], Some(2), "wrap back"),
+        ];
+        for case in cases {
+            let mut viewer = viewer();
+            for &forward in case.0 {
+                viewer.select_next(forward);
+            }
+            assert_eq!(
+                viewer.selected, case.1,
+                "Test case \"{}\" has been failed",
+                case.2
+            );
+        }
+
+        let empty = sophya_prog_test::SceneBuilder::new().build();
+        let mut viewer = Viewer::new(sophya_prog_test::analyze(empty, Default::default()));
+        viewer.select_next(true);
+        assert_eq!(viewer.selected, None);
+        assert_eq!(corners(&viewer.viewport), [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_viewer_click() {
+        // Every cell of the scene area is the unit square of the scene.
+        let cases = vec![
+            ((2, 2), Some(Some(0)), "first object"),
+            ((26, 6), Some(Some(1)), "second object"),
+            ((7, 7), Some(Some(2)), "topmost object"),
+            ((16, 2), Some(None), "empty cell"),
+            ((0, 0), None, "outside of the scene"),
+            ((31, 2), None, "right of the scene"),
+        ];
+        for case in cases {
+            let mut viewer = viewer();
+            viewer.scene_area = Rect::new(1, 1, 30, 10);
+            viewer.selected = Some(1);
+            viewer.click(case.0 .0, case.0 .1);
+            assert_eq!(
+                viewer.selected,
+                case.1.unwrap_or(Some(1)),
+                "Test case \"{}\" has been failed",
+                case.2
+            );
+        }
+
+        // The scene area is taken from the drawn frame, the clicks follow the zoom.
+        let mut viewer = viewer();
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(42, 12)).unwrap();
+        terminal.draw(|frame| viewer.draw(frame)).unwrap();
+        assert_eq!(viewer.scene_area, Rect::new(1, 1, 30, 9));
+        viewer.click(1, 6);
+        assert_eq!(viewer.selected, Some(0));
+        viewer.zoom(0.5);
+        viewer.click(1, 6);
+        assert_eq!(viewer.selected, Some(2));
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    io::Read,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{decompress, Error, Input, IntersectionMetrics, Object, ObjectArea, Output};
+
+/// COCO file, either the whole dataset or the detection results, i.e. the bare array of the
+/// annotations.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CocoFile {
+    Dataset(CocoDataset),
+    Results(Vec<CocoAnnotation>),
+}
+
+#[derive(Deserialize)]
+struct CocoDataset {
+    #[serde(default)]
+    images: Vec<CocoImage>,
+    annotations: Vec<CocoAnnotation>,
+    #[serde(default)]
+    categories: Vec<CocoCategory>,
+}
+
+#[derive(Deserialize)]
+struct CocoImage {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+struct CocoCategory {
+    id: i64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CocoAnnotation {
+    // The detection results have no ids.
+    #[serde(default)]
+    id: Option<i64>,
+    image_id: i64,
+    #[serde(default)]
+    category_id: Option<i64>,
+    bbox: [f32; 4],
+    #[serde(default)]
+    score: Option<f64>,
+}
+
+/// Intersection of two annotations of the COCO results.
+#[derive(Serialize)]
+struct CocoIntersection<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image_id: Option<i32>,
+    annotation_ids: (Value, Value),
+    #[serde(skip_serializing_if = "Option::is_none")]
+    categories: Option<(Option<&'a str>, Option<&'a str>)>,
+    bbox: [f32; 4],
+    area: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iou: Option<f32>,
+}
+
+fn layer(area: Option<&ObjectArea>) -> Option<&str> {
+    area.and_then(|area| area.layer.as_deref())
+}
+
+// Writes the numeric names as numbers, like the ids of the COCO annotations.
+fn annotation_id(name: &str) -> Value {
+    name.parse::<i64>()
+        .map_or_else(|_| json!(name), |id| json!(id))
+}
+
+impl Input {
+    /// Parses the COCO dataset with the `images`, `annotations` and `categories` arrays, or
+    /// the bare array of the detection results. Every annotation becomes an object named by
+    /// its id, or by its index if it has none, with the `bbox` as the rect, the category
+    /// name as the layer and the image id as the `z` level, so the annotations of different
+    /// images are not compared with `same_z`. The category id and the score are kept as the
+    /// properties.
+    pub fn from_coco_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let reader = decompress::decompress(reader).map_err(|err| parse_error(err.into()))?;
+        let file: CocoFile =
+            serde_json::from_reader(reader).map_err(|err| parse_error(err.into()))?;
+        let (annotations, images, categories) = match file {
+            CocoFile::Dataset(dataset) => (dataset.annotations, dataset.images, dataset.categories),
+            CocoFile::Results(annotations) => (annotations, Vec::new(), Vec::new()),
+        };
+
+        let images = images.iter().map(|image| image.id).collect::<HashSet<_>>();
+        let categories = categories
+            .into_iter()
+            .map(|category| (category.id, category.name))
+            .collect::<HashMap<_, _>>();
+        let objects = annotations
+            .into_iter()
+            .enumerate()
+            .map(|(idx, annotation)| {
+                let name = annotation.id.unwrap_or(idx as i64).to_string();
+                if !images.is_empty() && !images.contains(&annotation.image_id) {
+                    return Err(parse_error(anyhow::anyhow!(
+                        "The annotation {} refers to the unknown image {}",
+                        name,
+                        annotation.image_id
+                    )));
+                }
+                let z = i32::try_from(annotation.image_id).map_err(|_| {
+                    parse_error(anyhow::anyhow!(
+                        "The image id {} of the annotation {} is out of range",
+                        annotation.image_id,
+                        name
+                    ))
+                })?;
+                let layer = annotation.category_id.map(|id| {
+                    categories
+                        .get(&id)
+                        .cloned()
+                        .unwrap_or_else(|| id.to_string())
+                });
+
+                let mut properties = Vec::new();
+                if let Some(id) = annotation.category_id {
+                    properties.push(json!({ "name": "category_id", "value": id }));
+                }
+                if let Some(score) = annotation.score {
+                    properties.push(json!({ "name": "score", "value": score }));
+                }
+                let [x, y, width, height] = annotation.bbox;
+                Ok(Object {
+                    name,
+                    width,
+                    height,
+                    x,
+                    y,
+                    layer,
+                    z: Some(z),
+                    rotation: None,
+                    shape: None,
+                    properties,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { objects })
+    }
+}
+
+impl Output {
+    /// Writes the intersections as the JSON array keyed by the annotation ids, every record
+    /// has the image id, the categories of both annotations, the `bbox` and the area of the
+    /// intersection and its intersection over union, e.g. to find the duplicated annotations
+    /// of a dataset.
+    pub(crate) fn intersections_to_coco(&self) -> Result<Vec<u8>, Error> {
+        let areas = self
+            .areas
+            .iter()
+            .rev()
+            .map(|area| (area.name.as_str(), area))
+            .collect::<HashMap<_, _>>();
+        let records = self
+            .intersections
+            .iter()
+            .map(|intersection| {
+                let a = areas.get(intersection.names.0.as_str()).copied();
+                let b = areas.get(intersection.names.1.as_str()).copied();
+                let iou = intersection.metrics.map(|metrics| metrics.iou).or_else(|| {
+                    let (a, b) = (a?, b?);
+                    Some(IntersectionMetrics::new(&a.area, &b.area, &intersection.area).iou)
+                });
+                let categories = Some((layer(a), layer(b))).filter(|x| *x != (None, None));
+                let (from, to) = (
+                    intersection.area.top_left(),
+                    intersection.area.bottom_right(),
+                );
+                CocoIntersection {
+                    image_id: a.or(b).and_then(|area| area.z),
+                    annotation_ids: (
+                        annotation_id(&intersection.names.0),
+                        annotation_id(&intersection.names.1),
+                    ),
+                    categories,
+                    bbox: [from.x, from.y, to.x - from.x, to.y - from.y],
+                    area: intersection.area.area(),
+                    iou,
+                }
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_vec_pretty(&records)
+            .map_err(|err| Error::SerializeError { reason: err.into() })
+    }
+}
+
+#[test]
+fn test_coco() {
+    use crate::{analyze, Options, OutputFormat};
+
+    let dataset = r#"{
+        "images": [{ "id": 1, "file_name": "a.jpg" }, { "id": 2, "file_name": "b.jpg" }],
+        "categories": [{ "id": 3, "name": "cat" }],
+        "annotations": [
+            { "id": 10, "image_id": 1, "category_id": 3, "bbox": [0, 0, 10, 10] },
+            { "id": 11, "image_id": 1, "category_id": 3, "bbox": [0, 0, 10, 5] },
+            { "id": 12, "image_id": 2, "category_id": 4, "bbox": [0, 0, 10, 10] }
+        ]
+    }"#;
+    let input = Input::from_coco_reader(dataset.as_bytes()).unwrap();
+    let object = &input.objects[2];
+    assert_eq!(
+        (object.name.as_str(), object.layer.as_deref(), object.z),
+        ("12", Some("4"), Some(2))
+    );
+    assert_eq!(input.objects[0].layer.as_deref(), Some("cat"));
+
+    let output = analyze(
+        input,
+        Options {
+            same_z: true,
+            ..Options::default()
+        },
+    );
+    let records: Value =
+        serde_json::from_slice(&output.to_vec_as(OutputFormat::Coco).unwrap()).unwrap();
+    assert_eq!(
+        records,
+        json!([{
+            "image_id": 1,
+            "annotation_ids": [10, 11],
+            "categories": ["cat", "cat"],
+            "bbox": [0.0, 0.0, 10.0, 5.0],
+            "area": 50.0,
+            "iou": 0.5
+        }])
+    );
+
+    let cases = vec![
+        (
+            r#"[{ "image_id": 1, "bbox": [1, 2, 3, 4], "score": 0.9 }]"#,
+            Ok("0"),
+            "results",
+        ),
+        (
+            r#"{ "images": [{ "id": 1 }], "annotations": [{ "id": 5, "image_id": 2, "bbox": [0, 0, 1, 1] }] }"#,
+            Err("unknown image 2"),
+            "unknown image",
+        ),
+        (
+            r#"{ "images": [] }"#,
+            Err("did not match"),
+            "no annotations",
+        ),
+    ];
+    for case in cases {
+        let result = Input::from_coco_reader(case.0.as_bytes())
+            .map(|input| input.objects[0].name.clone())
+            .map_err(|err| err.to_string());
+        match (result, case.1) {
+            (Ok(name), Ok(expected)) => {
+                assert_eq!(name, expected, "Test case \"{}\" has been failed", case.2)
+            }
+            (Err(err), Err(expected)) => assert!(
+                err.contains(expected),
+                "Test case \"{}\" has been failed: {}",
+                case.2,
+                err
+            ),
+            (result, _) => panic!("Test case \"{}\" has been failed: {:?}", case.2, result),
+        }
+    }
+}
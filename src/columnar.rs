@@ -0,0 +1,463 @@
+use std::io::{Cursor, Read};
+
+use arrow_array::{
+    cast::AsArray,
+    types::{Float32Type, Float64Type, Int32Type, Int64Type},
+    Array, ArrayRef, RecordBatch,
+};
+use arrow_ipc::reader::{FileReader, StreamReader};
+use arrow_schema::{ArrowError, DataType};
+use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ProjectionMask};
+use serde_json::{json, Value};
+
+use crate::{
+    decompress, wkt, AnyShape, BoundingRect, CsvColumns, Error, Input, Object, Point2D, RectSoA,
+};
+
+// The magic bytes of the Arrow IPC file, the streams have no magic.
+const ARROW_MAGIC: &[u8] = b"ARROW1";
+
+fn parse_error(reason: impl Into<anyhow::Error>) -> Error {
+    Error::ParseError {
+        reason: reason.into(),
+    }
+}
+
+fn read_all<R: Read>(reader: R) -> Result<Vec<u8>, Error> {
+    let mut reader = decompress::decompress(reader).map_err(parse_error)?;
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).map_err(parse_error)?;
+    Ok(bytes)
+}
+
+// Casts the column to the given type, the columns of the other types are converted by Arrow.
+fn cast(column: &ArrayRef, name: &str, to: &DataType) -> Result<ArrayRef, Error> {
+    arrow_cast::cast(column, to).map_err(|err| {
+        parse_error(anyhow::anyhow!(
+            "The column \"{}\" is not {}: {}",
+            name,
+            to,
+            err
+        ))
+    })
+}
+
+// Returns the value of the other column as the property value.
+fn property_value(column: &ArrayRef, row: usize) -> Option<Value> {
+    if column.is_null(row) {
+        return None;
+    }
+    let value = match column.data_type() {
+        DataType::Boolean => json!(column.as_boolean().value(row)),
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            let column = arrow_cast::cast(column, &DataType::Float64).ok()?;
+            json!(column.as_primitive::<Float64Type>().value(row))
+        }
+        data_type if data_type.is_integer() => {
+            let column = arrow_cast::cast(column, &DataType::Int64).ok()?;
+            json!(column.as_primitive::<Int64Type>().value(row))
+        }
+        _ => {
+            let column = arrow_cast::cast(column, &DataType::Utf8).ok()?;
+            json!(column.as_string::<i32>().value(row))
+        }
+    };
+    Some(value)
+}
+
+// Returns the shape of the geometry column, WKB for the binary columns (e.g. GeoParquet) and
+// WKT or hex WKB for the string ones.
+fn geometry(column: &ArrayRef, row: usize) -> Option<Result<AnyShape, Error>> {
+    if column.is_null(row) {
+        return None;
+    }
+    let shape = match column.data_type() {
+        DataType::Binary => AnyShape::from_wkb(column.as_binary::<i32>().value(row)),
+        DataType::LargeBinary => AnyShape::from_wkb(column.as_binary::<i64>().value(row)),
+        _ => {
+            let text = match cast(column, "geometry", &DataType::Utf8) {
+                Ok(text) => text,
+                Err(err) => return Some(Err(err)),
+            };
+            let text = text.as_string::<i32>().value(row);
+            if text.trim().is_empty() {
+                return None;
+            }
+            wkt::parse_geometry(text)
+        }
+    };
+    Some(shape)
+}
+
+// Converts the record batches column by column, the columns are mapped like the CSV headers.
+fn objects(
+    batches: impl Iterator<Item = Result<RecordBatch, ArrowError>>,
+    columns: &CsvColumns,
+) -> Result<Vec<Object>, Error> {
+    let mut objects = Vec::new();
+    for batch in batches {
+        let batch = batch.map_err(parse_error)?;
+        let schema = batch.schema();
+        let find = |name: &str| schema.index_of(name).ok().map(|idx| batch.column(idx));
+        let column = |name: &str| {
+            find(name).ok_or_else(|| parse_error(anyhow::anyhow!("Missing column \"{}\"", name)))
+        };
+
+        let names = cast(column(&columns.name)?, &columns.name, &DataType::Utf8)?;
+        let geometries = find(&columns.geometry);
+        // The rect columns are optional if the shapes are given by the geometry column.
+        let rect = [&columns.x, &columns.y, &columns.width, &columns.height]
+            .iter()
+            .map(|name| {
+                let column = match geometries {
+                    Some(_) => find(name),
+                    None => Some(column(name)?),
+                };
+                column
+                    .map(|column| cast(column, name, &DataType::Float32))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let layers = find(&columns.layer)
+            .map(|layers| cast(layers, &columns.layer, &DataType::Utf8))
+            .transpose()?;
+        let levels = find(&columns.z)
+            .map(|levels| cast(levels, &columns.z, &DataType::Int32))
+            .transpose()?;
+        let rotations = find(&columns.rotation)
+            .map(|rotations| cast(rotations, &columns.rotation, &DataType::Float32))
+            .transpose()?;
+        let known = [
+            &columns.name,
+            &columns.x,
+            &columns.y,
+            &columns.width,
+            &columns.height,
+            &columns.layer,
+            &columns.z,
+            &columns.rotation,
+            &columns.geometry,
+        ];
+        // The other columns are kept as the properties.
+        let extra = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .filter(|(field, _)| !known.contains(&field.name()))
+            .collect::<Vec<_>>();
+
+        let names = names.as_string::<i32>();
+        for row in 0..batch.num_rows() {
+            let index = objects.len();
+            if names.is_null(row) {
+                return Err(parse_error(anyhow::anyhow!(
+                    "The name of the row {} is null",
+                    index
+                )));
+            }
+            let name = names.value(row).to_owned();
+            let shape = geometries
+                .and_then(|column| geometry(column, row))
+                .transpose()
+                .map_err(|err| parse_error(anyhow::anyhow!("{} on row {}", err, index)))?;
+            let object = match shape {
+                Some(shape) => Object::from_shape(name, shape),
+                None => {
+                    let mut values = [0_f32; 4];
+                    for (value, (column, field)) in values.iter_mut().zip(rect.iter().zip([
+                        &columns.x,
+                        &columns.y,
+                        &columns.width,
+                        &columns.height,
+                    ])) {
+                        let column = column
+                            .as_ref()
+                            .map(|column| column.as_primitive::<Float32Type>())
+                            .filter(|column| !column.is_null(row))
+                            .ok_or_else(|| {
+                                parse_error(anyhow::anyhow!(
+                                    "The row {} has neither a geometry nor the \"{}\" value",
+                                    index,
+                                    field
+                                ))
+                            })?;
+                        *value = column.value(row);
+                    }
+                    let [x, y, width, height] = values;
+                    Object {
+                        name,
+                        width,
+                        height,
+                        x,
+                        y,
+                        layer: None,
+                        z: None,
+                        rotation: None,
+                        shape: None,
+                        properties: Vec::new(),
+                    }
+                }
+            };
+            objects.push(Object {
+                layer: layers
+                    .as_ref()
+                    .map(|layers| layers.as_string::<i32>())
+                    .filter(|layers| !layers.is_null(row))
+                    .map(|layers| layers.value(row).to_owned())
+                    .filter(|layer| !layer.is_empty()),
+                z: levels
+                    .as_ref()
+                    .map(|levels| levels.as_primitive::<Int32Type>())
+                    .filter(|levels| !levels.is_null(row))
+                    .map(|levels| levels.value(row)),
+                rotation: rotations
+                    .as_ref()
+                    .map(|rotations| rotations.as_primitive::<Float32Type>())
+                    .filter(|rotations| !rotations.is_null(row))
+                    .map(|rotations| rotations.value(row)),
+                properties: extra
+                    .iter()
+                    .filter_map(|(field, column)| {
+                        let value = property_value(column, row)?;
+                        Some(json!({ "name": field.name(), "value": value }))
+                    })
+                    .collect(),
+                ..object
+            });
+        }
+    }
+    Ok(objects)
+}
+
+// Reads the rect columns of the record batches straight into the arrays, the other columns
+// are not converted.
+fn rects(
+    batches: impl Iterator<Item = Result<RecordBatch, ArrowError>>,
+    columns: &CsvColumns,
+) -> Result<RectSoA, Error> {
+    let mut rects = RectSoA::new();
+    for batch in batches {
+        let batch = batch.map_err(parse_error)?;
+        let schema = batch.schema();
+        let edges = [&columns.x, &columns.y, &columns.width, &columns.height]
+            .iter()
+            .map(|name| {
+                let column = schema
+                    .index_of(name)
+                    .map(|idx| batch.column(idx))
+                    .map_err(|_| parse_error(anyhow::anyhow!("Missing column \"{}\"", name)))?;
+                let column = cast(column, name, &DataType::Float32)?;
+                if column.null_count() > 0 {
+                    return Err(parse_error(anyhow::anyhow!(
+                        "The column \"{}\" has the null values",
+                        name
+                    )));
+                }
+                Ok(column)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let [x, y, width, height] =
+            [0, 1, 2, 3].map(|idx| edges[idx].as_primitive::<Float32Type>());
+        let values = x
+            .values()
+            .iter()
+            .zip(y.values())
+            .zip(width.values().iter().zip(height.values()));
+        for ((&x, &y), (&width, &height)) in values {
+            rects.push(BoundingRect::from_points(
+                Point2D::new(x, y),
+                Point2D::new(x + width, y + height),
+            ));
+        }
+    }
+    Ok(rects)
+}
+
+impl RectSoA {
+    /// Reads the rects of the Parquet file straight into the arrays: only the `x`, `y`,
+    /// `width` and `height` columns named like the CSV headers are decoded, so the names, the
+    /// geometries, the rotations and the other columns cost nothing. The rects are the same as
+    /// the areas of the objects read by [`Input::from_parquet_reader`] if the file has no
+    /// geometries and rotations.
+    pub fn from_parquet_reader<R: Read>(reader: R, columns: &CsvColumns) -> Result<Self, Error> {
+        let bytes = bytes::Bytes::from(read_all(reader)?);
+        let builder = ParquetRecordBatchReaderBuilder::try_new(bytes).map_err(parse_error)?;
+        let indices = [&columns.x, &columns.y, &columns.width, &columns.height]
+            .iter()
+            .filter_map(|name| builder.schema().index_of(name).ok())
+            .collect::<Vec<_>>();
+        let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+        let batches = builder.with_projection(mask).build().map_err(parse_error)?;
+        rects(batches, columns)
+    }
+
+    /// Reads the rects of the Arrow IPC file or stream like [`RectSoA::from_parquet_reader`].
+    pub fn from_arrow_reader<R: Read>(reader: R, columns: &CsvColumns) -> Result<Self, Error> {
+        let bytes = read_all(reader)?;
+        if bytes.starts_with(ARROW_MAGIC) {
+            let batches = FileReader::try_new(Cursor::new(bytes), None).map_err(parse_error)?;
+            rects(batches, columns)
+        } else {
+            let batches = StreamReader::try_new(bytes.as_slice(), None).map_err(parse_error)?;
+            rects(batches, columns)
+        }
+    }
+}
+
+impl Input {
+    /// Parses the Parquet file with the columns named like the CSV headers, the column types
+    /// are cast by Arrow, e.g. the `f64` coordinates or the integer names. The geometry column
+    /// holds the WKB or the WKT shapes, the other columns are kept as the properties.
+    pub fn from_parquet_reader<R: Read>(reader: R, columns: &CsvColumns) -> Result<Self, Error> {
+        let bytes = bytes::Bytes::from(read_all(reader)?);
+        let batches = ParquetRecordBatchReaderBuilder::try_new(bytes)
+            .and_then(|builder| builder.build())
+            .map_err(parse_error)?;
+        Ok(Self {
+            objects: objects(batches, columns)?,
+        })
+    }
+
+    /// Parses the Arrow IPC file or stream like [`Input::from_parquet_reader`].
+    pub fn from_arrow_reader<R: Read>(reader: R, columns: &CsvColumns) -> Result<Self, Error> {
+        let bytes = read_all(reader)?;
+        let objects = if bytes.starts_with(ARROW_MAGIC) {
+            let batches = FileReader::try_new(Cursor::new(bytes), None).map_err(parse_error)?;
+            objects(batches, columns)?
+        } else {
+            let batches = StreamReader::try_new(bytes.as_slice(), None).map_err(parse_error)?;
+            objects(batches, columns)?
+        };
+        Ok(Self { objects })
+    }
+}
+
+#[test]
+fn test_columnar() {
+    use std::sync::Arc;
+
+    use arrow_array::{BinaryArray, Float64Array, Int64Array, StringArray};
+    use arrow_ipc::writer::{FileWriter, StreamWriter};
+    use arrow_schema::{Field, Schema};
+    use parquet::arrow::ArrowWriter;
+
+    use crate::{Point2D, Polygon};
+
+    let triangle = AnyShape::Polygon(Polygon::new(vec![
+        Point2D::new(0.0, 0.0),
+        Point2D::new(4.0, 0.0),
+        Point2D::new(2.0, 3.0),
+    ]));
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("x", DataType::Float64, true),
+        Field::new("y", DataType::Float64, true),
+        Field::new("width", DataType::Float64, true),
+        Field::new("height", DataType::Float64, true),
+        Field::new("layer", DataType::Utf8, true),
+        Field::new("geometry", DataType::Binary, true),
+        Field::new("score", DataType::Float64, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(vec![1, 2])),
+        Arc::new(Float64Array::from(vec![Some(1.0), None])),
+        Arc::new(Float64Array::from(vec![Some(2.0), None])),
+        Arc::new(Float64Array::from(vec![Some(3.0), None])),
+        Arc::new(Float64Array::from(vec![Some(4.0), None])),
+        Arc::new(StringArray::from(vec![Some("door"), None])),
+        Arc::new(BinaryArray::from_opt_vec(vec![
+            None,
+            Some(triangle.to_wkb().as_slice()),
+        ])),
+        Arc::new(Float64Array::from(vec![Some(0.5), None])),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns).unwrap();
+    let columns = "name=id".parse::<CsvColumns>().unwrap();
+
+    let mut parquet = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut parquet, schema.clone(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+    let input = Input::from_parquet_reader(parquet.as_slice(), &columns).unwrap();
+    let objects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                [x.x, x.y, x.width, x.height],
+                x.layer.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        vec![
+            ("1", [1.0, 2.0, 3.0, 4.0], Some("door")),
+            ("2", [0.0, 0.0, 4.0, 3.0], None),
+        ]
+    );
+    assert_eq!(input.objects[1].shape, Some(triangle));
+    assert_eq!(
+        input.objects[0].properties,
+        vec![json!({ "name": "score", "value": 0.5 })]
+    );
+
+    // The IPC files and streams are read the same way.
+    let mut file = Vec::new();
+    let mut writer = FileWriter::try_new(&mut file, &schema).unwrap();
+    writer.write(&batch).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    let mut stream = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut stream, &schema).unwrap();
+    writer.write(&batch).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    assert_eq!(
+        Input::from_arrow_reader(file.as_slice(), &columns).unwrap(),
+        input
+    );
+    assert_eq!(
+        Input::from_arrow_reader(stream.as_slice(), &columns).unwrap(),
+        input
+    );
+
+    let err = Input::from_parquet_reader(parquet.as_slice(), &CsvColumns::default()).unwrap_err();
+    assert!(err.to_string().contains("Missing column \"name\""));
+    let err = Input::from_parquet_reader(
+        parquet.as_slice(),
+        &"name=id,geometry=shape".parse().unwrap(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("row 1 has neither a geometry"));
+    assert!(Input::from_parquet_reader("PAR1".as_bytes(), &columns).is_err());
+
+    // The rects are read straight into the arrays, the null rect values are rejected.
+    let rect_batch = batch.slice(0, 1);
+    let mut rects = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut rects, schema.clone(), None).unwrap();
+    writer.write(&rect_batch).unwrap();
+    writer.close().unwrap();
+    let soa = RectSoA::from_parquet_reader(rects.as_slice(), &columns).unwrap();
+    assert_eq!(
+        soa.iter().collect::<Vec<_>>(),
+        vec![input.objects[0].area().area]
+    );
+    let mut stream = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut stream, &schema).unwrap();
+    writer.write(&rect_batch).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    assert_eq!(
+        RectSoA::from_arrow_reader(stream.as_slice(), &columns).unwrap(),
+        soa
+    );
+    let err = RectSoA::from_parquet_reader(parquet.as_slice(), &columns).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("The column \"x\" has the null values"));
+    let err = RectSoA::from_parquet_reader(rects.as_slice(), &"name=id,width=w".parse().unwrap())
+        .unwrap_err();
+    assert!(err.to_string().contains("Missing column \"w\""));
+}
@@ -0,0 +1,65 @@
+use std::io::{self, BufRead, BufReader, Read};
+
+use flate2::bufread::MultiGzDecoder;
+use ruzstd::decoding::{FrameDecoder, StreamingDecoder};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// File extensions of the supported compressed files.
+pub(crate) const EXTENSIONS: &[&str] = &["gz", "zst"];
+
+/// Reader that transparently decompresses gzip and zstd streams.
+pub enum Decompressed<R: BufRead> {
+    Plain(R),
+    Gzip(Box<MultiGzDecoder<R>>),
+    Zstd(Box<StreamingDecoder<R, FrameDecoder>>),
+}
+
+/// Detects the compression by the magic bytes at the beginning of the stream.
+pub fn decompress<R: Read>(reader: R) -> io::Result<Decompressed<BufReader<R>>> {
+    let mut reader = BufReader::new(reader);
+    let header = reader.fill_buf()?;
+
+    Ok(if header.starts_with(GZIP_MAGIC) {
+        Decompressed::Gzip(Box::new(MultiGzDecoder::new(reader)))
+    } else if header.starts_with(ZSTD_MAGIC) {
+        let decoder = StreamingDecoder::new(reader)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Decompressed::Zstd(Box::new(decoder))
+    } else {
+        Decompressed::Plain(reader)
+    })
+}
+
+impl<R: BufRead> Read for Decompressed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.read(buf),
+            Self::Gzip(reader) => reader.read(buf),
+            Self::Zstd(reader) => reader.read(buf),
+        }
+    }
+}
+
+#[test]
+fn test_decompress() {
+    use std::io::Write;
+
+    let text = br#"{ "objects": [] }"#;
+
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    gzip.write_all(text).unwrap();
+    let gzip = gzip.finish().unwrap();
+    let zstd =
+        ruzstd::encoding::compress_to_vec(&text[..], ruzstd::encoding::CompressionLevel::Fastest);
+
+    for (name, bytes) in [("plain", text.to_vec()), ("gzip", gzip), ("zstd", zstd)] {
+        let mut decoded = Vec::new();
+        decompress(bytes.as_slice())
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, text, "Test case \"{}\" has been failed", name);
+    }
+}
@@ -0,0 +1,188 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{list_intersections_using, Algorithm, Input, IntersectionMetrics, Object};
+
+/// How the duplicate objects are detected, the first of the duplicates is kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupeMode {
+    /// The objects with exactly the same rect.
+    Geometry,
+    /// The objects with the same name.
+    Name,
+    /// The objects overlapping with at least the given intersection over union, in `(0, 1]`.
+    Iou(f32),
+}
+
+/// Parses `geometry`, `name` or the IoU mode with the threshold, e.g. `iou=0.9`.
+impl FromStr for DedupeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "geometry" => Ok(Self::Geometry),
+            "name" => Ok(Self::Name),
+            other => {
+                let threshold = other
+                    .strip_prefix("iou=")
+                    .ok_or_else(|| format!("Unknown dedupe mode \"{}\"", other))?;
+                match threshold.parse::<f32>() {
+                    Ok(threshold) if threshold > 0.0 && threshold <= 1.0 => {
+                        Ok(Self::Iou(threshold))
+                    }
+                    _ => Err(format!(
+                        "Expected the IoU threshold in (0, 1], got \"{}\"",
+                        threshold
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Object removed as a duplicate of the kept one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    pub name: String,
+    /// Name of the kept object.
+    pub original: String,
+}
+
+/// Hashable rect, rotation, level and shape of the object, the negative zeros are equal to the
+/// positive ones.
+fn geometry_key(object: &Object) -> ([u32; 5], Option<i32>, Option<String>) {
+    let rotation = object.rotation.unwrap_or_default();
+    let rect = [object.x, object.y, object.width, object.height, rotation];
+    let shape = object
+        .shape
+        .as_ref()
+        .map(|shape| serde_json::to_string(shape).unwrap_or_default());
+    (rect.map(|x| (x + 0.0).to_bits()), object.z, shape)
+}
+
+impl Input {
+    /// Removes the duplicate objects keeping the first one of them, returns the removed
+    /// objects in the input order.
+    pub fn dedupe(&mut self, mode: DedupeMode) -> Vec<Duplicate> {
+        // Index of the kept object for every removed one.
+        let mut originals: Vec<Option<usize>> = vec![None; self.objects.len()];
+        match mode {
+            DedupeMode::Geometry => {
+                let mut seen = HashMap::new();
+                for (idx, object) in self.objects.iter().enumerate() {
+                    let first = *seen.entry(geometry_key(object)).or_insert(idx);
+                    originals[idx] = Some(first).filter(|&first| first != idx);
+                }
+            }
+            DedupeMode::Name => {
+                let mut seen = HashMap::new();
+                for (idx, object) in self.objects.iter().enumerate() {
+                    let first = *seen.entry(object.name.as_str()).or_insert(idx);
+                    originals[idx] = Some(first).filter(|&first| first != idx);
+                }
+            }
+            DedupeMode::Iou(threshold) => {
+                let areas = self.objects.iter().map(Object::area).collect::<Vec<_>>();
+                // The pairs are sorted by the first index, so the removal of the first object
+                // of a pair is known before the pair is checked.
+                let found = list_intersections_using(&areas, Algorithm::auto(areas.len()));
+                for x in found {
+                    // The objects of different levels overlap only in the plan.
+                    if originals[x.a_idx].is_some()
+                        || originals[x.b_idx].is_some()
+                        || areas[x.a_idx].z != areas[x.b_idx].z
+                    {
+                        continue;
+                    }
+                    let metrics = IntersectionMetrics::new(
+                        &areas[x.a_idx].area,
+                        &areas[x.b_idx].area,
+                        &x.area,
+                    );
+                    if metrics.iou >= threshold {
+                        originals[x.b_idx] = Some(x.a_idx);
+                    }
+                }
+            }
+        }
+
+        let duplicates = originals
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, original)| {
+                Some(Duplicate {
+                    name: self.objects[idx].name.clone(),
+                    original: self.objects[(*original)?].name.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut removed = originals.iter().map(Option::is_some);
+        self.objects.retain(|_| !removed.next().unwrap_or_default());
+        duplicates
+    }
+}
+
+#[test]
+fn test_input_dedupe() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 0.0, 0.0, 4.0, 4.0)
+        .object("A", 10.0, 10.0, 1.0, 1.0)
+        .object("C", 0.1, 0.0, 4.0, 4.0)
+        .object("D", 0.0, -0.0, 4.0, 4.0)
+        .object("E", 20.0, 20.0, 1.0, 1.0)
+        .build();
+    let duplicate = |name: &str, original: &str| Duplicate {
+        name: name.to_owned(),
+        original: original.to_owned(),
+    };
+
+    let cases = vec![
+        (
+            DedupeMode::Geometry,
+            vec![duplicate("B", "A"), duplicate("D", "A")],
+            vec!["A", "A", "C", "E"],
+            "geometry",
+        ),
+        (
+            DedupeMode::Name,
+            vec![duplicate("A", "A")],
+            vec!["A", "B", "C", "D", "E"],
+            "name",
+        ),
+        (
+            DedupeMode::Iou(0.9),
+            vec![
+                duplicate("B", "A"),
+                duplicate("C", "A"),
+                duplicate("D", "A"),
+            ],
+            vec!["A", "A", "E"],
+            "iou",
+        ),
+        (
+            DedupeMode::Iou(1.0),
+            vec![duplicate("B", "A"), duplicate("D", "A")],
+            vec!["A", "A", "C", "E"],
+            "exact iou",
+        ),
+    ];
+    for case in cases {
+        let mut deduped = input.clone();
+        let duplicates = deduped.dedupe(case.0);
+        let names = deduped
+            .objects
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            (duplicates, names),
+            (case.1, case.2),
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+    }
+
+    assert_eq!("iou=0.5".parse(), Ok(DedupeMode::Iou(0.5)));
+    assert!("iou=0".parse::<DedupeMode>().is_err());
+    assert!("area".parse::<DedupeMode>().is_err());
+}
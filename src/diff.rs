@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{io, BoundingRect, Error, ObjectIntersection, Output, OutputFormat};
+
+/// Intersection present in both results, but with a different area.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ChangedIntersection {
+    pub names: (String, String),
+    pub before: BoundingRect,
+    pub after: BoundingRect,
+}
+
+/// Difference between two analysis results, the objects are matched by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct OutputDiff {
+    /// Names of the objects present only in the second results.
+    pub added_objects: Vec<String>,
+    /// Names of the objects present only in the first results.
+    pub removed_objects: Vec<String>,
+    /// Intersections present only in the second results.
+    pub appeared: Vec<ObjectIntersection>,
+    /// Intersections present only in the first results.
+    pub disappeared: Vec<ObjectIntersection>,
+    pub changed: Vec<ChangedIntersection>,
+}
+
+/// Row of the CSV diff, one per changed intersection.
+#[derive(Serialize)]
+struct DiffRecord<'a> {
+    change: &'a str,
+    first: &'a str,
+    second: &'a str,
+    before_area: Option<f32>,
+    after_area: Option<f32>,
+}
+
+impl<'a> DiffRecord<'a> {
+    fn new(
+        change: &'a str,
+        names: &'a (String, String),
+        before: Option<&BoundingRect>,
+        after: Option<&BoundingRect>,
+    ) -> Self {
+        Self {
+            change,
+            first: &names.0,
+            second: &names.1,
+            before_area: before.map(BoundingRect::area),
+            after_area: after.map(BoundingRect::area),
+        }
+    }
+}
+
+/// Returns the names of the intersecting objects in the order that does not depend on the
+/// order of the objects in the scene.
+fn pair_of(intersection: &ObjectIntersection) -> (&str, &str) {
+    let (a, b) = (&intersection.names.0, &intersection.names.1);
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn names_of(output: &Output) -> HashSet<&str> {
+    output
+        .areas
+        .iter()
+        .map(|object| object.name.as_str())
+        .collect()
+}
+
+fn pairs_of(output: &Output) -> HashMap<(&str, &str), &ObjectIntersection> {
+    output
+        .intersections
+        .iter()
+        .map(|intersection| (pair_of(intersection), intersection))
+        .collect()
+}
+
+/// Compares two analysis results and reports the added and removed objects and the
+/// intersections that appeared, disappeared or changed their area.
+pub fn diff_outputs(before: &Output, after: &Output) -> OutputDiff {
+    let missing_in = |output: &Output, names: &HashSet<&str>| {
+        output
+            .areas
+            .iter()
+            .filter(|object| !names.contains(object.name.as_str()))
+            .map(|object| object.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let (before_pairs, after_pairs) = (pairs_of(before), pairs_of(after));
+
+    let mut diff = OutputDiff {
+        added_objects: missing_in(after, &names_of(before)),
+        removed_objects: missing_in(before, &names_of(after)),
+        ..OutputDiff::default()
+    };
+    for intersection in &after.intersections {
+        match before_pairs.get(&pair_of(intersection)) {
+            None => diff.appeared.push(intersection.clone()),
+            Some(old) if old.area != intersection.area => diff.changed.push(ChangedIntersection {
+                names: intersection.names.clone(),
+                before: old.area,
+                after: intersection.area,
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.disappeared = before
+        .intersections
+        .iter()
+        .filter(|intersection| !after_pairs.contains_key(&pair_of(intersection)))
+        .cloned()
+        .collect();
+    diff
+}
+
+impl OutputDiff {
+    /// Returns true if the results are the same.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Serializes the diff in the given format, the CSV output lists only the intersections.
+    pub fn to_vec_as(&self, format: OutputFormat) -> Result<Vec<u8>, Error> {
+        if format != OutputFormat::Csv {
+            return io::encode(self, format);
+        }
+
+        let appeared = self
+            .appeared
+            .iter()
+            .map(|x| DiffRecord::new("appeared", &x.names, None, Some(&x.area)));
+        let disappeared = self
+            .disappeared
+            .iter()
+            .map(|x| DiffRecord::new("disappeared", &x.names, Some(&x.area), None));
+        let changed = self
+            .changed
+            .iter()
+            .map(|x| DiffRecord::new("changed", &x.names, Some(&x.before), Some(&x.after)));
+        io::encode_csv(appeared.chain(disappeared).chain(changed))
+    }
+}
+
+#[test]
+fn test_diff_outputs() {
+    let before = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 3.0, 3.0, 2.0, 2.0)
+        .object("C", 3.0, 0.0, 2.0, 1.0)
+        .build();
+    let after = crate::SceneBuilder::new()
+        .object("B", 2.0, 2.0, 2.0, 2.0)
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("D", 3.5, 3.5, 3.0, 3.0)
+        .build();
+    let analyze = |input| crate::analyze(input, crate::Options::default());
+    let (before, after) = (analyze(before), analyze(after));
+
+    let diff = diff_outputs(&before, &after);
+    assert_eq!(diff.added_objects, vec!["D".to_owned()]);
+    assert_eq!(diff.removed_objects, vec!["C".to_owned()]);
+    let names = |intersections: &[ObjectIntersection]| {
+        intersections
+            .iter()
+            .map(|x| x.names.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(&diff.appeared),
+        vec![
+            ("B".to_owned(), "D".to_owned()),
+            ("A".to_owned(), "D".to_owned())
+        ]
+    );
+    assert_eq!(
+        names(&diff.disappeared),
+        vec![("A".to_owned(), "C".to_owned())]
+    );
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].before.area(), 1.0);
+    assert_eq!(diff.changed[0].after.area(), 4.0);
+
+    assert!(diff_outputs(&after, &after).is_empty());
+
+    let csv = String::from_utf8(diff.to_vec_as(OutputFormat::Csv).unwrap()).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("change,first,second,before_area,after_area")
+    );
+    assert_eq!(lines.last(), Some("changed,B,A,1.0,4.0"));
+}
@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+/// Input processing error.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("File {path:?} could to be openned due to: {reason:}")]
+    FileReadError {
+        path: PathBuf,
+        reason: anyhow::Error,
+    },
+    #[cfg(feature = "net")]
+    #[error("Unable to fetch {url:} due to: {reason:}")]
+    FetchError { url: String, reason: anyhow::Error },
+    #[error("Parse error: {reason:}")]
+    ParseError { reason: anyhow::Error },
+    #[error("Serialize error: {reason:}")]
+    SerializeError { reason: anyhow::Error },
+    #[cfg(feature = "png")]
+    #[error("Render error: {reason:}")]
+    RenderError { reason: anyhow::Error },
+}
+
+/// Geometry invariants violation.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum GeometryError {
+    #[error("Coordinates must be finite numbers")]
+    NonFiniteCoordinate,
+    #[error("Rectangle corners must be ordered, \"from\" cannot exceed \"to\"")]
+    InvalidCorners,
+}
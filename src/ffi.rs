@@ -0,0 +1,232 @@
+//! C interface of the library.
+//!
+//! The header file is generated by `cbindgen`, see `include/sophya_prog_test.h`.
+
+use std::{os::raw::c_int, ptr};
+
+use crate::{list_intersections, BoundingRect, Intersection, Point2D};
+
+/// Rectangle given by its corners.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SophyaRect {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+/// Intersection of two rectangles of the scene.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SophyaIntersection {
+    /// Intersection area.
+    pub area: SophyaRect,
+    /// First rectangle index.
+    pub a_idx: usize,
+    /// Second rectangle index.
+    pub b_idx: usize,
+}
+
+/// Scene of rectangles.
+pub struct SophyaScene {
+    rects: Vec<BoundingRect>,
+}
+
+/// Intersections found in the scene.
+pub struct SophyaIntersections {
+    items: Vec<SophyaIntersection>,
+}
+
+impl From<BoundingRect> for SophyaRect {
+    fn from(rect: BoundingRect) -> Self {
+        Self {
+            x1: rect.from.x,
+            y1: rect.from.y,
+            x2: rect.to.x,
+            y2: rect.to.y,
+        }
+    }
+}
+
+impl From<&Intersection> for SophyaIntersection {
+    fn from(intersection: &Intersection) -> Self {
+        Self {
+            area: intersection.area.into(),
+            a_idx: intersection.a_idx,
+            b_idx: intersection.b_idx,
+        }
+    }
+}
+
+/// Creates an empty scene. The scene must be released by `sophya_scene_free`.
+#[no_mangle]
+pub extern "C" fn sophya_scene_new() -> *mut SophyaScene {
+    Box::into_raw(Box::new(SophyaScene { rects: Vec::new() }))
+}
+
+/// Releases the scene.
+///
+/// # Safety
+///
+/// The scene must be created by `sophya_scene_new` and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sophya_scene_free(scene: *mut SophyaScene) {
+    if !scene.is_null() {
+        drop(Box::from_raw(scene));
+    }
+}
+
+/// Adds a rectangle with the specified origin and size to the scene and returns its index.
+///
+/// # Safety
+///
+/// The scene must be a valid pointer returned by `sophya_scene_new`.
+#[no_mangle]
+pub unsafe extern "C" fn sophya_scene_add_rect(
+    scene: *mut SophyaScene,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+) -> usize {
+    let scene = &mut *scene;
+    scene.rects.push(BoundingRect::from_points(
+        Point2D { x, y },
+        Point2D {
+            x: x + width,
+            y: y + height,
+        },
+    ));
+    scene.rects.len() - 1
+}
+
+/// Searches for intersecting rectangles of the scene. The result must be released
+/// by `sophya_intersections_free`.
+///
+/// # Safety
+///
+/// The scene must be a valid pointer returned by `sophya_scene_new`.
+#[no_mangle]
+pub unsafe extern "C" fn sophya_scene_intersections(
+    scene: *const SophyaScene,
+) -> *mut SophyaIntersections {
+    let scene = &*scene;
+    let items = list_intersections(&scene.rects)
+        .iter()
+        .map(SophyaIntersection::from)
+        .collect();
+    Box::into_raw(Box::new(SophyaIntersections { items }))
+}
+
+/// Returns the number of the found intersections.
+///
+/// # Safety
+///
+/// The intersections must be a valid pointer returned by `sophya_scene_intersections`.
+#[no_mangle]
+pub unsafe extern "C" fn sophya_intersections_len(
+    intersections: *const SophyaIntersections,
+) -> usize {
+    let intersections = &*intersections;
+    intersections.items.len()
+}
+
+/// Returns the pointer to the array of the found intersections, the array length is
+/// returned by `sophya_intersections_len`. The array is valid until the intersections
+/// are released.
+///
+/// # Safety
+///
+/// The intersections must be a valid pointer returned by `sophya_scene_intersections`.
+#[no_mangle]
+pub unsafe extern "C" fn sophya_intersections_data(
+    intersections: *const SophyaIntersections,
+) -> *const SophyaIntersection {
+    let items = &(&*intersections).items;
+    if items.is_empty() {
+        ptr::null()
+    } else {
+        items.as_ptr()
+    }
+}
+
+/// Copies the intersection with the given index to `out`.
+///
+/// Returns 1 on success or 0 if the index is out of bounds.
+///
+/// # Safety
+///
+/// The intersections must be a valid pointer returned by `sophya_scene_intersections`,
+/// and `out` must point to a writable `SophyaIntersection`.
+#[no_mangle]
+pub unsafe extern "C" fn sophya_intersections_get(
+    intersections: *const SophyaIntersections,
+    idx: usize,
+    out: *mut SophyaIntersection,
+) -> c_int {
+    let intersections = &*intersections;
+    match intersections.items.get(idx) {
+        Some(item) => {
+            *out = *item;
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Releases the intersections.
+///
+/// # Safety
+///
+/// The intersections must be returned by `sophya_scene_intersections` and must not be
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn sophya_intersections_free(intersections: *mut SophyaIntersections) {
+    if !intersections.is_null() {
+        drop(Box::from_raw(intersections));
+    }
+}
+
+#[test]
+fn test_ffi_scene() {
+    unsafe {
+        let scene = sophya_scene_new();
+        assert_eq!(sophya_scene_add_rect(scene, 0.0, 0.0, 4.0, 4.0), 0);
+        assert_eq!(sophya_scene_add_rect(scene, 10.0, 10.0, 1.0, 1.0), 1);
+        assert_eq!(sophya_scene_add_rect(scene, 3.0, 2.0, 4.0, 4.0), 2);
+
+        let intersections = sophya_scene_intersections(scene);
+        sophya_scene_free(scene);
+
+        let len = sophya_intersections_len(intersections);
+        let items = std::slice::from_raw_parts(sophya_intersections_data(intersections), len);
+        let expected = SophyaIntersection {
+            area: SophyaRect {
+                x1: 3.0,
+                y1: 2.0,
+                x2: 4.0,
+                y2: 4.0,
+            },
+            a_idx: 0,
+            b_idx: 2,
+        };
+        assert_eq!(items, &[expected]);
+
+        let mut out = SophyaIntersection {
+            area: SophyaRect {
+                x1: 0.0,
+                y1: 0.0,
+                x2: 0.0,
+                y2: 0.0,
+            },
+            a_idx: 0,
+            b_idx: 0,
+        };
+        assert_eq!(sophya_intersections_get(intersections, 0, &mut out), 1);
+        assert_eq!(out, expected);
+        assert_eq!(sophya_intersections_get(intersections, 1, &mut out), 0);
+
+        sophya_intersections_free(intersections);
+    }
+}
@@ -0,0 +1,179 @@
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::{Input, Object};
+
+/// `key=value` predicate matching the objects having the property with the given value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyPredicate {
+    pub key: String,
+    pub value: String,
+}
+
+impl PropertyPredicate {
+    /// Whether the object has the property with the given value, see
+    /// [`Object::property_values`]. The value is parsed as JSON to compare it with the
+    /// non-string ones.
+    pub fn matches(&self, object: &Object) -> bool {
+        object
+            .property_values(&self.key)
+            .any(|value| match value.as_str() {
+                Some(value) => value == self.value,
+                None => {
+                    serde_json::from_str::<serde_json::Value>(&self.value)
+                        .ok()
+                        .as_ref()
+                        == Some(value)
+                }
+            })
+    }
+}
+
+impl FromStr for PropertyPredicate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected \"key=value\", got \"{}\"", s))?;
+        Ok(Self {
+            key: key.trim().to_owned(),
+            value: value.trim().to_owned(),
+        })
+    }
+}
+
+impl Object {
+    /// Returns the values of the property with the given key, both the
+    /// `{ "name": key, "value": value }` entries and the `{ key: value }` maps are searched.
+    pub fn property_values<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Iterator<Item = &'a serde_json::Value> + 'a {
+        self.properties.iter().filter_map(move |property| {
+            if property.get("name").and_then(|x| x.as_str()) == Some(key) {
+                property.get("value")
+            } else {
+                property.get(key)
+            }
+        })
+    }
+}
+
+/// Selects the input objects by their names and properties before the search, so the
+/// dropped objects cost nothing.
+#[derive(Debug, Clone, Default)]
+pub struct ObjectFilter {
+    /// Keep only the objects with the names matching any of these patterns, all if empty.
+    pub include: Vec<Regex>,
+    /// Drop the objects with the names matching any of these patterns.
+    pub exclude: Vec<Regex>,
+    /// Keep only the objects matching all of these predicates.
+    pub properties: Vec<PropertyPredicate>,
+}
+
+impl ObjectFilter {
+    /// Whether the filter keeps every object.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && self.properties.is_empty()
+    }
+
+    /// Whether the object passes the filter.
+    pub fn matches(&self, object: &Object) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|x| x.is_match(&object.name)))
+            && !self.exclude.iter().any(|x| x.is_match(&object.name))
+            && self.properties.iter().all(|x| x.matches(object))
+    }
+}
+
+impl Input {
+    /// Drops the objects not passing the filter.
+    pub fn filter(&mut self, filter: &ObjectFilter) {
+        if !filter.is_empty() {
+            self.objects.retain(|object| filter.matches(object));
+        }
+    }
+}
+
+#[test]
+fn test_object_filter() {
+    use serde_json::json;
+
+    let mut input = crate::SceneBuilder::new()
+        .object("door_1", 0.0, 0.0, 1.0, 1.0)
+        .object("door_2", 0.0, 0.0, 1.0, 1.0)
+        .object("backdoor_1", 0.0, 0.0, 1.0, 1.0)
+        .object("wall", 0.0, 0.0, 1.0, 1.0)
+        .build();
+    input.objects[0].properties = vec![json!({ "name": "type", "value": "fire" })];
+    input.objects[1].properties = vec![json!({ "floor": 2 })];
+    input.objects[3].properties = vec![json!({ "name": "type", "value": "fire" })];
+
+    let regex = |x: &str| Regex::new(x).unwrap();
+    let predicate = |x: &str| x.parse::<PropertyPredicate>().unwrap();
+    let cases = vec![
+        (
+            ObjectFilter::default(),
+            vec!["door_1", "door_2", "backdoor_1", "wall"],
+            "empty",
+        ),
+        (
+            ObjectFilter {
+                include: vec![regex("^door_")],
+                ..ObjectFilter::default()
+            },
+            vec!["door_1", "door_2"],
+            "include",
+        ),
+        (
+            ObjectFilter {
+                include: vec![regex("door"), regex("wall")],
+                exclude: vec![regex("^back"), regex("_2$")],
+                ..ObjectFilter::default()
+            },
+            vec!["door_1", "wall"],
+            "include and exclude",
+        ),
+        (
+            ObjectFilter {
+                properties: vec![predicate("type=fire")],
+                ..ObjectFilter::default()
+            },
+            vec!["door_1", "wall"],
+            "name value property",
+        ),
+        (
+            ObjectFilter {
+                properties: vec![predicate("floor=2")],
+                ..ObjectFilter::default()
+            },
+            vec!["door_2"],
+            "map property",
+        ),
+        (
+            ObjectFilter {
+                include: vec![regex("door")],
+                properties: vec![predicate("type=fire")],
+                ..ObjectFilter::default()
+            },
+            vec!["door_1"],
+            "name and property",
+        ),
+    ];
+    for case in cases {
+        let mut filtered = input.clone();
+        filtered.filter(&case.0);
+        assert_eq!(
+            filtered
+                .objects
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<_>>(),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+    assert!("type".parse::<PropertyPredicate>().is_err());
+}
@@ -0,0 +1,111 @@
+use crate::{BoundingRect, Point2D};
+
+/// Finds the largest axis-aligned rectangle inside the region that does not intersect
+/// any of the obstacles.
+///
+/// Returns `None` if the obstacles cover the whole region.
+///
+/// Note that this method checks every pair of candidate vertical edges, so it takes
+/// O(n^3 log n) time for `n` obstacles.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(obstacles = obstacles.len())))]
+pub fn largest_empty_rect(
+    region: &BoundingRect,
+    obstacles: &[BoundingRect],
+) -> Option<BoundingRect> {
+    let obstacles = obstacles
+        .iter()
+        .filter_map(|obstacle| obstacle.intersect(region))
+        .collect::<Vec<_>>();
+
+    // Every edge of the largest empty rectangle lies either on the region boundary
+    // or on an obstacle edge.
+    let mut xs = vec![region.from.x, region.to.x];
+    for obstacle in &obstacles {
+        xs.push(obstacle.from.x);
+        xs.push(obstacle.to.x);
+    }
+    xs.sort_by(f32::total_cmp);
+    xs.dedup();
+
+    let mut best: Option<(f32, BoundingRect)> = None;
+    let mut blocked = Vec::with_capacity(obstacles.len());
+    for (i, &left) in xs.iter().enumerate() {
+        for &right in &xs[i + 1..] {
+            blocked.clear();
+            blocked.extend(
+                obstacles
+                    .iter()
+                    .filter(|obstacle| obstacle.from.x < right && left < obstacle.to.x)
+                    .map(|obstacle| (obstacle.from.y, obstacle.to.y)),
+            );
+            blocked.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            // Walks through the free gaps between the blocked vertical intervals.
+            let mut bottom = region.from.y;
+            for &(from, to) in blocked.iter().chain(Some(&(region.to.y, region.to.y))) {
+                let area = (right - left) * (from - bottom);
+                if from > bottom && best.is_none_or(|(best_area, _)| area > best_area) {
+                    let rect = BoundingRect::from_points(
+                        Point2D { x: left, y: bottom },
+                        Point2D { x: right, y: from },
+                    );
+                    best = Some((area, rect));
+                }
+                bottom = bottom.max(to);
+            }
+        }
+    }
+
+    best.map(|(_, rect)| rect)
+}
+
+#[test]
+fn test_largest_empty_rect() {
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+    let region = rect(0.0, 0.0, 10.0, 10.0);
+
+    let cases = vec![
+        (vec![], Some(region), "no obstacles"),
+        (
+            vec![rect(0.0, 0.0, 10.0, 10.0)],
+            None,
+            "region is completely covered",
+        ),
+        (
+            vec![rect(0.0, 0.0, 4.0, 10.0)],
+            Some(rect(4.0, 0.0, 10.0, 10.0)),
+            "obstacle on the left",
+        ),
+        (
+            vec![rect(4.0, 3.0, 6.0, 5.0)],
+            Some(rect(0.0, 5.0, 10.0, 10.0)),
+            "obstacle in the center",
+        ),
+        (
+            vec![rect(-5.0, -5.0, 2.0, 12.0), rect(8.0, -5.0, 15.0, 12.0)],
+            Some(rect(2.0, 0.0, 8.0, 10.0)),
+            "obstacles outside the region are clipped",
+        ),
+        (
+            vec![
+                rect(0.0, 3.0, 3.0, 10.0),
+                rect(0.0, 0.0, 10.0, 1.0),
+                rect(7.0, 3.0, 10.0, 10.0),
+            ],
+            Some(rect(3.0, 1.0, 7.0, 10.0)),
+            "free corridor between obstacles",
+        ),
+    ];
+
+    for case in cases {
+        let actual = largest_empty_rect(&region, &case.0);
+        assert_eq!(actual, case.1, "Test case \"{}\" has been failed", case.2);
+        if let Some(actual) = actual {
+            for obstacle in &case.0 {
+                assert_eq!(actual.intersect(obstacle), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::{f32::consts::PI, str::FromStr};
+
+use crate::{Input, Object};
+
+/// Placement of the generated objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Distribution {
+    /// Objects are scattered uniformly over the scene.
+    #[default]
+    Uniform,
+    /// Objects are grouped around the random cluster centers.
+    Clustered,
+    /// Objects are placed into the cells of a square grid with a small random jitter.
+    Grid,
+}
+
+impl Distribution {
+    /// Names of all supported distributions.
+    pub const VARIANTS: &'static [&'static str] = &["uniform", "clustered", "grid"];
+}
+
+impl FromStr for Distribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Self::Uniform),
+            "clustered" => Ok(Self::Clustered),
+            "grid" => Ok(Self::Grid),
+            other => Err(format!("Unknown distribution \"{}\"", other)),
+        }
+    }
+}
+
+/// Settings of the generated scene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerateOptions {
+    /// Number of the objects.
+    pub count: usize,
+    /// Seed of the random generator, the same seed always gives the same scene.
+    pub seed: u64,
+    /// Expected ratio of the total object area to the scene area, higher density gives
+    /// more intersections.
+    pub density: f32,
+    pub distribution: Distribution,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            count: 1000,
+            seed: 0,
+            density: 0.1,
+            distribution: Distribution::default(),
+        }
+    }
+}
+
+/// SplitMix64 generator, it is tiny and its sequence never changes between the versions
+/// unlike the generators of the `rand` crate.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns the uniformly distributed number in `[0, 1)`.
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1_u64 << 24) as f32
+    }
+
+    fn range(&mut self, from: f32, to: f32) -> f32 {
+        from + (to - from) * self.next_f32()
+    }
+
+    /// Returns the normally distributed number, uses the Box-Muller transform.
+    fn normal(&mut self, mean: f32, deviation: f32) -> f32 {
+        let u = 1.0 - self.next_f32();
+        let v = self.next_f32();
+        mean + deviation * (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+}
+
+/// Generates the reproducible random scene, the object sides vary from 0.5 to 1.5.
+///
+/// The object centers lie in a square with the top left corner at the origin, its side is
+/// chosen to match the requested density.
+pub fn generate_scene(options: &GenerateOptions) -> Input {
+    let mut rng = SplitMix64(options.seed);
+    // The mean object area with the sides uniformly distributed in [0.5, 1.5].
+    let mean_area = 13.0 / 12.0;
+    let side = (options.count as f32 * mean_area / options.density.max(f32::EPSILON)).sqrt();
+
+    let clusters = match options.distribution {
+        Distribution::Clustered => (0..(options.count / 100).max(1))
+            .map(|_| (rng.range(0.0, side), rng.range(0.0, side)))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let columns = (options.count as f32).sqrt().ceil().max(1.0) as usize;
+    let cell = side / columns as f32;
+
+    let objects = (0..options.count)
+        .map(|idx| {
+            let (width, height) = (rng.range(0.5, 1.5), rng.range(0.5, 1.5));
+            let (x, y) = match options.distribution {
+                Distribution::Uniform => (rng.range(0.0, side), rng.range(0.0, side)),
+                Distribution::Clustered => {
+                    let (cx, cy) = clusters[rng.next_u64() as usize % clusters.len()];
+                    let deviation = side / (4.0 * (clusters.len() as f32).sqrt());
+                    (rng.normal(cx, deviation), rng.normal(cy, deviation))
+                }
+                Distribution::Grid => {
+                    let (column, row) = (idx % columns, idx / columns);
+                    let jitter = cell * 0.1;
+                    (
+                        (column as f32 + 0.5) * cell + rng.range(-jitter, jitter),
+                        (row as f32 + 0.5) * cell + rng.range(-jitter, jitter),
+                    )
+                }
+            };
+            Object {
+                name: format!("object-{}", idx),
+                width,
+                height,
+                x: x - width / 2.0,
+                y: y - height / 2.0,
+                layer: None,
+                z: None,
+                rotation: None,
+                shape: None,
+                properties: Vec::new(),
+            }
+        })
+        .collect();
+    Input { objects }
+}
+
+#[test]
+fn test_generate_scene() {
+    let cases = vec![
+        (Distribution::Uniform, "uniform"),
+        (Distribution::Clustered, "clustered"),
+        (Distribution::Grid, "grid"),
+    ];
+
+    for case in cases {
+        let options = GenerateOptions {
+            count: 500,
+            seed: 42,
+            density: 0.2,
+            distribution: case.0,
+        };
+        let input = generate_scene(&options);
+        assert_eq!(
+            input.objects.len(),
+            500,
+            "Test case \"{}\" has been failed",
+            case.1
+        );
+        assert_eq!(
+            generate_scene(&options),
+            input,
+            "Test case \"{}\" (same seed) has been failed",
+            case.1
+        );
+        assert_ne!(
+            generate_scene(&GenerateOptions {
+                seed: 43,
+                ..options
+            }),
+            input,
+            "Test case \"{}\" (other seed) has been failed",
+            case.1
+        );
+        assert!(
+            input
+                .objects
+                .iter()
+                .all(|object| (0.5..1.5).contains(&object.width)
+                    && object.x.is_finite()
+                    && object.y.is_finite()),
+            "Test case \"{}\" (object sizes) has been failed",
+            case.1
+        );
+    }
+
+    // The sequence is fixed, so the generated scenes can be used in the regression tests.
+    let mut rng = SplitMix64(0);
+    assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+    assert_eq!("grid".parse(), Ok(Distribution::Grid));
+    assert!(generate_scene(&GenerateOptions {
+        count: 0,
+        ..GenerateOptions::default()
+    })
+    .objects
+    .is_empty());
+}
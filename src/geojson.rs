@@ -0,0 +1,362 @@
+use std::{convert::TryFrom, io::Read};
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::{
+    decompress, AnyShape, BoundingRect, Error, Input, Object, ObjectArea, Output, Point2D, Polygon,
+    Shape,
+};
+
+/// GeoJSON file, the collection of the features or a single one.
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum GeoJson {
+    FeatureCollection { features: Vec<Feature> },
+    Feature(Feature),
+}
+
+#[derive(Deserialize)]
+struct Feature {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(default)]
+    bbox: Option<Vec<f32>>,
+    #[serde(default)]
+    geometry: Option<Value>,
+    #[serde(default)]
+    properties: Option<Map<String, Value>>,
+}
+
+// Returns the exterior ring of the polygon geometry, the closing point is dropped.
+fn exterior_ring(geometry: &Value) -> Option<Polygon> {
+    let ring = geometry
+        .get("coordinates")?
+        .as_array()?
+        .first()?
+        .as_array()?;
+    let mut vertices = ring
+        .iter()
+        .map(|position| {
+            let position = position.as_array()?;
+            let x = position.first()?.as_f64()? as f32;
+            let y = position.get(1)?.as_f64()? as f32;
+            Some(Point2D::new(x, y))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if vertices.len() > 1 && vertices.first() == vertices.last() {
+        vertices.pop();
+    }
+    Some(Polygon::new(vertices))
+}
+
+// Returns the closed ring of the polygon coordinates.
+fn ring(polygon: &Polygon) -> Value {
+    let positions = polygon
+        .vertices
+        .iter()
+        .chain(polygon.vertices.first())
+        .map(|point| json!([point.x, point.y]))
+        .collect::<Vec<_>>();
+    json!([positions])
+}
+
+// Writes the segments as the line strings and the other shapes as the polygons, the circles
+// are approximated.
+fn geometry(shape: &AnyShape) -> Value {
+    match shape {
+        AnyShape::Segment { from, to } => json!({
+            "type": "LineString",
+            "coordinates": [[from.x, from.y], [to.x, to.y]],
+        }),
+        shape => json!({ "type": "Polygon", "coordinates": ring(&shape.outline()) }),
+    }
+}
+
+fn feature(geometry: Value, properties: Map<String, Value>) -> Value {
+    json!({ "type": "Feature", "geometry": geometry, "properties": properties })
+}
+
+fn feature_collection(features: Vec<Value>) -> Result<Vec<u8>, Error> {
+    serde_json::to_vec_pretty(&json!({ "type": "FeatureCollection", "features": features }))
+        .map_err(|err| Error::SerializeError { reason: err.into() })
+}
+
+// Returns the feature of the area with its name, layer, level and properties, the
+// `{ "name": key, "value": value }` properties are written as the plain ones.
+fn area_feature(area: &ObjectArea, properties: &[Value]) -> Value {
+    let mut map = Map::new();
+    for property in properties {
+        match (
+            property.get("name").and_then(Value::as_str),
+            property.get("value"),
+        ) {
+            (Some(key), Some(value)) => {
+                map.insert(key.to_owned(), value.clone());
+            }
+            _ => {
+                if let Some(object) = property.as_object() {
+                    map.extend(object.clone());
+                }
+            }
+        }
+    }
+    map.insert("name".to_owned(), json!(area.name));
+    if let Some(layer) = &area.layer {
+        map.insert("layer".to_owned(), json!(layer));
+    }
+    if let Some(z) = area.z {
+        map.insert("z".to_owned(), json!(z));
+    }
+    feature(geometry(&area.exact_shape()), map)
+}
+
+impl Input {
+    /// Parses the GeoJSON feature collection or a single feature. Every feature becomes an
+    /// object, the polygons keep their exterior rings as the shapes and the features with the
+    /// other geometries or without any are read by their `bbox`. The objects are named by
+    /// the feature ids, then by the `name` property, then by the indices, and the properties
+    /// are kept as the `{ "name": key, "value": value }` ones.
+    pub fn from_geojson_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let reader = decompress::decompress(reader).map_err(|err| parse_error(err.into()))?;
+        let file: GeoJson =
+            serde_json::from_reader(reader).map_err(|err| parse_error(err.into()))?;
+        let features = match file {
+            GeoJson::FeatureCollection { features } => features,
+            GeoJson::Feature(feature) => vec![feature],
+        };
+
+        let objects = features
+            .into_iter()
+            .enumerate()
+            .map(|(idx, feature)| {
+                let mut properties = feature.properties.unwrap_or_default();
+                let name = match (&feature.id, properties.get("name")) {
+                    (Some(Value::String(id)), _) => id.clone(),
+                    (Some(Value::Number(id)), _) => id.to_string(),
+                    (_, Some(Value::String(name))) => {
+                        let name = name.clone();
+                        properties.remove("name");
+                        name
+                    }
+                    _ => idx.to_string(),
+                };
+                // The layers and the levels are read back like they are written.
+                let layer = match properties.get("layer") {
+                    Some(Value::String(layer)) => Some(layer.clone()),
+                    _ => None,
+                };
+                let z = properties
+                    .get("z")
+                    .and_then(Value::as_i64)
+                    .and_then(|z| i32::try_from(z).ok());
+                if layer.is_some() {
+                    properties.remove("layer");
+                }
+                if z.is_some() {
+                    properties.remove("z");
+                }
+                let polygon = feature
+                    .geometry
+                    .as_ref()
+                    .filter(|geometry| geometry["type"] == "Polygon")
+                    .and_then(exterior_ring);
+                let shape = match (polygon, feature.bbox.as_deref()) {
+                    (Some(polygon), _) if polygon.is_rect() => {
+                        Some(AnyShape::Rect(polygon.bounding_rect()))
+                    }
+                    (Some(polygon), _) if polygon.vertices.len() >= 3 => {
+                        Some(AnyShape::Polygon(polygon))
+                    }
+                    (_, Some([min_x, min_y, max_x, max_y])) => {
+                        Some(AnyShape::Rect(BoundingRect::from_points(
+                            Point2D::new(*min_x, *min_y),
+                            Point2D::new(*max_x, *max_y),
+                        )))
+                    }
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    parse_error(anyhow::anyhow!(
+                        "The feature \"{}\" has neither a polygon nor a 2D bbox",
+                        name
+                    ))
+                })?;
+
+                Ok(Object {
+                    layer,
+                    z,
+                    properties: properties
+                        .into_iter()
+                        .map(|(key, value)| json!({ "name": key, "value": value }))
+                        .collect(),
+                    ..Object::from_shape(name, shape)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { objects })
+    }
+
+    /// Writes the objects as the GeoJSON features with their exact shapes.
+    pub(crate) fn to_geojson(&self) -> Result<Vec<u8>, Error> {
+        feature_collection(
+            self.objects
+                .iter()
+                .map(|object| area_feature(&object.area(), &object.properties))
+                .collect(),
+        )
+    }
+}
+
+impl Output {
+    /// Writes the intersections as the GeoJSON polygons of their rects with the names of both
+    /// objects and the metrics as the properties.
+    pub(crate) fn intersections_to_geojson(&self) -> Result<Vec<u8>, Error> {
+        let features = self
+            .intersections
+            .iter()
+            .map(|intersection| {
+                let mut properties = Map::new();
+                properties.insert(
+                    "names".to_owned(),
+                    json!([intersection.names.0, intersection.names.1]),
+                );
+                properties.insert("area".to_owned(), json!(intersection.area.area()));
+                if let Some(metrics) = intersection.metrics {
+                    properties.insert("iou".to_owned(), json!(metrics.iou));
+                    properties.insert(
+                        "coverage".to_owned(),
+                        json!([metrics.coverage.0, metrics.coverage.1]),
+                    );
+                }
+                feature(geometry(&AnyShape::Rect(intersection.area)), properties)
+            })
+            .collect();
+        feature_collection(features)
+    }
+
+    /// Writes the areas as the GeoJSON features with their exact shapes.
+    pub(crate) fn areas_to_geojson(&self) -> Result<Vec<u8>, Error> {
+        feature_collection(
+            self.areas
+                .iter()
+                .map(|area| area_feature(area, &area.properties))
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_geojson() {
+    use crate::{analyze, Options, OutputFormat, OutputSection};
+
+    let collection = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "id": "parcel",
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[0, 0], [4, 0], [4, 4], [2, 5], [0, 4], [0, 0]], [[1, 1], [2, 1], [2, 2]]]
+                },
+                "properties": { "owner": "city" }
+            },
+            {
+                "type": "Feature",
+                "bbox": [3, 3, 6, 5],
+                "geometry": { "type": "Point", "coordinates": [4.5, 4] },
+                "properties": { "name": "house" }
+            },
+            {
+                "type": "Feature",
+                "id": 7,
+                "bbox": [10, 10, 11, 11],
+                "geometry": null,
+                "properties": { "layer": "tree", "z": 2 }
+            }
+        ]
+    }"#;
+    let input = Input::from_geojson_reader(collection.as_bytes()).unwrap();
+    let objects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                [x.x, x.y, x.width, x.height],
+                x.shape.is_some(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        vec![
+            ("parcel", [0.0, 0.0, 4.0, 5.0], true),
+            ("house", [3.0, 3.0, 3.0, 2.0], false),
+            ("7", [10.0, 10.0, 1.0, 1.0], false),
+        ]
+    );
+    assert_eq!(
+        (input.objects[2].layer.as_deref(), input.objects[2].z),
+        (Some("tree"), Some(2))
+    );
+    assert_eq!(
+        input.objects[0].properties,
+        vec![json!({ "name": "owner", "value": "city" })]
+    );
+
+    let output = analyze(input.clone(), Options::default());
+    let written: Value =
+        serde_json::from_slice(&output.to_vec_as(OutputFormat::GeoJson).unwrap()).unwrap();
+    assert_eq!(
+        written["features"],
+        json!([{
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[3.0, 3.0], [4.0, 3.0], [4.0, 4.5], [3.0, 4.5], [3.0, 3.0]]]
+            },
+            "properties": { "names": ["parcel", "house"], "area": 1.5 }
+        }])
+    );
+    let areas: Value = serde_json::from_slice(
+        &output
+            .section_to_vec_as(OutputSection::Areas, OutputFormat::GeoJson)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        areas["features"][1]["properties"],
+        json!({ "name": "house" })
+    );
+
+    // The written scene is read back with the same shapes and properties.
+    let scene = input
+        .to_vec_as(OutputFormat::GeoJson, Default::default())
+        .unwrap();
+    assert_eq!(Input::from_geojson_reader(scene.as_slice()).unwrap(), input);
+
+    let cases = vec![
+        (
+            r#"{ "type": "Point", "coordinates": [1, 2] }"#,
+            "unknown variant",
+            "geometry",
+        ),
+        (
+            r#"{ "type": "Feature", "geometry": { "type": "Point", "coordinates": [1, 2] } }"#,
+            "neither a polygon nor a 2D bbox",
+            "point",
+        ),
+    ];
+    for case in cases {
+        let err = Input::from_geojson_reader(case.0.as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains(case.1),
+            "Test case \"{}\" has been failed: {}",
+            case.2,
+            err
+        );
+    }
+}
@@ -0,0 +1,361 @@
+//! Broad-phase intersection search on the GPU.
+
+use std::{convert::TryFrom, mem, sync::OnceLock};
+
+use wgpu::util::DeviceExt;
+
+use crate::{list_intersections, Intersection, Shape};
+
+const SHADER: &str = include_str!("shaders/broad_phase.wgsl");
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS: u32 = 65_535;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    first_count: u32,
+    second_count: u32,
+    capacity: u32,
+    row_stride: u32,
+    same: u32,
+    _padding: [u32; 3],
+}
+
+// GPU resources required to run the broad-phase shader.
+struct Context {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    // Largest number of the rects bound at once.
+    chunk: usize,
+    // Largest number of the pairs found at once.
+    max_capacity: u32,
+}
+
+impl Context {
+    async fn new() -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions::default())
+            .await
+            .ok()?;
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor::default())
+            .await
+            .ok()?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("broad_phase"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("broad_phase"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let limits = device.limits();
+        let binding_size = limits
+            .max_storage_buffer_binding_size
+            .min(limits.max_buffer_size);
+        let chunk = binding_size / mem::size_of::<[f32; 4]>() as u64;
+        // The pairs follow the counter and the overflow flag.
+        let max_capacity = (binding_size / mem::size_of::<[u32; 2]>() as u64).saturating_sub(1);
+        Some(Self {
+            device,
+            queue,
+            pipeline,
+            chunk: usize::try_from(chunk.min(u64::from(u32::MAX))).ok()?,
+            max_capacity: u32::try_from(max_capacity.min(u64::from(u32::MAX))).ok()?,
+        })
+        .filter(|context| context.chunk > 0 && context.max_capacity > 0)
+    }
+
+    // Creates the buffer of the rects of one chunk.
+    fn rects_buffer(&self, rects: &[[f32; 4]]) -> wgpu::Buffer {
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("rects"),
+                contents: bytemuck::cast_slice(rects),
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+    }
+
+    // Runs the shader on the pair of the chunks and returns the found pairs of their local
+    // indices or `None` if they do not fit into the given capacity.
+    fn run(
+        &self,
+        first: (&wgpu::Buffer, u32),
+        second: (&wgpu::Buffer, u32),
+        same: bool,
+        capacity: u32,
+    ) -> Option<Option<Vec<[u32; 2]>>> {
+        let workgroups = first.1.div_ceil(WORKGROUP_SIZE).max(1);
+        let (groups_x, groups_y) = if workgroups > MAX_WORKGROUPS {
+            (MAX_WORKGROUPS, workgroups.div_ceil(MAX_WORKGROUPS))
+        } else {
+            (workgroups, 1)
+        };
+        let params = Params {
+            first_count: first.1,
+            second_count: second.1,
+            capacity,
+            row_stride: groups_x * WORKGROUP_SIZE,
+            same: u32::from(same),
+            _padding: [0; 3],
+        };
+
+        let params_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        // The pairs counter and the overflow flag are followed by the pairs array aligned to
+        // 8 bytes.
+        let pairs_size = (mem::size_of::<[u32; 2]>() * (capacity as usize + 1)) as u64;
+        let pairs_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pairs"),
+            size: pairs_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: pairs_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("broad_phase"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: first.0.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: pairs_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: second.0.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups_x, groups_y, 1);
+        }
+        encoder.copy_buffer_to_buffer(&pairs_buffer, 0, &readback_buffer, 0, pairs_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        readback_buffer.map_async(wgpu::MapMode::Read, .., |_| {});
+        self.device.poll(wgpu::PollType::wait_indefinitely()).ok()?;
+
+        let data = readback_buffer.get_mapped_range(..).ok()?;
+        let words: &[u32] = bytemuck::cast_slice(&data);
+        if words[1] != 0 {
+            return Some(None);
+        }
+        let pairs = bytemuck::cast_slice::<u32, [u32; 2]>(&words[2..])
+            .iter()
+            .take(words[0] as usize)
+            .copied()
+            .collect();
+        Some(Some(pairs))
+    }
+}
+
+// Returns the GPU context created by the first call or `None` if the GPU is not available.
+fn context() -> Option<&'static Context> {
+    static CONTEXT: OnceLock<Option<Context>> = OnceLock::new();
+    CONTEXT
+        .get_or_init(|| pollster::block_on(Context::new()))
+        .as_ref()
+}
+
+// Tests the pairs of two chunks on the CPU like the shader does.
+fn chunk_pairs(first: &[[f32; 4]], second: &[[f32; 4]], same: bool) -> Vec<[usize; 2]> {
+    let mut pairs = Vec::new();
+    for (i, a) in first.iter().enumerate() {
+        let start = if same { i + 1 } else { 0 };
+        for (j, b) in second.iter().enumerate().skip(start) {
+            if a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3] {
+                pairs.push([i, j]);
+            }
+        }
+    }
+    pairs
+}
+
+// Returns the sorted candidate pairs or `None` if the GPU fails. The rects are split into the
+// chunks fitting into one binding and every pair of the chunks is tested by its own pass.
+fn candidate_pairs(context: &Context, rects: &[[f32; 4]]) -> Option<Vec<[usize; 2]>> {
+    let chunks = rects
+        .chunks(context.chunk)
+        .map(|chunk| {
+            let count = u32::try_from(chunk.len()).ok()?;
+            Some((chunk, context.rects_buffer(chunk), count))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut pairs = Vec::new();
+    for (a, first) in chunks.iter().enumerate() {
+        for (b, second) in chunks.iter().enumerate().skip(a) {
+            let same = a == b;
+            let offsets = (a * context.chunk, b * context.chunk);
+            let mut capacity = first.2.clamp(1, context.max_capacity);
+            let found = loop {
+                let found =
+                    context.run((&first.1, first.2), (&second.1, second.2), same, capacity)?;
+                match found {
+                    Some(found) => {
+                        break found
+                            .into_iter()
+                            .map(|[i, j]| [i as usize, j as usize])
+                            .collect::<Vec<_>>()
+                    }
+                    None if capacity < context.max_capacity => {
+                        capacity = capacity.saturating_mul(2).min(context.max_capacity);
+                    }
+                    // The pairs do not fit into the largest buffer, the CPU tests them instead.
+                    None => break chunk_pairs(first.0, second.0, same),
+                }
+            };
+            pairs.extend(
+                found
+                    .into_iter()
+                    .map(|[i, j]| [i + offsets.0, j + offsets.1]),
+            );
+        }
+    }
+    pairs.sort_unstable();
+    Some(pairs)
+}
+
+/// Searches for intersecting shapes like [`list_intersections`], but runs the broad-phase
+/// pair test of the bounding rectangles in a GPU compute shader.
+///
+/// The candidate pairs are confirmed on the CPU, so the result is the same as the one of
+/// [`list_intersections`]. If there is no suitable GPU, the search falls back to the CPU. The
+/// GPU is set up by the first call and reused by the following ones.
+pub fn list_intersections_gpu<S: Shape>(objects: &[S]) -> Vec<Intersection> {
+    if objects.is_empty() {
+        return Vec::new();
+    }
+    let rects = objects
+        .iter()
+        .map(|shape| {
+            let rect = shape.bounding_rect();
+            [rect.from.x, rect.from.y, rect.to.x, rect.to.y]
+        })
+        .collect::<Vec<_>>();
+
+    let pairs = match context().and_then(|context| candidate_pairs(context, &rects)) {
+        Some(pairs) => pairs,
+        None => {
+            trace_event!("GPU is not available, falling back to the CPU search");
+            return list_intersections(objects);
+        }
+    };
+    trace_event!(candidates = pairs.len(), "GPU broad-phase finished");
+
+    pairs
+        .into_iter()
+        .filter_map(|[i, j]| {
+            let (a, b) = (&objects[i], &objects[j]);
+            let area = a.intersection(b)?;
+            let mtv = a.bounding_rect().mtv(&b.bounding_rect())?;
+            Some(Intersection {
+                area,
+                a_idx: i,
+                b_idx: j,
+                mtv,
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_shader_is_valid() {
+    use wgpu::naga;
+
+    let module = naga::front::wgsl::parse_str(SHADER).unwrap();
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .unwrap();
+}
+
+#[test]
+fn test_gpu_intersections() {
+    use crate::{BoundingRect, Point2D};
+
+    let objects = (0..200)
+        .map(|i| {
+            let x = (i % 20) as f32 * 3.0;
+            let y = (i / 20) as f32 * 3.0;
+            BoundingRect::from_points(
+                Point2D { x, y },
+                Point2D {
+                    x: x + 4.0,
+                    y: y + 3.0,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        list_intersections_gpu(&objects),
+        list_intersections(&objects)
+    );
+}
+
+#[test]
+fn test_chunk_pairs() {
+    let rects = [
+        [0.0, 0.0, 4.0, 4.0],
+        [4.0, 0.0, 6.0, 4.0],
+        [10.0, 10.0, 11.0, 11.0],
+        [2.0, 1.0, 2.0, 3.0],
+    ];
+    let cases = vec![
+        (
+            &rects[..],
+            &rects[..],
+            true,
+            vec![[0, 1], [0, 3]],
+            "same chunk",
+        ),
+        (&rects[..2], &rects[2..], false, vec![[0, 1]], "two chunks"),
+        (&rects[..0], &rects[..], false, vec![], "empty chunk"),
+    ];
+    for case in cases {
+        assert_eq!(
+            chunk_pairs(case.0, case.1, case.2),
+            case.3,
+            "Test case \"{}\" has been failed",
+            case.4
+        );
+    }
+    assert!(list_intersections_gpu::<crate::BoundingRect>(&[]).is_empty());
+}
@@ -0,0 +1,330 @@
+use std::{
+    convert::TryFrom,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use futures::{Stream, StreamExt};
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::{
+    analyze, BoundingRect, Input, Object, ObjectIntersection, Options, Output, Point2D, Shape,
+};
+
+/// Messages and the service stubs generated from `proto/sophya.proto`.
+pub mod proto {
+    tonic::include_proto!("sophya");
+}
+
+use proto::scene_service_server::SceneServiceServer;
+
+/// gRPC service answering the queries to the shared scene.
+#[derive(Debug, Clone)]
+pub struct SceneService {
+    output: Arc<Mutex<Output>>,
+}
+
+impl SceneService {
+    /// Creates the service serving the given scene.
+    pub fn new(output: Output) -> Self {
+        Self {
+            output: Arc::new(Mutex::new(output)),
+        }
+    }
+
+    /// Returns the tonic server of this service.
+    pub fn into_server(self) -> SceneServiceServer<Self> {
+        SceneServiceServer::new(self)
+    }
+
+    fn output(&self) -> MutexGuard<'_, Output> {
+        // The scene is updated only after the fallible steps, so it stays consistent.
+        self.output.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl From<Point2D> for proto::Point {
+    fn from(point: Point2D) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl From<BoundingRect> for proto::Rect {
+    fn from(rect: BoundingRect) -> Self {
+        Self {
+            from: Some(rect.top_left().into()),
+            to: Some(rect.bottom_right().into()),
+        }
+    }
+}
+
+impl From<&crate::ObjectArea> for proto::ObjectArea {
+    fn from(object: &crate::ObjectArea) -> Self {
+        Self {
+            name: object.name.clone(),
+            area: Some(object.area.into()),
+        }
+    }
+}
+
+impl From<&ObjectIntersection> for proto::Intersection {
+    fn from(intersection: &ObjectIntersection) -> Self {
+        Self {
+            first: intersection.names.0.clone(),
+            second: intersection.names.1.clone(),
+            area: Some(intersection.area.into()),
+        }
+    }
+}
+
+impl TryFrom<proto::Object> for Object {
+    type Error = Status;
+
+    fn try_from(object: proto::Object) -> Result<Self, Status> {
+        let values = [object.x, object.y, object.width, object.height];
+        if !values.iter().all(|value| value.is_finite()) {
+            return Err(Status::invalid_argument(format!(
+                "Object \"{}\" has non-finite coordinates",
+                object.name
+            )));
+        }
+        if object.width < 0.0 || object.height < 0.0 {
+            return Err(Status::invalid_argument(format!(
+                "Object \"{}\" has negative size",
+                object.name
+            )));
+        }
+        Ok(Self {
+            name: object.name,
+            width: object.width,
+            height: object.height,
+            x: object.x,
+            y: object.y,
+            layer: None,
+            z: None,
+            rotation: None,
+            shape: None,
+            properties: Vec::new(),
+        })
+    }
+}
+
+fn point(point: Option<proto::Point>) -> Result<Point2D, Status> {
+    let point = point.ok_or_else(|| Status::invalid_argument("The point is required"))?;
+    Ok(Point2D::new(point.x, point.y))
+}
+
+fn rect(rect: proto::Rect) -> Result<BoundingRect, Status> {
+    Ok(BoundingRect::from_points(
+        point(rect.from)?,
+        point(rect.to)?,
+    ))
+}
+
+#[tonic::async_trait]
+impl proto::scene_service_server::SceneService for SceneService {
+    async fn load(
+        &self,
+        request: Request<proto::Scene>,
+    ) -> Result<Response<proto::Analysis>, Status> {
+        let objects = request
+            .into_inner()
+            .objects
+            .into_iter()
+            .map(Object::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        let output = analyze(Input { objects }, Options::default());
+        let analysis = proto::Analysis {
+            areas: output.areas.iter().map(Into::into).collect(),
+            intersections: output.intersections.iter().map(Into::into).collect(),
+        };
+        *self.output() = output;
+        Ok(Response::new(analysis))
+    }
+
+    type IngestStream = Pin<Box<dyn Stream<Item = Result<proto::Intersections, Status>> + Send>>;
+
+    async fn ingest(
+        &self,
+        request: Request<Streaming<proto::Object>>,
+    ) -> Result<Response<Self::IngestStream>, Status> {
+        let service = self.clone();
+        let answers = request.into_inner().map(move |object| {
+            let area = Object::try_from(object?)?.area();
+            let mut output = service.output();
+            let found = output
+                .areas
+                .iter()
+                .filter_map(|other| {
+                    Some(ObjectIntersection {
+                        area: other.intersection(&area)?,
+                        names: (other.name.clone(), area.name.clone()),
+                        metrics: None,
+                        properties: None,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let intersections = found.iter().map(Into::into).collect();
+            output.areas.push(area);
+            output.intersections.extend(found);
+            Ok(proto::Intersections { intersections })
+        });
+        Ok(Response::new(Box::pin(answers)))
+    }
+
+    async fn query(
+        &self,
+        request: Request<proto::Query>,
+    ) -> Result<Response<proto::QueryResult>, Status> {
+        use proto::query::Kind;
+
+        let kind = request
+            .into_inner()
+            .kind
+            .ok_or_else(|| Status::invalid_argument("The query kind is required"))?;
+        let output = self.output();
+        let mut result = proto::QueryResult::default();
+        match kind {
+            Kind::IntersectionsOf(name) => {
+                if output.object(&name).is_none() {
+                    return Err(Status::not_found(format!("Unknown object \"{}\"", name)));
+                }
+                result.intersections = output.intersections_of(&name).map(Into::into).collect();
+            }
+            Kind::ObjectsAt(at) => {
+                result.objects = output
+                    .objects_at(point(Some(at))?)
+                    .map(Into::into)
+                    .collect();
+            }
+            Kind::ObjectsIn(within) => {
+                result.objects = output.objects_in(rect(within)?).map(Into::into).collect();
+            }
+            Kind::Nearest(to) => {
+                result.objects = output
+                    .nearest(point(Some(to))?)
+                    .map(|(object, _)| object.into())
+                    .into_iter()
+                    .collect();
+            }
+        }
+        Ok(Response::new(result))
+    }
+}
+
+#[test]
+fn test_scene_service() {
+    use proto::{
+        query::Kind, scene_service_client::SceneServiceClient,
+        scene_service_server::SceneService as _,
+    };
+
+    let object = |name: &str, x: f32, y: f32, size: f32| proto::Object {
+        name: name.to_owned(),
+        x,
+        y,
+        width: size,
+        height: size,
+    };
+    let names = |objects: &[proto::ObjectArea]| {
+        objects
+            .iter()
+            .map(|object| object.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let service = SceneService::new(analyze(Input { objects: vec![] }, Options::default()));
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let analysis = service
+            .load(Request::new(proto::Scene {
+                objects: vec![object("A", 0.0, 0.0, 4.0), object("B", 2.0, 2.0, 4.0)],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(analysis.areas.len(), 2);
+        assert_eq!(analysis.intersections.len(), 1);
+
+        let invalid = proto::Scene {
+            objects: vec![object("C", 0.0, 0.0, -1.0)],
+        };
+        let status = service.load(Request::new(invalid)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        // The streaming methods are tested through the real transport.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tonic::transport::Server::builder()
+            .add_service(service.clone().into_server())
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
+        tokio::spawn(server);
+
+        let channel = tonic::transport::Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = SceneServiceClient::new(channel);
+        let objects = futures::stream::iter(vec![
+            object("C", 5.0, 5.0, 2.0),
+            object("D", 20.0, 0.0, 1.0),
+        ]);
+        let answers = client
+            .ingest(objects)
+            .await
+            .unwrap()
+            .into_inner()
+            .map(|answer| answer.unwrap().intersections.len())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(answers, vec![1, 0]);
+
+        let cases = vec![
+            (
+                Kind::ObjectsAt(Point2D::new(3.0, 3.0).into()),
+                vec!["A", "B"],
+                "point",
+            ),
+            (
+                Kind::ObjectsIn(
+                    BoundingRect::from_points(Point2D::new(5.5, 5.5), Point2D::new(30.0, 0.5))
+                        .into(),
+                ),
+                vec!["B", "C", "D"],
+                "rect",
+            ),
+            (
+                Kind::Nearest(Point2D::new(19.0, 0.0).into()),
+                vec!["D"],
+                "nearest",
+            ),
+        ];
+        for case in cases {
+            let result = client
+                .query(proto::Query { kind: Some(case.0) })
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(
+                names(&result.objects),
+                case.1,
+                "Test case \"{}\" has been failed",
+                case.2
+            );
+        }
+
+        let result = client
+            .query(proto::Query {
+                kind: Some(Kind::IntersectionsOf("B".to_owned())),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(result.intersections.len(), 2);
+    });
+}
@@ -0,0 +1,128 @@
+use crate::{morton::Grid, Shape};
+
+/// Returns the distance of the point along the Hilbert curve filling the 32-bit grid. Unlike
+/// the Morton order the consecutive codes are always the neighbouring cells.
+pub fn hilbert_encode(x: u32, y: u32) -> u64 {
+    let (mut x, mut y) = (x, y);
+    let mut code = 0;
+    for level in (0..32).rev() {
+        let side = 1_u32 << level;
+        let (rx, ry) = (x & side != 0, y & side != 0);
+        code += u64::from(side) * u64::from(side) * ((3 * u64::from(rx)) ^ u64::from(ry));
+        // The quadrant is turned, so the curve enters it from the previous one.
+        if !ry {
+            if rx {
+                x = u32::MAX - x;
+                y = u32::MAX - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+    }
+    code
+}
+
+/// Returns the point at the given distance along the Hilbert curve, the inverse of
+/// [`hilbert_encode`].
+pub fn hilbert_decode(code: u64) -> (u32, u32) {
+    let (mut x, mut y) = (0_u32, 0_u32);
+    let mut rest = code;
+    for level in 0..32 {
+        let side = 1_u32 << level;
+        let rx = (rest >> 1) & 1 == 1;
+        let ry = (rest ^ u64::from(rx)) & 1 == 1;
+        if !ry {
+            if rx {
+                x = side - 1 - x;
+                y = side - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += side * u32::from(rx);
+        y += side * u32::from(ry);
+        rest /= 4;
+    }
+    (x, y)
+}
+
+/// Returns the indices of the shapes in the Hilbert order of the centers of their bounding
+/// rects like [`morton_order`](crate::morton_order), it keeps the neighbouring shapes closer
+/// in the order than the Morton one.
+pub fn hilbert_order<S: Shape>(shapes: &[S]) -> Vec<usize> {
+    let rects = shapes.iter().map(Shape::bounding_rect).collect::<Vec<_>>();
+    let grid = Grid::new(rects.iter().copied());
+    let mut order = (0..rects.len()).collect::<Vec<_>>();
+    order.sort_by_cached_key(|&idx| {
+        let (x, y) = grid.cell(&rects[idx]);
+        hilbert_encode(x, y)
+    });
+    order
+}
+
+/// Sorts the shapes in the Hilbert order like [`hilbert_order`].
+pub fn sort_by_hilbert<S: Shape>(shapes: &mut [S]) {
+    let grid = Grid::new(shapes.iter().map(Shape::bounding_rect));
+    shapes.sort_by_cached_key(|shape| {
+        let (x, y) = grid.cell(&shape.bounding_rect());
+        hilbert_encode(x, y)
+    });
+}
+
+#[test]
+fn test_hilbert() {
+    use crate::{BoundingRect, Point2D};
+
+    let cases = vec![
+        ((0, 0), 0, "origin"),
+        ((u32::MAX, 0), u64::MAX, "end"),
+        ((0, 1 << 31), 1 << 62, "second quadrant"),
+        ((1 << 31, 1 << 31), 2 << 62, "third quadrant"),
+        ((u32::MAX, (1 << 31) - 1), 3 << 62, "fourth quadrant"),
+    ];
+    for case in cases {
+        let ((x, y), code, name) = case;
+        assert_eq!(
+            hilbert_encode(x, y),
+            code,
+            "Test case \"{}\" has been failed",
+            name
+        );
+        assert_eq!(
+            hilbert_decode(code),
+            (x, y),
+            "Test case \"{}\" has been failed",
+            name
+        );
+    }
+
+    // The consecutive codes are the neighbouring cells.
+    for start in [0, 1 << 40, u64::MAX - 1000] {
+        for code in start..start + 1000 {
+            let (x1, y1) = hilbert_decode(code);
+            let (x2, y2) = hilbert_decode(code + 1);
+            assert_eq!(x1.abs_diff(x2) + y1.abs_diff(y2), 1, "code = {}", code);
+            assert_eq!(hilbert_encode(x1, y1), code);
+        }
+    }
+
+    let rect = |x: f32, y: f32| {
+        BoundingRect::from_points(Point2D::new(x, y), Point2D::new(x + 1.0, y + 1.0))
+    };
+    // The quadrants are visited in the U order: top left, bottom left, bottom right, top right.
+    let mut rects = vec![
+        rect(9.0, 9.0),
+        rect(0.0, 9.0),
+        rect(9.0, 0.0),
+        rect(0.0, 0.0),
+    ];
+    assert_eq!(hilbert_order(&rects), vec![3, 1, 0, 2]);
+    sort_by_hilbert(&mut rects);
+    assert_eq!(
+        rects,
+        vec![
+            rect(0.0, 0.0),
+            rect(0.0, 9.0),
+            rect(9.0, 9.0),
+            rect(9.0, 0.0)
+        ]
+    );
+}
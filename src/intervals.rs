@@ -0,0 +1,380 @@
+use std::{cmp::Ordering, iter};
+
+use crate::{rects::lines_intersection, BoundingRect};
+
+/// Overlap of two labelled intervals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalOverlap<T, L> {
+    /// Start of the common part.
+    pub start: T,
+    /// End of the common part.
+    pub end: T,
+    /// Labels of the first and the second interval in the input order.
+    pub labels: (L, L),
+}
+
+/// Searches for the overlapping `(start, end, label)` intervals, e.g. the conflicting bookings
+/// of the same room, with the 1D math of the rect intersections: the ends are excluded, so the
+/// back-to-back intervals do not overlap. The reversed intervals are swapped and the ones with
+/// the ends not comparable even to themselves, e.g. NaN, never overlap.
+///
+/// This method sweeps the intervals sorted by their starts, so it takes O(n log n + k) time for
+/// `k` overlaps. The overlaps are sorted by the positions of their intervals in the input like
+/// the [`list_intersections`](crate::list_intersections) results.
+pub fn list_interval_overlaps<T, L>(intervals: &[(T, T, L)]) -> Vec<IntervalOverlap<T, L>>
+where
+    T: PartialOrd + Copy,
+    L: Clone,
+{
+    let bounds = intervals
+        .iter()
+        .map(|&(start, end, _)| {
+            if end < start {
+                (end, start)
+            } else {
+                (start, end)
+            }
+        })
+        .collect::<Vec<_>>();
+    // The starts of the comparable intervals are totally ordered.
+    let comparable = |value: &T| value.partial_cmp(value).is_some();
+    let mut order = (0..bounds.len())
+        .filter(|&idx| comparable(&bounds[idx].0) && comparable(&bounds[idx].1))
+        .collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        bounds[a]
+            .0
+            .partial_cmp(&bounds[b].0)
+            .unwrap_or(Ordering::Equal)
+    });
+
+    // The intervals ending before the current start cannot overlap the next ones.
+    let mut active: Vec<usize> = Vec::new();
+    let mut pairs = Vec::new();
+    for idx in order {
+        let start = bounds[idx].0;
+        active.retain(|&other| bounds[other].1 >= start);
+        for &other in &active {
+            let (a, b) = if other < idx {
+                (other, idx)
+            } else {
+                (idx, other)
+            };
+            if let Some(overlap) = lines_intersection(bounds[a], bounds[b]) {
+                pairs.push((a, b, overlap));
+            }
+        }
+        active.push(idx);
+    }
+    pairs.sort_by_key(|&(a, b, _)| (a, b));
+
+    pairs
+        .into_iter()
+        .map(|(a, b, (start, end))| IntervalOverlap {
+            start,
+            end,
+            labels: (intervals[a].2.clone(), intervals[b].2.clone()),
+        })
+        .collect()
+}
+
+/// Segment tree over the closed intervals of one axis, it lists the intervals containing a
+/// coordinate in O(log n + k) time, e.g. the rects crossed by a vertical line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentTree {
+    /// Sorted distinct ends of the intervals.
+    coords: Vec<f32>,
+    /// Number of the leaves, a power of two.
+    leaves: usize,
+    /// Intervals of every node are `items[offsets[node]..offsets[node + 1]]`.
+    offsets: Vec<usize>,
+    items: Vec<usize>,
+    len: usize,
+}
+
+impl SegmentTree {
+    /// Builds the tree of the `(start, end)` intervals, the reversed intervals are swapped and
+    /// the intervals with the NaN ends are never found.
+    pub fn new(intervals: &[(f32, f32)]) -> Self {
+        let bounds = intervals
+            .iter()
+            // The negative zeros become the positive ones, so they share a slot.
+            .map(|&(start, end)| (start + 0.0, end + 0.0))
+            .map(|(start, end)| {
+                if end < start {
+                    (end, start)
+                } else {
+                    (start, end)
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut coords = bounds
+            .iter()
+            .flat_map(|&(start, end)| [start, end])
+            .filter(|coord| !coord.is_nan())
+            .collect::<Vec<_>>();
+        coords.sort_by(f32::total_cmp);
+        coords.dedup();
+
+        // The even slots are the ends themselves and the odd ones are the gaps between them.
+        let slots = (2 * coords.len()).saturating_sub(1);
+        let leaves = slots.next_power_of_two();
+        let ranges = bounds
+            .iter()
+            .map(|&(start, end)| {
+                let slot = |coord: f32| coords.binary_search_by(|c| c.total_cmp(&coord)).ok();
+                Some((2 * slot(start)?, 2 * slot(end)?))
+            })
+            .collect::<Vec<_>>();
+
+        // Every interval is stored in the O(log n) nodes covering its slots, the nodes are
+        // counted first to pack them into a single array.
+        let covering = |(from, to): (usize, usize), visit: &mut dyn FnMut(usize)| {
+            let (mut left, mut right) = (from + leaves, to + leaves + 1);
+            while left < right {
+                if left % 2 == 1 {
+                    visit(left);
+                    left += 1;
+                }
+                if right % 2 == 1 {
+                    right -= 1;
+                    visit(right);
+                }
+                left /= 2;
+                right /= 2;
+            }
+        };
+        let mut offsets = vec![0; 2 * leaves + 1];
+        for range in ranges.iter().flatten() {
+            covering(*range, &mut |node| offsets[node + 1] += 1);
+        }
+        for node in 0..2 * leaves {
+            offsets[node + 1] += offsets[node];
+        }
+        let mut next = offsets.clone();
+        let mut items = vec![0; offsets[2 * leaves]];
+        for (idx, range) in ranges.iter().enumerate() {
+            if let Some(range) = range {
+                covering(*range, &mut |node| {
+                    items[next[node]] = idx;
+                    next[node] += 1;
+                });
+            }
+        }
+
+        Self {
+            coords,
+            leaves,
+            offsets,
+            items,
+            len: intervals.len(),
+        }
+    }
+
+    /// Builds the tree of the horizontal extents of the rects, so [`SegmentTree::stab`] lists
+    /// the rects crossed by the vertical line.
+    pub fn from_rects_x(rects: &[BoundingRect]) -> Self {
+        let intervals = rects
+            .iter()
+            .map(|rect| (rect.from.x, rect.to.x))
+            .collect::<Vec<_>>();
+        Self::new(&intervals)
+    }
+
+    /// Builds the tree of the vertical extents of the rects, so [`SegmentTree::stab`] lists
+    /// the rects crossed by the horizontal line.
+    pub fn from_rects_y(rects: &[BoundingRect]) -> Self {
+        let intervals = rects
+            .iter()
+            .map(|rect| (rect.from.y, rect.to.y))
+            .collect::<Vec<_>>();
+        Self::new(&intervals)
+    }
+
+    /// Returns the number of the intervals.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the tree has no intervals.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the indices of the intervals containing the coordinate, their ends included,
+    /// in no particular order.
+    pub fn stab(&self, value: f32) -> impl Iterator<Item = usize> + '_ {
+        let value = value + 0.0;
+        let slot = match self.coords.binary_search_by(|c| c.total_cmp(&value)) {
+            Ok(idx) => Some(2 * idx),
+            // The values outside of all the intervals or NaN have no slot.
+            Err(idx) if idx > 0 && idx < self.coords.len() && !value.is_nan() => Some(2 * idx - 1),
+            Err(_) => None,
+        };
+        slot.into_iter()
+            .flat_map(move |slot| {
+                iter::successors(Some(slot + self.leaves), |&node| {
+                    if node > 1 {
+                        Some(node / 2)
+                    } else {
+                        None
+                    }
+                })
+            })
+            .flat_map(move |node| {
+                self.items[self.offsets[node]..self.offsets[node + 1]]
+                    .iter()
+                    .copied()
+            })
+    }
+}
+
+#[test]
+fn test_list_interval_overlaps() {
+    let cases = vec![
+        (vec![], vec![], "empty"),
+        (
+            vec![(9, 12, "alice"), (12, 14, "bob")],
+            vec![],
+            "back to back",
+        ),
+        (
+            vec![(9, 12, "alice"), (11, 14, "bob"), (13, 15, "carol")],
+            vec![(11, 12, ("alice", "bob")), (13, 14, ("bob", "carol"))],
+            "chain",
+        ),
+        (
+            vec![(10, 20, "day"), (8, 9, "early"), (12, 11, "reversed")],
+            vec![(11, 12, ("day", "reversed"))],
+            "reversed",
+        ),
+        (
+            vec![(15, 18, "late"), (10, 20, "day"), (10, 20, "copy")],
+            vec![
+                (15, 18, ("late", "day")),
+                (15, 18, ("late", "copy")),
+                (10, 20, ("day", "copy")),
+            ],
+            "nested",
+        ),
+    ];
+    for case in cases {
+        let overlaps = list_interval_overlaps(&case.0)
+            .into_iter()
+            .map(|overlap| (overlap.start, overlap.end, overlap.labels))
+            .collect::<Vec<_>>();
+        assert_eq!(overlaps, case.1, "Test case \"{}\" has been failed", case.2);
+    }
+
+    let nan = f32::NAN;
+    let cases = vec![
+        (
+            vec![(0.0, 2.0, "a"), (nan, 1.0, "nan"), (1.0, 3.0, "b")],
+            vec![(1.0, 2.0, ("a", "b"))],
+            "nan start",
+        ),
+        (
+            vec![(1.0, nan, "nan"), (0.0, 2.0, "a"), (nan, nan, "both")],
+            vec![],
+            "nan ends",
+        ),
+    ];
+    for case in cases {
+        let overlaps = list_interval_overlaps(&case.0)
+            .into_iter()
+            .map(|overlap| (overlap.start, overlap.end, overlap.labels))
+            .collect::<Vec<_>>();
+        assert_eq!(overlaps, case.1, "Test case \"{}\" has been failed", case.2);
+    }
+
+    // The overlaps are the same as the intersections of the unit-height rects.
+    let intervals = (0..50)
+        .map(|idx| {
+            let start = ((idx * 37) % 101) as f32;
+            (start, start + ((idx * 13) % 17) as f32, idx)
+        })
+        .collect::<Vec<_>>();
+    let rects = intervals
+        .iter()
+        .map(|&(start, end, _)| {
+            crate::BoundingRect::from_points(
+                crate::Point2D::new(start, 0.0),
+                crate::Point2D::new(end, 1.0),
+            )
+        })
+        .collect::<Vec<_>>();
+    let expected = crate::list_intersections(&rects)
+        .into_iter()
+        .map(|intersection| {
+            (
+                intersection.area.from.x,
+                intersection.area.to.x,
+                (intersection.a_idx, intersection.b_idx),
+            )
+        })
+        .collect::<Vec<_>>();
+    let overlaps = list_interval_overlaps(&intervals)
+        .into_iter()
+        .map(|overlap| (overlap.start, overlap.end, overlap.labels))
+        .collect::<Vec<_>>();
+    assert!(!overlaps.is_empty());
+    assert_eq!(overlaps, expected);
+}
+
+#[test]
+fn test_segment_tree() {
+    let stab = |tree: &SegmentTree, value: f32| {
+        let mut found = tree.stab(value).collect::<Vec<_>>();
+        found.sort_unstable();
+        found
+    };
+
+    let tree = SegmentTree::new(&[(0.0, 10.0), (5.0, 5.0), (8.0, 3.0), (12.0, 14.0)]);
+    let cases = vec![
+        (-1.0, vec![], "before"),
+        (0.0, vec![0], "start"),
+        (4.0, vec![0, 2], "gap"),
+        (5.0, vec![0, 1, 2], "point interval"),
+        (10.0, vec![0], "end"),
+        (11.0, vec![], "between"),
+        (14.0, vec![3], "last end"),
+        (15.0, vec![], "after"),
+        (f32::NAN, vec![], "nan"),
+    ];
+    for case in cases {
+        assert_eq!(
+            stab(&tree, case.0),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+    assert_eq!(tree.len(), 4);
+    assert!(SegmentTree::new(&[]).stab(0.0).next().is_none());
+
+    // The negative and the positive zeros are the same ends.
+    let tree = SegmentTree::new(&[(-1.0, -0.0), (0.0, 1.0), (-0.0, 0.0)]);
+    for value in [0.0, -0.0] {
+        assert_eq!(stab(&tree, value), vec![0, 1, 2], "value = {}", value);
+    }
+    assert_eq!(stab(&tree, 0.5), vec![1]);
+
+    // The stabbed rects are the ones containing a point of the vertical line.
+    let rects = (0..200)
+        .map(|idx| {
+            let x = ((idx * 37) % 101) as f32;
+            crate::BoundingRect::from_points(
+                crate::Point2D::new(x, 0.0),
+                crate::Point2D::new(x + ((idx * 13) % 17) as f32, 1.0),
+            )
+        })
+        .collect::<Vec<_>>();
+    let tree = SegmentTree::from_rects_x(&rects);
+    for step in 0..250 {
+        let x = step as f32 / 2.0 - 5.0;
+        let expected = (0..rects.len())
+            .filter(|&idx| rects[idx].contains(crate::Point2D::new(x, 0.5)))
+            .collect::<Vec<_>>();
+        assert_eq!(stab(&tree, x), expected, "x = {}", x);
+    }
+}
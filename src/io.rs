@@ -0,0 +1,1876 @@
+use std::{fs::File, io::Read, path::Path, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decompress, query::ObjectRecord, AnalysisStats, AnyShape, BoundingRect, Error, ImageSize,
+    NdjsonWriter, Page, Point2D, Polygon, Shape, Unit,
+};
+
+/// Input file contents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Input {
+    /// Objects to analyze.
+    pub objects: Vec<Object>,
+}
+
+/// Rectangular object of the input file.
+///
+/// Besides the origin-size form the object can be described by its corners, i.e. the `from`
+/// and `to` points instead of the `x`, `y`, `width` and `height` fields.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Object {
+    pub name: String,
+    pub width: f32,
+    pub height: f32,
+    pub x: f32,
+    pub y: f32,
+    /// Layer of the object, e.g. "walls", used to choose the tested pairs of objects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    /// Level of the object, e.g. the floor of a building, used to test only the objects of the
+    /// same level.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z: Option<i32>,
+    /// Rotation in degrees about the center, the positive angles turn the x axis towards the
+    /// y axis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<f32>,
+    /// Exact shape of the circle, polygon and segment objects tagged by its `kind`, the `x`,
+    /// `y`, `width` and `height` hold its bounding rect. The rects have no shape.
+    #[serde(flatten, default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<AnyShape>,
+    #[serde(default)]
+    pub properties: Vec<serde_json::Value>,
+}
+
+/// Kind of the input object, the `kind` tag of [`AnyShape`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ShapeKind {
+    #[default]
+    Rect,
+    Circle,
+    Polygon,
+    Segment,
+}
+
+/// Object in any of the supported rect forms or of any shape kind, the names and the
+/// properties are generic to share it with the borrowed objects.
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "N: Deserialize<'de>, P: Deserialize<'de>"))]
+pub(crate) struct RawObject<N = String, P = serde_json::Value> {
+    pub(crate) name: N,
+    #[serde(default)]
+    kind: ShapeKind,
+    width: Option<f32>,
+    height: Option<f32>,
+    x: Option<f32>,
+    y: Option<f32>,
+    from: Option<Point2D>,
+    to: Option<Point2D>,
+    #[serde(default)]
+    pub(crate) layer: Option<N>,
+    #[serde(default)]
+    pub(crate) z: Option<i32>,
+    #[serde(default)]
+    pub(crate) rotation: Option<f32>,
+    center: Option<Point2D>,
+    radius: Option<f32>,
+    vertices: Option<Vec<Point2D>>,
+    #[serde(default)]
+    pub(crate) properties: Vec<P>,
+}
+
+impl<N, P> RawObject<N, P> {
+    pub(crate) fn shape<E: serde::de::Error>(&self) -> Result<Option<AnyShape>, E> {
+        let shape = match self.kind {
+            ShapeKind::Rect => return Ok(None),
+            ShapeKind::Circle => {
+                let radius = self.radius.ok_or_else(|| E::missing_field("radius"))?;
+                if radius < 0.0 {
+                    return Err(E::custom("the radius cannot be negative"));
+                }
+                AnyShape::Circle {
+                    center: self.center.ok_or_else(|| E::missing_field("center"))?,
+                    radius,
+                }
+            }
+            ShapeKind::Polygon => {
+                let vertices = self
+                    .vertices
+                    .clone()
+                    .ok_or_else(|| E::missing_field("vertices"))?;
+                if vertices.len() < 3 {
+                    return Err(E::invalid_length(vertices.len(), &"at least 3 vertices"));
+                }
+                AnyShape::Polygon(Polygon::new(vertices))
+            }
+            ShapeKind::Segment => AnyShape::Segment {
+                from: self.from.ok_or_else(|| E::missing_field("from"))?,
+                to: self.to.ok_or_else(|| E::missing_field("to"))?,
+            },
+        };
+        Ok(Some(shape))
+    }
+
+    /// Returns the `x`, `y`, `width` and `height` of the object with the given shape.
+    pub(crate) fn rect<E: serde::de::Error>(
+        &self,
+        shape: Option<&AnyShape>,
+    ) -> Result<(f32, f32, f32, f32), E> {
+        // The rect fields of the shapes are ignored, they are written only for the consumers.
+        Ok(if let Some(shape) = shape {
+            let rect = shape.bounding_rect();
+            let (from, to) = (rect.top_left(), rect.bottom_right());
+            (from.x, from.y, to.x - from.x, to.y - from.y)
+        } else if self.from.is_some() || self.to.is_some() {
+            let from = self.from.ok_or_else(|| E::missing_field("from"))?;
+            let to = self.to.ok_or_else(|| E::missing_field("to"))?;
+            (from.x, from.y, to.x - from.x, to.y - from.y)
+        } else {
+            (
+                self.x.ok_or_else(|| E::missing_field("x"))?,
+                self.y.ok_or_else(|| E::missing_field("y"))?,
+                self.width.ok_or_else(|| E::missing_field("width"))?,
+                self.height.ok_or_else(|| E::missing_field("height"))?,
+            )
+        })
+    }
+}
+
+impl RawObject {
+    fn into_object<E: serde::de::Error>(self) -> Result<Object, E> {
+        let shape = self.shape()?;
+        let (x, y, width, height) = self.rect(shape.as_ref())?;
+        Ok(Object {
+            name: self.name,
+            width,
+            height,
+            x,
+            y,
+            layer: self.layer,
+            z: self.z,
+            rotation: self.rotation,
+            shape,
+            properties: self.properties,
+        })
+    }
+}
+
+struct ObjectVisitor;
+
+impl<'de> serde::de::Visitor<'de> for ObjectVisitor {
+    type Value = Object;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("an object")
+    }
+
+    // The conversion happens inside the visitor, so its errors get the position as well.
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Object, A::Error> {
+        RawObject::deserialize(serde::de::value::MapAccessDeserializer::new(map))?.into_object()
+    }
+}
+
+impl<'de> Deserialize<'de> for Object {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ObjectVisitor)
+    }
+}
+
+/// Representation of the object rects in the written scenes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RectForm {
+    /// The `x`, `y`, `width` and `height` fields.
+    #[default]
+    OriginSize,
+    /// The `from` and `to` corner points.
+    Corners,
+}
+
+impl RectForm {
+    /// Names of all supported forms.
+    pub const VARIANTS: &'static [&'static str] = &["origin-size", "corners"];
+}
+
+impl FromStr for RectForm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "origin-size" => Ok(Self::OriginSize),
+            "corners" => Ok(Self::Corners),
+            other => Err(format!("Unknown rect form \"{}\"", other)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CornersObject<'a> {
+    name: &'a str,
+    /// Corners of the unrotated rect, omitted for the other shapes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<Point2D>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<Point2D>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layer: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    z: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rotation: Option<f32>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    shape: Option<&'a AnyShape>,
+    properties: &'a [serde_json::Value],
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum SceneObject<'a> {
+    OriginSize(&'a Object),
+    Corners(CornersObject<'a>),
+}
+
+#[derive(Serialize)]
+struct Scene<'a> {
+    objects: Vec<SceneObject<'a>>,
+}
+
+/// Named area occupied by an object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ObjectArea {
+    pub name: String,
+    /// Serialized with the computed width, height and area.
+    #[serde(with = "crate::rect_serde::measured")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::rect_serde::measured::Repr")
+    )]
+    pub area: BoundingRect,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z: Option<i32>,
+    /// Exact shape of the rotated or non-rectangular object, the area is its bounding rect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shape: Option<AnyShape>,
+    /// Properties of the input object, copied if [`Options::properties`](crate::Options) is set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<serde_json::Value>,
+}
+
+/// Intersection of two named objects.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ObjectIntersection {
+    pub names: (String, String),
+    /// Serialized with the computed width, height and area.
+    #[serde(with = "crate::rect_serde::measured")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::rect_serde::measured::Repr")
+    )]
+    pub area: BoundingRect,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<IntersectionMetrics>,
+    /// Properties of the first and the second object, attached if
+    /// [`Options::properties`](crate::Options) is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<(Vec<serde_json::Value>, Vec<serde_json::Value>)>,
+}
+
+/// Overlap metrics of an intersection, included if [`Options::metrics`](crate::Options) is set.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct IntersectionMetrics {
+    /// Area of the intersection.
+    pub area: f32,
+    /// Intersection over union of the object areas.
+    pub iou: f32,
+    /// Percents of the first and the second object areas covered by the intersection.
+    pub coverage: (f32, f32),
+}
+
+impl IntersectionMetrics {
+    /// Calculates the metrics of the intersection of the given object areas. The degenerate
+    /// objects have no area, so their IoU and coverage are zero.
+    pub fn new(a: &BoundingRect, b: &BoundingRect, intersection: &BoundingRect) -> Self {
+        Self::from_areas(a.area(), b.area(), intersection.area())
+    }
+
+    /// Calculates the metrics of the intersection by the areas of the objects and of their
+    /// common part, e.g. of the rotated objects.
+    pub fn from_areas(a: f32, b: f32, area: f32) -> Self {
+        let ratio = |total: f32| if total > 0.0 { area / total } else { 0.0 };
+        Self {
+            area,
+            iou: ratio(a + b - area),
+            coverage: (100.0 * ratio(a), 100.0 * ratio(b)),
+        }
+    }
+}
+
+/// Analysis results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Output {
+    /// Slice of the intersections in the output, if they are paginated. Written first, so the
+    /// consumers know the total before reading the intersections.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<Page>,
+    /// Unit of the coordinates, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<Unit>,
+    pub areas: Vec<ObjectArea>,
+    pub intersections: Vec<ObjectIntersection>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<AnalysisStats>,
+}
+
+/// Part of the results written instead of the whole output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputSection {
+    /// Only the intersections array.
+    Intersections,
+    /// Only the areas array.
+    Areas,
+    /// The compact scene summary without the histogram, see [`SceneSummary`](crate::SceneSummary).
+    Summary,
+}
+
+impl OutputSection {
+    /// Names of all supported sections.
+    pub const VARIANTS: &'static [&'static str] = &["intersections", "areas", "summary"];
+}
+
+impl FromStr for OutputSection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "intersections" => Ok(Self::Intersections),
+            "areas" => Ok(Self::Areas),
+            "summary" => Ok(Self::Summary),
+            other => Err(format!("Unknown output section \"{}\"", other)),
+        }
+    }
+}
+
+/// Serialization format of the input files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    #[default]
+    Json,
+    Yaml,
+    /// Table with one object per row, see [`CsvColumns`].
+    Csv,
+    /// Objects are described as the `[[objects]]` array of tables.
+    Toml,
+    /// JSON Lines, one object per line, see [`Input::ndjson_objects`].
+    Ndjson,
+    MessagePack,
+    Cbor,
+    /// COCO annotations, see [`Input::from_coco_reader`]. The files are plain JSON, so the
+    /// format is never detected by the extension.
+    Coco,
+    /// YOLO label file, see [`Input::from_yolo_reader`]. The coordinates are kept normalized,
+    /// the `.txt` files are not detected as the labels.
+    Yolo,
+    /// Pascal VOC annotation file, see [`Input::from_voc_reader`].
+    Voc,
+    /// GeoJSON features, see [`Input::from_geojson_reader`].
+    GeoJson,
+    /// One WKT geometry per line with the optional name, see [`Input::from_wkt_reader`].
+    Wkt,
+    /// ESRI shapefile, see [`Input::from_shapefile`]. Requires the `shapefile` feature, the
+    /// streams are read without the `.dbf` attributes.
+    Shapefile,
+    /// SVG drawing, see [`Input::from_svg_reader`].
+    Svg,
+    /// Object layers of the Tiled map, see [`Input::from_tiled_reader`].
+    Tiled,
+    /// DXF drawing, see [`Input::from_dxf_reader`]. Requires the `dxf` feature.
+    Dxf,
+    /// Parquet file with the columns named like the CSV headers, see
+    /// [`Input::from_parquet_reader`]. Requires the `arrow` feature.
+    Parquet,
+    /// Arrow IPC file or stream like [`InputFormat::Parquet`], see [`Input::from_arrow_reader`].
+    Arrow,
+    /// Latest run of the SQLite database, see [`Input::from_sqlite`]. Requires the `sqlite`
+    /// feature.
+    Sqlite,
+}
+
+impl InputFormat {
+    /// Names of all supported formats.
+    pub const VARIANTS: &'static [&'static str] = &[
+        "json",
+        "yaml",
+        "csv",
+        "toml",
+        "ndjson",
+        "msgpack",
+        "cbor",
+        "coco",
+        "yolo",
+        "voc",
+        "geojson",
+        "wkt",
+        "shapefile",
+        "svg",
+        "tiled",
+        "dxf",
+        "parquet",
+        "arrow",
+        "sqlite",
+    ];
+
+    /// Detects the format by the file extension, the compression extensions like `.gz` are
+    /// skipped.
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match extension(path.as_ref())? {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "csv" => Some(Self::Csv),
+            "toml" => Some(Self::Toml),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "msgpack" | "mpk" => Some(Self::MessagePack),
+            "cbor" => Some(Self::Cbor),
+            "xml" => Some(Self::Voc),
+            "geojson" => Some(Self::GeoJson),
+            "wkt" => Some(Self::Wkt),
+            "shp" => Some(Self::Shapefile),
+            "svg" => Some(Self::Svg),
+            "tmx" | "tmj" => Some(Self::Tiled),
+            "dxf" => Some(Self::Dxf),
+            "parquet" => Some(Self::Parquet),
+            "arrow" | "arrows" | "feather" | "ipc" => Some(Self::Arrow),
+            "sqlite" | "sqlite3" | "db" => Some(Self::Sqlite),
+            _ => None,
+        }
+    }
+
+    /// Whether the file has the extension of the format, the `.txt` files are the YOLO labels
+    /// then.
+    pub fn matches_extension<P: AsRef<Path>>(self, path: P) -> bool {
+        let path = path.as_ref();
+        Self::from_extension(path) == Some(self)
+            || (self == Self::Yolo && extension(path) == Some("txt"))
+    }
+
+    /// Detects the format by the extension of the URL path.
+    pub fn from_url(url: &str) -> Option<Self> {
+        let path = url.split(['?', '#']).next()?;
+        Self::from_extension(path)
+    }
+}
+
+// Returns the extension of the file, the compression extensions like `.gz` are skipped.
+fn extension(path: &Path) -> Option<&str> {
+    let extension = path.extension()?.to_str()?;
+    if decompress::EXTENSIONS.contains(&extension) {
+        Path::new(path.file_stem()?).extension()?.to_str()
+    } else {
+        Some(extension)
+    }
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "csv" => Ok(Self::Csv),
+            "toml" => Ok(Self::Toml),
+            "ndjson" => Ok(Self::Ndjson),
+            "msgpack" => Ok(Self::MessagePack),
+            "cbor" => Ok(Self::Cbor),
+            "coco" => Ok(Self::Coco),
+            "yolo" => Ok(Self::Yolo),
+            "voc" => Ok(Self::Voc),
+            "geojson" => Ok(Self::GeoJson),
+            "wkt" => Ok(Self::Wkt),
+            "shapefile" => Ok(Self::Shapefile),
+            "svg" => Ok(Self::Svg),
+            "tiled" => Ok(Self::Tiled),
+            "dxf" => Ok(Self::Dxf),
+            "parquet" => Ok(Self::Parquet),
+            "arrow" => Ok(Self::Arrow),
+            "sqlite" => Ok(Self::Sqlite),
+            other => Err(format!("Unknown input format \"{}\"", other)),
+        }
+    }
+}
+
+/// Header names of the CSV input columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvColumns {
+    pub name: String,
+    pub x: String,
+    pub y: String,
+    pub width: String,
+    pub height: String,
+    /// Optional column, the objects have no layer if the table does not have it.
+    pub layer: String,
+    /// Optional column, the empty cells mean no level.
+    pub z: String,
+    /// Optional column, the empty cells mean no rotation.
+    pub rotation: String,
+    /// Optional column of the WKT or the hex WKB shapes, the rect columns may be omitted or
+    /// left empty if the table has it.
+    pub geometry: String,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        Self {
+            name: "name".to_owned(),
+            x: "x".to_owned(),
+            y: "y".to_owned(),
+            width: "width".to_owned(),
+            height: "height".to_owned(),
+            layer: "layer".to_owned(),
+            z: "z".to_owned(),
+            rotation: "rotation".to_owned(),
+            geometry: "geometry".to_owned(),
+        }
+    }
+}
+
+/// Parses the `field=header` pairs separated by commas, e.g. `name=id,x=left,y=top`, the
+/// omitted fields keep the default header names.
+impl FromStr for CsvColumns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = Self::default();
+        for pair in s.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (field, header) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Expected \"field=header\", got \"{}\"", pair))?;
+            let target = match field.trim() {
+                "name" => &mut columns.name,
+                "x" => &mut columns.x,
+                "y" => &mut columns.y,
+                "width" => &mut columns.width,
+                "height" => &mut columns.height,
+                "layer" => &mut columns.layer,
+                "z" => &mut columns.z,
+                "rotation" => &mut columns.rotation,
+                "geometry" => &mut columns.geometry,
+                other => return Err(format!("Unknown object field \"{}\"", other)),
+            };
+            *target = header.trim().to_owned();
+        }
+        Ok(columns)
+    }
+}
+
+/// Serialization format of the analysis results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Pretty printed JSON.
+    #[default]
+    Json,
+    Yaml,
+    /// Intersections only, one row per pair of objects.
+    Csv,
+    Toml,
+    MessagePack,
+    Cbor,
+    /// Aligned text tables of the areas and the intersections, see [`Output::to_table`].
+    Table,
+    /// JSON Lines, one record per line, see [`OutputRecord`](crate::OutputRecord). The other
+    /// values are written as a single line.
+    Ndjson,
+    /// JSON array of the intersections keyed by the COCO annotation ids, see
+    /// [`Input::from_coco_reader`].
+    Coco,
+    /// YOLO labels of the scene objects, see [`Input::to_yolo_labels`].
+    Yolo,
+    /// GeoJSON feature collection of the intersections, of the areas or of the scene objects
+    /// with their exact shapes.
+    GeoJson,
+    /// One WKT geometry per line like [`OutputFormat::GeoJson`], followed by the tab separated
+    /// names of the object or of the intersecting objects.
+    Wkt,
+    /// KML placemarks of the intersections or of the areas for Google Earth.
+    Kml,
+    /// PostGIS SQL script inserting the intersections or the areas into the table of the
+    /// section, see [`Output::intersections_to_sql`].
+    Sql,
+}
+
+impl OutputFormat {
+    /// Names of all supported formats.
+    pub const VARIANTS: &'static [&'static str] = &[
+        "json", "yaml", "csv", "toml", "msgpack", "cbor", "table", "ndjson", "coco", "yolo",
+        "geojson", "wkt", "kml", "sql",
+    ];
+
+    /// Returns the conventional file extension of the format.
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Csv => "csv",
+            Self::Toml => "toml",
+            Self::MessagePack => "msgpack",
+            Self::Cbor => "cbor",
+            Self::Table => "txt",
+            Self::Ndjson => "ndjson",
+            Self::Coco => "json",
+            Self::Yolo => "txt",
+            Self::GeoJson => "geojson",
+            Self::Wkt => "wkt",
+            Self::Kml => "kml",
+            Self::Sql => "sql",
+        }
+    }
+
+    /// Returns `true` if the format is not a human readable text.
+    pub const fn is_binary(self) -> bool {
+        matches!(self, Self::MessagePack | Self::Cbor)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "csv" => Ok(Self::Csv),
+            "toml" => Ok(Self::Toml),
+            "msgpack" => Ok(Self::MessagePack),
+            "cbor" => Ok(Self::Cbor),
+            "table" => Ok(Self::Table),
+            "ndjson" => Ok(Self::Ndjson),
+            "coco" => Ok(Self::Coco),
+            "yolo" => Ok(Self::Yolo),
+            "geojson" => Ok(Self::GeoJson),
+            "wkt" => Ok(Self::Wkt),
+            "kml" => Ok(Self::Kml),
+            "sql" => Ok(Self::Sql),
+            other => Err(format!("Unknown output format \"{}\"", other)),
+        }
+    }
+}
+
+/// Intersections part of the results.
+#[derive(Serialize)]
+pub(crate) struct IntersectionsSection<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<Page>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit: Option<Unit>,
+    intersections: &'a [ObjectIntersection],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a AnalysisStats>,
+}
+
+/// Areas part of the results.
+#[derive(Serialize)]
+pub(crate) struct AreasSection<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit: Option<Unit>,
+    areas: &'a [ObjectArea],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a AnalysisStats>,
+}
+
+/// Flat CSV row of an intersection.
+#[derive(Serialize)]
+struct IntersectionRecord<'a> {
+    first: &'a str,
+    second: &'a str,
+    from_x: f32,
+    from_y: f32,
+    to_x: f32,
+    to_y: f32,
+    area: f32,
+    // The metrics columns are written only if the metrics are included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    iou: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_coverage: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    second_coverage: Option<f32>,
+}
+
+/// Serializes the value in any structured format.
+pub(crate) fn encode<T: Serialize>(value: &T, format: OutputFormat) -> Result<Vec<u8>, Error> {
+    let reason = |err: anyhow::Error| Error::SerializeError { reason: err };
+    match format {
+        OutputFormat::Json => serde_json::to_vec_pretty(value).map_err(|e| reason(e.into())),
+        OutputFormat::Ndjson => serde_json::to_vec(value).map_err(|e| reason(e.into())),
+        OutputFormat::Yaml => serde_yaml::to_string(value)
+            .map(String::into_bytes)
+            .map_err(|e| reason(e.into())),
+        OutputFormat::Toml => toml::to_string_pretty(value)
+            .map(String::into_bytes)
+            .map_err(|e| reason(e.into())),
+        OutputFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| reason(e.into())),
+        OutputFormat::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes).map_err(|e| reason(e.into()))?;
+            Ok(bytes)
+        }
+        OutputFormat::Csv => Err(reason(anyhow::anyhow!("CSV is not a structured format"))),
+        OutputFormat::Table => Err(reason(anyhow::anyhow!("Table is not a structured format"))),
+        OutputFormat::Coco => Err(reason(anyhow::anyhow!(
+            "COCO output lists only the intersections"
+        ))),
+        OutputFormat::Yolo => Err(reason(anyhow::anyhow!(
+            "YOLO labels are written only for the scenes"
+        ))),
+        OutputFormat::GeoJson | OutputFormat::Wkt | OutputFormat::Kml | OutputFormat::Sql => {
+            Err(reason(anyhow::anyhow!(
+                "{:?} lists only the geometries of the objects",
+                format
+            )))
+        }
+    }
+}
+
+/// Writes the CSV records.
+pub(crate) fn encode_csv<T: Serialize>(
+    records: impl IntoIterator<Item = T>,
+) -> Result<Vec<u8>, Error> {
+    let reason = |err: anyhow::Error| Error::SerializeError { reason: err };
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for record in records {
+        writer.serialize(record).map_err(|e| reason(e.into()))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| reason(e.into_error().into()))
+}
+
+impl Output {
+    /// Parses the results written in the given format, gzip and zstd compressed streams are
+    /// decompressed on the fly. The CSV output lists only the intersections, so it cannot be
+    /// read back.
+    pub fn from_reader_as<R: Read>(reader: R, format: OutputFormat) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let mut reader = decompress::decompress(reader).map_err(|e| parse_error(e.into()))?;
+        match format {
+            OutputFormat::Json => {
+                serde_json::from_reader(reader).map_err(|e| parse_error(e.into()))
+            }
+            OutputFormat::Ndjson => Self::from_ndjson(std::io::BufReader::new(reader)),
+            OutputFormat::Yaml => {
+                serde_yaml::from_reader(reader).map_err(|e| parse_error(e.into()))
+            }
+            OutputFormat::Toml => {
+                let mut text = String::new();
+                reader
+                    .read_to_string(&mut text)
+                    .map_err(|e| parse_error(e.into()))?;
+                toml::from_str(&text).map_err(|e| parse_error(e.into()))
+            }
+            OutputFormat::MessagePack => {
+                rmp_serde::from_read(reader).map_err(|e| parse_error(e.into()))
+            }
+            OutputFormat::Cbor => ciborium::from_reader(reader).map_err(|e| parse_error(e.into())),
+            OutputFormat::Csv
+            | OutputFormat::Table
+            | OutputFormat::Coco
+            | OutputFormat::Yolo
+            | OutputFormat::GeoJson
+            | OutputFormat::Wkt
+            | OutputFormat::Kml
+            | OutputFormat::Sql => Err(parse_error(anyhow::anyhow!(
+                "{:?} results cannot be parsed back",
+                format
+            ))),
+        }
+    }
+
+    /// Serializes the results in the given format, the tables are not colored.
+    pub fn to_vec_as(&self, format: OutputFormat) -> Result<Vec<u8>, Error> {
+        match format {
+            OutputFormat::Csv => self.intersections_to_csv(),
+            OutputFormat::Coco => self.intersections_to_coco(),
+            OutputFormat::GeoJson => self.intersections_to_geojson(),
+            OutputFormat::Wkt => Ok(self.intersections_to_wkt()),
+            OutputFormat::Kml => Ok(self.intersections_to_kml()),
+            OutputFormat::Sql => Ok(self.intersections_to_sql(None, None)),
+            OutputFormat::Table => self.to_table(None, false).map(String::into_bytes),
+            OutputFormat::Ndjson => {
+                let mut writer = NdjsonWriter::new(Vec::new());
+                self.write_ndjson(&mut writer)?;
+                writer.into_inner()
+            }
+            format => encode(self, format),
+        }
+    }
+
+    /// Serializes only the given section of the results, the analysis statistics are kept
+    /// if present. The CSV and table summaries are not supported.
+    pub fn section_to_vec_as(
+        &self,
+        section: OutputSection,
+        format: OutputFormat,
+    ) -> Result<Vec<u8>, Error> {
+        match (section, format) {
+            (section, OutputFormat::Table) => {
+                self.to_table(Some(section), false).map(String::into_bytes)
+            }
+            (OutputSection::Intersections, OutputFormat::Csv) => self.intersections_to_csv(),
+            (OutputSection::Intersections, OutputFormat::Coco) => self.intersections_to_coco(),
+            (OutputSection::Intersections, OutputFormat::GeoJson) => {
+                self.intersections_to_geojson()
+            }
+            (OutputSection::Intersections, OutputFormat::Wkt) => Ok(self.intersections_to_wkt()),
+            (OutputSection::Intersections, OutputFormat::Kml) => Ok(self.intersections_to_kml()),
+            (OutputSection::Intersections, OutputFormat::Sql) => {
+                Ok(self.intersections_to_sql(None, None))
+            }
+            (OutputSection::Intersections, OutputFormat::Ndjson) => {
+                let mut writer = NdjsonWriter::new(Vec::new());
+                self.write_ndjson_parts(&mut writer, false, true)?;
+                writer.into_inner()
+            }
+            (OutputSection::Intersections, format) => encode(&self.intersections_section(), format),
+            (OutputSection::Areas, OutputFormat::Csv) => {
+                encode_csv(self.areas.iter().map(ObjectRecord::from))
+            }
+            (OutputSection::Areas, OutputFormat::GeoJson) => self.areas_to_geojson(),
+            (OutputSection::Areas, OutputFormat::Wkt) => Ok(self.areas_to_wkt()),
+            (OutputSection::Areas, OutputFormat::Kml) => Ok(self.areas_to_kml()),
+            (OutputSection::Areas, OutputFormat::Sql) => Ok(self.areas_to_sql(None, None)),
+            (OutputSection::Areas, OutputFormat::Ndjson) => {
+                let mut writer = NdjsonWriter::new(Vec::new());
+                self.write_ndjson_parts(&mut writer, true, false)?;
+                writer.into_inner()
+            }
+            (OutputSection::Areas, format) => encode(&self.areas_section(), format),
+            (OutputSection::Summary, format) => crate::summarize(self, 0).to_vec_as(format),
+        }
+    }
+
+    pub(crate) fn intersections_section(&self) -> IntersectionsSection<'_> {
+        IntersectionsSection {
+            page: self.page,
+            unit: self.unit,
+            intersections: &self.intersections,
+            stats: self.stats.as_ref(),
+        }
+    }
+
+    pub(crate) fn areas_section(&self) -> AreasSection<'_> {
+        AreasSection {
+            unit: self.unit,
+            areas: &self.areas,
+            stats: self.stats.as_ref(),
+        }
+    }
+
+    fn intersections_to_csv(&self) -> Result<Vec<u8>, Error> {
+        encode_csv(self.intersections.iter().map(|intersection| {
+            let (from, to) = (
+                intersection.area.top_left(),
+                intersection.area.bottom_right(),
+            );
+            IntersectionRecord {
+                first: &intersection.names.0,
+                second: &intersection.names.1,
+                from_x: from.x,
+                from_y: from.y,
+                to_x: to.x,
+                to_y: to.y,
+                area: intersection.area.area(),
+                iou: intersection.metrics.map(|metrics| metrics.iou),
+                first_coverage: intersection.metrics.map(|metrics| metrics.coverage.0),
+                second_coverage: intersection.metrics.map(|metrics| metrics.coverage.1),
+            }
+        }))
+    }
+
+    /// Serializes the results in the given text format.
+    pub fn to_string_as(&self, format: OutputFormat) -> Result<String, Error> {
+        if format.is_binary() {
+            return Err(Error::SerializeError {
+                reason: anyhow::anyhow!("{:?} is a binary format", format),
+            });
+        }
+
+        let bytes = self.to_vec_as(format)?;
+        String::from_utf8(bytes).map_err(|err| Error::SerializeError { reason: err.into() })
+    }
+}
+
+impl Input {
+    /// Merges several inputs into one scene. Object names are prefixed by the source name,
+    /// e.g. `scene.json:A`, which is also kept in the `source` property.
+    pub fn merge<I, S>(inputs: I) -> Self
+    where
+        I: IntoIterator<Item = (S, Input)>,
+        S: AsRef<str>,
+    {
+        let objects = inputs
+            .into_iter()
+            .flat_map(|(source, input)| {
+                let source = source.as_ref().to_owned();
+                input.objects.into_iter().map(move |mut object| {
+                    object.name = format!("{}:{}", source, object.name);
+                    object
+                        .properties
+                        .push(serde_json::json!({ "name": "source", "value": source }));
+                    object
+                })
+            })
+            .collect();
+
+        Self { objects }
+    }
+
+    /// Serializes the scene in the given format with the given representation of the object
+    /// rects, CSV tables always use the origin-size form. The YOLO labels are written as
+    /// they are, i.e. as already normalized, see [`Input::to_yolo_labels`].
+    pub fn to_vec_as(&self, format: OutputFormat, form: RectForm) -> Result<Vec<u8>, Error> {
+        match format {
+            OutputFormat::Yolo => return self.to_yolo_labels(ImageSize::default()),
+            OutputFormat::GeoJson => return self.to_geojson(),
+            OutputFormat::Wkt => return Ok(self.to_wkt()),
+            _ => {}
+        }
+        if format == OutputFormat::Csv {
+            let columns = CsvColumns::default();
+            // The layer, level, rotation and geometry columns are written only if they are used.
+            let layers = self.objects.iter().any(|object| object.layer.is_some());
+            let levels = self.objects.iter().any(|object| object.z.is_some());
+            let rotations = self.objects.iter().any(|object| object.rotation.is_some());
+            let shapes = self.objects.iter().any(|object| object.shape.is_some());
+            let mut header = vec![
+                columns.name,
+                columns.x,
+                columns.y,
+                columns.width,
+                columns.height,
+            ];
+            if layers {
+                header.push(columns.layer);
+            }
+            if levels {
+                header.push(columns.z);
+            }
+            if rotations {
+                header.push(columns.rotation);
+            }
+            if shapes {
+                header.push(columns.geometry);
+            }
+            let rows = self.objects.iter().map(|object| {
+                let mut row = vec![
+                    object.name.clone(),
+                    object.x.to_string(),
+                    object.y.to_string(),
+                    object.width.to_string(),
+                    object.height.to_string(),
+                ];
+                if layers {
+                    row.push(object.layer.clone().unwrap_or_default());
+                }
+                if levels {
+                    row.push(object.z.map(|x| x.to_string()).unwrap_or_default());
+                }
+                if rotations {
+                    row.push(object.rotation.map(|x| x.to_string()).unwrap_or_default());
+                }
+                if shapes {
+                    row.push(
+                        object
+                            .shape
+                            .as_ref()
+                            .map(AnyShape::to_wkt)
+                            .unwrap_or_default(),
+                    );
+                }
+                row
+            });
+            return encode_csv(std::iter::once(header).chain(rows));
+        }
+
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| match form {
+                RectForm::OriginSize => SceneObject::OriginSize(object),
+                RectForm::Corners => {
+                    let rect = object.shape.is_none().then(|| {
+                        (
+                            Point2D::new(object.x, object.y),
+                            Point2D::new(object.x + object.width, object.y + object.height),
+                        )
+                    });
+                    SceneObject::Corners(CornersObject {
+                        name: &object.name,
+                        from: rect.map(|rect| rect.0),
+                        to: rect.map(|rect| rect.1),
+                        layer: object.layer.as_deref(),
+                        z: object.z,
+                        rotation: object.rotation,
+                        shape: object.shape.as_ref(),
+                        properties: &object.properties,
+                    })
+                }
+            })
+            .collect::<Vec<_>>();
+        if format == OutputFormat::Ndjson {
+            let mut bytes = Vec::new();
+            for object in &objects {
+                bytes.extend(encode(object, format)?);
+                bytes.push(b'\n');
+            }
+            return Ok(bytes);
+        }
+        encode(&Scene { objects }, format)
+    }
+
+    /// Parses the input from the JSON reader.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        Self::from_reader_as(reader, InputFormat::Json)
+    }
+
+    /// Parses the input from the reader in the given format, gzip and zstd compressed
+    /// streams are decompressed on the fly.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(?format)))]
+    pub fn from_reader_as<R: Read>(reader: R, format: InputFormat) -> Result<Self, Error> {
+        let mut reader = decompress::decompress(reader)
+            .map_err(|err| Error::ParseError { reason: err.into() })?;
+        let input: Self = match format {
+            InputFormat::Json => serde_json::from_reader(reader)
+                .map_err(|err| Error::ParseError { reason: err.into() })?,
+            InputFormat::Yaml => serde_yaml::from_reader(reader)
+                .map_err(|err| Error::ParseError { reason: err.into() })?,
+            InputFormat::Csv => return Self::from_csv_plain(reader, &CsvColumns::default()),
+            InputFormat::Toml => {
+                let mut text = String::new();
+                reader
+                    .read_to_string(&mut text)
+                    .map_err(|err| Error::ParseError { reason: err.into() })?;
+                toml::from_str(&text).map_err(|err| Error::ParseError { reason: err.into() })?
+            }
+            InputFormat::Ndjson => Self {
+                objects: Self::ndjson_objects(reader).collect::<Result<_, _>>()?,
+            },
+            InputFormat::MessagePack => rmp_serde::from_read(reader)
+                .map_err(|err| Error::ParseError { reason: err.into() })?,
+            InputFormat::Cbor => ciborium::from_reader(reader)
+                .map_err(|err| Error::ParseError { reason: err.into() })?,
+            InputFormat::Coco => return Self::from_coco_reader(reader),
+            InputFormat::Yolo => return Self::from_yolo_reader(reader, ImageSize::default()),
+            InputFormat::Voc => return Self::from_voc_reader(reader),
+            InputFormat::GeoJson => return Self::from_geojson_reader(reader),
+            InputFormat::Wkt => return Self::from_wkt_reader(reader),
+            InputFormat::Svg => return Self::from_svg_reader(reader),
+            InputFormat::Tiled => return Self::from_tiled_reader(reader),
+            #[cfg(feature = "shapefile")]
+            InputFormat::Shapefile => return Self::from_shapefile_reader(reader),
+            #[cfg(not(feature = "shapefile"))]
+            InputFormat::Shapefile => {
+                return Err(Error::ParseError {
+                    reason: anyhow::anyhow!("Shapefile inputs require the \"shapefile\" feature"),
+                })
+            }
+            #[cfg(feature = "dxf")]
+            InputFormat::Dxf => return Self::from_dxf_reader(reader),
+            #[cfg(not(feature = "dxf"))]
+            InputFormat::Dxf => {
+                return Err(Error::ParseError {
+                    reason: anyhow::anyhow!("DXF inputs require the \"dxf\" feature"),
+                })
+            }
+            #[cfg(feature = "arrow")]
+            InputFormat::Parquet => {
+                return Self::from_parquet_reader(reader, &CsvColumns::default())
+            }
+            #[cfg(feature = "arrow")]
+            InputFormat::Arrow => return Self::from_arrow_reader(reader, &CsvColumns::default()),
+            #[cfg(not(feature = "arrow"))]
+            InputFormat::Parquet | InputFormat::Arrow => {
+                return Err(Error::ParseError {
+                    reason: anyhow::anyhow!("{:?} inputs require the \"arrow\" feature", format),
+                })
+            }
+            #[cfg(feature = "sqlite")]
+            InputFormat::Sqlite => return Self::from_sqlite_reader(reader),
+            #[cfg(not(feature = "sqlite"))]
+            InputFormat::Sqlite => {
+                return Err(Error::ParseError {
+                    reason: anyhow::anyhow!("SQLite inputs require the \"sqlite\" feature"),
+                })
+            }
+        };
+
+        trace_event!(objects = input.objects.len(), "Input parsed");
+        Ok(input)
+    }
+
+    /// Lazily parses the objects from the JSON Lines reader one by one, so the whole input is
+    /// never held in memory as text.
+    pub fn ndjson_objects<R: Read>(reader: R) -> impl Iterator<Item = Result<Object, Error>> {
+        serde_json::Deserializer::from_reader(std::io::BufReader::new(reader))
+            .into_iter::<Object>()
+            .map(|object| object.map_err(|err| Error::ParseError { reason: err.into() }))
+    }
+
+    /// Parses the CSV table with the given header names, the other columns are ignored.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub fn from_csv_reader<R: Read>(reader: R, columns: &CsvColumns) -> Result<Self, Error> {
+        let reader = decompress::decompress(reader)
+            .map_err(|err| Error::ParseError { reason: err.into() })?;
+        Self::from_csv_plain(reader, columns)
+    }
+
+    // Parses the already decompressed CSV table.
+    pub(crate) fn from_csv_plain<R: Read>(reader: R, columns: &CsvColumns) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+
+        let mut reader = csv::Reader::from_reader(reader);
+        let headers = reader.headers().map_err(|err| parse_error(err.into()))?;
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header == name)
+                .ok_or_else(|| parse_error(anyhow::anyhow!("Missing CSV column \"{}\"", name)))
+        };
+        let name_idx = column(&columns.name)?;
+        let geometry_idx = headers.iter().position(|header| header == columns.geometry);
+        // The rect columns are optional if the shapes are given by the geometry column.
+        let rect_column = |name: &str| match geometry_idx {
+            Some(_) => Ok(headers.iter().position(|header| header == name)),
+            None => column(name).map(Some),
+        };
+        let indices = [
+            rect_column(&columns.x)?,
+            rect_column(&columns.y)?,
+            rect_column(&columns.width)?,
+            rect_column(&columns.height)?,
+        ];
+        let layer_idx = headers.iter().position(|header| header == columns.layer);
+        let z_idx = headers.iter().position(|header| header == columns.z);
+        let rotation_idx = headers.iter().position(|header| header == columns.rotation);
+
+        let mut objects = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|err| parse_error(err.into()))?;
+            let line = record.position().map_or(0, |pos| pos.line());
+            let shape = geometry_idx
+                .and_then(|idx| record.get(idx))
+                .filter(|field| !field.trim().is_empty())
+                .map(|field| {
+                    crate::wkt::parse_geometry(field)
+                        .map_err(|err| parse_error(anyhow::anyhow!("{} on line {}", err, line)))
+                })
+                .transpose()?;
+            let [x, y, width, height] = match &shape {
+                Some(shape) => {
+                    let object = Object::from_shape(String::new(), shape.clone());
+                    [object.x, object.y, object.width, object.height]
+                }
+                None => {
+                    let mut values = [0_f32; 4];
+                    for (value, idx) in values.iter_mut().zip(&indices) {
+                        let field = idx
+                            .and_then(|idx| record.get(idx))
+                            .unwrap_or_default()
+                            .trim();
+                        *value = field.parse().map_err(|err| {
+                            parse_error(anyhow::anyhow!(
+                                "Invalid number \"{}\" on line {}: {}",
+                                field,
+                                line,
+                                err
+                            ))
+                        })?;
+                    }
+                    values
+                }
+            };
+            let z = z_idx
+                .and_then(|idx| record.get(idx))
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+                .map(|field| {
+                    field.parse().map_err(|err| {
+                        parse_error(anyhow::anyhow!(
+                            "Invalid level \"{}\" on line {}: {}",
+                            field,
+                            line,
+                            err
+                        ))
+                    })
+                })
+                .transpose()?;
+            let rotation = rotation_idx
+                .and_then(|idx| record.get(idx))
+                .map(str::trim)
+                .filter(|field| !field.is_empty())
+                .map(|field| {
+                    field.parse().map_err(|err| {
+                        parse_error(anyhow::anyhow!(
+                            "Invalid rotation \"{}\" on line {}: {}",
+                            field,
+                            line,
+                            err
+                        ))
+                    })
+                })
+                .transpose()?;
+            objects.push(Object {
+                name: record.get(name_idx).unwrap_or_default().to_owned(),
+                width,
+                height,
+                x,
+                y,
+                layer: layer_idx
+                    .and_then(|idx| record.get(idx))
+                    .filter(|layer| !layer.is_empty())
+                    .map(str::to_owned),
+                z,
+                rotation,
+                shape: shape.filter(|shape| !matches!(shape, AnyShape::Rect(_))),
+                properties: Vec::new(),
+            });
+        }
+
+        trace_event!(objects = objects.len(), "Input parsed");
+        Ok(Self { objects })
+    }
+
+    /// Reads and parses the input file, the format is detected by the extension and
+    /// falls back to JSON.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let format = InputFormat::from_extension(&path).unwrap_or_default();
+        Self::from_path_as(path, format)
+    }
+
+    /// Reads and parses the input file in the given format.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(path = ?path.as_ref(), ?format))
+    )]
+    pub fn from_path_as<P: AsRef<Path>>(path: P, format: InputFormat) -> Result<Self, Error> {
+        Self::from_reader_as(open_file(path)?, format)
+    }
+}
+
+/// Opens the file for reading, the error is attached to its path.
+pub fn open_file<P: AsRef<Path>>(path: P) -> Result<File, Error> {
+    File::open(&path).map_err(|err| Error::FileReadError {
+        path: path.as_ref().to_owned(),
+        reason: err.into(),
+    })
+}
+
+/// Starts the HTTP(S) GET request and returns the reader of the response body.
+#[cfg(feature = "net")]
+#[cfg_attr(feature = "tracing", tracing::instrument)]
+pub fn open_url(url: &str) -> Result<impl Read + Send, Error> {
+    let response = ureq::get(url).call().map_err(|err| Error::FetchError {
+        url: url.to_owned(),
+        reason: err.into(),
+    })?;
+
+    Ok(response.into_body().into_reader())
+}
+
+/// Returns the JSON schema of the input files.
+#[cfg(feature = "schemars")]
+pub fn input_schema() -> schemars::Schema {
+    schemars::schema_for!(Input)
+}
+
+/// Returns the JSON schema of the analysis results.
+#[cfg(feature = "schemars")]
+pub fn output_schema() -> schemars::Schema {
+    schemars::schema_for!(Output)
+}
+
+impl Object {
+    /// Returns the object of the given shape with its bounding rect, the rects are kept as
+    /// the plain objects.
+    pub(crate) fn from_shape(name: String, shape: AnyShape) -> Self {
+        let rect = shape.bounding_rect();
+        let (from, to) = (rect.top_left(), rect.bottom_right());
+        Self {
+            name,
+            width: to.x - from.x,
+            height: to.y - from.y,
+            x: from.x,
+            y: from.y,
+            layer: None,
+            z: None,
+            rotation: None,
+            shape: Some(shape).filter(|shape| !matches!(shape, AnyShape::Rect(_))),
+            properties: Vec::new(),
+        }
+    }
+
+    /// Returns the area occupied by this object.
+    pub fn area(&self) -> ObjectArea {
+        let (area, shape) = object_geometry(
+            (self.x, self.y, self.width, self.height),
+            self.rotation,
+            self.shape.as_ref(),
+        );
+        ObjectArea {
+            name: self.name.clone(),
+            area,
+            layer: self.layer.clone(),
+            z: self.z,
+            shape,
+            properties: Vec::new(),
+        }
+    }
+}
+
+/// Returns the area of the object rect and its exact shape turned by the rotation.
+pub(crate) fn object_geometry(
+    (x, y, width, height): (f32, f32, f32, f32),
+    rotation: Option<f32>,
+    shape: Option<&AnyShape>,
+) -> (BoundingRect, Option<AnyShape>) {
+    let rect = BoundingRect::from_points(
+        Point2D { x, y },
+        Point2D {
+            x: x + width,
+            y: y + height,
+        },
+    );
+
+    // The full turns keep the exact rect.
+    let rotation = rotation.filter(|rotation| rotation % 360.0 != 0.0);
+    let shape = match rotation {
+        Some(rotation) => {
+            let center = Point2D::new(
+                (rect.from.x + rect.to.x) / 2.0,
+                (rect.from.y + rect.to.y) / 2.0,
+            );
+            let shape = shape.cloned().unwrap_or(AnyShape::Rect(rect));
+            Some(shape.rotated(center, rotation))
+        }
+        None => shape.cloned(),
+    };
+    (shape.as_ref().map_or(rect, Shape::bounding_rect), shape)
+}
+
+impl ObjectArea {
+    /// Returns the exact shape of the object, its area if it is a rect.
+    pub fn exact_shape(&self) -> AnyShape {
+        self.shape.clone().unwrap_or(AnyShape::Rect(self.area))
+    }
+}
+
+impl Shape for ObjectArea {
+    fn bounding_rect(&self) -> BoundingRect {
+        self.area
+    }
+}
+
+#[test]
+fn test_input_shapes() {
+    let json = r#"{
+        "objects": [
+            { "name": "A", "kind": "circle", "center": { "x": 2, "y": 2 }, "radius": 1 },
+            {
+                "name": "B",
+                "kind": "polygon",
+                "vertices": [{ "x": 0, "y": 0 }, { "x": 4, "y": 0 }, { "x": 0, "y": 3 }]
+            },
+            { "name": "C", "kind": "segment", "from": { "x": 5, "y": 0 }, "to": { "x": 3, "y": 1 } },
+            { "name": "D", "kind": "rect", "from": { "x": 0, "y": 0 }, "to": { "x": 1, "y": 1 } }
+        ]
+    }"#;
+    let input = Input::from_reader(json.as_bytes()).unwrap();
+    let rects = input
+        .objects
+        .iter()
+        .map(|x| (x.x, x.y, x.width, x.height))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rects,
+        vec![
+            (1.0, 1.0, 2.0, 2.0),
+            (0.0, 0.0, 4.0, 3.0),
+            (3.0, 0.0, 2.0, 1.0),
+            (0.0, 0.0, 1.0, 1.0)
+        ]
+    );
+    assert!(matches!(
+        input.objects[0].shape,
+        Some(AnyShape::Circle { radius, .. }) if radius == 1.0
+    ));
+    assert_eq!(input.objects[3].shape, None);
+
+    // The bounding rects are written next to the shapes and ignored when read back.
+    for form in [RectForm::OriginSize, RectForm::Corners] {
+        let bytes = input.to_vec_as(OutputFormat::Json, form).unwrap();
+        assert_eq!(Input::from_reader(bytes.as_slice()).unwrap(), input);
+    }
+
+    let cases = vec![
+        (
+            r#"{ "name": "A", "kind": "circle", "radius": 1 }"#,
+            "center",
+        ),
+        (
+            r#"{ "name": "A", "kind": "polygon", "vertices": [] }"#,
+            "3 vertices",
+        ),
+        (r#"{ "name": "A", "kind": "star" }"#, "unknown variant"),
+    ];
+    for case in cases {
+        let json = format!(r#"{{ "objects": [{}] }}"#, case.0);
+        let err = Input::from_reader(json.as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains(case.1),
+            "Test case \"{}\" has been failed",
+            err
+        );
+    }
+}
+
+#[test]
+fn test_input_from_reader() {
+    let json = r#"{
+        "objects": [
+            { "name": "A", "width": 2.0, "height": 3.0, "x": 1.0, "y": -1.0 },
+            {
+                "name": "B",
+                "width": 1.0,
+                "height": 1.0,
+                "x": 0.0,
+                "y": 0.0,
+                "properties": [{ "name": "type", "value": "collision" }]
+            }
+        ]
+    }"#;
+
+    let input = Input::from_reader(json.as_bytes()).unwrap();
+    assert_eq!(input.objects.len(), 2);
+    assert!(input.objects[0].properties.is_empty());
+    assert_eq!(input.objects[1].properties.len(), 1);
+    assert_eq!(
+        input.objects[0].area(),
+        ObjectArea {
+            name: "A".to_owned(),
+            area: BoundingRect::from_points(
+                Point2D { x: 1.0, y: -1.0 },
+                Point2D { x: 3.0, y: 2.0 }
+            ),
+            layer: None,
+            z: None,
+            shape: None,
+            properties: Vec::new(),
+        }
+    );
+
+    let err = Input::from_reader(r#"{ "objects": [{ "name": "A" }] }"#.as_bytes()).unwrap_err();
+    assert!(matches!(err, Error::ParseError { .. }));
+
+    let err = Input::from_path("objects/missing.json").unwrap_err();
+    assert!(matches!(err, Error::FileReadError { .. }));
+}
+
+#[test]
+fn test_input_yaml() {
+    let yaml = "
+objects:
+  - { name: A, width: 2.0, height: 3.0, x: 1.0, y: -1.0 }
+  - name: B
+    width: 1.0
+    height: 1.0
+    x: 0.0
+    y: 0.0
+";
+    let input = Input::from_reader_as(yaml.as_bytes(), InputFormat::Yaml).unwrap();
+    assert_eq!(input.objects.len(), 2);
+    assert_eq!(
+        input.objects[1].area().area.bottom_right(),
+        Point2D::new(1.0, 1.0)
+    );
+
+    assert_eq!(
+        InputFormat::from_extension("a/b.yml"),
+        Some(InputFormat::Yaml)
+    );
+    assert_eq!(
+        InputFormat::from_extension("a/b.json"),
+        Some(InputFormat::Json)
+    );
+    assert_eq!(
+        InputFormat::from_extension("a/b.yaml.gz"),
+        Some(InputFormat::Yaml)
+    );
+    assert_eq!(
+        InputFormat::from_extension("a/000001.xml"),
+        Some(InputFormat::Voc)
+    );
+    assert_eq!(InputFormat::from_extension("a/b"), None);
+    assert_eq!(InputFormat::from_extension("a/b.zst"), None);
+    assert!(InputFormat::Yolo.matches_extension("labels/000001.txt.gz"));
+    assert!(InputFormat::Json.matches_extension("a/b.json"));
+    assert!(!InputFormat::Json.matches_extension("a/b.txt"));
+    assert_eq!(
+        InputFormat::from_url("https://example.com/a.yml?token=b.json"),
+        Some(InputFormat::Yaml)
+    );
+}
+
+#[test]
+fn test_input_toml() {
+    let toml = r#"
+[[objects]]
+name = "A"
+width = 2.0
+height = 3.0
+x = 1.0
+y = -1.0
+
+[[objects]]
+name = "B"
+width = 1.0
+height = 1.0
+x = 0.0
+y = 0.0
+properties = [{ name = "type", value = "collision" }]
+"#;
+    let input = Input::from_reader_as(toml.as_bytes(), InputFormat::Toml).unwrap();
+    assert_eq!(input.objects.len(), 2);
+    assert_eq!(input.objects[1].properties.len(), 1);
+
+    let json = Input::from_path("objects/test.json").unwrap();
+    let output = crate::analyze(json.clone(), crate::Options::default());
+    let toml = toml::to_string(&json).unwrap();
+    let input = Input::from_reader_as(toml.as_bytes(), InputFormat::Toml).unwrap();
+    assert_eq!(crate::analyze(input, crate::Options::default()), output);
+}
+
+#[test]
+fn test_input_ndjson() {
+    let ndjson = r#"{ "name": "A", "width": 2.0, "height": 3.0, "x": 1.0, "y": -1.0 }
+{ "name": "B", "width": 1.0, "height": 1.0, "x": 0.0, "y": 0.0 }
+
+{ "name": "C", "width": 1.0, "height": 1.0, "x": 5.0, "y": 5.0 }
+"#;
+    let input = Input::from_reader_as(ndjson.as_bytes(), InputFormat::Ndjson).unwrap();
+    assert_eq!(input.objects.len(), 3);
+    assert_eq!(input.objects[2].name, "C");
+
+    let mut objects = Input::ndjson_objects("{ \"name\": \"A\" }\n{}".as_bytes());
+    let err = objects.next().unwrap().unwrap_err();
+    assert!(err.to_string().contains("line 1"));
+}
+
+#[test]
+fn test_input_binary() {
+    let input = Input::from_path("objects/test.json").unwrap();
+
+    let msgpack = rmp_serde::to_vec_named(&input).unwrap();
+    assert_eq!(
+        Input::from_reader_as(msgpack.as_slice(), InputFormat::MessagePack).unwrap(),
+        input
+    );
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&input, &mut cbor).unwrap();
+    assert_eq!(
+        Input::from_reader_as(cbor.as_slice(), InputFormat::Cbor).unwrap(),
+        input
+    );
+}
+
+#[test]
+fn test_input_csv() {
+    let csv = "id,left,top,width,height,kind\nA,1,-1,2,3,wall\nB,0,0,1,1,door\n";
+    let columns: CsvColumns = "name=id, x=left, y=top".parse().unwrap();
+
+    let input = Input::from_csv_reader(csv.as_bytes(), &columns).unwrap();
+    assert_eq!(input.objects.len(), 2);
+    assert_eq!(input.objects[0].name, "A");
+    assert_eq!(
+        input.objects[0].area().area.bottom_right(),
+        Point2D::new(3.0, 2.0)
+    );
+
+    let err = Input::from_reader_as(csv.as_bytes(), InputFormat::Csv).unwrap_err();
+    assert!(err.to_string().contains("Missing CSV column \"name\""));
+    let err = Input::from_csv_reader("id,left,top,width,height\nA,a,0,1,1\n".as_bytes(), &columns)
+        .unwrap_err();
+    assert!(err.to_string().contains("on line 2"));
+
+    let csv = "name,x,y,width,height,floor\nA,0,0,1,1,2\nB,0,0,1,1,\n";
+    let columns: CsvColumns = "z=floor".parse().unwrap();
+    let input = Input::from_csv_reader(csv.as_bytes(), &columns).unwrap();
+    let levels = input.objects.iter().map(|x| x.z).collect::<Vec<_>>();
+    assert_eq!(levels, vec![Some(2), None]);
+    let err = Input::from_csv_reader(
+        "name,x,y,width,height,floor\nA,0,0,1,1,1.5\n".as_bytes(),
+        &columns,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("Invalid level \"1.5\" on line 2"));
+
+    // The compressed tables are decompressed once by every entry point.
+    let csv = "name,x,y,width,height\nA,0,0,1,1\n";
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    std::io::Write::write_all(&mut gzip, csv.as_bytes()).unwrap();
+    let gzip = gzip.finish().unwrap();
+    let plain = Input::from_csv_reader(csv.as_bytes(), &Default::default()).unwrap();
+    assert_eq!(
+        Input::from_reader_as(gzip.as_slice(), InputFormat::Csv).unwrap(),
+        plain
+    );
+    assert_eq!(
+        Input::from_reader_strict(gzip.as_slice(), InputFormat::Csv).unwrap(),
+        plain
+    );
+
+    assert!("name".parse::<CsvColumns>().is_err());
+    assert!("depth=z".parse::<CsvColumns>().is_err());
+}
+
+#[test]
+fn test_input_merge() {
+    let input = Input::from_path("objects/test.json").unwrap();
+    let count = input.objects.len();
+
+    let merged = Input::merge(vec![("a.json", input.clone()), ("b.json", input)]);
+    assert_eq!(merged.objects.len(), count * 2);
+    assert_eq!(merged.objects[0].name, "a.json:A");
+    assert_eq!(merged.objects[count].name, "b.json:A");
+    assert_eq!(
+        merged.objects[count].properties.last(),
+        Some(&serde_json::json!({ "name": "source", "value": "b.json" }))
+    );
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn test_open_url() {
+    use std::io::Write;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/scene.json", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let body = std::fs::read("objects/test.json").unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0_u8; 1024];
+        let _ = stream.read(&mut request).unwrap();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(&body).unwrap();
+    });
+
+    let input = Input::from_reader(open_url(&url).unwrap()).unwrap();
+    assert_eq!(input, Input::from_path("objects/test.json").unwrap());
+    server.join().unwrap();
+
+    let err = open_url("http://127.0.0.1:1/scene.json").err().unwrap();
+    assert!(matches!(err, Error::FetchError { .. }));
+}
+
+#[test]
+fn test_input_corners() {
+    let json = r#"{
+        "objects": [
+            { "name": "A", "from": { "x": 1.0, "y": -1.0 }, "to": { "x": 3.0, "y": 2.0 } },
+            { "name": "B", "width": 2.0, "height": 3.0, "x": 1.0, "y": -1.0 }
+        ]
+    }"#;
+    let input = Input::from_reader(json.as_bytes()).unwrap();
+    assert_eq!(input.objects[0].area().area, input.objects[1].area().area);
+    assert_eq!(input.objects[0].width, 2.0);
+
+    let err = Input::from_reader(
+        r#"{ "objects": [{ "name": "A", "from": { "x": 1.0, "y": 1.0 } }] }"#.as_bytes(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("missing field `to`"));
+
+    let input = Input::from_path("objects/test.json").unwrap();
+    for format in [
+        OutputFormat::Json,
+        OutputFormat::Yaml,
+        OutputFormat::Toml,
+        OutputFormat::Cbor,
+    ] {
+        for form in [RectForm::OriginSize, RectForm::Corners] {
+            let bytes = input.to_vec_as(format, form).unwrap();
+            let input_format = match format {
+                OutputFormat::Json => InputFormat::Json,
+                OutputFormat::Yaml => InputFormat::Yaml,
+                OutputFormat::Toml => InputFormat::Toml,
+                _ => InputFormat::Cbor,
+            };
+            let decoded = Input::from_reader_as(bytes.as_slice(), input_format).unwrap();
+            for (a, b) in decoded.objects.iter().zip(&input.objects) {
+                let (a, b) = (a.area(), b.area());
+                assert_eq!(a.name, b.name);
+                let close =
+                    |p: Point2D, q: Point2D| (p.x - q.x).abs() < 1e-3 && (p.y - q.y).abs() < 1e-3;
+                assert!(
+                    close(a.area.top_left(), b.area.top_left())
+                        && close(a.area.bottom_right(), b.area.bottom_right()),
+                    "Test case \"{:?} {:?}\" has been failed",
+                    format,
+                    form
+                );
+            }
+        }
+    }
+
+    let csv = input
+        .to_vec_as(OutputFormat::Csv, RectForm::Corners)
+        .unwrap();
+    let mut expected = input;
+    for object in &mut expected.objects {
+        object.properties.clear();
+    }
+    assert_eq!(
+        Input::from_reader_as(csv.as_slice(), InputFormat::Csv).unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_output_round_trip() {
+    let input = Input::from_path("objects/test.json").unwrap();
+    let output = crate::analyze(input, crate::Options::default());
+
+    let json = serde_json::to_string(&output).unwrap();
+    assert_eq!(serde_json::from_str::<Output>(&json).unwrap(), output);
+
+    let formats = [
+        OutputFormat::Json,
+        OutputFormat::Yaml,
+        OutputFormat::Toml,
+        OutputFormat::MessagePack,
+        OutputFormat::Cbor,
+    ];
+    for format in IntoIterator::into_iter(formats) {
+        let bytes = output.to_vec_as(format).unwrap();
+        assert_eq!(
+            Output::from_reader_as(bytes.as_slice(), format).unwrap(),
+            output,
+            "Test case \"{:?}\" has been failed",
+            format
+        );
+    }
+    let csv = output.to_vec_as(OutputFormat::Csv).unwrap();
+    assert!(Output::from_reader_as(csv.as_slice(), OutputFormat::Csv).is_err());
+}
+
+#[test]
+fn test_output_formats() {
+    let input = Input::from_path("objects/test.json").unwrap();
+    let output = crate::analyze(input, crate::Options::default());
+
+    let yaml = output.to_string_as(OutputFormat::Yaml).unwrap();
+    assert_eq!(serde_yaml::from_str::<Output>(&yaml).unwrap(), output);
+    let toml = output.to_string_as(OutputFormat::Toml).unwrap();
+    assert_eq!(toml::from_str::<Output>(&toml).unwrap(), output);
+
+    let csv = output.to_string_as(OutputFormat::Csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("first,second,from_x,from_y,to_x,to_y,area")
+    );
+    assert_eq!(lines.count(), output.intersections.len());
+
+    let msgpack = output.to_vec_as(OutputFormat::MessagePack).unwrap();
+    assert_eq!(rmp_serde::from_slice::<Output>(&msgpack).unwrap(), output);
+    let cbor = output.to_vec_as(OutputFormat::Cbor).unwrap();
+    assert_eq!(
+        ciborium::from_reader::<Output, _>(cbor.as_slice()).unwrap(),
+        output
+    );
+    assert!(output.to_string_as(OutputFormat::Cbor).is_err());
+
+    assert_eq!("yaml".parse(), Ok(OutputFormat::Yaml));
+    assert!(OutputFormat::VARIANTS
+        .iter()
+        .all(|name| name.parse::<OutputFormat>().is_ok()));
+    assert!(InputFormat::VARIANTS
+        .iter()
+        .all(|name| name.parse::<InputFormat>().is_ok()));
+    assert!("xml".parse::<OutputFormat>().is_err());
+}
+
+#[test]
+fn test_output_sections() {
+    let input = Input::from_path("objects/test.json").unwrap();
+    let output = crate::analyze(input, crate::Options::default());
+    let json = |section| {
+        let bytes = output
+            .section_to_vec_as(section, OutputFormat::Json)
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+    };
+
+    let intersections = json(OutputSection::Intersections);
+    assert_eq!(intersections.as_object().unwrap().len(), 1);
+    assert_eq!(
+        serde_json::from_value::<Vec<ObjectIntersection>>(intersections["intersections"].clone())
+            .unwrap(),
+        output.intersections
+    );
+    let areas = json(OutputSection::Areas);
+    assert_eq!(areas.as_object().unwrap().len(), 1);
+    assert_eq!(
+        serde_json::from_value::<Vec<ObjectArea>>(areas["areas"].clone()).unwrap(),
+        output.areas
+    );
+    let summary = json(OutputSection::Summary);
+    assert_eq!(summary["objects"], output.areas.len());
+    assert_eq!(summary["histogram"], serde_json::json!([]));
+
+    let csv = output
+        .section_to_vec_as(OutputSection::Areas, OutputFormat::Csv)
+        .unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().next(), Some("name,from_x,from_y,to_x,to_y"));
+    assert_eq!(csv.lines().count(), output.areas.len() + 1);
+    assert_eq!(
+        output
+            .section_to_vec_as(OutputSection::Intersections, OutputFormat::Csv)
+            .unwrap(),
+        output.to_vec_as(OutputFormat::Csv).unwrap()
+    );
+    assert!(output
+        .section_to_vec_as(OutputSection::Summary, OutputFormat::Csv)
+        .is_err());
+    assert!(OutputSection::VARIANTS
+        .iter()
+        .all(|name| name.parse::<OutputSection>().is_ok()));
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+fn test_input_schema() {
+    let schema = serde_json::to_value(input_schema()).unwrap();
+    assert_eq!(schema["required"], serde_json::json!(["objects"]));
+
+    let object = &schema["$defs"]["Object"];
+    assert_eq!(
+        object["required"],
+        serde_json::json!(["name", "width", "height", "x", "y"])
+    );
+    assert!(object["properties"]["properties"].is_object());
+}
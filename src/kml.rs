@@ -0,0 +1,168 @@
+use std::fmt::Write;
+
+use crate::{render::escape_xml, AnyShape, Output, Polygon};
+
+// Returns the closed ring of the polygon as the KML coordinates.
+fn coordinates(polygon: &Polygon) -> String {
+    polygon
+        .vertices
+        .iter()
+        .chain(polygon.vertices.first())
+        .map(|point| format!("{},{}", point.x, point.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Writes the segments as the line strings and the other shapes as the polygons, the circles
+// are approximated.
+fn geometry(shape: &AnyShape) -> String {
+    match shape {
+        AnyShape::Segment { from, to } => format!(
+            "<LineString><coordinates>{},{} {},{}</coordinates></LineString>",
+            from.x, from.y, to.x, to.y
+        ),
+        shape => format!(
+            "<Polygon><outerBoundaryIs><LinearRing><coordinates>{}</coordinates></LinearRing>\
+             </outerBoundaryIs></Polygon>",
+            coordinates(&shape.outline())
+        ),
+    }
+}
+
+// Writes the placemark with the given name, extended data and geometry.
+fn placemark(kml: &mut String, name: &str, data: &[(&str, String)], shape: &AnyShape) {
+    writeln!(kml, "    <Placemark>").unwrap();
+    writeln!(kml, "      <name>{}</name>", escape_xml(name)).unwrap();
+    writeln!(kml, "      <ExtendedData>").unwrap();
+    for (key, value) in data {
+        writeln!(
+            kml,
+            r#"        <Data name="{}"><value>{}</value></Data>"#,
+            key,
+            escape_xml(value)
+        )
+        .unwrap();
+    }
+    writeln!(kml, "      </ExtendedData>").unwrap();
+    writeln!(kml, "      {}", geometry(shape)).unwrap();
+    writeln!(kml, "    </Placemark>").unwrap();
+}
+
+fn document(name: &str, placemarks: String) -> Vec<u8> {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n  <Document>\n    <name>{}</name>\n\
+         {}  </Document>\n</kml>\n",
+        name, placemarks
+    )
+    .into_bytes()
+}
+
+impl Output {
+    /// Writes the intersections as the KML placemarks with the polygons of their rects. The
+    /// placemarks are named by both objects, the names, the area and the metrics are written
+    /// as the extended data.
+    pub(crate) fn intersections_to_kml(&self) -> Vec<u8> {
+        let mut placemarks = String::new();
+        for intersection in &self.intersections {
+            let (first, second) = &intersection.names;
+            let mut data = vec![
+                ("first", first.clone()),
+                ("second", second.clone()),
+                ("area", intersection.area.area().to_string()),
+            ];
+            if let Some(metrics) = intersection.metrics {
+                data.push(("iou", metrics.iou.to_string()));
+                data.push(("first_coverage", metrics.coverage.0.to_string()));
+                data.push(("second_coverage", metrics.coverage.1.to_string()));
+            }
+            placemark(
+                &mut placemarks,
+                &format!("{} & {}", first, second),
+                &data,
+                &AnyShape::Rect(intersection.area),
+            );
+        }
+        document("Intersections", placemarks)
+    }
+
+    /// Writes the areas as the KML placemarks with their exact shapes, the layers and the
+    /// levels are written as the extended data.
+    pub(crate) fn areas_to_kml(&self) -> Vec<u8> {
+        let mut placemarks = String::new();
+        for area in &self.areas {
+            let mut data = vec![("area", area.area.area().to_string())];
+            if let Some(layer) = &area.layer {
+                data.push(("layer", layer.clone()));
+            }
+            if let Some(z) = area.z {
+                data.push(("z", z.to_string()));
+            }
+            placemark(&mut placemarks, &area.name, &data, &area.exact_shape());
+        }
+        document("Areas", placemarks)
+    }
+}
+
+#[test]
+fn test_kml() {
+    use crate::{analyze, Input, Object, Options, OutputFormat, OutputSection};
+
+    let object = |name: &str, rect: [f32; 4]| Object {
+        name: name.to_owned(),
+        x: rect[0],
+        y: rect[1],
+        width: rect[2],
+        height: rect[3],
+        layer: None,
+        z: None,
+        rotation: None,
+        shape: None,
+        properties: Vec::new(),
+    };
+    let input = Input {
+        objects: vec![
+            object("<park>", [0.0, 0.0, 4.0, 4.0]),
+            object("house", [3.0, 3.0, 3.0, 2.0]),
+        ],
+    };
+    let output = analyze(input, Options::default());
+    let kml = String::from_utf8(output.to_vec_as(OutputFormat::Kml).unwrap()).unwrap();
+    let document = roxmltree::Document::parse(&kml).unwrap();
+    let placemarks = document
+        .descendants()
+        .filter(|node| node.has_tag_name("Placemark"))
+        .map(|node| {
+            let text = |tag: &str| {
+                node.descendants()
+                    .find(|child| child.has_tag_name(tag))
+                    .and_then(|child| child.text())
+                    .unwrap_or_default()
+                    .to_owned()
+            };
+            (text("name"), text("coordinates"))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        placemarks,
+        vec![(
+            "<park> & house".to_owned(),
+            "3,3 4,3 4,4 3,4 3,3".to_owned()
+        )]
+    );
+    assert!(kml.contains("<Data name=\"second\"><value>house</value></Data>"));
+
+    let areas = output
+        .section_to_vec_as(OutputSection::Areas, OutputFormat::Kml)
+        .unwrap();
+    let areas = String::from_utf8(areas).unwrap();
+    let document = roxmltree::Document::parse(&areas).unwrap();
+    assert_eq!(
+        document
+            .descendants()
+            .filter(|node| node.has_tag_name("Placemark"))
+            .count(),
+        2
+    );
+    assert!(Output::from_reader_as(kml.as_bytes(), OutputFormat::Kml).is_err());
+}
@@ -0,0 +1,237 @@
+use std::str::FromStr;
+
+/// Which pairs of the object layers are tested for the intersections, e.g. the electrical and
+/// the plumbing layers may overlap while two walls may not.
+///
+/// The objects without a layer belong to the same unnamed layer.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LayerRule {
+    /// Every pair of the objects is tested.
+    #[default]
+    All,
+    /// Only the objects of the same layer are tested.
+    Within,
+    /// Only the objects of different layers are tested.
+    Across,
+    /// Only the listed pairs of layers are tested.
+    Matrix(CollisionMatrix),
+}
+
+impl LayerRule {
+    /// Whether the objects of the given layers are tested.
+    pub fn tests(&self, a: Option<&str>, b: Option<&str>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Within => a == b,
+            Self::Across => a != b,
+            Self::Matrix(matrix) => matrix.contains(a, b),
+        }
+    }
+}
+
+/// Symmetric list of the layer pairs tested for the intersections, parsed from the
+/// comma-separated `first:second` pairs, e.g. `wall:wall,wall:door`.
+///
+/// The `*` layer matches any layer including the unnamed one, e.g. `wall:*`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CollisionMatrix {
+    pub pairs: Vec<(String, String)>,
+}
+
+impl CollisionMatrix {
+    /// Whether the pair of layers is listed in any order.
+    pub fn contains(&self, a: Option<&str>, b: Option<&str>) -> bool {
+        let matches = |pattern: &str, layer: Option<&str>| pattern == "*" || Some(pattern) == layer;
+        self.pairs.iter().any(|(first, second)| {
+            (matches(first, a) && matches(second, b)) || (matches(first, b) && matches(second, a))
+        })
+    }
+}
+
+impl FromStr for CollisionMatrix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pairs = s
+            .split(',')
+            .filter(|pair| !pair.trim().is_empty())
+            .map(|pair| {
+                let (first, second) = pair
+                    .split_once(':')
+                    .ok_or_else(|| format!("Expected \"first:second\", got \"{}\"", pair))?;
+                Ok((first.trim().to_owned(), second.trim().to_owned()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { pairs })
+    }
+}
+
+#[test]
+fn test_layer_rules() {
+    let input = crate::SceneBuilder::new()
+        .object("wall_1", 0.0, 0.0, 4.0, 4.0)
+        .layer("wall")
+        .object("wall_2", 1.0, 1.0, 4.0, 4.0)
+        .layer("wall")
+        .object("pipe", 2.0, 2.0, 4.0, 4.0)
+        .layer("plumbing")
+        .object("cable", 3.0, 3.0, 4.0, 4.0)
+        .layer("electrical")
+        .object("box", 3.0, 0.0, 4.0, 4.0)
+        .build();
+    let matrix = |x: &str| LayerRule::Matrix(x.parse().unwrap());
+
+    let cases = vec![
+        (
+            LayerRule::All,
+            vec![
+                "wall_1 wall_2",
+                "wall_1 pipe",
+                "wall_1 cable",
+                "wall_1 box",
+                "wall_2 pipe",
+                "wall_2 cable",
+                "wall_2 box",
+                "pipe cable",
+                "pipe box",
+                "cable box",
+            ],
+            "all",
+        ),
+        (LayerRule::Within, vec!["wall_1 wall_2"], "within"),
+        (
+            LayerRule::Across,
+            vec![
+                "wall_1 pipe",
+                "wall_1 cable",
+                "wall_1 box",
+                "wall_2 pipe",
+                "wall_2 cable",
+                "wall_2 box",
+                "pipe cable",
+                "pipe box",
+                "cable box",
+            ],
+            "across",
+        ),
+        (
+            matrix("wall:wall, electrical:plumbing"),
+            vec!["wall_1 wall_2", "pipe cable"],
+            "matrix",
+        ),
+        (
+            matrix("*:electrical"),
+            vec!["wall_1 cable", "wall_2 cable", "pipe cable", "cable box"],
+            "wildcard",
+        ),
+    ];
+    for case in cases {
+        let output = crate::analyze(
+            input.clone(),
+            crate::Options {
+                layers: case.0,
+                ..crate::Options::default()
+            },
+        );
+        let pairs = output
+            .intersections
+            .iter()
+            .map(|x| format!("{} {}", x.names.0, x.names.1))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, case.1, "Test case \"{}\" has been failed", case.2);
+    }
+    assert!("wall".parse::<CollisionMatrix>().is_err());
+
+    // The layers are split before the search, so only the walls are tested against each other.
+    let stats = crate::analyze(
+        input,
+        crate::Options {
+            stats: true,
+            layers: LayerRule::Within,
+            ..crate::Options::default()
+        },
+    )
+    .stats
+    .unwrap();
+    assert_eq!((stats.candidate_pairs, stats.intersections), (1, 1));
+}
+
+#[test]
+fn test_same_z() {
+    let input = crate::SceneBuilder::new()
+        .object("floor_1", 0.0, 0.0, 4.0, 4.0)
+        .z(1)
+        .object("room_1", 1.0, 1.0, 1.0, 1.0)
+        .z(1)
+        .object("floor_2", 0.0, 0.0, 4.0, 4.0)
+        .z(2)
+        .object("column", 2.0, 2.0, 1.0, 1.0)
+        .object("beam", 2.5, 2.5, 1.0, 1.0)
+        .build();
+    let z_pairs = |x: &str| crate::Options {
+        z_pairs: Some(x.parse().unwrap()),
+        ..crate::Options::default()
+    };
+
+    let cases = vec![
+        (
+            crate::Options::default(),
+            vec![
+                "floor_1 room_1",
+                "floor_1 floor_2",
+                "floor_1 column",
+                "floor_1 beam",
+                "room_1 floor_2",
+                "floor_2 column",
+                "floor_2 beam",
+                "column beam",
+            ],
+            "all levels",
+        ),
+        (
+            crate::Options {
+                same_z: true,
+                ..crate::Options::default()
+            },
+            vec!["floor_1 room_1", "column beam"],
+            "same level",
+        ),
+        (
+            z_pairs("1:2"),
+            vec!["floor_1 floor_2", "room_1 floor_2"],
+            "level pairs",
+        ),
+        (
+            z_pairs("2:*"),
+            vec![
+                "floor_1 floor_2",
+                "room_1 floor_2",
+                "floor_2 column",
+                "floor_2 beam",
+            ],
+            "wildcard",
+        ),
+    ];
+    for case in cases {
+        let output = crate::analyze(input.clone(), case.0);
+        let pairs = output
+            .intersections
+            .iter()
+            .map(|x| format!("{} {}", x.names.0, x.names.1))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, case.1, "Test case \"{}\" has been failed", case.2);
+    }
+
+    // The levels are split before the search, so the floors are not tested against each other.
+    let output = crate::analyze(
+        input,
+        crate::Options {
+            stats: true,
+            same_z: true,
+            ..crate::Options::default()
+        },
+    );
+    let stats = output.stats.unwrap();
+    assert_eq!((stats.candidate_pairs, stats.intersections), (2, 2));
+    assert_eq!(output.areas[2].z, Some(2));
+}
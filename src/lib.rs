@@ -1,6 +1,134 @@
-pub use rects::{BoundingRect, Point2D};
+// Emits a tracing event if the `tracing` feature is enabled.
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
+}
+
+pub use algorithms::{list_intersections_using, list_intersections_using_with_stats, Algorithm};
+pub use analyze::{analyze, analyze_each, analyze_join, analyze_objects, Options};
+pub use borrowed::{BorrowedInput, BorrowedObject};
+pub use broad_phase::list_potential_collisions;
+pub use builder::{RectBuilder, SceneBuilder};
+pub use channel::spawn_intersections;
+pub use decompress::{decompress, Decompressed};
+pub use dedupe::{DedupeMode, Duplicate};
+pub use diff::{diff_outputs, ChangedIntersection, OutputDiff};
+pub use error::{Error, GeometryError};
+pub use filter::{ObjectFilter, PropertyPredicate};
+pub use free_space::largest_empty_rect;
+pub use generate::{generate_scene, Distribution, GenerateOptions};
+#[cfg(feature = "gpu")]
+pub use gpu::list_intersections_gpu;
+pub use hilbert::{hilbert_decode, hilbert_encode, hilbert_order, sort_by_hilbert};
+pub use intervals::{list_interval_overlaps, IntervalOverlap, SegmentTree};
+#[cfg(feature = "net")]
+pub use io::open_url;
+#[cfg(feature = "schemars")]
+pub use io::{input_schema, output_schema};
+pub use io::{
+    open_file, CsvColumns, Input, InputFormat, IntersectionMetrics, Object, ObjectArea,
+    ObjectIntersection, Output, OutputFormat, OutputSection, RectForm,
+};
+pub use layers::{CollisionMatrix, LayerRule};
+pub use morton::{morton_decode, morton_encode, morton_order, sort_by_morton};
+pub use ndjson::{NdjsonWriter, OutputRecord};
+pub use nms::NmsOptions;
+pub use page::Page;
+pub use query::QueryResult;
+pub use rects::{BoundingRect, Point2D, Vector2D};
+#[cfg(feature = "png")]
+pub use render::render_png;
+pub use render::{render_svg, render_text, Charset, RenderOptions};
+pub use resolve::{resolve_overlaps, Resolution, ResolveOptions};
+pub use scene::{Scene, SceneIntersection, ShapeHandle};
+pub use shapes::{AnyShape, EdgeCrossing, Polygon, ShapeGroup};
+pub use soa::{
+    list_intersections_soa, list_intersections_soa_using, list_intersections_soa_using_with_stats,
+    list_intersections_soa_with_stats, RectSoA,
+};
+pub use sort::{SortKey, SortOrder};
+#[cfg(feature = "sqlite")]
+pub use sqlite::store_sqlite;
+pub use stats::{AnalysisStats, PhaseTime};
+#[cfg(feature = "async")]
+pub use stream::stream_intersections;
+pub use strict::{check_strict, SchemaViolation};
+pub use summary::{summarize, HistogramBin, SceneSummary};
+pub use units::Unit;
+pub use validate::{validate_input, validate_json, Diagnostic, Severity};
+pub use yolo::ImageSize;
 
+mod algorithms;
+mod analyze;
+#[cfg(feature = "approx")]
+mod approx_impls;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls;
+mod borrowed;
+mod broad_phase;
+mod builder;
+#[cfg(feature = "dxf")]
+mod cad;
+mod canonical;
+mod channel;
+mod coco;
+#[cfg(feature = "arrow")]
+mod columnar;
+mod decompress;
+mod dedupe;
+mod diff;
+mod error;
+pub mod ffi;
+mod filter;
+mod free_space;
+mod generate;
+mod geojson;
+#[cfg(feature = "gpu")]
+mod gpu;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+mod hilbert;
+mod intervals;
+mod io;
+mod kml;
+mod layers;
+mod morton;
+mod ndjson;
+mod nms;
+mod packed_rtree;
+mod page;
+mod precision;
+#[cfg(feature = "proptest-support")]
+pub mod proptest_support;
+mod query;
+pub mod rect_serde;
 mod rects;
+mod render;
+mod resolve;
+mod scene;
+mod shapes;
+#[cfg(feature = "shapefile")]
+mod shp;
+mod soa;
+mod sort;
+mod sql;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stats;
+#[cfg(feature = "async")]
+mod stream;
+mod strict;
+mod summary;
+mod svg;
+mod table;
+mod tiled;
+mod units;
+mod validate;
+mod voc;
+mod wkt;
+mod yolo;
 
 /// A Common shape.
 pub trait Shape {
@@ -10,6 +138,18 @@ pub trait Shape {
     fn intersection(&self, other: &Self) -> Option<BoundingRect> {
         self.bounding_rect().intersect(&other.bounding_rect())
     }
+    /// Returns the rectangles that cover this shape.
+    ///
+    /// The default implementation approximates the shape by its bounding rectangle.
+    fn region(&self) -> Vec<BoundingRect> {
+        vec![self.bounding_rect()]
+    }
+}
+
+impl Shape for BoundingRect {
+    fn bounding_rect(&self) -> BoundingRect {
+        *self
+    }
 }
 
 /// Shapes intersection summary.
@@ -21,28 +161,150 @@ pub struct Intersection {
     pub a_idx: usize,
     /// Second shape index.
     pub b_idx: usize,
+    /// Minimum translation vector of the first shape that resolves the overlap.
+    pub mtv: Vector2D,
 }
 
 /// Searches for intersecting shapes in the specified list.
-/// 
+///
 /// Note that this method uses a naive O(n^2) method to find shapes intersection.
 pub fn list_intersections<S: Shape>(objects: &[S]) -> Vec<Intersection> {
+    list_intersections_with_stats(objects).0
+}
+
+/// Searches for intersecting shapes in the specified list like [`list_intersections`]
+/// and collects the search statistics.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(shapes = objects.len())))]
+pub fn list_intersections_with_stats<S: Shape>(
+    objects: &[S],
+) -> (Vec<Intersection>, AnalysisStats) {
+    let mut stats = AnalysisStats {
+        shapes: objects.len(),
+        candidate_pairs: objects.len() * objects.len().saturating_sub(1) / 2,
+        ..AnalysisStats::default()
+    };
+
     let mut intersections = Vec::new();
-    for i in 0..objects.len() {
-        for j in (i + 1)..objects.len() {
-            if let Some(area) = objects[i].intersection(&objects[j]) {
-                intersections.push(Intersection {
-                    area,
-                    a_idx: i,
-                    b_idx: j,
-                })
+    stats.measure("search", || {
+        for i in 0..objects.len() {
+            for j in (i + 1)..objects.len() {
+                // The shapes claiming an intersection of the merely touching bounding rects
+                // have no translation vector and are skipped.
+                let intersection = objects[i].intersection(&objects[j]).and_then(|area| {
+                    let mtv = objects[i]
+                        .bounding_rect()
+                        .mtv(&objects[j].bounding_rect())?;
+                    Some(Intersection {
+                        area,
+                        a_idx: i,
+                        b_idx: j,
+                        mtv,
+                    })
+                });
+                intersections.extend(intersection);
             }
         }
-    }
+    });
+
+    stats.intersections = intersections.len();
+    stats.peak_memory_estimate =
+        stats::search_memory_estimate::<S>(objects.len(), intersections.len());
+    trace_event!(
+        pairs = stats.candidate_pairs,
+        intersections = stats.intersections,
+        "Intersection search finished"
+    );
+    (intersections, stats)
+}
 
+/// Searches for intersections between the shapes of two lists, the shapes of the same list
+/// are not tested against each other.
+///
+/// The `a_idx` of the found intersections refers to the first list and the `b_idx` to the
+/// second one, the intersections are ordered by them. The second list is indexed by the packed
+/// R-tree queried by the shapes of the first one in parallel.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(first = first.len(), second = second.len()))
+)]
+pub fn list_intersections_between<S: Shape + Sync>(first: &[S], second: &[S]) -> Vec<Intersection> {
+    let intersections = algorithms::search_between(first, second).0;
+    trace_event!(intersections = intersections.len(), "Join search finished");
     intersections
 }
 
+/// Calculates the bounding rectangles area shared by every shape in the specified list.
+///
+/// Returns `None` if the list is empty or there is no such area.
+pub fn intersect_all<S: Shape>(objects: &[S]) -> Option<BoundingRect> {
+    let (first, rest) = objects.split_first()?;
+    rest.iter().try_fold(first.bounding_rect(), |area, shape| {
+        area.intersect(&shape.bounding_rect())
+    })
+}
+
+/// Calculates the exact region shared by every shape in the specified list, using
+/// the [`Shape::region`] of each shape.
+///
+/// The result is empty if there is no such region. Note that the resulting rectangles
+/// may overlap each other if the regions of the shapes have overlapping rectangles.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(shapes = objects.len())))]
+pub fn intersect_all_regions<S: Shape>(objects: &[S]) -> Vec<BoundingRect> {
+    let (first, rest) = match objects.split_first() {
+        Some(split) => split,
+        None => return Vec::new(),
+    };
+
+    rest.iter().fold(first.region(), |region, shape| {
+        let other = shape.region();
+        region
+            .iter()
+            .flat_map(|a| other.iter().filter_map(move |b| a.intersect(b)))
+            .collect()
+    })
+}
+
+// Shape claiming to intersect any other one, even the touching and the distant ones.
+#[cfg(test)]
+pub(crate) struct Claiming(pub BoundingRect);
+
+#[cfg(test)]
+impl Shape for Claiming {
+    fn bounding_rect(&self) -> BoundingRect {
+        self.0
+    }
+
+    fn intersection(&self, _other: &Self) -> Option<BoundingRect> {
+        Some(self.0)
+    }
+}
+
+// The first rect touches the second one and overlaps the third one.
+#[cfg(test)]
+pub(crate) fn claiming_shapes() -> Vec<Claiming> {
+    [(0.0, 0.0), (2.0, 0.0), (1.0, 1.0)]
+        .iter()
+        .map(|&(x, y)| {
+            Claiming(BoundingRect::from_points(
+                Point2D { x, y },
+                Point2D {
+                    x: x + 2.0,
+                    y: y + 2.0,
+                },
+            ))
+        })
+        .collect()
+}
+
+#[test]
+fn test_claiming_shapes() {
+    let pairs = list_intersections(&claiming_shapes())
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![(0, 2), (1, 2)]);
+}
+
 #[test]
 fn test_objects_intersection() {
     struct TestShape {
@@ -102,6 +364,7 @@ fn test_objects_intersection() {
             },
             a_idx: 0,
             b_idx: 1,
+            mtv: Vector2D { x: 0.0, y: 2.0 },
         },
         Intersection {
             area: BoundingRect {
@@ -110,9 +373,115 @@ fn test_objects_intersection() {
             },
             a_idx: 0,
             b_idx: 2,
+            mtv: Vector2D { x: -2.0, y: 0.0 },
         },
     ];
 
     let actual = list_intersections(&objects);
     assert_eq!(actual, expected);
 }
+
+#[test]
+fn test_list_intersections_between() {
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+    let first = vec![rect(0.0, 0.0, 4.0, 4.0), rect(2.0, 2.0, 6.0, 6.0)];
+    let second = vec![rect(10.0, 10.0, 11.0, 11.0), rect(3.0, 3.0, 5.0, 5.0)];
+
+    let pairs = list_intersections_between(&first, &second)
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx, x.area))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        pairs,
+        vec![
+            (0, 1, rect(3.0, 3.0, 4.0, 4.0)),
+            (1, 1, rect(3.0, 3.0, 5.0, 5.0))
+        ]
+    );
+    assert!(list_intersections_between(&first, &[]).is_empty());
+
+    // The indexed join finds the same pairs as the search over both lists.
+    let scene = |seed: u64| {
+        generate_scene(&GenerateOptions {
+            count: 300,
+            seed,
+            density: 0.5,
+            ..GenerateOptions::default()
+        })
+        .objects
+        .iter()
+        .map(|object| object.area().area)
+        .collect::<Vec<_>>()
+    };
+    let (first, second) = (scene(1), scene(2));
+    let both = first.iter().chain(&second).copied().collect::<Vec<_>>();
+    let expected = list_intersections(&both)
+        .into_iter()
+        .filter(|x| x.a_idx < first.len() && x.b_idx >= first.len())
+        .map(|x| (x.a_idx, x.b_idx - first.len(), x.area))
+        .collect::<Vec<_>>();
+    let actual = list_intersections_between(&first, &second)
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx, x.area))
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_intersect_all() {
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+
+    let cases = vec![
+        (vec![], None, "empty list"),
+        (
+            vec![rect(0.0, 0.0, 4.0, 4.0)],
+            Some(rect(0.0, 0.0, 4.0, 4.0)),
+            "single shape",
+        ),
+        (
+            vec![
+                rect(0.0, 0.0, 4.0, 4.0),
+                rect(2.0, 1.0, 6.0, 6.0),
+                rect(1.0, 2.0, 3.0, 7.0),
+            ],
+            Some(rect(2.0, 2.0, 3.0, 4.0)),
+            "common area",
+        ),
+        (
+            vec![
+                rect(0.0, 0.0, 4.0, 4.0),
+                rect(2.0, 1.0, 6.0, 6.0),
+                rect(5.0, 5.0, 7.0, 7.0),
+            ],
+            None,
+            "pairwise intersections only",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            intersect_all(&case.0),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+
+    // The bounding rectangle of the "L"-shaped group intersects the second shape,
+    // but the group itself does not.
+    let groups = vec![
+        ShapeGroup::new(vec![rect(0.0, 0.0, 1.0, 4.0), rect(0.0, 0.0, 4.0, 1.0)]),
+        ShapeGroup::new(vec![rect(2.0, 2.0, 4.0, 4.0)]),
+    ];
+    assert_eq!(intersect_all(&groups), Some(rect(2.0, 2.0, 4.0, 4.0)));
+    assert_eq!(intersect_all_regions(&groups), vec![]);
+    assert_eq!(
+        intersect_all_regions(&groups[..1]),
+        vec![rect(0.0, 0.0, 1.0, 4.0), rect(0.0, 0.0, 4.0, 1.0)]
+    );
+}
@@ -1,115 +1,184 @@
-use std::{fs::File, path::PathBuf};
+use std::{ffi::OsString, path::PathBuf};
 
-use serde::{Deserialize, Serialize};
-use sophya_prog_test::{list_intersections, BoundingRect, Point2D, Shape};
-use structopt::StructOpt;
+use structopt::{clap::AppSettings, StructOpt};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Input {
-    pub objects: Vec<Object>,
-}
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct Object {
-    pub name: String,
-    pub width: f32,
-    pub height: f32,
-    pub x: f32,
-    pub y: f32,
-    #[serde(default)]
-    pub properties: Vec<serde_json::Value>,
-}
+use cli::report::{ErrorFormat, ErrorReport};
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct ObjectArea {
-    name: String,
-    area: BoundingRect,
-}
+mod cli;
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct ObjectIntersection {
-    names: (String, String),
-    area: BoundingRect,
+/// Analyzes the intersections of rectangular objects
+///
+/// The option defaults are read from the sophya.toml file in the current directory or its
+/// parents and from the SHAPES_<OPTION> environment variables, e.g. SHAPES_MIN_AREA=1. The
+/// variables override the config and the options given on the command line override both.
+///
+/// Exits with 1 on the IO and other errors, 2 on the parse errors, 3 if the validation fails
+/// and 4 if `--fail-on-intersection` finds intersections.
+#[derive(StructOpt)]
+#[structopt(global_settings = &[AppSettings::AllArgsOverrideSelf])]
+struct Cli {
+    #[structopt(flatten)]
+    verbosity: cli::Verbosity,
+    /// Read the option defaults from the given TOML file instead of the discovered sophya.toml
+    // The config options are handled before the arguments are parsed, see `config::find`.
+    #[allow(dead_code)]
+    #[structopt(long, global = true, parse(from_os_str))]
+    config: Option<PathBuf>,
+    /// Do not read the discovered sophya.toml
+    #[allow(dead_code)]
+    #[structopt(long, global = true, conflicts_with = "config")]
+    no_config: bool,
+    /// How the error of the failed run is reported to stderr, "json" prints a single line
+    /// object with the kind, path, line, column and message fields
+    // The error format is read before the arguments are parsed, see `ErrorFormat::from_args`.
+    #[allow(dead_code)]
+    #[structopt(
+        long,
+        global = true,
+        default_value = "text",
+        possible_values = ErrorFormat::VARIANTS
+    )]
+    error_format: ErrorFormat,
+    #[structopt(subcommand)]
+    command: Command,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct Output {
-    areas: Vec<ObjectArea>,
-    intersections: Vec<ObjectIntersection>,
+#[derive(StructOpt)]
+enum Command {
+    Intersect(cli::intersect::Intersect),
+    Validate(cli::validate::Validate),
+    Convert(cli::convert::Convert),
+    Stats(cli::stats::Stats),
+    Render(cli::render::Render),
+    View(cli::view::View),
+    Repl(cli::repl::Repl),
+    Serve(cli::serve::Serve),
+    Diff(cli::diff::Diff),
+    Join(cli::join::Join),
+    Query(cli::query::Query),
+    Generate(cli::generate::Generate),
+    Nms(cli::nms::Nms),
+    Bench(cli::bench::Bench),
+    Completions(cli::completions::Completions),
 }
 
-impl Object {
-    fn area(&self) -> ObjectArea {
-        let rect = BoundingRect::from_points(
-            Point2D {
-                x: self.x,
-                y: self.y,
-            },
-            Point2D {
-                x: self.x + self.width,
-                y: self.y + self.height,
-            },
-        );
+impl Cli {
+    /// Parses the arguments, `intersect` is implied if no subcommand is given. The config and
+    /// environment defaults are inserted right after the subcommand, so the given options
+    /// override them.
+    fn from_args_or_intersect(mut args: Vec<OsString>) -> anyhow::Result<Self> {
+        // The global options may precede the subcommand.
+        let mut position = 1;
+        while let Some(arg) = args.get(position).and_then(|arg| arg.to_str()) {
+            if arg == "--config" || arg == "--error-format" {
+                position += 2;
+            } else if cli::Verbosity::is_flag(arg)
+                || arg == "--no-config"
+                || arg.starts_with("--config=")
+                || arg.starts_with("--error-format=")
+            {
+                position += 1;
+            } else {
+                break;
+            }
+        }
+        let position = position.min(args.len());
+        let implied = match args.get(position).and_then(|arg| arg.to_str()) {
+            Some(arg) => {
+                !Command::NAMES.contains(&arg)
+                    && !matches!(arg, "-h" | "--help" | "-V" | "--version")
+            }
+            None => true,
+        };
+        if implied {
+            args.insert(position, "intersect".into());
+        }
 
-        ObjectArea {
-            name: self.name.clone(),
-            area: rect,
+        let subcommand = args[position].to_string_lossy().into_owned();
+        if subcommand != "help" {
+            let bin = args[0].clone();
+            let probe = |option: OsString| {
+                let matches = Self::clap()
+                    .get_matches_from_safe([bin.clone(), args[position].clone(), option])
+                    .map_err(|err| err.kind)?;
+                Ok(matches.subcommand().1.cloned().unwrap_or_default())
+            };
+            let config = cli::config::find(&args)?;
+            let defaults =
+                cli::config::defaults(config.as_deref(), &subcommand, &probe, std::env::vars_os())?;
+            args.splice(position + 1..position + 1, defaults);
         }
+        Ok(Self::from_iter_safe(args)?)
     }
 }
 
-impl Shape for ObjectArea {
-    fn bounding_rect(&self) -> BoundingRect {
-        self.area
+impl Command {
+    /// Names of the subcommands, used to detect the bare `tool file.json` invocation.
+    const NAMES: &'static [&'static str] = &[
+        "intersect",
+        "validate",
+        "convert",
+        "stats",
+        "render",
+        "view",
+        "repl",
+        "serve",
+        "diff",
+        "join",
+        "query",
+        "generate",
+        "nms",
+        "bench",
+        "completions",
+        "help",
+    ];
+
+    fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Intersect(cmd) => cmd.run(),
+            Self::Validate(cmd) => cmd.run(),
+            Self::Convert(cmd) => cmd.run(),
+            Self::Stats(cmd) => cmd.run(),
+            Self::Render(cmd) => cmd.run(),
+            Self::View(cmd) => cmd.run(),
+            Self::Repl(cmd) => cmd.run(),
+            Self::Serve(cmd) => cmd.run(),
+            Self::Diff(cmd) => cmd.run(),
+            Self::Join(cmd) => cmd.run(),
+            Self::Query(cmd) => cmd.run(),
+            Self::Generate(cmd) => cmd.run(),
+            Self::Nms(cmd) => cmd.run(),
+            Self::Bench(cmd) => cmd.run(),
+            Self::Completions(cmd) => cmd.run(Cli::clap()),
+        }
     }
 }
 
-/// Searches for intersecting objects in the given input file
-#[derive(StructOpt)]
-struct Args {
-    /// Input file (*.json)
-    input_file: PathBuf,
-}
-
-#[derive(Debug, thiserror::Error)]
-enum Error {
-    #[error("File {path:?} could to be openned due to: {reason:}")]
-    FileReadError {
-        path: PathBuf,
-        reason: anyhow::Error,
-    },
-    #[error("Parse error: {reason:}")]
-    ParseError { reason: anyhow::Error },
-}
-
-impl Args {
-    fn run(self) -> Result<Output, Error> {
-        let file = File::open(&self.input_file).map_err(|err| Error::FileReadError {
-            path: self.input_file,
-            reason: err.into(),
-        })?;
-
-        let input: Input = serde_json::from_reader(file)
-            .map_err(|err| Error::ParseError { reason: err.into() })?;
-
-        let areas = input.objects.iter().map(Object::area).collect::<Vec<_>>();
-        let intersections = list_intersections(&areas)
-            .iter()
-            .map(|x| ObjectIntersection {
-                area: x.area,
-                names: (areas[x.a_idx].name.clone(), areas[x.b_idx].name.clone()),
-            })
-            .collect();
-
-        Ok(Output {
-            areas,
-            intersections,
-        })
+fn main() {
+    let args: Vec<OsString> = std::env::args_os().collect();
+    // The config and the argument errors precede the parsed error format.
+    let error_format = ErrorFormat::from_args(&args);
+    let result = Cli::from_args_or_intersect(args).and_then(|cli| {
+        cli.verbosity.init();
+        cli.command.run()
+    });
+
+    if let Err(err) = result {
+        // The help, the version and the usage errors in the text format are printed by clap.
+        if let Some(err) = err.downcast_ref::<structopt::clap::Error>() {
+            let (help, version) = (
+                structopt::clap::ErrorKind::HelpDisplayed,
+                structopt::clap::ErrorKind::VersionDisplayed,
+            );
+            if error_format == ErrorFormat::Text || err.kind == help || err.kind == version {
+                err.exit();
+            }
+        }
+        let report = ErrorReport::new(&err);
+        match error_format {
+            ErrorFormat::Text => eprintln!("Error: {:?}", err),
+            ErrorFormat::Json => eprintln!("{}", report.to_json()),
+        }
+        std::process::exit(report.kind.exit_code());
     }
 }
-
-fn main() -> anyhow::Result<()> {
-    let output = Args::from_args().run()?;
-    println!("{}", serde_json::to_string_pretty(&output).unwrap());
-    Ok(())
-}
@@ -0,0 +1,155 @@
+use crate::{BoundingRect, Shape};
+
+// Spreads the bits of the value into the even bits of the result.
+fn spread(value: u32) -> u64 {
+    let mut value = u64::from(value);
+    value = (value | value << 16) & 0x0000_FFFF_0000_FFFF;
+    value = (value | value << 8) & 0x00FF_00FF_00FF_00FF;
+    value = (value | value << 4) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | value << 2) & 0x3333_3333_3333_3333;
+    (value | value << 1) & 0x5555_5555_5555_5555
+}
+
+// Gathers the even bits of the value, the inverse of `spread`.
+fn compact(value: u64) -> u32 {
+    let mut value = value & 0x5555_5555_5555_5555;
+    value = (value | value >> 1) & 0x3333_3333_3333_3333;
+    value = (value | value >> 2) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | value >> 4) & 0x00FF_00FF_00FF_00FF;
+    value = (value | value >> 8) & 0x0000_FFFF_0000_FFFF;
+    ((value | value >> 16) & 0x0000_0000_FFFF_FFFF) as u32
+}
+
+/// Interleaves the bits of the coordinates into the Morton (Z-order) code, the bits of `x`
+/// are the even ones.
+pub fn morton_encode(x: u32, y: u32) -> u64 {
+    spread(x) | spread(y) << 1
+}
+
+/// Splits the Morton code back into the `x` and `y` coordinates.
+pub fn morton_decode(code: u64) -> (u32, u32) {
+    (compact(code), compact(code >> 1))
+}
+
+/// Maps the centers of the rects onto the integer grid spanning all of them.
+pub(crate) struct Grid {
+    from: (f64, f64),
+    scale: (f64, f64),
+}
+
+impl Grid {
+    pub(crate) fn new(rects: impl IntoIterator<Item = BoundingRect>) -> Self {
+        let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for rect in rects {
+            let (x, y) = center(&rect);
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let scale = |from: f64, to: f64| {
+            if to > from {
+                f64::from(u32::MAX) / (to - from)
+            } else {
+                0.0
+            }
+        };
+        Self {
+            from: min,
+            scale: (scale(min.0, max.0), scale(min.1, max.1)),
+        }
+    }
+
+    /// Returns the grid cell of the rect center, the NaN centers are in the first cell.
+    pub(crate) fn cell(&self, rect: &BoundingRect) -> (u32, u32) {
+        let (x, y) = center(rect);
+        // The float to integer casts saturate.
+        (
+            ((x - self.from.0) * self.scale.0) as u32,
+            ((y - self.from.1) * self.scale.1) as u32,
+        )
+    }
+}
+
+fn center(rect: &BoundingRect) -> (f64, f64) {
+    (
+        (f64::from(rect.from.x) + f64::from(rect.to.x)) / 2.0,
+        (f64::from(rect.from.y) + f64::from(rect.to.y)) / 2.0,
+    )
+}
+
+/// Returns the indices of the shapes in the Morton order of the centers of their bounding
+/// rects, so the neighbouring shapes are mostly close in the order. The centers are mapped
+/// onto the 32-bit grid spanning all of them and the ties keep the input order.
+pub fn morton_order<S: Shape>(shapes: &[S]) -> Vec<usize> {
+    let rects = shapes.iter().map(Shape::bounding_rect).collect::<Vec<_>>();
+    let grid = Grid::new(rects.iter().copied());
+    let mut order = (0..rects.len()).collect::<Vec<_>>();
+    order.sort_by_cached_key(|&idx| {
+        let (x, y) = grid.cell(&rects[idx]);
+        morton_encode(x, y)
+    });
+    order
+}
+
+/// Sorts the shapes in the Morton order like [`morton_order`].
+pub fn sort_by_morton<S: Shape>(shapes: &mut [S]) {
+    let grid = Grid::new(shapes.iter().map(Shape::bounding_rect));
+    shapes.sort_by_cached_key(|shape| {
+        let (x, y) = grid.cell(&shape.bounding_rect());
+        morton_encode(x, y)
+    });
+}
+
+#[test]
+fn test_morton() {
+    use crate::Point2D;
+
+    let cases = vec![
+        ((0, 0), 0, "origin"),
+        ((1, 0), 1, "x bit"),
+        ((0, 1), 2, "y bit"),
+        ((3, 5), 0b100111, "mixed"),
+        ((u32::MAX, 0), 0x5555_5555_5555_5555, "max x"),
+        ((u32::MAX, u32::MAX), u64::MAX, "max"),
+    ];
+    for case in cases {
+        let ((x, y), code, name) = case;
+        assert_eq!(
+            morton_encode(x, y),
+            code,
+            "Test case \"{}\" has been failed",
+            name
+        );
+        assert_eq!(
+            morton_decode(code),
+            (x, y),
+            "Test case \"{}\" has been failed",
+            name
+        );
+    }
+
+    let rect = |x: f32, y: f32| {
+        BoundingRect::from_points(Point2D::new(x, y), Point2D::new(x + 1.0, y + 1.0))
+    };
+    // The quadrants are visited in the Z order: top left, top right, bottom left, bottom right.
+    let mut rects = vec![
+        rect(9.0, 9.0),
+        rect(0.0, 9.0),
+        rect(9.0, 0.0),
+        rect(0.0, 0.0),
+        rect(1.0, 0.0),
+    ];
+    assert_eq!(morton_order(&rects), vec![3, 4, 2, 1, 0]);
+    sort_by_morton(&mut rects);
+    assert_eq!(
+        rects,
+        vec![
+            rect(0.0, 0.0),
+            rect(1.0, 0.0),
+            rect(9.0, 0.0),
+            rect(0.0, 9.0),
+            rect(9.0, 9.0),
+        ]
+    );
+    assert!(morton_order::<BoundingRect>(&[]).is_empty());
+    assert_eq!(morton_order(&[rect(2.0, 2.0), rect(2.0, 2.0)]), vec![0, 1]);
+}
@@ -0,0 +1,170 @@
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AnalysisStats, Error, ObjectArea, ObjectIntersection, Output, Page, Unit};
+
+/// Line of the NDJSON output, tagged by the `type` field, e.g.
+/// `{"type":"area","name":"A","area":{...}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputRecord {
+    /// Unit of the coordinates, written first, e.g. `{"type":"unit","unit":"m"}`.
+    Unit {
+        unit: Unit,
+    },
+    Page(Page),
+    Area(ObjectArea),
+    Intersection(ObjectIntersection),
+    Stats(AnalysisStats),
+}
+
+// Borrowed record, written without cloning the output.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RecordRef<'a> {
+    Unit { unit: Unit },
+    Page(&'a Page),
+    Area(&'a ObjectArea),
+    Intersection(&'a ObjectIntersection),
+    Stats(&'a AnalysisStats),
+}
+
+impl<'a> From<&'a OutputRecord> for RecordRef<'a> {
+    fn from(record: &'a OutputRecord) -> Self {
+        match record {
+            OutputRecord::Unit { unit } => Self::Unit { unit: *unit },
+            OutputRecord::Page(page) => Self::Page(page),
+            OutputRecord::Area(area) => Self::Area(area),
+            OutputRecord::Intersection(intersection) => Self::Intersection(intersection),
+            OutputRecord::Stats(stats) => Self::Stats(stats),
+        }
+    }
+}
+
+/// Writes the output records as JSON lines, every record is passed to the underlying writer
+/// as soon as it is written, so the line buffered writers flush it right away.
+#[derive(Debug)]
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Creates the writer of the records.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes the record as a single line.
+    pub fn write(&mut self, record: &OutputRecord) -> Result<(), Error> {
+        self.write_ref(RecordRef::from(record))
+    }
+
+    fn write_ref(&mut self, record: RecordRef) -> Result<(), Error> {
+        let reason = |err: anyhow::Error| Error::SerializeError { reason: err };
+        serde_json::to_writer(&mut self.writer, &record).map_err(|e| reason(e.into()))?;
+        self.writer.write_all(b"\n").map_err(|e| reason(e.into()))
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn into_inner(mut self) -> Result<W, Error> {
+        self.writer
+            .flush()
+            .map_err(|e| Error::SerializeError { reason: e.into() })?;
+        Ok(self.writer)
+    }
+}
+
+impl Output {
+    /// Writes the unit, the page, the areas, the intersections and the statistics as the NDJSON
+    /// records.
+    pub fn write_ndjson<W: Write>(&self, writer: &mut NdjsonWriter<W>) -> Result<(), Error> {
+        self.write_ndjson_parts(writer, true, true)
+    }
+
+    /// Writes the selected parts of the output, the page is written with the intersections.
+    pub(crate) fn write_ndjson_parts<W: Write>(
+        &self,
+        writer: &mut NdjsonWriter<W>,
+        areas: bool,
+        intersections: bool,
+    ) -> Result<(), Error> {
+        if let Some(unit) = self.unit {
+            writer.write_ref(RecordRef::Unit { unit })?;
+        }
+        if let Some(page) = self.page.as_ref().filter(|_| intersections) {
+            writer.write_ref(RecordRef::Page(page))?;
+        }
+        for area in self.areas.iter().filter(|_| areas) {
+            writer.write_ref(RecordRef::Area(area))?;
+        }
+        for intersection in self.intersections.iter().filter(|_| intersections) {
+            writer.write_ref(RecordRef::Intersection(intersection))?;
+        }
+        if let Some(stats) = &self.stats {
+            writer.write_ref(RecordRef::Stats(stats))?;
+        }
+        Ok(())
+    }
+
+    /// Parses the NDJSON output line by line, the empty lines are skipped.
+    pub(crate) fn from_ndjson(reader: impl BufRead) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let mut output = Self {
+            page: None,
+            unit: None,
+            areas: Vec::new(),
+            intersections: Vec::new(),
+            stats: None,
+        };
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| parse_error(e.into()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).map_err(|e| {
+                parse_error(anyhow::anyhow!("Invalid record on line {}: {}", idx + 1, e))
+            })?;
+            match record {
+                OutputRecord::Unit { unit } => output.unit = Some(unit),
+                OutputRecord::Page(page) => output.page = Some(page),
+                OutputRecord::Area(area) => output.areas.push(area),
+                OutputRecord::Intersection(intersection) => output.intersections.push(intersection),
+                OutputRecord::Stats(stats) => output.stats = Some(stats),
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[test]
+fn test_output_ndjson() {
+    use crate::OutputFormat;
+
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 3.0, 3.0, 2.0, 2.0)
+        .build();
+    let mut output = crate::analyze(input, crate::Options::default());
+    output.paginate(0, Some(10));
+    output.unit = Some(Unit::M);
+
+    let bytes = output.to_vec_as(OutputFormat::Ndjson).unwrap();
+    let text = String::from_utf8(bytes.clone()).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], r#"{"type":"unit","unit":"m"}"#);
+    assert_eq!(
+        lines[1],
+        r#"{"type":"page","offset":0,"limit":10,"total":1}"#
+    );
+    assert!(lines[2].starts_with(r#"{"type":"area","name":"A","#));
+    assert!(lines[4].starts_with(r#"{"type":"intersection","names":["A","B"],"#));
+    assert_eq!(
+        Output::from_reader_as(bytes.as_slice(), OutputFormat::Ndjson).unwrap(),
+        output
+    );
+
+    let err = Output::from_reader_as(&b"\n{\"type\":\"bogus\"}"[..], OutputFormat::Ndjson);
+    assert!(err.unwrap_err().to_string().contains("line 2"));
+}
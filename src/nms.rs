@@ -0,0 +1,151 @@
+use crate::{list_intersections_using, Algorithm, Error, Input, IntersectionMetrics, Object};
+
+/// Options of the non-maximum suppression of the detection boxes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NmsOptions {
+    /// The boxes overlapping a kept box with a greater intersection over union are
+    /// suppressed.
+    pub iou_threshold: f32,
+    /// Suppress only the boxes of the same class, i.e. of the same layer.
+    pub per_class: bool,
+    /// Property holding the score of the box, it must be a number.
+    pub score: String,
+}
+
+impl Default for NmsOptions {
+    fn default() -> Self {
+        Self {
+            iou_threshold: 0.5,
+            per_class: false,
+            score: "score".to_owned(),
+        }
+    }
+}
+
+// Returns the numeric score of the object.
+fn score(object: &Object, key: &str) -> Result<f64, Error> {
+    object
+        .property_values(key)
+        .find_map(serde_json::Value::as_f64)
+        .ok_or_else(|| Error::ParseError {
+            reason: anyhow::anyhow!(
+                "The object \"{}\" has no numeric \"{}\" property",
+                object.name,
+                key
+            ),
+        })
+}
+
+impl Input {
+    /// Keeps only the boxes that are not suppressed by the boxes with the higher scores, the
+    /// kept boxes are sorted by the descending score and the equal scores keep the input
+    /// order. The boxes are compared by their bounding rects, the candidate pairs are found by
+    /// the intersection search.
+    pub fn non_max_suppression(&mut self, options: &NmsOptions) -> Result<(), Error> {
+        let scores = self
+            .objects
+            .iter()
+            .map(|object| score(object, &options.score))
+            .collect::<Result<Vec<_>, _>>()?;
+        let areas = self.objects.iter().map(Object::area).collect::<Vec<_>>();
+
+        let mut overlaps = vec![Vec::new(); areas.len()];
+        for x in list_intersections_using(&areas, Algorithm::auto(areas.len())) {
+            let (a, b) = (&areas[x.a_idx], &areas[x.b_idx]);
+            if options.per_class && a.layer != b.layer {
+                continue;
+            }
+            if IntersectionMetrics::new(&a.area, &b.area, &x.area).iou > options.iou_threshold {
+                overlaps[x.a_idx].push(x.b_idx);
+                overlaps[x.b_idx].push(x.a_idx);
+            }
+        }
+
+        let mut order = (0..self.objects.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        let mut suppressed = vec![false; self.objects.len()];
+        let mut kept = Vec::new();
+        for idx in order {
+            if !suppressed[idx] {
+                kept.push(idx);
+                for &other in &overlaps[idx] {
+                    suppressed[other] = true;
+                }
+            }
+        }
+
+        let mut objects = std::mem::take(&mut self.objects)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        self.objects = kept
+            .into_iter()
+            .filter_map(|idx| objects[idx].take())
+            .collect();
+        Ok(())
+    }
+}
+
+#[test]
+fn test_non_max_suppression() {
+    use serde_json::json;
+
+    let mut input = crate::SceneBuilder::new()
+        .object("cat_1", 0.0, 0.0, 10.0, 10.0)
+        .layer("cat")
+        .object("cat_2", 1.0, 1.0, 10.0, 10.0)
+        .layer("cat")
+        .object("dog", 1.0, 0.0, 10.0, 10.0)
+        .layer("dog")
+        .object("cat_3", 20.0, 0.0, 10.0, 10.0)
+        .layer("cat")
+        .object("cat_4", 0.0, 5.0, 10.0, 10.0)
+        .layer("cat")
+        .build();
+    let scores = [json!(0.8), json!(0.9), json!(0.95), json!(0.3), json!(0.7)];
+    for (object, score) in input.objects.iter_mut().zip(scores) {
+        object.properties = vec![json!({ "name": "score", "value": score })];
+    }
+
+    let cases = vec![
+        (
+            NmsOptions::default(),
+            vec!["dog", "cat_4", "cat_3"],
+            "class agnostic",
+        ),
+        (
+            NmsOptions {
+                per_class: true,
+                ..NmsOptions::default()
+            },
+            vec!["dog", "cat_2", "cat_4", "cat_3"],
+            "per class",
+        ),
+        (
+            NmsOptions {
+                iou_threshold: 0.9,
+                ..NmsOptions::default()
+            },
+            vec!["dog", "cat_2", "cat_1", "cat_4", "cat_3"],
+            "high threshold",
+        ),
+    ];
+    for case in cases {
+        let mut kept = input.clone();
+        kept.non_max_suppression(&case.0).unwrap();
+        let names = kept
+            .objects
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, case.1, "Test case \"{}\" has been failed", case.2);
+    }
+
+    input.objects[3].properties.clear();
+    let err = input
+        .non_max_suppression(&NmsOptions::default())
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("\"cat_3\" has no numeric \"score\""));
+}
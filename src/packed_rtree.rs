@@ -0,0 +1,126 @@
+use std::mem;
+
+use crate::{BoundingRect, Point2D};
+
+// Number of the children of every node.
+const NODE_SIZE: usize = 16;
+
+/// R-tree packed bottom-up from the entries in the given order: every leaf is a run of the
+/// consecutive entries and every inner node a run of the consecutive nodes of the level below,
+/// so the locality of the order, e.g. along a space-filling curve, is the locality of the tree.
+pub(crate) struct PackedRTree {
+    // Entries in the packed order with their indices.
+    entries: Vec<(BoundingRect, usize)>,
+    // Bounds of the nodes from the leaves up to the root.
+    levels: Vec<Vec<BoundingRect>>,
+}
+
+// Returns the bounds of the rects, the degenerate rects are kept.
+fn bounds(rects: impl Iterator<Item = BoundingRect>) -> BoundingRect {
+    rects.fold(BoundingRect::EMPTY, |bounds, rect| BoundingRect {
+        from: Point2D::new(
+            bounds.from.x.min(rect.from.x),
+            bounds.from.y.min(rect.from.y),
+        ),
+        to: Point2D::new(bounds.to.x.max(rect.to.x), bounds.to.y.max(rect.to.y)),
+    })
+}
+
+// Returns true if the rects intersect or touch each other.
+fn overlaps(a: &BoundingRect, b: &BoundingRect) -> bool {
+    a.from.x <= b.to.x && b.from.x <= a.to.x && a.from.y <= b.to.y && b.from.y <= a.to.y
+}
+
+impl PackedRTree {
+    /// Packs the rects in the given order of their indices.
+    pub(crate) fn new(rects: &[BoundingRect], order: &[usize]) -> Self {
+        let entries = order
+            .iter()
+            .map(|&idx| (rects[idx], idx))
+            .collect::<Vec<_>>();
+        let mut levels = Vec::new();
+        let mut level = entries
+            .chunks(NODE_SIZE)
+            .map(|leaf| bounds(leaf.iter().map(|entry| entry.0)))
+            .collect::<Vec<_>>();
+        while level.len() > 1 {
+            let next = level
+                .chunks(NODE_SIZE)
+                .map(|nodes| bounds(nodes.iter().copied()))
+                .collect();
+            levels.push(mem::replace(&mut level, next));
+        }
+        levels.push(level);
+        Self { entries, levels }
+    }
+
+    /// Calls the function with the index of every rect intersecting or touching the given one.
+    pub(crate) fn for_each_intersecting(&self, rect: &BoundingRect, mut f: impl FnMut(usize)) {
+        let top = self.levels.len() - 1;
+        let mut stack = (0..self.levels[top].len())
+            .map(|node| (top, node))
+            .collect::<Vec<_>>();
+        while let Some((level, node)) = stack.pop() {
+            if !overlaps(&self.levels[level][node], rect) {
+                continue;
+            }
+            let children = node * NODE_SIZE..(node + 1) * NODE_SIZE;
+            if level == 0 {
+                let end = children.end.min(self.entries.len());
+                for entry in &self.entries[children.start..end] {
+                    if overlaps(&entry.0, rect) {
+                        f(entry.1);
+                    }
+                }
+            } else {
+                let end = children.end.min(self.levels[level - 1].len());
+                stack.extend((children.start..end).map(|child| (level - 1, child)));
+            }
+        }
+    }
+
+    /// Returns the memory used by the tree.
+    pub(crate) fn memory(&self) -> usize {
+        self.entries.len() * mem::size_of::<(BoundingRect, usize)>()
+            + self.levels.iter().map(Vec::len).sum::<usize>() * mem::size_of::<BoundingRect>()
+    }
+}
+
+#[test]
+fn test_packed_rtree() {
+    use crate::{generate_scene, hilbert_order, Distribution, GenerateOptions};
+
+    let input = generate_scene(&GenerateOptions {
+        count: 1000,
+        seed: 3,
+        density: 0.5,
+        distribution: Distribution::Clustered,
+    });
+    let mut rects = input
+        .objects
+        .iter()
+        .map(|object| object.area().area)
+        .collect::<Vec<_>>();
+    // The degenerate rect touching the first one.
+    rects.push(BoundingRect::from_points(
+        rects[0].from,
+        Point2D::new(rects[0].from.x, rects[0].from.y - 1.0),
+    ));
+    let order = hilbert_order(&rects);
+    let tree = PackedRTree::new(&rects, &order);
+    assert_eq!(tree.levels.len(), 3);
+    assert_eq!(tree.levels[2].len(), 1);
+
+    for query in &rects {
+        let mut actual = Vec::new();
+        tree.for_each_intersecting(query, |idx| actual.push(idx));
+        actual.sort_unstable();
+        let expected = (0..rects.len())
+            .filter(|&idx| overlaps(&rects[idx], query))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+    }
+
+    let empty = PackedRTree::new(&[], &[]);
+    empty.for_each_intersecting(&rects[0], |_| panic!("the tree is empty"));
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Output;
+
+/// Slice of the intersections written instead of all of them, see [`Output::paginate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Page {
+    /// Number of the skipped intersections.
+    pub offset: usize,
+    /// Maximum number of the intersections in the page, all the rest if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// Number of all the found intersections.
+    pub total: usize,
+}
+
+impl Page {
+    /// Returns the offset of the next page, if there are intersections left.
+    pub fn next_offset(&self) -> Option<usize> {
+        let next = self.offset + self.limit?;
+        Some(next).filter(|&next| next < self.total)
+    }
+}
+
+impl Output {
+    /// Keeps only the given slice of the intersections and records it with the total number
+    /// of the intersections in [`Output::page`]. The areas are kept as is.
+    ///
+    /// The intersections are expected to be sorted already, so the consecutive pages do not
+    /// overlap.
+    pub fn paginate(&mut self, offset: usize, limit: Option<usize>) {
+        let total = self.intersections.len();
+        self.intersections.drain(..offset.min(total));
+        if let Some(limit) = limit {
+            self.intersections.truncate(limit);
+        }
+        self.page = Some(Page {
+            offset,
+            limit,
+            total,
+        });
+    }
+}
+
+#[test]
+fn test_output_paginate() {
+    let output = crate::analyze(
+        crate::generate_scene(&crate::GenerateOptions {
+            count: 50,
+            seed: 1,
+            density: 0.5,
+            ..crate::GenerateOptions::default()
+        }),
+        crate::Options::default(),
+    );
+    let total = output.intersections.len();
+    assert!(total > 10);
+
+    let cases = vec![
+        (0, Some(4), 0..4, Some(4), "first page"),
+        (4, Some(4), 4..8, Some(8), "second page"),
+        (total - 2, Some(4), total - 2..total, None, "last page"),
+        (3, None, 3..total, None, "no limit"),
+        (total + 5, Some(4), total..total, None, "past the end"),
+    ];
+    for case in cases {
+        let mut page = output.clone();
+        page.paginate(case.0, case.1);
+        assert_eq!(
+            (
+                page.intersections.as_slice(),
+                page.page.unwrap().next_offset()
+            ),
+            (&output.intersections[case.2], case.3),
+            "Test case \"{}\" has been failed",
+            case.4
+        );
+        assert_eq!(page.page.unwrap().total, total);
+        assert_eq!(page.areas, output.areas);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{
+    BoundingRect, Input, IntersectionMetrics, ObjectArea, ObjectIntersection, Output, OutputRecord,
+    Point2D,
+};
+
+/// Rounds the value to the given number of decimal places, the ties are rounded to the even
+/// digit. The value is rounded as it is written, i.e. by its shortest decimal representation,
+/// so `2.675` is a tie even though the nearest `f32` is slightly smaller.
+pub(crate) fn round_half_even(value: f32, decimals: u32) -> f32 {
+    if !value.is_finite() {
+        return value;
+    }
+    // The `Display` of floats never uses the exponent form.
+    let text = value.abs().to_string();
+    let (int, frac) = text.split_once('.').unwrap_or((&text, ""));
+    let decimals = decimals as usize;
+    if frac.len() <= decimals {
+        return value;
+    }
+
+    let mut digits = int
+        .bytes()
+        .chain(frac.bytes().take(decimals))
+        .map(|digit| digit - b'0')
+        .collect::<Vec<_>>();
+    let next = frac.as_bytes()[decimals] - b'0';
+    let rest = frac.as_bytes()[decimals + 1..]
+        .iter()
+        .any(|&digit| digit != b'0');
+    let odd = digits.last().is_some_and(|digit| digit % 2 == 1);
+    if next > 5 || (next == 5 && (rest || odd)) {
+        let carry = digits.iter_mut().rev().all(|digit| {
+            *digit = (*digit + 1) % 10;
+            *digit == 0
+        });
+        if carry {
+            digits.insert(0, 1);
+        }
+    }
+
+    let split = digits.len() - decimals;
+    let mut rounded = digits[..split]
+        .iter()
+        .map(|digit| char::from(b'0' + digit))
+        .collect::<String>();
+    rounded.push('.');
+    rounded.extend(digits[split..].iter().map(|digit| char::from(b'0' + digit)));
+    let rounded = rounded
+        .parse::<f32>()
+        .expect("the rounded digits form a number");
+    // The small negative values are rounded to the positive zero.
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded.copysign(value)
+    }
+}
+
+/// Returns the value of the shortest decimal representation of the float, so the differences
+/// and the products of the written values carry no binary noise, e.g. `1.1 - 0.2` is `0.9`.
+pub(crate) fn decimal(value: f32) -> f64 {
+    if value.is_finite() {
+        value.to_string().parse().unwrap_or(value as f64)
+    } else {
+        value as f64
+    }
+}
+
+fn round_point(point: Point2D, decimals: u32) -> Point2D {
+    Point2D::new(
+        round_half_even(point.x, decimals),
+        round_half_even(point.y, decimals),
+    )
+}
+
+fn round_rect(rect: BoundingRect, decimals: u32) -> BoundingRect {
+    BoundingRect::from_points(
+        round_point(rect.top_left(), decimals),
+        round_point(rect.bottom_right(), decimals),
+    )
+}
+
+impl ObjectArea {
+    /// Rounds the coordinates of the area and of the shape to the given number of decimal
+    /// places.
+    pub fn round(&mut self, decimals: u32) {
+        self.area = round_rect(self.area, decimals);
+        self.shape = self.shape.as_ref().map(|shape| {
+            shape.map(
+                |point| round_point(point, decimals),
+                |length| round_half_even(length, decimals),
+            )
+        });
+    }
+}
+
+impl ObjectIntersection {
+    /// Rounds the coordinates of the intersection and its metrics to the given number of
+    /// decimal places.
+    pub fn round(&mut self, decimals: u32) {
+        self.area = round_rect(self.area, decimals);
+        if let Some(metrics) = self.metrics.as_mut() {
+            *metrics = IntersectionMetrics {
+                area: round_half_even(metrics.area, decimals),
+                iou: round_half_even(metrics.iou, decimals),
+                coverage: (
+                    round_half_even(metrics.coverage.0, decimals),
+                    round_half_even(metrics.coverage.1, decimals),
+                ),
+            };
+        }
+    }
+}
+
+impl Output {
+    /// Rounds the coordinates and the areas of the results to the given number of decimal
+    /// places, e.g. `2.9999998` to `3` for the clean diffs. The statistics are kept.
+    pub fn round(&mut self, decimals: u32) {
+        for area in &mut self.areas {
+            area.round(decimals);
+        }
+        for intersection in &mut self.intersections {
+            intersection.round(decimals);
+        }
+    }
+}
+
+impl OutputRecord {
+    /// Rounds the areas and the intersections like [`Output::round`].
+    pub fn round(&mut self, decimals: u32) {
+        match self {
+            Self::Area(area) => area.round(decimals),
+            Self::Intersection(intersection) => intersection.round(decimals),
+            _ => {}
+        }
+    }
+}
+
+impl Input {
+    /// Rounds the rects and the shapes of the objects to the given number of decimal places,
+    /// the rotations are kept.
+    pub fn round(&mut self, decimals: u32) {
+        for object in &mut self.objects {
+            object.x = round_half_even(object.x, decimals);
+            object.y = round_half_even(object.y, decimals);
+            object.width = round_half_even(object.width, decimals);
+            object.height = round_half_even(object.height, decimals);
+            object.shape = object.shape.as_ref().map(|shape| {
+                shape.map(
+                    |point| round_point(point, decimals),
+                    |length| round_half_even(length, decimals),
+                )
+            });
+        }
+    }
+}
+
+#[test]
+fn test_round_half_even() {
+    let cases = vec![
+        (2.9999998, 2, 3.0, "noise"),
+        (2.675, 2, 2.68, "tie up"),
+        (2.665, 2, 2.66, "tie down"),
+        (0.5, 0, 0.0, "tie to zero"),
+        (1.5, 0, 2.0, "tie to two"),
+        (9.96, 1, 10.0, "carry"),
+        (-1.25, 1, -1.2, "negative tie"),
+        (-0.001, 2, 0.0, "negative zero"),
+        (1.5, 3, 1.5, "shorter"),
+        (123456.78, 0, 123457.0, "integer"),
+        (f32::INFINITY, 2, f32::INFINITY, "infinity"),
+    ];
+    for case in cases {
+        let rounded = round_half_even(case.0, case.1);
+        assert_eq!(rounded, case.2, "Test case \"{}\" has been failed", case.3);
+        assert!(rounded.is_sign_positive() || case.2 < 0.0);
+    }
+    assert_eq!(decimal(1.1) - decimal(0.2), 0.9000000000000001);
+}
+
+#[test]
+fn test_output_round() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.1, 0.2, 2.9999998, 1.0)
+        .object("B", 1.0049, 0.0, 1.0, 1.0)
+        .build();
+    let mut output = crate::analyze(
+        input,
+        crate::Options {
+            metrics: true,
+            ..crate::Options::default()
+        },
+    );
+    output.round(2);
+
+    let from = output.areas[0].area.top_left();
+    let to = output.areas[0].area.bottom_right();
+    assert_eq!((from.x, from.y, to.x, to.y), (0.1, 0.2, 3.1, 1.2));
+    let intersection = &output.intersections[0];
+    assert_eq!(intersection.area.top_left(), Point2D::new(1.0, 0.2));
+    let metrics = intersection.metrics.unwrap();
+    assert_eq!((metrics.area, metrics.iou), (0.8, 0.25));
+
+    // The computed sizes are written without the noise of the subtraction.
+    let json = String::from_utf8(output.to_vec_as(crate::OutputFormat::Ndjson).unwrap()).unwrap();
+    assert!(
+        json.contains(
+            r#""from":{"x":0.1,"y":0.2},"to":{"x":3.1,"y":1.2},"width":3.0,"height":1.0,"area":3.0"#
+        ),
+        "{}",
+        json
+    );
+}
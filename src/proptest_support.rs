@@ -0,0 +1,79 @@
+//! Proptest strategies for the geometry types and whole scenes.
+
+use proptest::{collection::vec, prelude::*};
+
+use crate::{BoundingRect, Input, Object, Point2D};
+
+/// Generates points inside the specified bounds.
+pub fn any_point_in(bounds: BoundingRect) -> impl Strategy<Value = Point2D> {
+    (bounds.from.x..=bounds.to.x, bounds.from.y..=bounds.to.y).prop_map(|(x, y)| Point2D { x, y })
+}
+
+/// Generates rectangles inside the specified bounds, including degenerate ones.
+pub fn any_rect_in(bounds: BoundingRect) -> impl Strategy<Value = BoundingRect> {
+    (any_point_in(bounds), any_point_in(bounds)).prop_map(|(a, b)| BoundingRect::from_points(a, b))
+}
+
+/// Generates rectangles with positive width and height.
+pub fn non_degenerate_rect() -> impl Strategy<Value = BoundingRect> {
+    (-1e4_f32..1e4, -1e4_f32..1e4, 1e-2_f32..1e3, 1e-2_f32..1e3).prop_map(
+        |(x, y, width, height)| {
+            BoundingRect::from_points(
+                Point2D { x, y },
+                Point2D {
+                    x: x + width,
+                    y: y + height,
+                },
+            )
+        },
+    )
+}
+
+/// Generates scenes with up to `max_objects` uniquely named objects inside the specified bounds.
+pub fn any_scene_in(bounds: BoundingRect, max_objects: usize) -> impl Strategy<Value = Input> {
+    vec(any_rect_in(bounds), 0..=max_objects).prop_map(|rects| Input {
+        objects: rects
+            .into_iter()
+            .enumerate()
+            .map(|(idx, rect)| Object {
+                name: format!("object_{}", idx),
+                width: rect.to.x - rect.from.x,
+                height: rect.to.y - rect.from.y,
+                x: rect.from.x,
+                y: rect.from.y,
+                layer: None,
+                z: None,
+                rotation: None,
+                shape: None,
+                properties: Vec::new(),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+proptest! {
+    #[test]
+    fn test_intersection_is_symmetric(a in non_degenerate_rect(), b in non_degenerate_rect()) {
+        prop_assert_eq!(a.intersect(&b), b.intersect(&a));
+    }
+
+    #[test]
+    fn test_intersection_is_contained(
+        a in any_rect_in(BoundingRect::from_points(Point2D::new(-10.0, -10.0), Point2D::new(10.0, 10.0))),
+        b in non_degenerate_rect(),
+    ) {
+        if let Some(area) = a.intersect(&b) {
+            prop_assert_eq!(area.union(&a), a);
+            prop_assert_eq!(area.union(&b), b);
+        }
+    }
+
+    #[test]
+    fn test_scene_analysis_keeps_objects(
+        input in any_scene_in(BoundingRect::from_points(Point2D::new(0.0, 0.0), Point2D::new(100.0, 100.0)), 16),
+    ) {
+        let output = crate::analyze(input.clone(), crate::Options::default());
+        prop_assert_eq!(output.areas.len(), input.objects.len());
+    }
+}
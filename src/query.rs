@@ -0,0 +1,149 @@
+use std::iter::FromIterator;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    io, BoundingRect, Error, ObjectArea, ObjectIntersection, Output, OutputFormat, Point2D,
+};
+
+/// Objects found by a spatial query.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct QueryResult {
+    pub objects: Vec<ObjectArea>,
+}
+
+/// Flat CSV row of an object area.
+#[derive(Serialize)]
+pub(crate) struct ObjectRecord<'a> {
+    name: &'a str,
+    from_x: f32,
+    from_y: f32,
+    to_x: f32,
+    to_y: f32,
+}
+
+impl QueryResult {
+    /// Serializes the found objects in the given format.
+    pub fn to_vec_as(&self, format: OutputFormat) -> Result<Vec<u8>, Error> {
+        if format != OutputFormat::Csv {
+            return io::encode(self, format);
+        }
+
+        io::encode_csv(self.objects.iter().map(ObjectRecord::from))
+    }
+}
+
+impl<'a> From<&'a ObjectArea> for ObjectRecord<'a> {
+    fn from(object: &'a ObjectArea) -> Self {
+        let (from, to) = (object.area.top_left(), object.area.bottom_right());
+        Self {
+            name: &object.name,
+            from_x: from.x,
+            from_y: from.y,
+            to_x: to.x,
+            to_y: to.y,
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a ObjectArea> for QueryResult {
+    fn from_iter<I: IntoIterator<Item = &'a ObjectArea>>(objects: I) -> Self {
+        Self {
+            objects: objects.into_iter().cloned().collect(),
+        }
+    }
+}
+
+/// Spatial queries over the analyzed scene.
+impl Output {
+    /// Returns the area of the object with the given name.
+    pub fn object(&self, name: &str) -> Option<&ObjectArea> {
+        self.areas.iter().find(|object| object.name == name)
+    }
+
+    /// Returns the intersections involving the object with the given name.
+    pub fn intersections_of<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a ObjectIntersection> + 'a {
+        self.intersections.iter().filter(move |intersection| {
+            intersection.names.0 == name || intersection.names.1 == name
+        })
+    }
+
+    /// Returns the objects that contain the point, including the ones touching it by the border.
+    pub fn objects_at(&self, point: Point2D) -> impl Iterator<Item = &ObjectArea> + '_ {
+        self.areas
+            .iter()
+            .filter(move |object| object.area.contains(point))
+    }
+
+    /// Returns the objects that overlap the rectangle.
+    pub fn objects_in(&self, rect: BoundingRect) -> impl Iterator<Item = &ObjectArea> + '_ {
+        self.areas
+            .iter()
+            .filter(move |object| object.area.intersect(&rect).is_some())
+    }
+
+    /// Returns the object closest to the point and the distance to it, the first one
+    /// of several equally close objects.
+    pub fn nearest(&self, point: Point2D) -> Option<(&ObjectArea, f32)> {
+        self.areas
+            .iter()
+            .map(|object| (object, object.area.distance_to(point)))
+            .fold(None, |nearest, candidate| match nearest {
+                Some((_, distance)) if distance <= candidate.1 => nearest,
+                _ => Some(candidate),
+            })
+    }
+}
+
+#[test]
+fn test_output_queries() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 2.0, 2.0, 4.0, 4.0)
+        .object("C", 10.0, 0.0, 2.0, 2.0)
+        .build();
+    let output = crate::analyze(input, crate::Options::default());
+    fn names<'a>(objects: impl Iterator<Item = &'a ObjectArea>) -> Vec<&'a str> {
+        objects.map(|object| object.name.as_str()).collect()
+    }
+
+    assert_eq!(
+        output.object("C").unwrap().area.top_left(),
+        Point2D::new(10.0, 0.0)
+    );
+    assert!(output.object("D").is_none());
+    assert_eq!(output.intersections_of("B").count(), 1);
+    assert_eq!(output.intersections_of("C").count(), 0);
+
+    let cases = vec![
+        (Point2D::new(3.0, 3.0), vec!["A", "B"], "overlap"),
+        (Point2D::new(1.0, 1.0), vec!["A"], "single object"),
+        (Point2D::new(12.0, 2.0), vec!["C"], "corner"),
+        (Point2D::new(8.0, 1.0), vec![], "empty space"),
+    ];
+    for case in cases {
+        assert_eq!(
+            names(output.objects_at(case.0)),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+
+    let rect = BoundingRect::from_points(Point2D::new(5.0, 0.0), Point2D::new(11.0, 3.0));
+    assert_eq!(names(output.objects_in(rect)), vec!["B", "C"]);
+
+    let (nearest, distance) = output.nearest(Point2D::new(8.0, 1.0)).unwrap();
+    assert_eq!((nearest.name.as_str(), distance), ("C", 2.0));
+
+    let result = output.objects_in(rect).collect::<QueryResult>();
+    let csv = String::from_utf8(result.to_vec_as(OutputFormat::Csv).unwrap()).unwrap();
+    assert_eq!(
+        csv,
+        "name,from_x,from_y,to_x,to_y\nB,2.0,2.0,6.0,6.0\nC,10.0,0.0,12.0,2.0\n"
+    );
+}
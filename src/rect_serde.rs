@@ -0,0 +1,319 @@
+//! Alternative serde representations of [`BoundingRect`].
+//!
+//! Each submodule can be used with the `#[serde(with = "...")]` attribute:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use sophya_prog_test::{rect_serde, BoundingRect};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Zone {
+//!     #[serde(with = "rect_serde::origin_size")]
+//!     area: BoundingRect,
+//! }
+//!
+//! let zone: Zone = serde_json::from_str(r#"{ "area": { "x": 1, "y": 2, "width": 3, "height": 4 } }"#)
+//!     .unwrap();
+//! assert_eq!(
+//!     serde_json::to_string(&zone).unwrap(),
+//!     r#"{"area":{"x":1.0,"y":2.0,"width":3.0,"height":4.0}}"#
+//! );
+//! ```
+
+use std::convert::TryFrom;
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{BoundingRect, GeometryError, Point2D};
+
+#[derive(Serialize, Deserialize)]
+struct OriginSize {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+// Checks rectangle invariants for the corners given in any representation.
+fn rect_from_corners(x1: f32, y1: f32, x2: f32, y2: f32) -> Result<BoundingRect, GeometryError> {
+    if ![x1, y1, x2, y2].iter().all(|x| x.is_finite()) {
+        return Err(GeometryError::NonFiniteCoordinate);
+    }
+    if x1 > x2 || y1 > y2 {
+        return Err(GeometryError::InvalidCorners);
+    }
+
+    Ok(BoundingRect {
+        from: Point2D { x: x1, y: y1 },
+        to: Point2D { x: x2, y: y2 },
+    })
+}
+
+impl From<&BoundingRect> for OriginSize {
+    fn from(rect: &BoundingRect) -> Self {
+        Self {
+            x: rect.from.x,
+            y: rect.from.y,
+            width: rect.to.x - rect.from.x,
+            height: rect.to.y - rect.from.y,
+        }
+    }
+}
+
+impl TryFrom<OriginSize> for BoundingRect {
+    type Error = GeometryError;
+
+    fn try_from(raw: OriginSize) -> Result<Self, Self::Error> {
+        rect_from_corners(raw.x, raw.y, raw.x + raw.width, raw.y + raw.height)
+    }
+}
+
+/// Corners form `{ "from": { "x": x1, "y": y1 }, "to": { "x": x2, "y": y2 } }`,
+/// the same as the default one.
+pub mod corners {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(rect: &BoundingRect, serializer: S) -> Result<S::Ok, S::Error> {
+        rect.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BoundingRect, D::Error> {
+        BoundingRect::deserialize(deserializer)
+    }
+}
+
+/// Origin and size form `{ "x": x, "y": y, "width": width, "height": height }`.
+pub mod origin_size {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(rect: &BoundingRect, serializer: S) -> Result<S::Ok, S::Error> {
+        OriginSize::from(rect).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BoundingRect, D::Error> {
+        let raw = OriginSize::deserialize(deserializer)?;
+        BoundingRect::try_from(raw).map_err(D::Error::custom)
+    }
+}
+
+/// Array form `[x1, y1, x2, y2]`.
+pub mod array {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(rect: &BoundingRect, serializer: S) -> Result<S::Ok, S::Error> {
+        [rect.from.x, rect.from.y, rect.to.x, rect.to.y].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BoundingRect, D::Error> {
+        let [x1, y1, x2, y2] = <[f32; 4]>::deserialize(deserializer)?;
+        rect_from_corners(x1, y1, x2, y2).map_err(D::Error::custom)
+    }
+}
+
+/// Corners form with the computed size and area
+/// `{ "from": ..., "to": ..., "width": width, "height": height, "area": area }`, the computed
+/// fields are ignored by the deserialization.
+pub mod measured {
+    use super::*;
+    use crate::precision::decimal;
+
+    #[derive(Serialize, Deserialize)]
+    #[cfg_attr(
+        feature = "schemars",
+        derive(schemars::JsonSchema),
+        schemars(rename = "MeasuredRect")
+    )]
+    pub(crate) struct Repr {
+        from: Point2D,
+        to: Point2D,
+        #[serde(default)]
+        width: f32,
+        #[serde(default)]
+        height: f32,
+        #[serde(default)]
+        area: f32,
+    }
+
+    pub fn serialize<S: Serializer>(rect: &BoundingRect, serializer: S) -> Result<S::Ok, S::Error> {
+        // Computed from the written corners, so the rounded ones give the rounded sizes.
+        let width = decimal(rect.to.x) - decimal(rect.from.x);
+        let height = decimal(rect.to.y) - decimal(rect.from.y);
+        Repr {
+            from: rect.from,
+            to: rect.to,
+            width: width as f32,
+            height: height as f32,
+            area: (width * height) as f32,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BoundingRect, D::Error> {
+        let raw = Repr::deserialize(deserializer)?;
+        rect_from_corners(raw.from.x, raw.from.y, raw.to.x, raw.to.y).map_err(D::Error::custom)
+    }
+}
+
+/// Accepts any of the supported forms and serializes rectangles in the corners form.
+pub mod any {
+    use std::fmt;
+
+    use serde::de::{value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor};
+
+    use super::*;
+
+    // Fields of the both map forms.
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct MapRepr {
+        from: Option<Point2D>,
+        to: Option<Point2D>,
+        x: Option<f32>,
+        y: Option<f32>,
+        width: Option<f32>,
+        height: Option<f32>,
+    }
+
+    struct AnyVisitor;
+
+    impl<'de> Visitor<'de> for AnyVisitor {
+        type Value = BoundingRect;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("rectangle in the corners, origin and size or array form")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            array::deserialize(serde::de::value::SeqAccessDeserializer::new(seq))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            let raw = MapRepr::deserialize(MapAccessDeserializer::new(map))?;
+            let rect = match raw {
+                MapRepr {
+                    from: Some(from),
+                    to: Some(to),
+                    x: None,
+                    y: None,
+                    width: None,
+                    height: None,
+                } => rect_from_corners(from.x, from.y, to.x, to.y),
+                MapRepr {
+                    from: None,
+                    to: None,
+                    x: Some(x),
+                    y: Some(y),
+                    width: Some(width),
+                    height: Some(height),
+                } => BoundingRect::try_from(OriginSize {
+                    x,
+                    y,
+                    width,
+                    height,
+                }),
+                _ => {
+                    return Err(A::Error::custom(
+                        "unsupported combination of rectangle fields",
+                    ))
+                }
+            };
+            rect.map_err(A::Error::custom)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(rect: &BoundingRect, serializer: S) -> Result<S::Ok, S::Error> {
+        corners::serialize(rect, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BoundingRect, D::Error> {
+        deserializer.deserialize_any(AnyVisitor)
+    }
+}
+
+#[test]
+fn test_rect_representations() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Zones {
+        #[serde(with = "corners")]
+        corners: BoundingRect,
+        #[serde(with = "origin_size")]
+        origin_size: BoundingRect,
+        #[serde(with = "array")]
+        array: BoundingRect,
+        #[serde(with = "measured")]
+        measured: BoundingRect,
+    }
+
+    let rect = BoundingRect::from_points(Point2D::new(1.0, 2.0), Point2D::new(4.0, 6.0));
+    let zones = Zones {
+        corners: rect,
+        origin_size: rect,
+        array: rect,
+        measured: rect,
+    };
+
+    let json = serde_json::to_value(&zones).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "corners": { "from": { "x": 1.0, "y": 2.0 }, "to": { "x": 4.0, "y": 6.0 } },
+            "origin_size": { "x": 1.0, "y": 2.0, "width": 3.0, "height": 4.0 },
+            "array": [1.0, 2.0, 4.0, 6.0],
+            "measured": {
+                "from": { "x": 1.0, "y": 2.0 },
+                "to": { "x": 4.0, "y": 6.0 },
+                "width": 3.0,
+                "height": 4.0,
+                "area": 12.0,
+            },
+        })
+    );
+    assert_eq!(serde_json::from_value::<Zones>(json).unwrap(), zones);
+
+    #[derive(Debug, Deserialize)]
+    struct AnyZone(#[serde(with = "any")] BoundingRect);
+
+    let cases = vec![
+        (
+            r#"{ "from": { "x": 1, "y": 2 }, "to": { "x": 4, "y": 6 } }"#,
+            Some(rect),
+            "corners",
+        ),
+        (
+            r#"{ "x": 1, "y": 2, "width": 3, "height": 4 }"#,
+            Some(rect),
+            "origin and size",
+        ),
+        ("[1, 2, 4, 6]", Some(rect), "array"),
+        ("[4, 2, 1, 6]", None, "array with swapped corners"),
+        (
+            r#"{ "x": 1, "y": 2, "width": -3, "height": 4 }"#,
+            None,
+            "negative width",
+        ),
+        ("[1, 2, 4]", None, "short array"),
+        (
+            r#"{ "x": 1, "y": 2, "to": { "x": 4, "y": 6 } }"#,
+            None,
+            "mixed forms",
+        ),
+    ];
+    for case in cases {
+        assert_eq!(
+            serde_json::from_str::<AnyZone>(case.0).ok().map(|x| x.0),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+}
@@ -1,15 +1,29 @@
-use std::mem;
+use std::{convert::TryFrom, mem};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+use crate::GeometryError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(try_from = "RawPoint2D")]
 pub struct Point2D {
     pub x: f32,
     pub y: f32,
 }
 
+/// Two-dimensional displacement vector.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Vector2D {
+    pub x: f32,
+    pub y: f32,
+}
+
 /// Bounding rectangle.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(try_from = "RawBoundingRect")]
 pub struct BoundingRect {
     /// Top left corner of the rectangle.
     pub(crate) from: Point2D,
@@ -17,9 +31,82 @@ pub struct BoundingRect {
     pub(crate) to: Point2D,
 }
 
+// Unchecked representations of the geometry types used for deserialization.
+
+#[derive(Deserialize)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "Point2D")
+)]
+struct RawPoint2D {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+#[cfg_attr(
+    feature = "schemars",
+    derive(schemars::JsonSchema),
+    schemars(rename = "BoundingRect")
+)]
+struct RawBoundingRect {
+    from: Point2D,
+    to: Point2D,
+}
+
+impl TryFrom<RawPoint2D> for Point2D {
+    type Error = GeometryError;
+
+    fn try_from(raw: RawPoint2D) -> Result<Self, Self::Error> {
+        if raw.x.is_finite() && raw.y.is_finite() {
+            Ok(Self { x: raw.x, y: raw.y })
+        } else {
+            Err(GeometryError::NonFiniteCoordinate)
+        }
+    }
+}
+
+impl TryFrom<RawBoundingRect> for BoundingRect {
+    type Error = GeometryError;
+
+    fn try_from(raw: RawBoundingRect) -> Result<Self, Self::Error> {
+        if raw.from.x <= raw.to.x && raw.from.y <= raw.to.y {
+            Ok(Self {
+                from: raw.from,
+                to: raw.to,
+            })
+        } else {
+            Err(GeometryError::InvalidCorners)
+        }
+    }
+}
+
+impl Point2D {
+    /// Creates a point with the specified coordinates.
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl Vector2D {
+    /// Creates a vector with the specified components.
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
 impl BoundingRect {
+    /// Empty rectangle, that is the identity element of the [`union`](Self::union) operation.
+    ///
+    /// Its corners are infinite, so it cannot be deserialized back.
+    pub const EMPTY: BoundingRect = BoundingRect {
+        from: Point2D::new(f32::INFINITY, f32::INFINITY),
+        to: Point2D::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+    };
+
     /// Creates rectangle from the specified points. Points can be specified in any order.
-    pub fn from_points(a: Point2D, b: Point2D) -> BoundingRect {
+    pub const fn from_points(a: Point2D, b: Point2D) -> BoundingRect {
         let (ax, bx) = if a.x < b.x { (a.x, b.x) } else { (b.x, a.x) };
         let (ay, by) = if a.y < b.y { (a.y, b.y) } else { (b.y, a.y) };
 
@@ -30,8 +117,98 @@ impl BoundingRect {
     }
 }
 
-// Calculates the intersection of the lines.
-fn lines_intersection(mut a: (f32, f32), mut b: (f32, f32)) -> Option<(f32, f32)> {
+impl BoundingRect {
+    /// Returns the top left corner of the rectangle.
+    pub const fn top_left(&self) -> Point2D {
+        self.from
+    }
+
+    /// Returns the bottom right corner of the rectangle.
+    pub const fn bottom_right(&self) -> Point2D {
+        self.to
+    }
+
+    /// Returns true if the rectangle has no area, like [`BoundingRect::EMPTY`]
+    /// or degenerate rectangles.
+    pub const fn is_empty(&self) -> bool {
+        !(self.from.x < self.to.x && self.from.y < self.to.y)
+    }
+
+    /// Returns the area of the rectangle, empty rectangles have no area.
+    pub fn area(&self) -> f32 {
+        if self.is_empty() {
+            0.0
+        } else {
+            (self.to.x - self.from.x) * (self.to.y - self.from.y)
+        }
+    }
+
+    /// Returns true if the point lies inside the rectangle or on its border.
+    pub fn contains(&self, point: Point2D) -> bool {
+        self.from.x <= point.x
+            && point.x <= self.to.x
+            && self.from.y <= point.y
+            && point.y <= self.to.y
+    }
+
+    /// Returns the distance from the point to the closest point of the rectangle,
+    /// zero if the rectangle contains the point.
+    pub fn distance_to(&self, point: Point2D) -> f32 {
+        let dx = (self.from.x - point.x).max(point.x - self.to.x).max(0.0);
+        let dy = (self.from.y - point.y).max(point.y - self.to.y).max(0.0);
+        dx.hypot(dy)
+    }
+
+    /// Returns this rectangle moved by the specified vector.
+    pub const fn translate(&self, v: Vector2D) -> BoundingRect {
+        Self {
+            from: Point2D {
+                x: self.from.x + v.x,
+                y: self.from.y + v.y,
+            },
+            to: Point2D {
+                x: self.to.x + v.x,
+                y: self.to.y + v.y,
+            },
+        }
+    }
+
+    // Returns true if the corners are inverted like the ones of [`BoundingRect::EMPTY`], the
+    // degenerate rectangles are not inverted.
+    const fn is_inverted(&self) -> bool {
+        !(self.from.x <= self.to.x && self.from.y <= self.to.y)
+    }
+
+    /// Returns the smallest rectangle that contains both rectangles.
+    ///
+    /// [`BoundingRect::EMPTY`] and the inverted rectangles do not affect the result, the
+    /// degenerate ones, e.g. points and lines, are contained like the others.
+    pub fn union(&self, other: &BoundingRect) -> BoundingRect {
+        if self.is_inverted() {
+            return *other;
+        }
+        if other.is_inverted() {
+            return *self;
+        }
+
+        Self {
+            from: Point2D {
+                x: self.from.x.min(other.from.x),
+                y: self.from.y.min(other.from.y),
+            },
+            to: Point2D {
+                x: self.to.x.max(other.to.x),
+                y: self.to.y.max(other.to.y),
+            },
+        }
+    }
+}
+
+// Calculates the intersection of the lines, the ends are excluded.
+pub(crate) fn lines_intersection<T: PartialOrd + Copy>(
+    mut a: (T, T),
+    mut b: (T, T),
+) -> Option<(T, T)> {
     if b.0 < a.0 {
         mem::swap(&mut a, &mut b);
     }
@@ -53,6 +230,40 @@ impl BoundingRect {
             Point2D { x: x2, y: y2 },
         ))
     }
+
+    /// Calculates the minimum translation vector, that is the smallest displacement
+    /// of this rectangle that separates it from another one.
+    ///
+    /// Returns `None` if the rectangles do not intersect.
+    pub fn mtv(&self, other: &BoundingRect) -> Option<Vector2D> {
+        self.intersect(other)?;
+        Some(overlap_mtv(
+            [(self.from.x, self.to.x), (self.from.y, self.to.y)],
+            [(other.from.x, other.to.x), (other.from.y, other.to.y)],
+        ))
+    }
+}
+
+// Calculates the minimum translation vector of the intersecting rects given by their x and y
+// edges.
+pub(crate) fn overlap_mtv(a: [(f32, f32); 2], b: [(f32, f32); 2]) -> Vector2D {
+    // Picks the shortest displacement along the axis: either to the left or to the right.
+    let axis_shift = |(a_from, a_to): (f32, f32), (b_from, b_to): (f32, f32)| {
+        let (left, right) = (b_from - a_to, b_to - a_from);
+        if -left < right {
+            left
+        } else {
+            right
+        }
+    };
+
+    let dx = axis_shift(a[0], b[0]);
+    let dy = axis_shift(a[1], b[1]);
+    if dx.abs() < dy.abs() {
+        Vector2D { x: dx, y: 0.0 }
+    } else {
+        Vector2D { x: 0.0, y: dy }
+    }
 }
 
 #[test]
@@ -197,3 +408,153 @@ fn test_rects_intersection() {
         );
     }
 }
+
+#[test]
+fn test_rects_mtv() {
+    let rect = BoundingRect {
+        from: Point2D { x: 0.0, y: 0.0 },
+        to: Point2D { x: 4.0, y: 4.0 },
+    };
+
+    let cases = vec![
+        (
+            BoundingRect {
+                from: Point2D { x: 3.0, y: 1.0 },
+                to: Point2D { x: 6.0, y: 3.0 },
+            },
+            Some(Vector2D { x: -1.0, y: 0.0 }),
+            "overlap on the right",
+        ),
+        (
+            BoundingRect {
+                from: Point2D { x: 1.0, y: -2.0 },
+                to: Point2D { x: 3.0, y: 2.0 },
+            },
+            Some(Vector2D { x: 0.0, y: 2.0 }),
+            "overlap above",
+        ),
+        (
+            BoundingRect {
+                from: Point2D { x: 5.0, y: 5.0 },
+                to: Point2D { x: 6.0, y: 6.0 },
+            },
+            None,
+            "no intersection",
+        ),
+    ];
+
+    for case in cases {
+        let mtv = rect.mtv(&case.0);
+        assert_eq!(mtv, case.1, "Test case \"{}\" has been failed", case.2);
+        assert_eq!(
+            case.0.mtv(&rect),
+            mtv.map(|v| Vector2D { x: -v.x, y: -v.y }),
+            "Test case \"{}\" (inverted) has been failed",
+            case.2
+        );
+
+        if let Some(v) = mtv {
+            assert_eq!(rect.translate(v).intersect(&case.0), None);
+        }
+    }
+}
+
+#[test]
+fn test_rects_deserialize() {
+    let cases = vec![
+        (
+            r#"{ "from": { "x": 1.0, "y": 2.0 }, "to": { "x": 3.0, "y": 4.0 } }"#,
+            Some(BoundingRect {
+                from: Point2D { x: 1.0, y: 2.0 },
+                to: Point2D { x: 3.0, y: 4.0 },
+            }),
+            "valid rect",
+        ),
+        (
+            r#"{ "from": { "x": 1.0, "y": 2.0 }, "to": { "x": 1.0, "y": 2.0 } }"#,
+            Some(BoundingRect {
+                from: Point2D { x: 1.0, y: 2.0 },
+                to: Point2D { x: 1.0, y: 2.0 },
+            }),
+            "degenerate rect",
+        ),
+        (
+            r#"{ "from": { "x": 3.0, "y": 2.0 }, "to": { "x": 1.0, "y": 4.0 } }"#,
+            None,
+            "swapped corners",
+        ),
+        (
+            r#"{ "from": { "x": 1.0, "y": 2.0 }, "to": { "x": 1e39, "y": 4.0 } }"#,
+            None,
+            "infinite coordinate",
+        ),
+        (
+            r#"{ "from": { "x": 1.0, "y": 2.0 } }"#,
+            None,
+            "missing corner",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            serde_json::from_str::<BoundingRect>(case.0).ok(),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+}
+
+#[test]
+fn test_empty_rect() {
+    const RECT: BoundingRect =
+        BoundingRect::from_points(Point2D::new(3.0, 4.0), Point2D::new(1.0, 2.0));
+    let degenerate = BoundingRect::from_points(Point2D::new(9.0, 9.0), Point2D::new(9.0, 12.0));
+
+    assert!(BoundingRect::EMPTY.is_empty());
+    assert!(degenerate.is_empty());
+    assert!(!RECT.is_empty());
+
+    assert_eq!(BoundingRect::EMPTY.union(&RECT), RECT);
+    assert_eq!(RECT.union(&BoundingRect::EMPTY), RECT);
+    // The degenerate rects extend the union.
+    assert_eq!(
+        RECT.union(&degenerate),
+        BoundingRect::from_points(Point2D::new(1.0, 2.0), Point2D::new(9.0, 12.0))
+    );
+    assert_eq!(BoundingRect::EMPTY.union(&degenerate), degenerate);
+    let point = BoundingRect::from_points(Point2D::new(0.0, 0.0), Point2D::new(0.0, 0.0));
+    assert_eq!(
+        point.union(&RECT),
+        BoundingRect::from_points(Point2D::new(0.0, 0.0), Point2D::new(3.0, 4.0))
+    );
+    assert_eq!(
+        BoundingRect::EMPTY.union(&BoundingRect::EMPTY),
+        BoundingRect::EMPTY
+    );
+
+    assert_eq!(BoundingRect::EMPTY.intersect(&RECT), None);
+    assert_eq!(RECT.intersect(&BoundingRect::EMPTY), None);
+    assert_eq!(BoundingRect::EMPTY.intersect(&BoundingRect::EMPTY), None);
+}
+
+#[test]
+fn test_rect_distance_to() {
+    let rect = BoundingRect::from_points(Point2D::new(1.0, 1.0), Point2D::new(5.0, 3.0));
+    let cases = vec![
+        (Point2D::new(2.0, 2.0), 0.0, true, "inside"),
+        (Point2D::new(5.0, 2.0), 0.0, true, "on the border"),
+        (Point2D::new(7.0, 2.0), 2.0, false, "to the right"),
+        (Point2D::new(2.0, 0.0), 1.0, false, "above"),
+        (Point2D::new(8.0, 7.0), 5.0, false, "diagonally"),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            (rect.distance_to(case.0), rect.contains(case.0)),
+            (case.1, case.2),
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+    }
+}
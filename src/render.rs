@@ -0,0 +1,474 @@
+use std::fmt::Write;
+
+#[cfg(feature = "png")]
+use crate::Error;
+use crate::{AnyShape, BoundingRect, Output, Point2D};
+
+const OBJECT_STROKE: &str = "#1f77b4";
+const OBJECT_FILL: &str = "#aec7e8";
+const INTERSECTION_FILL: &str = "#d62728";
+const GRID_STROKE: &str = "#dddddd";
+
+/// Escapes the text to be used inside the XML.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Returns the area of the scene to draw: the bounds of all objects with a small margin.
+pub(crate) fn scene_viewport(output: &Output) -> BoundingRect {
+    let bounds = output
+        .areas
+        .iter()
+        .fold(BoundingRect::EMPTY, |bounds, object| {
+            bounds.union(&object.area)
+        });
+    if bounds.is_empty() {
+        return BoundingRect::from_points(Point2D::new(0.0, 0.0), Point2D::new(1.0, 1.0));
+    }
+
+    let (from, to) = (bounds.top_left(), bounds.bottom_right());
+    let margin = (to.x - from.x).max(to.y - from.y) * 0.05;
+    BoundingRect::from_points(
+        Point2D::new(from.x - margin, from.y - margin),
+        Point2D::new(to.x + margin, to.y + margin),
+    )
+}
+
+fn write_shape(svg: &mut String, shape: &AnyShape, style: &str) {
+    match shape {
+        AnyShape::Rect(rect) => write_rect(svg, rect, style),
+        AnyShape::Circle { center, radius } => writeln!(
+            svg,
+            r#"  <circle cx="{}" cy="{}" r="{}" {}/>"#,
+            center.x, center.y, radius, style
+        )
+        .unwrap(),
+        AnyShape::Polygon(polygon) => {
+            let points = polygon
+                .vertices
+                .iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect::<Vec<_>>();
+            writeln!(
+                svg,
+                r#"  <polygon points="{}" {}/>"#,
+                points.join(" "),
+                style
+            )
+            .unwrap()
+        }
+        AnyShape::Segment { from, to } => writeln!(
+            svg,
+            r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+            from.x, from.y, to.x, to.y, style
+        )
+        .unwrap(),
+    }
+}
+
+fn write_rect(svg: &mut String, rect: &BoundingRect, style: &str) {
+    let (from, to) = (rect.top_left(), rect.bottom_right());
+    writeln!(
+        svg,
+        r#"  <rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+        from.x,
+        from.y,
+        to.x - from.x,
+        to.y - from.y,
+        style
+    )
+    .unwrap();
+}
+
+/// Rendering settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// Area of the scene to draw, the bounds of all objects by default.
+    pub viewport: Option<BoundingRect>,
+    /// Width of the image in pixels, the height follows the aspect ratio of the viewport.
+    pub width: u32,
+    /// Distance between the grid lines in the scene units, no grid by default.
+    pub grid: Option<f32>,
+    /// Draw the legend of the used colors.
+    pub legend: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            viewport: None,
+            width: 1024,
+            grid: None,
+            legend: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Returns the viewport and the image size in pixels.
+    fn frame(&self, output: &Output) -> (BoundingRect, u32, u32) {
+        let viewport = self.viewport.unwrap_or_else(|| scene_viewport(output));
+        let (from, to) = (viewport.top_left(), viewport.bottom_right());
+        let height = (self.width as f32 * (to.y - from.y) / (to.x - from.x)).round() as u32;
+        (viewport, self.width, height.max(1))
+    }
+}
+
+fn write_grid(svg: &mut String, viewport: &BoundingRect, step: f32, stroke: f32) {
+    let (from, to) = (viewport.top_left(), viewport.bottom_right());
+    let style = format!(r#"stroke="{}" stroke-width="{}""#, GRID_STROKE, stroke);
+
+    let mut x = (from.x / step).ceil() * step;
+    while x <= to.x {
+        writeln!(
+            svg,
+            r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+            x, from.y, x, to.y, style
+        )
+        .unwrap();
+        x += step;
+    }
+    let mut y = (from.y / step).ceil() * step;
+    while y <= to.y {
+        writeln!(
+            svg,
+            r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+            from.x, y, to.x, y, style
+        )
+        .unwrap();
+        y += step;
+    }
+}
+
+fn write_legend(svg: &mut String, viewport: &BoundingRect, font_size: f32) {
+    let origin = viewport.top_left();
+    let (x, y) = (origin.x + font_size, origin.y + font_size);
+    write_rect(
+        svg,
+        &BoundingRect::from_points(
+            Point2D::new(x - font_size / 2.0, y - font_size / 2.0),
+            Point2D::new(x + font_size * 8.0, y + font_size * 3.0),
+        ),
+        r#"fill="white" fill-opacity="0.8" stroke="gray""#,
+    );
+    for (idx, (fill, label)) in [(OBJECT_FILL, "object"), (INTERSECTION_FILL, "intersection")]
+        .iter()
+        .enumerate()
+    {
+        let y = y + idx as f32 * font_size * 1.5;
+        write_rect(
+            svg,
+            &BoundingRect::from_points(
+                Point2D::new(x, y),
+                Point2D::new(x + font_size, y + font_size),
+            ),
+            &format!(r#"fill="{}""#, fill),
+        );
+        writeln!(
+            svg,
+            r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="{}">{}</text>"#,
+            x + font_size * 1.5,
+            y + font_size * 0.85,
+            font_size,
+            label
+        )
+        .unwrap();
+    }
+}
+
+/// Draws the object rectangles labeled by their names and highlights the intersection areas.
+pub fn render_svg(output: &Output, options: &RenderOptions) -> String {
+    let (viewport, pixel_width, pixel_height) = options.frame(output);
+    let (from, to) = (viewport.top_left(), viewport.bottom_right());
+    let (width, height) = (to.x - from.x, to.y - from.y);
+    let stroke = width.max(height) / 500.0;
+    let font_size = width.max(height) / 50.0;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        pixel_width, pixel_height, from.x, from.y, width, height
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+        from.x, from.y, width, height
+    )
+    .unwrap();
+    if let Some(step) = options.grid.filter(|step| *step > 0.0) {
+        write_grid(&mut svg, &viewport, step, stroke / 2.0);
+    }
+
+    let object_style = format!(
+        r#"fill="{}" fill-opacity="0.4" stroke="{}" stroke-width="{}""#,
+        OBJECT_FILL, OBJECT_STROKE, stroke
+    );
+    for object in &output.areas {
+        match &object.shape {
+            Some(shape) => write_shape(&mut svg, shape, &object_style),
+            None => write_rect(&mut svg, &object.area, &object_style),
+        }
+    }
+
+    let intersection_style = format!(r#"fill="{}" fill-opacity="0.7""#, INTERSECTION_FILL);
+    for intersection in &output.intersections {
+        write_rect(&mut svg, &intersection.area, &intersection_style);
+    }
+
+    for object in &output.areas {
+        let origin = object.area.top_left();
+        writeln!(
+            svg,
+            r#"  <text x="{}" y="{}" font-family="sans-serif" font-size="{}">{}</text>"#,
+            origin.x + font_size / 4.0,
+            origin.y + font_size,
+            font_size,
+            escape_xml(&object.name)
+        )
+        .unwrap();
+    }
+
+    if options.legend {
+        write_legend(&mut svg, &viewport, font_size);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Characters used to draw the scene in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Ascii,
+    Unicode,
+}
+
+impl Charset {
+    /// Returns the characters of the cells covered by one and several objects.
+    const fn fills(self) -> (char, char) {
+        match self {
+            Self::Ascii => ('.', '#'),
+            Self::Unicode => ('░', '█'),
+        }
+    }
+}
+
+/// Draws the scene as a character grid, the width of the image is the number of columns.
+/// Cells covered by several objects are marked as the overlaps and objects are labeled by
+/// their names, which are truncated to the object width.
+pub fn render_text(output: &Output, options: &RenderOptions, charset: Charset) -> String {
+    let (viewport, columns, _) = options.frame(output);
+    let (from, to) = (viewport.top_left(), viewport.bottom_right());
+    let cell_width = (to.x - from.x) / columns as f32;
+    // Terminal cells are roughly twice as tall as they are wide.
+    let cell_height = cell_width * 2.0;
+    let rows = (((to.y - from.y) / cell_height).round() as usize).max(1);
+    let columns = columns as usize;
+
+    let cell_of = |x: f32, y: f32| {
+        (
+            ((x - from.x) / cell_width).max(0.0) as usize,
+            ((y - from.y) / cell_height).max(0.0) as usize,
+        )
+    };
+    let cell_center = |column: usize, row: usize| {
+        Point2D::new(
+            from.x + (column as f32 + 0.5) * cell_width,
+            from.y + (row as f32 + 0.5) * cell_height,
+        )
+    };
+    let contains = |rect: &BoundingRect, point: Point2D| {
+        let (a, b) = (rect.top_left(), rect.bottom_right());
+        a.x <= point.x && point.x < b.x && a.y <= point.y && point.y < b.y
+    };
+
+    let mut coverage = vec![vec![0_usize; columns]; rows];
+    for object in &output.areas {
+        let (a, b) = (object.area.top_left(), object.area.bottom_right());
+        let (first_column, first_row) = cell_of(a.x, a.y);
+        let (last_column, last_row) = cell_of(b.x, b.y);
+        let rows = coverage.iter_mut().enumerate();
+        for (row, counts) in rows.take(last_row + 1).skip(first_row) {
+            let columns = counts.iter_mut().enumerate();
+            for (column, count) in columns.take(last_column + 1).skip(first_column) {
+                if contains(&object.area, cell_center(column, row)) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    let (single, several) = charset.fills();
+    let mut cells = coverage
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&count| match count {
+                    0 => ' ',
+                    1 => single,
+                    _ => several,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    for object in &output.areas {
+        let (a, b) = (object.area.top_left(), object.area.bottom_right());
+        let (column, row) = cell_of(a.x, a.y);
+        if row >= rows || column >= columns || viewport.intersect(&object.area).is_none() {
+            continue;
+        }
+        let width = (cell_of(b.x, b.y).0.saturating_sub(column)).max(1);
+        for (offset, c) in object.name.chars().take(width).enumerate() {
+            if let Some(cell) = cells[row].get_mut(column + offset) {
+                *cell = c;
+            }
+        }
+    }
+
+    cells
+        .into_iter()
+        .map(|row| {
+            let mut line = row.into_iter().collect::<String>();
+            line.truncate(line.trim_end().len());
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+/// Rasterizes the rendered scene into the PNG image, the labels use the system fonts.
+#[cfg(feature = "png")]
+pub fn render_png(output: &Output, options: &RenderOptions) -> Result<Vec<u8>, Error> {
+    use resvg::{tiny_skia, usvg};
+
+    let reason = |err: anyhow::Error| Error::RenderError { reason: err };
+
+    let mut usvg_options = usvg::Options::default();
+    let fonts = usvg_options.fontdb_mut();
+    fonts.load_system_fonts();
+    // Without the fontconfig settings the generic family falls back to Arial, which may be
+    // missing, so take any available sans font instead.
+    let query = usvg::fontdb::Query {
+        families: &[usvg::fontdb::Family::SansSerif],
+        ..Default::default()
+    };
+    if fonts.query(&query).is_none() {
+        let families = fonts
+            .faces()
+            .filter_map(|face| face.families.first().map(|(name, _)| name.clone()))
+            .collect::<Vec<_>>();
+        let family = families
+            .iter()
+            .find(|name| name.contains("Sans") && !name.contains("Mono"))
+            .or_else(|| families.first())
+            .cloned();
+        if let Some(family) = family {
+            fonts.set_sans_serif_family(family);
+        }
+    }
+    let tree = usvg::Tree::from_str(&render_svg(output, options), &usvg_options)
+        .map_err(|err| reason(err.into()))?;
+
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| reason(anyhow::anyhow!("Invalid image size {:?}", size)))?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|err| reason(err.into()))
+}
+
+#[test]
+fn test_render_svg() {
+    let input = crate::SceneBuilder::new()
+        .object("A & B", 0.0, 0.0, 4.0, 4.0)
+        .object("C", 3.0, 2.0, 4.0, 4.0)
+        .build();
+    let output = crate::analyze(input, crate::Options::default());
+
+    let svg = render_svg(&output, &RenderOptions::default());
+    assert!(svg.starts_with(concat!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="1024" height="891" "#,
+        r#"viewBox="-0.35 -0.35 7.7 6.7">"#
+    )));
+    assert_eq!(svg.matches("<rect ").count(), 4);
+    assert_eq!(svg.matches(INTERSECTION_FILL).count(), 1);
+    assert!(svg.contains(r##"<rect x="3" y="2" width="1" height="2" fill="#d62728""##));
+    assert!(svg.contains(">A &amp; B</text>"));
+
+    let options = RenderOptions {
+        viewport: Some(BoundingRect::from_points(
+            Point2D::new(0.0, 0.0),
+            Point2D::new(10.0, 5.0),
+        )),
+        width: 200,
+        grid: Some(2.0),
+        legend: true,
+    };
+    let svg = render_svg(&output, &options);
+    assert!(svg.contains(r#"width="200" height="100" viewBox="0 0 10 5""#));
+    // Vertical lines at x = 0, 2, ..., 10 and horizontal ones at y = 0, 2, 4.
+    assert_eq!(svg.matches("<line ").count(), 9);
+    assert!(svg.contains(">intersection</text>"));
+}
+
+#[test]
+fn test_render_text() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 8.0, 4.0)
+        .object("B", 4.0, 2.0, 6.0, 4.0)
+        .build();
+    let output = crate::analyze(input, crate::Options::default());
+    let options = RenderOptions {
+        viewport: Some(BoundingRect::from_points(
+            Point2D::new(0.0, 0.0),
+            Point2D::new(12.0, 8.0),
+        )),
+        width: 12,
+        ..RenderOptions::default()
+    };
+
+    assert_eq!(
+        render_text(&output, &options, Charset::Ascii),
+        concat!(
+            "A.......\n", //
+            "....B###..\n",
+            "    ......\n",
+            "\n",
+        )
+    );
+    assert_eq!(
+        render_text(&output, &options, Charset::Unicode)
+            .lines()
+            .nth(1),
+        Some("░░░░B███░░")
+    );
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn test_render_png() {
+    let input = crate::Input::from_path("objects/test.json").unwrap();
+    let output = crate::analyze(input, crate::Options::default());
+    let options = RenderOptions {
+        width: 320,
+        legend: true,
+        ..RenderOptions::default()
+    };
+
+    let png = render_png(&output, &options).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    // The width is stored big-endian right after the IHDR chunk type.
+    assert_eq!(&png[16..20], &320_u32.to_be_bytes());
+}
@@ -0,0 +1,182 @@
+use crate::{list_intersections, BoundingRect, Intersection, Shape, Vector2D};
+
+/// Overlap resolution settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveOptions {
+    /// Indices of the shapes that must stay in place.
+    pub pinned: Vec<usize>,
+    /// Region that the shapes must not leave.
+    pub boundary: Option<BoundingRect>,
+    /// Maximum number of the resolution passes.
+    pub max_iterations: usize,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            pinned: Vec::new(),
+            boundary: None,
+            max_iterations: 100,
+        }
+    }
+}
+
+/// Overlap resolution summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution {
+    /// Offsets that should be applied to the shapes, in the same order as the shapes.
+    pub offsets: Vec<Vector2D>,
+    /// Number of the performed resolution passes.
+    pub iterations: usize,
+    /// Intersections that are still left after the last pass.
+    pub remaining: Vec<Intersection>,
+}
+
+impl Resolution {
+    /// Returns true if all the overlaps have been resolved.
+    pub fn is_resolved(&self) -> bool {
+        self.remaining.is_empty()
+    }
+}
+
+// Moves the rectangle back inside the boundary.
+fn clamp_offset(rect: &BoundingRect, boundary: &BoundingRect) -> Vector2D {
+    let clamp = |from: f32, to: f32, min: f32, max: f32| {
+        if from < min {
+            min - from
+        } else if to > max {
+            (max - to).max(min - from)
+        } else {
+            0.0
+        }
+    };
+
+    Vector2D {
+        x: clamp(rect.from.x, rect.to.x, boundary.from.x, boundary.to.x),
+        y: clamp(rect.from.y, rect.to.y, boundary.from.y, boundary.to.y),
+    }
+}
+
+/// Iteratively pushes overlapping shapes apart until they no longer intersect.
+///
+/// Each pass moves every pair of intersecting shapes along their minimum translation vector:
+/// free shapes share the displacement equally, while pinned shapes never move, even if they
+/// are outside of the boundary. The shapes themselves are left untouched, the returned
+/// offsets should be applied by the caller.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(shapes = objects.len())))]
+pub fn resolve_overlaps<S: Shape>(objects: &[S], options: &ResolveOptions) -> Resolution {
+    let is_pinned = |idx: usize| options.pinned.contains(&idx);
+
+    let mut rects = objects.iter().map(Shape::bounding_rect).collect::<Vec<_>>();
+    let mut offsets = vec![Vector2D { x: 0.0, y: 0.0 }; rects.len()];
+    let mut iterations = 0;
+
+    let mut remaining = list_intersections(&rects);
+    while !remaining.is_empty() && iterations < options.max_iterations {
+        iterations += 1;
+
+        let mut shifts = vec![Vector2D { x: 0.0, y: 0.0 }; rects.len()];
+        for intersection in &remaining {
+            let (a_share, b_share) =
+                match (is_pinned(intersection.a_idx), is_pinned(intersection.b_idx)) {
+                    (false, false) => (0.5, 0.5),
+                    (false, true) => (1.0, 0.0),
+                    (true, false) => (0.0, 1.0),
+                    (true, true) => continue,
+                };
+
+            let shift = &mut shifts[intersection.a_idx];
+            shift.x += intersection.mtv.x * a_share;
+            shift.y += intersection.mtv.y * a_share;
+            let shift = &mut shifts[intersection.b_idx];
+            shift.x -= intersection.mtv.x * b_share;
+            shift.y -= intersection.mtv.y * b_share;
+        }
+
+        for (idx, mut shift) in shifts.into_iter().enumerate() {
+            // The pinned shapes stay in place even outside of the boundary.
+            if is_pinned(idx) {
+                continue;
+            }
+            let mut rect = rects[idx].translate(shift);
+            if let Some(boundary) = &options.boundary {
+                let clamp = clamp_offset(&rect, boundary);
+                rect = rect.translate(clamp);
+                shift.x += clamp.x;
+                shift.y += clamp.y;
+            }
+
+            rects[idx] = rect;
+            offsets[idx].x += shift.x;
+            offsets[idx].y += shift.y;
+        }
+
+        remaining = list_intersections(&rects);
+        trace_event!(
+            iteration = iterations,
+            remaining = remaining.len(),
+            "Resolution pass finished"
+        );
+    }
+
+    Resolution {
+        offsets,
+        iterations,
+        remaining,
+    }
+}
+
+#[test]
+fn test_resolve_overlaps() {
+    use crate::Point2D;
+
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+
+    let objects = vec![
+        rect(0.0, 0.0, 4.0, 4.0),
+        rect(3.0, 1.0, 6.0, 3.0),
+        rect(5.0, 0.0, 8.0, 2.0),
+        rect(1.0, 2.0, 2.0, 5.0),
+    ];
+    let options = ResolveOptions {
+        pinned: vec![0],
+        boundary: Some(rect(0.0, 0.0, 12.0, 12.0)),
+        ..ResolveOptions::default()
+    };
+
+    let resolution = resolve_overlaps(&objects, &options);
+    assert!(resolution.is_resolved());
+    assert_eq!(resolution.offsets[0], Vector2D { x: 0.0, y: 0.0 });
+
+    let moved = objects
+        .iter()
+        .zip(&resolution.offsets)
+        .map(|(rect, offset)| rect.translate(*offset))
+        .collect::<Vec<_>>();
+    assert!(list_intersections(&moved).is_empty());
+    let boundary = options.boundary.unwrap();
+    for rect in &moved {
+        assert_eq!(clamp_offset(rect, &boundary), Vector2D { x: 0.0, y: 0.0 });
+    }
+
+    // Pinned shapes are never moved, so their overlaps cannot be resolved.
+    let options = ResolveOptions {
+        pinned: vec![0, 1],
+        ..ResolveOptions::default()
+    };
+    let resolution = resolve_overlaps(&objects[..2], &options);
+    assert!(!resolution.is_resolved());
+    assert_eq!(resolution.iterations, options.max_iterations);
+
+    // The pinned shape sticking out of the boundary is not pulled back inside.
+    let options = ResolveOptions {
+        pinned: vec![0],
+        boundary: Some(rect(1.0, 1.0, 12.0, 12.0)),
+        ..ResolveOptions::default()
+    };
+    let resolution = resolve_overlaps(&objects, &options);
+    assert!(resolution.is_resolved());
+    assert_eq!(resolution.offsets[0], Vector2D { x: 0.0, y: 0.0 });
+}
@@ -0,0 +1,288 @@
+use std::convert::TryFrom;
+
+use crate::{list_intersections_using, Algorithm, AnyShape, BoundingRect, Shape, Vector2D};
+
+/// Stable handle of the shape in the [`Scene`]. The handles of the removed shapes are never
+/// reused, so they do not refer to the shapes inserted later: the slot whose 2^32 - 1
+/// generations are exhausted is retired instead of starting over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShapeHandle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone)]
+struct Slot<S> {
+    generation: u32,
+    shape: Option<S>,
+}
+
+// Generation of the retired slots, no handle has it.
+const RETIRED: u32 = u32::MAX;
+
+impl<S> Slot<S> {
+    // Invalidates the handles of the removed shape, returns `false` if the slot is retired.
+    fn next_generation(&mut self) -> bool {
+        self.generation += 1;
+        self.generation != RETIRED
+    }
+}
+
+/// Intersection of two shapes of the [`Scene`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneIntersection {
+    /// Handles of the first and the second shape in the slot order.
+    pub handles: (ShapeHandle, ShapeHandle),
+    /// Shapes intersection area.
+    pub area: BoundingRect,
+    /// Minimum translation vector of the first shape that resolves the overlap.
+    pub mtv: Vector2D,
+}
+
+/// Scene owning its shapes in the slots of a single arena. The removed shapes free their
+/// slots for the next ones and [`Scene::clear`] keeps the allocated slots, so the scenes
+/// built and torn down per frame do not allocate once they are warmed up.
+#[derive(Debug, Clone)]
+pub struct Scene<S = AnyShape> {
+    slots: Vec<Slot<S>>,
+    free: Vec<u32>,
+    retired: usize,
+}
+
+impl<S> Default for Scene<S> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            retired: 0,
+        }
+    }
+}
+
+impl<S> Scene<S> {
+    /// Creates an empty scene.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty scene with the slots for the given number of shapes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::new(),
+            retired: 0,
+        }
+    }
+
+    /// Adds the shape into a free slot and returns its handle.
+    pub fn insert(&mut self, shape: S) -> ShapeHandle {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.shape = Some(shape);
+            return ShapeHandle {
+                index,
+                generation: slot.generation,
+            };
+        }
+        let index = u32::try_from(self.slots.len()).expect("too many shapes in the scene");
+        self.slots.push(Slot {
+            generation: 0,
+            shape: Some(shape),
+        });
+        ShapeHandle {
+            index,
+            generation: 0,
+        }
+    }
+
+    /// Removes the shape and returns it, the handle becomes invalid.
+    pub fn remove(&mut self, handle: ShapeHandle) -> Option<S> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let shape = slot.shape.take()?;
+        // The handles of the next shape in this slot differ from the removed one.
+        if slot.next_generation() {
+            self.free.push(handle.index);
+        } else {
+            self.retired += 1;
+        }
+        Some(shape)
+    }
+
+    /// Returns the shape of the handle unless it has been removed.
+    pub fn get(&self, handle: ShapeHandle) -> Option<&S> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.shape.as_ref()
+    }
+
+    /// Returns the mutable shape of the handle unless it has been removed.
+    pub fn get_mut(&mut self, handle: ShapeHandle) -> Option<&mut S> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.shape.as_mut()
+    }
+
+    /// Returns `true` if the scene still has the shape of the handle.
+    pub fn contains(&self, handle: ShapeHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Returns the number of the shapes.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len() - self.retired
+    }
+
+    /// Returns `true` if the scene has no shapes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all shapes and invalidates their handles, the slots are kept for the next ones.
+    pub fn clear(&mut self) {
+        self.free.clear();
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            if slot.shape.take().is_some() && !slot.next_generation() {
+                self.retired += 1;
+            }
+            if slot.generation != RETIRED {
+                self.free.push(index as u32);
+            }
+        }
+    }
+
+    /// Returns the handles and the shapes in the slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (ShapeHandle, &S)> + '_ {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = ShapeHandle {
+                index: index as u32,
+                generation: slot.generation,
+            };
+            slot.shape.as_ref().map(|shape| (handle, shape))
+        })
+    }
+}
+
+// Shape borrowed from the scene, so the search does not copy the shapes.
+struct Borrowed<'a, S>(&'a S);
+
+impl<S: Shape> Shape for Borrowed<'_, S> {
+    fn bounding_rect(&self) -> BoundingRect {
+        self.0.bounding_rect()
+    }
+
+    fn intersection(&self, other: &Self) -> Option<BoundingRect> {
+        self.0.intersection(other.0)
+    }
+
+    fn region(&self) -> Vec<BoundingRect> {
+        self.0.region()
+    }
+}
+
+impl<S: Shape + Sync> Scene<S> {
+    /// Searches for the intersecting shapes with the algorithm chosen by
+    /// [`Algorithm::auto`], the intersections are sorted by the slots of their shapes.
+    pub fn intersections(&self) -> Vec<SceneIntersection> {
+        let (handles, shapes): (Vec<_>, Vec<_>) = self
+            .iter()
+            .map(|(handle, shape)| (handle, Borrowed(shape)))
+            .unzip();
+        list_intersections_using(&shapes, Algorithm::auto(shapes.len()))
+            .into_iter()
+            .map(|intersection| SceneIntersection {
+                handles: (handles[intersection.a_idx], handles[intersection.b_idx]),
+                area: intersection.area,
+                mtv: intersection.mtv,
+            })
+            .collect()
+    }
+}
+
+impl<S> Extend<S> for Scene<S> {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for shape in iter {
+            self.insert(shape);
+        }
+    }
+}
+
+#[test]
+fn test_scene() {
+    use crate::Point2D;
+
+    let rect = |x: f32, y: f32, size: f32| {
+        AnyShape::Rect(BoundingRect::from_points(
+            Point2D::new(x, y),
+            Point2D::new(x + size, y + size),
+        ))
+    };
+    let mut scene = Scene::new();
+    let a = scene.insert(rect(0.0, 0.0, 4.0));
+    let b = scene.insert(rect(3.0, 3.0, 2.0));
+    let c = scene.insert(AnyShape::Circle {
+        center: Point2D::new(4.0, 0.0),
+        radius: 1.0,
+    });
+    assert_eq!(scene.len(), 3);
+    let handles = scene
+        .intersections()
+        .into_iter()
+        .map(|intersection| intersection.handles)
+        .collect::<Vec<_>>();
+    assert_eq!(handles, vec![(a, b), (a, c)]);
+
+    // The removed slot is reused, but the old handle stays invalid.
+    assert_eq!(scene.remove(b), Some(rect(3.0, 3.0, 2.0)));
+    assert_eq!(scene.remove(b), None);
+    let d = scene.insert(rect(10.0, 10.0, 1.0));
+    assert_ne!(b, d);
+    assert!(!scene.contains(b));
+    assert_eq!(scene.get(d), Some(&rect(10.0, 10.0, 1.0)));
+    *scene.get_mut(d).unwrap() = rect(1.0, 1.0, 1.0);
+    assert_eq!(
+        scene.iter().map(|(handle, _)| handle).collect::<Vec<_>>(),
+        vec![a, d, c]
+    );
+    assert_eq!(scene.intersections().len(), 2);
+
+    // The cleared scene keeps its slots and invalidates all handles.
+    let slots = scene.slots.capacity();
+    scene.clear();
+    assert!(scene.is_empty());
+    assert!(!scene.contains(a) && !scene.contains(c) && !scene.contains(d));
+    scene.extend((0..3).map(|idx| rect(idx as f32, 0.0, 1.0)));
+    assert_eq!(scene.len(), 3);
+    assert_eq!(scene.slots.capacity(), slots);
+    assert!(scene.iter().all(|(handle, _)| handle != a));
+
+    // The slot with the exhausted generations is never reused.
+    let mut scene = Scene::new();
+    let a = scene.insert(rect(0.0, 0.0, 1.0));
+    let b = scene.insert(rect(0.0, 0.0, 1.0));
+    scene.slots[a.index as usize].generation = RETIRED - 1;
+    scene.slots[b.index as usize].generation = RETIRED - 1;
+    let a = ShapeHandle {
+        generation: RETIRED - 1,
+        ..a
+    };
+    let b = ShapeHandle {
+        generation: RETIRED - 1,
+        ..b
+    };
+    assert!(scene.remove(a).is_some());
+    assert_eq!(scene.len(), 1);
+    let c = scene.insert(rect(1.0, 1.0, 1.0));
+    assert_ne!(c.index, a.index);
+    scene.clear();
+    assert!(scene.is_empty() && !scene.contains(b));
+    assert_eq!(scene.free, vec![c.index]);
+    assert_eq!(scene.insert(rect(2.0, 2.0, 1.0)).index, c.index);
+    assert_eq!(scene.len(), 1);
+}
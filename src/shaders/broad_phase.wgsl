@@ -0,0 +1,56 @@
+// Broad-phase pair test of two chunks of the rectangles: every invocation tests its rectangle
+// of the first chunk against all the rectangles of the second one, or against the following
+// ones if both chunks are the same, and appends the overlapping pairs to the output buffer.
+
+struct Params {
+    first_count: u32,
+    second_count: u32,
+    capacity: u32,
+    row_stride: u32,
+    same: u32,
+    _padding_1: u32,
+    _padding_2: u32,
+    _padding_3: u32,
+}
+
+struct Pairs {
+    // Never exceeds the capacity, the pairs that do not fit set the overflow flag instead.
+    count: atomic<u32>,
+    overflow: atomic<u32>,
+    data: array<vec2<u32>>,
+}
+
+// Rectangles given as (x1, y1, x2, y2).
+@group(0) @binding(0) var<storage, read> first: array<vec4<f32>>;
+@group(0) @binding(1) var<uniform> params: Params;
+@group(0) @binding(2) var<storage, read_write> pairs: Pairs;
+@group(0) @binding(3) var<storage, read> second: array<vec4<f32>>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x + id.y * params.row_stride;
+    if (i >= params.first_count) {
+        return;
+    }
+
+    let a = first[i];
+    var j = select(0u, i + 1u, params.same != 0u);
+    while (j < params.second_count) {
+        let b = second[j];
+        // The test is inclusive, so the candidates are a superset of the exact intersections.
+        if (a.x <= b.z && b.x <= a.z && a.y <= b.w && b.y <= a.w) {
+            loop {
+                let slot = atomicLoad(&pairs.count);
+                if (slot >= params.capacity) {
+                    atomicStore(&pairs.overflow, 1u);
+                    return;
+                }
+                if (atomicCompareExchangeWeak(&pairs.count, slot, slot + 1u).exchanged) {
+                    pairs.data[slot] = vec2<u32>(i, j);
+                    break;
+                }
+            }
+        }
+        j = j + 1u;
+    }
+}
@@ -0,0 +1,754 @@
+use std::f32::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{list_intersections, BoundingRect, Intersection, Point2D, Shape};
+
+/// Number of the vertices approximating the circle outlines.
+const CIRCLE_VERTICES: usize = 32;
+
+/// Group of shapes that form a single logical object.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShapeGroup<S> {
+    /// Shapes of the group.
+    pub children: Vec<S>,
+}
+
+impl<S: Shape> ShapeGroup<S> {
+    /// Creates a group from the specified shapes.
+    pub fn new(children: Vec<S>) -> Self {
+        Self { children }
+    }
+
+    /// Searches for overlapping children of this group.
+    pub fn self_intersections(&self) -> Vec<Intersection> {
+        list_intersections(&self.children)
+    }
+}
+
+impl<S: Shape> Shape for ShapeGroup<S> {
+    /// Returns the union of the children bounding rectangles.
+    ///
+    /// The bounding rectangle of an empty group is [`BoundingRect::EMPTY`].
+    fn bounding_rect(&self) -> BoundingRect {
+        self.children
+            .iter()
+            .fold(BoundingRect::EMPTY, |rect, child| {
+                rect.union(&child.bounding_rect())
+            })
+    }
+
+    /// Returns the regions of the children.
+    fn region(&self) -> Vec<BoundingRect> {
+        self.children.iter().flat_map(Shape::region).collect()
+    }
+}
+
+/// Simple polygon given by its vertices in traversal order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Polygon {
+    /// Polygon vertices, the last vertex is connected with the first one.
+    pub vertices: Vec<Point2D>,
+}
+
+/// Crossing of two polygon edges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EdgeCrossing {
+    /// First edge index, the edge starts at the vertex with the same index.
+    pub a_idx: usize,
+    /// Second edge index.
+    pub b_idx: usize,
+    /// Crossing point.
+    pub point: Point2D,
+}
+
+// Returns the cross product of the vectors `ab` and `ac`.
+fn cross(a: Point2D, b: Point2D, c: Point2D) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// Calculates the common point of the segments if they intersect.
+fn segments_intersection(a: (Point2D, Point2D), b: (Point2D, Point2D)) -> Option<Point2D> {
+    let d1 = cross(b.0, b.1, a.0);
+    let d2 = cross(b.0, b.1, a.1);
+    let d3 = cross(a.0, a.1, b.0);
+    let d4 = cross(a.0, a.1, b.1);
+
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        let t = d1 / (d1 - d2);
+        return Some(Point2D {
+            x: a.0.x + t * (a.1.x - a.0.x),
+            y: a.0.y + t * (a.1.y - a.0.y),
+        });
+    }
+
+    // Handles touching and collinear segments.
+    let on_segment = |p: Point2D, (s0, s1): (Point2D, Point2D)| {
+        p.x >= s0.x.min(s1.x)
+            && p.x <= s0.x.max(s1.x)
+            && p.y >= s0.y.min(s1.y)
+            && p.y <= s0.y.max(s1.y)
+    };
+    [(d1, a.0, b), (d2, a.1, b), (d3, b.0, a), (d4, b.1, a)]
+        .iter()
+        .find(|(d, p, segment)| *d == 0.0 && on_segment(*p, *segment))
+        .map(|(_, p, _)| *p)
+}
+
+impl Polygon {
+    /// Creates a polygon from the specified vertices.
+    pub fn new(vertices: Vec<Point2D>) -> Self {
+        Self { vertices }
+    }
+
+    /// Returns the polygon edges.
+    pub fn edges(&self) -> impl Iterator<Item = (Point2D, Point2D)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
+    /// Searches for crossing edges, that is the polygon is not simple.
+    ///
+    /// Note that this method uses a naive O(n^2) method to find edges crossings.
+    pub fn self_intersections(&self) -> Vec<EdgeCrossing> {
+        let edges = self.edges().collect::<Vec<_>>();
+        let n = edges.len();
+
+        let mut crossings = Vec::new();
+        for i in 0..n {
+            for j in (i + 1)..n {
+                // Adjacent edges always share a vertex.
+                if j == i + 1 || (i == 0 && j == n - 1) {
+                    continue;
+                }
+
+                if let Some(point) = segments_intersection(edges[i], edges[j]) {
+                    crossings.push(EdgeCrossing {
+                        a_idx: i,
+                        b_idx: j,
+                        point,
+                    });
+                }
+            }
+        }
+
+        crossings
+    }
+
+    /// Returns `true` if the polygon is its bounding rect, i.e. it has the four corners of the
+    /// rect in any order making a simple polygon.
+    pub fn is_rect(&self) -> bool {
+        let corners = Polygon::from(self.bounding_rect()).vertices;
+        self.vertices.len() == 4
+            && corners.iter().all(|corner| self.vertices.contains(corner))
+            && self.area() != 0.0
+    }
+
+    /// Returns `true` if all the turns of the polygon edges have the same direction.
+    pub fn is_convex(&self) -> bool {
+        let n = self.vertices.len();
+        let turns = (0..n).map(|i| {
+            let (a, b, c) = (
+                self.vertices[i],
+                self.vertices[(i + 1) % n],
+                self.vertices[(i + 2) % n],
+            );
+            cross(a, b, c)
+        });
+        let (left, right) = turns.fold((false, false), |(left, right), turn| {
+            (left || turn > 0.0, right || turn < 0.0)
+        });
+        !(left && right)
+    }
+
+    /// Splits the simple polygon into triangles by clipping its ears. The vertices touching an
+    /// ear block it only if their edges enter it, so the reflex vertices on the diagonals and
+    /// the touching ones are allowed. The rest of a self-intersecting polygon without ears is
+    /// approximated by its bounding rect and the degenerate rest without area is dropped, so
+    /// every part is convex.
+    pub fn triangulate(&self) -> Vec<Polygon> {
+        let orientation = self.signed_area().signum();
+        let vertices = &self.vertices;
+        let mut indices = (0..vertices.len()).collect::<Vec<_>>();
+        let mut triangles = Vec::new();
+        while indices.len() > 3 {
+            let n = indices.len();
+            let rest = Polygon::new(indices.iter().map(|&i| vertices[i]).collect());
+            let rest_area = rest.signed_area() * orientation;
+            if rest_area <= 0.0 {
+                if rest_area < 0.0 {
+                    triangles.push(Polygon::from(rest.bounding_rect()));
+                }
+                return triangles;
+            }
+
+            let corners = |i: usize| (indices[(i + n - 1) % n], indices[i], indices[(i + 1) % n]);
+            let is_ear = |i: usize| {
+                let (a, b, c) = corners(i);
+                let (pa, pb, pc) = (vertices[a], vertices[b], vertices[c]);
+                let sides = |p: Point2D| {
+                    [
+                        cross(pa, pb, p) * orientation,
+                        cross(pb, pc, p) * orientation,
+                        cross(pc, pa, p) * orientation,
+                    ]
+                };
+                // The vertex inside the ear blocks it, the one on its border only if any of its
+                // edges enters the ear, i.e. leaves every border line it lies on inwards.
+                let blocks = |k: usize| {
+                    let on = sides(vertices[indices[k]]);
+                    if on.iter().any(|&side| side < 0.0) {
+                        return false;
+                    }
+                    let neighbors = [indices[(k + n - 1) % n], indices[(k + 1) % n]];
+                    on.iter().all(|&side| side > 0.0)
+                        || neighbors.iter().any(|&neighbor| {
+                            let to = sides(vertices[neighbor]);
+                            (0..3).all(|line| on[line] != 0.0 || to[line] > 0.0)
+                        })
+                };
+                cross(pa, pb, pc) * orientation > 0.0
+                    && !(0..n)
+                        .filter(|&k| ![a, b, c].contains(&indices[k]))
+                        .any(blocks)
+            };
+            match (0..n).find(|&i| is_ear(i)) {
+                Some(i) => {
+                    let (a, b, c) = corners(i);
+                    triangles.push(Polygon::new(vec![vertices[a], vertices[b], vertices[c]]));
+                    indices.remove(i);
+                }
+                None => {
+                    triangles.push(Polygon::from(rest.bounding_rect()));
+                    return triangles;
+                }
+            }
+        }
+        triangles.push(Polygon::new(indices.iter().map(|&i| vertices[i]).collect()));
+        triangles
+    }
+
+    // Returns the area with the sign of the vertex order.
+    fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|(a, b)| a.x * b.y - b.x * a.y)
+            .sum::<f32>()
+            / 2.0
+    }
+
+    /// Returns the area enclosed by the simple polygon.
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    /// Returns the part of the polygon lying inside the given convex polygon, it has no
+    /// vertices if they do not overlap. The touching polygons have the degenerate common part.
+    ///
+    /// ```
+    /// use sophya_prog_test::{BoundingRect, Point2D, Polygon};
+    ///
+    /// let a = Polygon::from(BoundingRect::from_points(Point2D::new(0.0, 0.0), Point2D::new(4.0, 4.0)));
+    /// let b = Polygon::from(BoundingRect::from_points(Point2D::new(2.0, 2.0), Point2D::new(6.0, 6.0)));
+    /// assert_eq!(a.clip(&b).area(), 4.0);
+    /// ```
+    pub fn clip(&self, convex: &Polygon) -> Polygon {
+        // The inner side of the edges depends on the vertex order, `signum` of zero is one.
+        let orientation = convex.signed_area().signum();
+        let mut vertices = self.vertices.clone();
+        for (a, b) in convex.edges() {
+            let side = |p: Point2D| cross(a, b, p) * orientation;
+            let crossing = |p: Point2D, q: Point2D| {
+                let t = side(p) / (side(p) - side(q));
+                Point2D::new(p.x + t * (q.x - p.x), p.y + t * (q.y - p.y))
+            };
+
+            let input = std::mem::take(&mut vertices);
+            let previous = input
+                .iter()
+                .copied()
+                .cycle()
+                .skip(input.len().saturating_sub(1));
+            for (previous, current) in previous.zip(input.iter().copied()) {
+                match (side(previous) >= 0.0, side(current) >= 0.0) {
+                    (true, true) => vertices.push(current),
+                    (true, false) => vertices.push(crossing(previous, current)),
+                    (false, true) => {
+                        vertices.push(crossing(previous, current));
+                        vertices.push(current);
+                    }
+                    (false, false) => {}
+                }
+            }
+        }
+        Polygon::new(vertices)
+    }
+}
+
+/// Shape of any supported kind, tagged by the `kind` field when serialized, e.g.
+/// `{ "kind": "circle", "center": { "x": 1, "y": 1 }, "radius": 2 }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnyShape {
+    Rect(BoundingRect),
+    Circle { center: Point2D, radius: f32 },
+    Polygon(Polygon),
+    Segment { from: Point2D, to: Point2D },
+}
+
+impl AnyShape {
+    /// Returns the outline polygon, the circles are approximated by the inscribed polygons and
+    /// the segments have two vertices.
+    pub fn outline(&self) -> Polygon {
+        match self {
+            Self::Rect(rect) => Polygon::from(*rect),
+            Self::Circle { center, radius } => Polygon::new(
+                (0..CIRCLE_VERTICES)
+                    .map(|i| {
+                        let (sin, cos) = (TAU * i as f32 / CIRCLE_VERTICES as f32).sin_cos();
+                        Point2D::new(center.x + radius * cos, center.y + radius * sin)
+                    })
+                    .collect(),
+            ),
+            Self::Polygon(polygon) => polygon.clone(),
+            Self::Segment { from, to } => Polygon::new(vec![*from, *to]),
+        }
+    }
+
+    /// Returns the shape with the points and the lengths, i.e. the radius, mapped by the given
+    /// functions.
+    pub(crate) fn map(
+        &self,
+        point: impl Fn(Point2D) -> Point2D,
+        length: impl Fn(f32) -> f32,
+    ) -> AnyShape {
+        match self {
+            Self::Rect(rect) => {
+                Self::Rect(BoundingRect::from_points(point(rect.from), point(rect.to)))
+            }
+            Self::Circle { center, radius } => Self::Circle {
+                center: point(*center),
+                radius: length(*radius),
+            },
+            Self::Polygon(polygon) => Self::Polygon(Polygon::new(
+                polygon.vertices.iter().copied().map(point).collect(),
+            )),
+            Self::Segment { from, to } => Self::Segment {
+                from: point(*from),
+                to: point(*to),
+            },
+        }
+    }
+
+    /// Returns the shape rotated about the given center by the given degrees, the positive
+    /// angles turn the x axis towards the y axis. The rotated rects become polygons.
+    pub fn rotated(&self, center: Point2D, degrees: f32) -> AnyShape {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let rotate = |p: Point2D| {
+            let (x, y) = (p.x - center.x, p.y - center.y);
+            Point2D::new(center.x + x * cos - y * sin, center.y + x * sin + y * cos)
+        };
+        match self {
+            Self::Rect(_) | Self::Polygon(_) => Self::Polygon(Polygon::new(
+                self.outline().vertices.into_iter().map(rotate).collect(),
+            )),
+            Self::Circle { center, radius } => Self::Circle {
+                center: rotate(*center),
+                radius: *radius,
+            },
+            Self::Segment { from, to } => Self::Segment {
+                from: rotate(*from),
+                to: rotate(*to),
+            },
+        }
+    }
+
+    // Returns the convex parts covering the shape, the segments have none.
+    fn convex_parts(&self) -> Vec<Polygon> {
+        match self {
+            Self::Segment { .. } => Vec::new(),
+            Self::Polygon(polygon) if !polygon.is_convex() => polygon.triangulate(),
+            shape => vec![shape.outline()],
+        }
+    }
+
+    /// Returns the tight bounding rect and the area of the common part of the shapes if they
+    /// overlap or touch.
+    ///
+    /// ```
+    /// use sophya_prog_test::{AnyShape, Point2D};
+    ///
+    /// let circle = AnyShape::Circle { center: Point2D::new(0.0, 0.0), radius: 1.0 };
+    /// let segment = AnyShape::Segment { from: Point2D::new(-2.0, 0.0), to: Point2D::new(0.0, 0.0) };
+    /// let (rect, area) = segment.overlap(&circle).unwrap();
+    /// assert_eq!((rect.top_left(), rect.bottom_right()), (Point2D::new(-1.0, 0.0), Point2D::new(0.0, 0.0)));
+    /// assert_eq!(area, 0.0);
+    /// ```
+    pub fn overlap(&self, other: &AnyShape) -> Option<(BoundingRect, f32)> {
+        if let (Self::Segment { from: a0, to: a1 }, Self::Segment { from: b0, to: b1 }) =
+            (self, other)
+        {
+            return segments_intersection((*a0, *a1), (*b0, *b1))
+                .map(|point| (BoundingRect::from_points(point, point), 0.0));
+        }
+
+        // The segment is clipped by the other shape, the parts of the shapes are disjoint, so
+        // their areas are summed up.
+        let (subject, clip) = match self {
+            Self::Segment { .. } => (self, other),
+            _ => (other, self),
+        };
+        let subject = subject.outline();
+        clip.convex_parts()
+            .iter()
+            .map(|part| subject.clip(part))
+            .filter(|common| !common.vertices.is_empty())
+            .fold(None, |overlap: Option<(BoundingRect, f32)>, common| {
+                let (rect, area) = (common.bounding_rect(), common.area());
+                Some(overlap.map_or((rect, area), |(total, sum)| {
+                    (total.union(&rect), sum + area)
+                }))
+            })
+    }
+}
+
+impl Shape for AnyShape {
+    fn bounding_rect(&self) -> BoundingRect {
+        match self {
+            Self::Rect(rect) => *rect,
+            Self::Circle { center, radius } => BoundingRect::from_points(
+                Point2D::new(center.x - radius, center.y - radius),
+                Point2D::new(center.x + radius, center.y + radius),
+            ),
+            shape => shape.outline().bounding_rect(),
+        }
+    }
+}
+
+impl From<BoundingRect> for Polygon {
+    /// Returns the corners of the rectangle in the clockwise order of the screen coordinates.
+    fn from(rect: BoundingRect) -> Self {
+        Self::new(vec![
+            rect.from,
+            Point2D::new(rect.to.x, rect.from.y),
+            rect.to,
+            Point2D::new(rect.from.x, rect.to.y),
+        ])
+    }
+}
+
+impl Shape for Polygon {
+    /// Returns the bounding rectangle of the polygon vertices.
+    ///
+    /// The bounding rectangle of a polygon without vertices is [`BoundingRect::EMPTY`].
+    fn bounding_rect(&self) -> BoundingRect {
+        if self.vertices.is_empty() {
+            return BoundingRect::EMPTY;
+        }
+
+        let mut rect = BoundingRect::from_points(self.vertices[0], self.vertices[0]);
+        for p in &self.vertices[1..] {
+            rect.from.x = rect.from.x.min(p.x);
+            rect.from.y = rect.from.y.min(p.y);
+            rect.to.x = rect.to.x.max(p.x);
+            rect.to.y = rect.to.y.max(p.y);
+        }
+        rect
+    }
+}
+
+#[test]
+fn test_polygon_self_intersections() {
+    let polygon = |points: &[(f32, f32)]| {
+        Polygon::new(points.iter().map(|&(x, y)| Point2D { x, y }).collect())
+    };
+
+    let cases = vec![
+        (
+            polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]),
+            vec![],
+            "square",
+        ),
+        (
+            polygon(&[(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0)]),
+            vec![EdgeCrossing {
+                a_idx: 0,
+                b_idx: 2,
+                point: Point2D { x: 2.0, y: 2.0 },
+            }],
+            "bow tie",
+        ),
+        (
+            polygon(&[(0.0, 0.0), (4.0, 0.0), (2.0, 0.0), (2.0, 3.0)]),
+            vec![EdgeCrossing {
+                a_idx: 0,
+                b_idx: 2,
+                point: Point2D { x: 2.0, y: 0.0 },
+            }],
+            "edge touches another edge",
+        ),
+    ];
+
+    for case in cases {
+        assert_eq!(
+            case.0.self_intersections(),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+    }
+
+    assert_eq!(
+        polygon(&[(1.0, 3.0), (-2.0, 0.0), (4.0, 1.0)]).bounding_rect(),
+        BoundingRect::from_points(Point2D { x: -2.0, y: 0.0 }, Point2D { x: 4.0, y: 3.0 })
+    );
+}
+
+#[test]
+fn test_polygon_clip() {
+    let polygon = |points: &[(f32, f32)]| {
+        Polygon::new(points.iter().map(|&(x, y)| Point2D { x, y }).collect())
+    };
+    let square = polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+
+    let cases = vec![
+        (
+            polygon(&[(2.0, 2.0), (6.0, 2.0), (6.0, 6.0), (2.0, 6.0)]),
+            4.0,
+            BoundingRect::from_points(Point2D::new(2.0, 2.0), Point2D::new(4.0, 4.0)),
+            "overlapping squares",
+        ),
+        (
+            polygon(&[(2.0, -1.0), (5.0, 2.0), (2.0, 5.0), (-1.0, 2.0)]),
+            14.0,
+            BoundingRect::from_points(Point2D::new(0.0, 0.0), Point2D::new(4.0, 4.0)),
+            "diamond with the counter clockwise vertices",
+        ),
+        (
+            polygon(&[(1.0, 1.0), (1.0, 2.0), (2.0, 2.0), (2.0, 1.0)]),
+            1.0,
+            BoundingRect::from_points(Point2D::new(1.0, 1.0), Point2D::new(2.0, 2.0)),
+            "nested square",
+        ),
+        (
+            polygon(&[(4.0, 0.0), (6.0, 0.0), (6.0, 4.0), (4.0, 4.0)]),
+            0.0,
+            BoundingRect::from_points(Point2D::new(4.0, 0.0), Point2D::new(4.0, 4.0)),
+            "touching squares",
+        ),
+    ];
+    for case in cases {
+        let clipped = case.0.clip(&square);
+        assert_eq!(
+            (clipped.area(), clipped.bounding_rect()),
+            (case.1, case.2),
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+    }
+
+    // The bounding rects overlap, the polygons do not.
+    let apart = polygon(&[(6.0, 3.0), (9.0, 6.0), (6.0, 9.0), (3.0, 6.0)]);
+    assert!(apart
+        .bounding_rect()
+        .intersection(&square.bounding_rect())
+        .is_some());
+    assert!(apart.clip(&square).vertices.is_empty());
+}
+
+#[test]
+fn test_any_shape_overlap() {
+    let point = Point2D::new;
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        AnyShape::Rect(BoundingRect::from_points(point(x1, y1), point(x2, y2)))
+    };
+    // The "L" shaped polygon without the top right quarter of the 4x4 square.
+    let corner = AnyShape::Polygon(Polygon::new(vec![
+        point(0.0, 0.0),
+        point(2.0, 0.0),
+        point(2.0, 2.0),
+        point(4.0, 2.0),
+        point(4.0, 4.0),
+        point(0.0, 4.0),
+    ]));
+    let circle = AnyShape::Circle {
+        center: point(3.0, 1.0),
+        radius: 0.5,
+    };
+
+    let cases = vec![
+        (
+            rect(1.0, 1.0, 3.0, 3.0),
+            corner.clone(),
+            Some((
+                BoundingRect::from_points(point(1.0, 1.0), point(3.0, 3.0)),
+                3.0,
+            )),
+            "rect and concave polygon",
+        ),
+        (
+            circle.clone(),
+            corner.clone(),
+            None,
+            "circle in the cut out quarter",
+        ),
+        (
+            AnyShape::Segment {
+                from: point(1.0, 1.0),
+                to: point(5.0, 1.0),
+            },
+            corner.clone(),
+            Some((
+                BoundingRect::from_points(point(1.0, 1.0), point(2.0, 1.0)),
+                0.0,
+            )),
+            "segment crossing the polygon",
+        ),
+        (
+            AnyShape::Segment {
+                from: point(0.0, 0.0),
+                to: point(2.0, 2.0),
+            },
+            AnyShape::Segment {
+                from: point(0.0, 2.0),
+                to: point(2.0, 0.0),
+            },
+            Some((
+                BoundingRect::from_points(point(1.0, 1.0), point(1.0, 1.0)),
+                0.0,
+            )),
+            "crossing segments",
+        ),
+    ];
+    for case in cases {
+        assert_eq!(
+            case.0.overlap(&case.1),
+            case.2,
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+        assert_eq!(
+            case.1.overlap(&case.0),
+            case.2,
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+    }
+
+    assert!(!corner.outline().is_convex());
+    assert_eq!(
+        corner
+            .outline()
+            .triangulate()
+            .iter()
+            .map(Polygon::area)
+            .sum::<f32>(),
+        12.0
+    );
+    assert_eq!(
+        circle.bounding_rect(),
+        BoundingRect::from_points(point(2.5, 0.5), point(3.5, 1.5))
+    );
+    let rotated = rect(0.0, 0.0, 2.0, 2.0).rotated(point(1.0, 1.0), 90.0);
+    assert!((rotated.outline().area() - 4.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_polygon_triangulate() {
+    let point = Point2D::new;
+    let polygon =
+        |points: &[(f32, f32)]| Polygon::new(points.iter().map(|&(x, y)| point(x, y)).collect());
+    let big = AnyShape::Rect(BoundingRect::from_points(
+        point(-1.0, -1.0),
+        point(5.0, 5.0),
+    ));
+    let left = AnyShape::Rect(BoundingRect::from_points(
+        point(-1.0, -1.0),
+        point(2.0, 5.0),
+    ));
+
+    let cases = vec![
+        (
+            // Two triangles touching by the reflex vertices in the center of the square.
+            polygon(&[
+                (0.0, 0.0),
+                (2.0, 2.0),
+                (4.0, 0.0),
+                (4.0, 4.0),
+                (2.0, 2.0),
+                (0.0, 4.0),
+            ]),
+            8.0,
+            4.0,
+            "touching reflex vertices",
+        ),
+        (
+            // The square with the collinear vertex and the notch reaching its diagonal.
+            polygon(&[
+                (0.0, 0.0),
+                (2.0, 0.0),
+                (4.0, 0.0),
+                (4.0, 4.0),
+                (2.0, 2.0),
+                (0.0, 4.0),
+            ]),
+            12.0,
+            6.0,
+            "reflex vertex on the diagonal",
+        ),
+        (
+            polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (2.0, 2.0), (0.0, 4.0)]),
+            12.0,
+            6.0,
+            "notch",
+        ),
+    ];
+    for case in cases {
+        let parts = case.0.triangulate();
+        assert!(
+            parts.iter().all(Polygon::is_convex),
+            "Test case \"{}\" has been failed: {:?}",
+            case.3,
+            parts
+        );
+        assert_eq!(
+            parts.iter().map(Polygon::area).sum::<f32>(),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+        let shape = AnyShape::Polygon(case.0);
+        let areas = (
+            big.overlap(&shape).map(|x| x.1),
+            left.overlap(&shape).map(|x| x.1),
+        );
+        assert_eq!(
+            areas,
+            (Some(case.1), Some(case.2)),
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+    }
+}
+
+#[test]
+fn test_shape_group_self_intersections() {
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+
+    let group = ShapeGroup::new(vec![
+        rect(0.0, 0.0, 2.0, 2.0),
+        rect(2.0, 0.0, 4.0, 2.0),
+        rect(1.0, 1.0, 3.0, 3.0),
+    ]);
+
+    let pairs = group
+        .self_intersections()
+        .iter()
+        .map(|x| (x.a_idx, x.b_idx))
+        .collect::<Vec<_>>();
+    assert_eq!(pairs, vec![(0, 2), (1, 2)]);
+    assert_eq!(group.bounding_rect(), rect(0.0, 0.0, 4.0, 3.0));
+}
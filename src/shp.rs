@@ -0,0 +1,262 @@
+use std::{
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use ::shapefile::{
+    dbase::{FieldValue, Record},
+    PolygonRing, Reader, Shape, ShapeReader,
+};
+use serde_json::{json, Value};
+
+use crate::{decompress, wkt::ring_shape, AnyShape, Error, Input, Object, Point2D};
+
+fn parse_error(reason: impl Into<anyhow::Error>) -> Error {
+    Error::ParseError {
+        reason: reason.into(),
+    }
+}
+
+// Returns the points of the first outer ring, the holes are ignored.
+fn outer_ring<P>(rings: &[PolygonRing<P>], point: impl Fn(&P) -> Point2D) -> Option<Vec<Point2D>> {
+    rings.iter().find_map(|ring| match ring {
+        PolygonRing::Outer(points) => Some(points.iter().map(&point).collect()),
+        PolygonRing::Inner(_) => None,
+    })
+}
+
+// Returns the shape of the polygon or of the two-point polyline, the null shapes are skipped.
+fn shape(idx: usize, shape: Shape) -> Result<Option<AnyShape>, Error> {
+    let ring = match &shape {
+        Shape::NullShape => return Ok(None),
+        Shape::Polygon(polygon) => {
+            outer_ring(polygon.rings(), |p| Point2D::new(p.x as f32, p.y as f32))
+        }
+        Shape::PolygonM(polygon) => {
+            outer_ring(polygon.rings(), |p| Point2D::new(p.x as f32, p.y as f32))
+        }
+        Shape::PolygonZ(polygon) => {
+            outer_ring(polygon.rings(), |p| Point2D::new(p.x as f32, p.y as f32))
+        }
+        Shape::Polyline(line) => match line.parts().as_slice() {
+            [part] => match part.as_slice() {
+                [from, to] => {
+                    return Ok(Some(AnyShape::Segment {
+                        from: Point2D::new(from.x as f32, from.y as f32),
+                        to: Point2D::new(to.x as f32, to.y as f32),
+                    }))
+                }
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    };
+    match ring {
+        Some(ring) => ring_shape(ring).map(Some),
+        None => Err(parse_error(anyhow::anyhow!(
+            "The shape {} is a {:?}, only the polygons and the two-point polylines are supported",
+            idx,
+            shape.shapetype()
+        ))),
+    }
+}
+
+fn field_value(value: FieldValue) -> Value {
+    match value {
+        FieldValue::Character(Some(text)) | FieldValue::Memo(text) => json!(text),
+        FieldValue::Numeric(Some(number)) => json!(number),
+        FieldValue::Float(Some(number)) => json!(number),
+        FieldValue::Logical(Some(flag)) => json!(flag),
+        FieldValue::Date(Some(date)) => json!(format!(
+            "{:04}-{:02}-{:02}",
+            date.year(),
+            date.month(),
+            date.day()
+        )),
+        FieldValue::Integer(number) => json!(number),
+        FieldValue::Currency(number) | FieldValue::Double(number) => json!(number),
+        _ => Value::Null,
+    }
+}
+
+// Builds the objects from the shapes and their attribute records.
+fn objects(shapes: Vec<(Shape, Option<Record>)>) -> Result<Vec<Object>, Error> {
+    let mut objects = Vec::new();
+    for (idx, (geometry, record)) in shapes.into_iter().enumerate() {
+        let Some(geometry) = shape(idx, geometry)? else {
+            continue;
+        };
+        // The attributes are sorted by their names, the hash map of the record has no order.
+        let mut fields = record
+            .map(|record| {
+                record
+                    .into_iter()
+                    .map(|(key, value)| (key, field_value(value)))
+                    .filter(|(_, value)| !value.is_null())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        let name = fields
+            .iter()
+            .position(|(key, value)| key.eq_ignore_ascii_case("name") && value.is_string())
+            .map(|position| fields.remove(position).1);
+        let name = match name {
+            Some(Value::String(name)) => name,
+            _ => idx.to_string(),
+        };
+        objects.push(Object {
+            properties: fields
+                .into_iter()
+                .map(|(key, value)| json!({ "name": key, "value": value }))
+                .collect(),
+            ..Object::from_shape(name, geometry)
+        });
+    }
+    Ok(objects)
+}
+
+impl Input {
+    /// Reads the ESRI shapefile and its `.dbf` attributes next to it if there are any. Every
+    /// polygon becomes an object with its outer ring as the shape and the two-point polylines
+    /// become the segments, the null shapes are skipped. The objects are named by the `NAME`
+    /// attribute or by the shape indices, the other attributes are kept as the
+    /// `{ "name": key, "value": value }` properties.
+    pub fn from_shapefile<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let dbf = path.with_extension("dbf");
+        if !dbf.exists() {
+            return Self::from_shapefile_reader(crate::open_file(path)?);
+        }
+        let shapes = Reader::from_path(path)
+            .and_then(|mut reader| reader.read())
+            .map_err(parse_error)?
+            .into_iter()
+            .map(|(shape, record)| (shape, Some(record)))
+            .collect();
+        Ok(Self {
+            objects: objects(shapes)?,
+        })
+    }
+
+    /// Reads the geometries of the `.shp` stream without the attributes, the objects are
+    /// named by the shape indices.
+    pub fn from_shapefile_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut reader = decompress::decompress(reader).map_err(parse_error)?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(parse_error)?;
+        let shapes = ShapeReader::new(Cursor::new(bytes))
+            .and_then(ShapeReader::read)
+            .map_err(parse_error)?
+            .into_iter()
+            .map(|shape| (shape, None))
+            .collect();
+        Ok(Self {
+            objects: objects(shapes)?,
+        })
+    }
+}
+
+#[test]
+fn test_shapefile() {
+    use std::convert::TryFrom;
+
+    use ::shapefile::{
+        dbase::{FieldName, TableWriterBuilder},
+        Point, Polygon, Polyline, Writer,
+    };
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("parcels.shp");
+    let table = TableWriterBuilder::new()
+        .add_character_field(FieldName::try_from("NAME").unwrap(), 20)
+        .add_numeric_field(FieldName::try_from("OWNERS").unwrap(), 10, 0);
+    let mut writer = Writer::from_path(&path, table).unwrap();
+    let ring = |points: &[(f64, f64)]| {
+        PolygonRing::Outer(points.iter().map(|&(x, y)| Point::new(x, y)).collect())
+    };
+    let record = |name: Option<&str>, owners: f64| {
+        let mut record = Record::default();
+        record.insert(
+            "NAME".to_owned(),
+            FieldValue::Character(name.map(str::to_owned)),
+        );
+        record.insert("OWNERS".to_owned(), FieldValue::Numeric(Some(owners)));
+        record
+    };
+    let shapes = vec![
+        (
+            Polygon::new(ring(&[
+                (0.0, 0.0),
+                (0.0, 4.0),
+                (4.0, 4.0),
+                (4.0, 0.0),
+                (0.0, 0.0),
+            ])),
+            record(Some("park"), 1.0),
+        ),
+        (
+            Polygon::new(ring(&[(3.0, 3.0), (2.0, 6.0), (6.0, 6.0), (3.0, 3.0)])),
+            record(None, 2.0),
+        ),
+    ];
+    for (shape, record) in &shapes {
+        writer.write_shape_and_record(shape, record).unwrap();
+    }
+    drop(writer);
+
+    let input = Input::from_shapefile(&path).unwrap();
+    let objects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                [x.x, x.y, x.width, x.height],
+                x.shape
+                    .as_ref()
+                    .map(AnyShape::outline)
+                    .map(|x| x.vertices.len()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        vec![
+            ("park", [0.0, 0.0, 4.0, 4.0], None),
+            ("1", [2.0, 3.0, 4.0, 3.0], Some(3)),
+        ]
+    );
+    assert_eq!(
+        input.objects[1].properties,
+        vec![json!({ "name": "OWNERS", "value": 2.0 })]
+    );
+
+    // Without the attributes the objects are named by the indices.
+    let file = std::fs::File::open(&path).unwrap();
+    let geometries = Input::from_shapefile_reader(file).unwrap();
+    let names = geometries
+        .objects
+        .iter()
+        .map(|x| x.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["0", "1"]);
+    assert!(Input::from_shapefile_reader("not a shapefile".as_bytes()).is_err());
+
+    // The two-point polylines are the segments, the other lines are not supported.
+    let line = |points: &[(f64, f64)]| {
+        Shape::Polyline(Polyline::new(
+            points.iter().map(|&(x, y)| Point::new(x, y)).collect(),
+        ))
+    };
+    assert_eq!(
+        shape(0, line(&[(1.0, 1.0), (5.0, 1.0)])).unwrap(),
+        Some(AnyShape::Segment {
+            from: Point2D::new(1.0, 1.0),
+            to: Point2D::new(5.0, 1.0),
+        })
+    );
+    assert!(shape(0, line(&[(1.0, 1.0), (5.0, 1.0), (5.0, 5.0)])).is_err());
+    assert_eq!(shape(0, Shape::NullShape).unwrap(), None);
+}
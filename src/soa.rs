@@ -0,0 +1,264 @@
+use std::{borrow::Cow, iter::FromIterator, mem};
+
+use crate::{
+    algorithms::{search_with_stats, Searched},
+    rects::{lines_intersection, overlap_mtv},
+    Algorithm, AnalysisStats, BoundingRect, Intersection, Point2D,
+};
+
+/// Rects stored as the separate arrays of their left, top, right and bottom edges, so the
+/// scans along one axis read only the contiguous edges they need.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RectSoA {
+    x1: Vec<f32>,
+    y1: Vec<f32>,
+    x2: Vec<f32>,
+    y2: Vec<f32>,
+}
+
+impl RectSoA {
+    /// Creates the empty storage.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the empty storage for the given number of rects.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            x1: Vec::with_capacity(capacity),
+            y1: Vec::with_capacity(capacity),
+            x2: Vec::with_capacity(capacity),
+            y2: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends the rect.
+    pub fn push(&mut self, rect: BoundingRect) {
+        self.x1.push(rect.from.x);
+        self.y1.push(rect.from.y);
+        self.x2.push(rect.to.x);
+        self.y2.push(rect.to.y);
+    }
+
+    /// Returns the rect at the given index.
+    pub fn get(&self, idx: usize) -> Option<BoundingRect> {
+        Some(BoundingRect {
+            from: Point2D::new(*self.x1.get(idx)?, self.y1[idx]),
+            to: Point2D::new(self.x2[idx], self.y2[idx]),
+        })
+    }
+
+    /// Returns the number of the rects.
+    pub fn len(&self) -> usize {
+        self.x1.len()
+    }
+
+    /// Returns `true` if there are no rects.
+    pub fn is_empty(&self) -> bool {
+        self.x1.is_empty()
+    }
+
+    /// Returns the rects in the insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = BoundingRect> + '_ {
+        (0..self.len()).map(move |idx| BoundingRect {
+            from: Point2D::new(self.x1[idx], self.y1[idx]),
+            to: Point2D::new(self.x2[idx], self.y2[idx]),
+        })
+    }
+
+    /// Returns the left edges of the rects.
+    pub fn x1(&self) -> &[f32] {
+        &self.x1
+    }
+
+    /// Returns the top edges of the rects.
+    pub fn y1(&self) -> &[f32] {
+        &self.y1
+    }
+
+    /// Returns the right edges of the rects.
+    pub fn x2(&self) -> &[f32] {
+        &self.x2
+    }
+
+    /// Returns the bottom edges of the rects.
+    pub fn y2(&self) -> &[f32] {
+        &self.y2
+    }
+}
+
+impl From<&[BoundingRect]> for RectSoA {
+    fn from(rects: &[BoundingRect]) -> Self {
+        rects.iter().copied().collect()
+    }
+}
+
+impl Extend<BoundingRect> for RectSoA {
+    fn extend<I: IntoIterator<Item = BoundingRect>>(&mut self, iter: I) {
+        for rect in iter {
+            self.push(rect);
+        }
+    }
+}
+
+impl FromIterator<BoundingRect> for RectSoA {
+    fn from_iter<I: IntoIterator<Item = BoundingRect>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut rects = Self::with_capacity(iter.size_hint().0);
+        for rect in iter {
+            rects.push(rect);
+        }
+        rects
+    }
+}
+
+impl Searched for RectSoA {
+    type Found = Intersection;
+
+    fn len(&self) -> usize {
+        self.x1.len()
+    }
+
+    fn rect(&self, idx: usize) -> BoundingRect {
+        BoundingRect {
+            from: Point2D::new(self.x1[idx], self.y1[idx]),
+            to: Point2D::new(self.x2[idx], self.y2[idx]),
+        }
+    }
+
+    fn left(&self, idx: usize) -> f32 {
+        self.x1[idx]
+    }
+
+    fn right(&self, idx: usize) -> f32 {
+        self.x2[idx]
+    }
+
+    fn rects(&self) -> Cow<'_, [BoundingRect]> {
+        Cow::Owned(self.iter().collect())
+    }
+
+    // Intersects the edges like `BoundingRect::intersect` without building the rects.
+    fn intersect(&self, a_idx: usize, b_idx: usize) -> Option<Intersection> {
+        let edges = |idx: usize| [(self.x1[idx], self.x2[idx]), (self.y1[idx], self.y2[idx])];
+        let (a, b) = (edges(a_idx), edges(b_idx));
+        let (x1, x2) = lines_intersection(a[0], b[0])?;
+        let (y1, y2) = lines_intersection(a[1], b[1])?;
+        Some(Intersection {
+            area: BoundingRect::from_points(Point2D::new(x1, y1), Point2D::new(x2, y2)),
+            a_idx,
+            b_idx,
+            mtv: overlap_mtv(a, b),
+        })
+    }
+
+    fn pair(found: &Intersection) -> (usize, usize) {
+        (found.a_idx, found.b_idx)
+    }
+
+    fn memory(&self) -> usize {
+        self.x1.len() * 4 * mem::size_of::<f32>()
+    }
+}
+
+/// Searches for intersecting rects stored as the arrays like [`list_intersections`], the
+/// intersections are the same and in the same order. It sweeps the edges sorted by the left
+/// ones like [`Algorithm::Sweep`].
+///
+/// [`list_intersections`]: crate::list_intersections
+pub fn list_intersections_soa(rects: &RectSoA) -> Vec<Intersection> {
+    list_intersections_soa_with_stats(rects).0
+}
+
+/// Searches for intersecting rects like [`list_intersections_soa`] and collects the search
+/// statistics, the candidate pairs are the pairs that have actually been tested.
+pub fn list_intersections_soa_with_stats(rects: &RectSoA) -> (Vec<Intersection>, AnalysisStats) {
+    list_intersections_soa_using_with_stats(rects, Algorithm::Sweep)
+}
+
+/// Searches for intersecting rects stored as the arrays with the given algorithm like
+/// [`list_intersections_using`], the algorithms read the edges from the arrays and test the
+/// pairs on them.
+///
+/// [`list_intersections_using`]: crate::list_intersections_using
+pub fn list_intersections_soa_using(rects: &RectSoA, algorithm: Algorithm) -> Vec<Intersection> {
+    list_intersections_soa_using_with_stats(rects, algorithm).0
+}
+
+/// Searches for intersecting rects like [`list_intersections_soa_using`] and collects the
+/// search statistics.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(shapes = rects.len(), ?algorithm))
+)]
+pub fn list_intersections_soa_using_with_stats(
+    rects: &RectSoA,
+    algorithm: Algorithm,
+) -> (Vec<Intersection>, AnalysisStats) {
+    search_with_stats(rects, algorithm)
+}
+
+#[test]
+fn test_rect_soa() {
+    use crate::{generate_scene, list_intersections, Distribution, GenerateOptions};
+
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D::new(x1, y1), Point2D::new(x2, y2))
+    };
+    let rects = vec![rect(0.0, 0.0, 4.0, 4.0), rect(4.0, 1.0, 6.0, 2.0)];
+    let soa = RectSoA::from(rects.as_slice());
+    assert_eq!(soa.len(), 2);
+    assert_eq!(soa.get(1), Some(rects[1]));
+    assert_eq!(soa.get(2), None);
+    assert_eq!(soa.iter().collect::<Vec<_>>(), rects);
+    assert_eq!(soa.x2(), &[4.0, 6.0]);
+    assert!(RectSoA::new().is_empty());
+
+    let mut cases = vec![
+        (
+            vec![
+                rect(0.0, 0.0, 4.0, 4.0),
+                // Touches the first one by the edge.
+                rect(4.0, 0.0, 6.0, 4.0),
+                // Degenerate rect inside the first one.
+                rect(2.0, 1.0, 2.0, 3.0),
+                rect(0.0, 0.0, 4.0, 4.0),
+                // Degenerate rect on the left edge of the previous one.
+                rect(0.0, 1.0, 0.0, 2.0),
+                rect(-10.0, -10.0, 10.0, 10.0),
+            ],
+            "edge cases".to_owned(),
+        ),
+        (vec![], "empty".to_owned()),
+    ];
+    for &distribution in &[Distribution::Uniform, Distribution::Clustered] {
+        let input = generate_scene(&GenerateOptions {
+            count: 300,
+            seed: 7,
+            density: 0.5,
+            distribution,
+        });
+        let rects = input
+            .objects
+            .iter()
+            .map(|object| object.area().area)
+            .collect();
+        cases.push((rects, format!("{:?}", distribution)));
+    }
+    for case in cases {
+        let soa = RectSoA::from(case.0.as_slice());
+        let expected = list_intersections(&case.0);
+        let (actual, stats) = list_intersections_soa_with_stats(&soa);
+        assert_eq!(actual, expected, "Test case \"{}\" has been failed", case.1);
+        assert_eq!(stats.intersections, actual.len());
+        for &algorithm in Algorithm::ALL {
+            assert_eq!(
+                list_intersections_soa_using(&soa, algorithm),
+                expected,
+                "Test case \"{} with {}\" has been failed",
+                case.1,
+                algorithm.name()
+            );
+        }
+    }
+}
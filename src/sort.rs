@@ -0,0 +1,151 @@
+use std::{cmp::Ordering, str::FromStr};
+
+use crate::{ObjectArea, ObjectIntersection, Output};
+
+/// Key the output areas and intersections are sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Keeps the order of the input objects and of the found intersections.
+    #[default]
+    Index,
+    /// Sorts the areas by the object name and the intersections by the pair of names.
+    Name,
+    /// Sorts by the area of the object or of the intersection.
+    Area,
+}
+
+impl SortKey {
+    /// Names of all supported sort keys.
+    pub const VARIANTS: &'static [&'static str] = &["index", "name", "area"];
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "index" => Ok(Self::Index),
+            "name" => Ok(Self::Name),
+            "area" => Ok(Self::Area),
+            other => Err(format!("Unknown sort key \"{}\"", other)),
+        }
+    }
+}
+
+/// Direction of the sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Sorts the items stably, so the equal items keep their index order in both directions.
+fn sort<T>(items: &mut [T], order: SortOrder, compare: impl Fn(&T, &T) -> Ordering) {
+    match order {
+        SortOrder::Ascending => items.sort_by(compare),
+        SortOrder::Descending => items.sort_by(|a, b| compare(b, a)),
+    }
+}
+
+impl Output {
+    /// Sorts both the areas and the intersections by the given key.
+    ///
+    /// Sorting by the index in the descending order reverses the lists, every other key
+    /// keeps the index order of the equal items.
+    pub fn sort(&mut self, key: SortKey, order: SortOrder) {
+        match key {
+            SortKey::Index => {
+                if order == SortOrder::Descending {
+                    self.areas.reverse();
+                    self.intersections.reverse();
+                }
+            }
+            SortKey::Name => {
+                sort(&mut self.areas, order, |a: &ObjectArea, b| {
+                    a.name.cmp(&b.name)
+                });
+                sort(
+                    &mut self.intersections,
+                    order,
+                    |a: &ObjectIntersection, b| a.names.cmp(&b.names),
+                );
+            }
+            SortKey::Area => {
+                sort(&mut self.areas, order, |a: &ObjectArea, b| {
+                    a.area.area().total_cmp(&b.area.area())
+                });
+                sort(
+                    &mut self.intersections,
+                    order,
+                    |a: &ObjectIntersection, b| a.area.area().total_cmp(&b.area.area()),
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_output_sort() {
+    let input = crate::SceneBuilder::new()
+        .object("C", 0.0, 0.0, 4.0, 4.0)
+        .object("A", 3.0, 3.0, 1.0, 1.0)
+        .object("B", 2.0, 0.0, 4.0, 4.0)
+        .build();
+    let output = crate::analyze(input, crate::Options::default());
+    fn names(output: &Output) -> (Vec<&str>, Vec<String>) {
+        (
+            output.areas.iter().map(|x| x.name.as_str()).collect(),
+            output
+                .intersections
+                .iter()
+                .map(|x| format!("{}{}", x.names.0, x.names.1))
+                .collect(),
+        )
+    }
+
+    let cases = vec![
+        (
+            SortKey::Index,
+            SortOrder::Ascending,
+            (vec!["C", "A", "B"], vec!["CA", "CB", "AB"]),
+            "index",
+        ),
+        (
+            SortKey::Index,
+            SortOrder::Descending,
+            (vec!["B", "A", "C"], vec!["AB", "CB", "CA"]),
+            "reversed index",
+        ),
+        (
+            SortKey::Name,
+            SortOrder::Ascending,
+            (vec!["A", "B", "C"], vec!["AB", "CA", "CB"]),
+            "name",
+        ),
+        (
+            SortKey::Area,
+            SortOrder::Ascending,
+            (vec!["A", "C", "B"], vec!["CA", "AB", "CB"]),
+            "area with ties",
+        ),
+        (
+            SortKey::Area,
+            SortOrder::Descending,
+            (vec!["C", "B", "A"], vec!["CB", "CA", "AB"]),
+            "descending area with ties",
+        ),
+    ];
+    for case in cases {
+        let mut sorted = output.clone();
+        sorted.sort(case.0, case.1);
+        let (areas, intersections) = names(&sorted);
+        assert_eq!(
+            (areas, intersections.iter().map(String::as_str).collect()),
+            case.2,
+            "Test case \"{}\" has been failed",
+            case.3
+        );
+    }
+    assert_eq!("area".parse(), Ok(SortKey::Area));
+}
@@ -0,0 +1,201 @@
+use std::fmt::Write;
+
+use crate::{AnyShape, Output};
+
+// Quotes every part of the possibly schema qualified table name, e.g. `public.collisions`.
+fn quote_table(table: &str) -> String {
+    table
+        .split('.')
+        .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn quote_text(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn number(value: Option<f32>) -> String {
+    value.map_or_else(|| "NULL".to_owned(), |value| value.to_string())
+}
+
+fn geometry(shape: &AnyShape, srid: Option<i32>) -> String {
+    let wkt = quote_text(&shape.to_wkt());
+    match srid {
+        Some(srid) => format!("ST_GeomFromText({}, {})", wkt, srid),
+        None => format!("ST_GeomFromText({})", wkt),
+    }
+}
+
+fn geometry_type(kind: &str, srid: Option<i32>) -> String {
+    match srid {
+        Some(srid) => format!("geometry({}, {})", kind, srid),
+        None => format!("geometry({})", kind),
+    }
+}
+
+// Writes the table definition and one `INSERT` per row in a single transaction.
+fn script(table: &str, columns: &[(&str, &str)], rows: Vec<Vec<String>>) -> Vec<u8> {
+    let table = quote_table(table);
+    let mut sql = String::from("BEGIN;\n");
+    let definitions = columns
+        .iter()
+        .map(|(name, kind)| format!("    {} {}", name, kind))
+        .collect::<Vec<_>>()
+        .join(",\n");
+    writeln!(
+        sql,
+        "CREATE TABLE IF NOT EXISTS {} (\n{}\n);",
+        table, definitions
+    )
+    .unwrap();
+    let names = columns
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ");
+    for row in rows {
+        writeln!(
+            sql,
+            "INSERT INTO {} ({}) VALUES ({});",
+            table,
+            names,
+            row.join(", ")
+        )
+        .unwrap();
+    }
+    sql.push_str("COMMIT;\n");
+    sql.into_bytes()
+}
+
+impl Output {
+    /// Writes the intersections as the PostGIS SQL script: the table is created if it does not
+    /// exist and every intersection is inserted with the names of both objects, its area, the
+    /// metrics (`NULL` unless computed) and the rect as the WKT polygon of the given SRID. The
+    /// table is `intersections` unless given.
+    pub fn intersections_to_sql(&self, table: Option<&str>, srid: Option<i32>) -> Vec<u8> {
+        let geom = geometry_type("Polygon", srid);
+        let columns = [
+            ("first", "text NOT NULL"),
+            ("second", "text NOT NULL"),
+            ("area", "double precision NOT NULL"),
+            ("iou", "double precision"),
+            ("first_coverage", "double precision"),
+            ("second_coverage", "double precision"),
+            ("geom", geom.as_str()),
+        ];
+        let rows = self
+            .intersections
+            .iter()
+            .map(|intersection| {
+                let metrics = intersection.metrics.as_ref();
+                vec![
+                    quote_text(&intersection.names.0),
+                    quote_text(&intersection.names.1),
+                    intersection.area.area().to_string(),
+                    number(metrics.map(|metrics| metrics.iou)),
+                    number(metrics.map(|metrics| metrics.coverage.0)),
+                    number(metrics.map(|metrics| metrics.coverage.1)),
+                    geometry(&AnyShape::Rect(intersection.area), srid),
+                ]
+            })
+            .collect();
+        script(table.unwrap_or("intersections"), &columns, rows)
+    }
+
+    /// Writes the areas as the PostGIS SQL script like [`Output::intersections_to_sql`], the
+    /// exact shapes are inserted as the polygons and the line strings. The table is `areas`
+    /// unless given.
+    pub fn areas_to_sql(&self, table: Option<&str>, srid: Option<i32>) -> Vec<u8> {
+        let geom = geometry_type("Geometry", srid);
+        let columns = [
+            ("name", "text NOT NULL"),
+            ("layer", "text"),
+            ("z", "integer"),
+            ("area", "double precision NOT NULL"),
+            ("geom", geom.as_str()),
+        ];
+        let rows = self
+            .areas
+            .iter()
+            .map(|area| {
+                vec![
+                    quote_text(&area.name),
+                    area.layer
+                        .as_deref()
+                        .map_or_else(|| "NULL".to_owned(), quote_text),
+                    area.z.map_or_else(|| "NULL".to_owned(), |z| z.to_string()),
+                    area.area.area().to_string(),
+                    geometry(&area.exact_shape(), srid),
+                ]
+            })
+            .collect();
+        script(table.unwrap_or("areas"), &columns, rows)
+    }
+}
+
+#[test]
+fn test_sql() {
+    use crate::{analyze, Input, Object, Options, OutputFormat, OutputSection};
+
+    let object = |name: &str, rect: [f32; 4]| Object {
+        name: name.to_owned(),
+        x: rect[0],
+        y: rect[1],
+        width: rect[2],
+        height: rect[3],
+        layer: None,
+        z: None,
+        rotation: None,
+        shape: None,
+        properties: Vec::new(),
+    };
+    let input = Input {
+        objects: vec![
+            Object {
+                layer: Some("walls".to_owned()),
+                ..object("O'Hare", [0.0, 0.0, 4.0, 4.0])
+            },
+            object("house", [3.0, 3.0, 3.0, 2.0]),
+        ],
+    };
+    let output = analyze(
+        input,
+        Options {
+            metrics: true,
+            ..Options::default()
+        },
+    );
+
+    let sql = String::from_utf8(output.intersections_to_sql(Some("public.collisions"), Some(3857)))
+        .unwrap();
+    assert_eq!(
+        sql,
+        "BEGIN;\n\
+         CREATE TABLE IF NOT EXISTS \"public\".\"collisions\" (\n    \
+         first text NOT NULL,\n    \
+         second text NOT NULL,\n    \
+         area double precision NOT NULL,\n    \
+         iou double precision,\n    \
+         first_coverage double precision,\n    \
+         second_coverage double precision,\n    \
+         geom geometry(Polygon, 3857)\n);\n\
+         INSERT INTO \"public\".\"collisions\" (first, second, area, iou, first_coverage, \
+         second_coverage, geom) VALUES ('O''Hare', 'house', 1, 0.04761905, 6.25, 16.666668, \
+         ST_GeomFromText('POLYGON ((3 3, 4 3, 4 4, 3 4, 3 3))', 3857));\n\
+         COMMIT;\n"
+    );
+
+    let areas = output
+        .section_to_vec_as(OutputSection::Areas, OutputFormat::Sql)
+        .unwrap();
+    let areas = String::from_utf8(areas).unwrap();
+    assert!(areas.contains("geom geometry(Geometry)\n"));
+    assert!(areas.contains("VALUES ('O''Hare', 'walls', NULL, 16, ST_GeomFromText("));
+    assert!(areas.contains("VALUES ('house', NULL, NULL, 6, ST_GeomFromText("));
+    assert_eq!(
+        output.to_vec_as(OutputFormat::Sql).unwrap(),
+        output.intersections_to_sql(None, None)
+    );
+    assert!(Output::from_reader_as(sql.as_bytes(), OutputFormat::Sql).is_err());
+}
@@ -0,0 +1,417 @@
+use std::{io::Read, path::Path};
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+
+use crate::{
+    BoundingRect, Error, Input, IntersectionMetrics, Object, ObjectIntersection, Output, Point2D,
+};
+
+// Every stored analysis is a run, the objects and the intersections refer to their runs, so
+// the runs can be compared with the plain SQL.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY,
+        label TEXT,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE IF NOT EXISTS objects (
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        name TEXT NOT NULL,
+        x REAL NOT NULL,
+        y REAL NOT NULL,
+        width REAL NOT NULL,
+        height REAL NOT NULL,
+        layer TEXT,
+        z INTEGER,
+        rotation REAL,
+        shape TEXT,
+        properties TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS intersections (
+        run_id INTEGER NOT NULL REFERENCES runs (id),
+        first TEXT NOT NULL,
+        second TEXT NOT NULL,
+        x REAL NOT NULL,
+        y REAL NOT NULL,
+        width REAL NOT NULL,
+        height REAL NOT NULL,
+        area REAL,
+        iou REAL,
+        first_coverage REAL,
+        second_coverage REAL,
+        properties TEXT
+    );
+    CREATE INDEX IF NOT EXISTS objects_run_id ON objects (run_id);
+    CREATE INDEX IF NOT EXISTS intersections_run_id ON intersections (run_id);
+";
+
+fn parse_error(reason: impl Into<anyhow::Error>) -> Error {
+    Error::ParseError {
+        reason: reason.into(),
+    }
+}
+
+fn serialize_error(reason: impl Into<anyhow::Error>) -> Error {
+    Error::SerializeError {
+        reason: reason.into(),
+    }
+}
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> BoundingRect {
+    BoundingRect::from_points(Point2D::new(x, y), Point2D::new(x + width, y + height))
+}
+
+// Returns the given run or the latest one if no run is given.
+fn find_run(connection: &Connection, run: Option<i64>) -> Result<i64, Error> {
+    // The databases without the runs table are never stored into.
+    let stored: bool = connection
+        .query_row(
+            "SELECT count(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'runs'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(parse_error)?;
+    if !stored {
+        return Err(parse_error(anyhow::anyhow!("The database has no runs")));
+    }
+    let found = match run {
+        Some(run) => connection
+            .query_row("SELECT id FROM runs WHERE id = ?1", [run], |row| row.get(0))
+            .optional(),
+        None => connection
+            .query_row("SELECT max(id) FROM runs", [], |row| row.get(0))
+            .optional()
+            .map(Option::flatten),
+    }
+    .map_err(parse_error)?;
+    found.ok_or_else(|| match run {
+        Some(run) => parse_error(anyhow::anyhow!("The run {} is not found", run)),
+        None => parse_error(anyhow::anyhow!("The database has no runs")),
+    })
+}
+
+// Opens the stored database, the missing files are not created.
+fn open(path: &Path) -> Result<Connection, Error> {
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(|err| {
+        Error::FileReadError {
+            path: path.to_owned(),
+            reason: err.into(),
+        }
+    })
+}
+
+/// Appends the scene and its analysis results to the SQLite database as a new run and returns
+/// the id of the run, the tables are created if the database is new. The objects are stored
+/// into the `objects` table and the intersections into the `intersections` table, both refer
+/// to the `runs` table by their `run_id`. The shapes, the properties and the missing metrics
+/// are stored as JSON and as `NULL`s, e.g. the new intersections of the latest run are
+/// selected with:
+///
+/// ```sql
+/// SELECT first, second FROM intersections WHERE run_id = 2
+/// EXCEPT SELECT first, second FROM intersections WHERE run_id = 1
+/// ```
+pub fn store_sqlite<P: AsRef<Path>>(
+    path: P,
+    label: Option<&str>,
+    input: &Input,
+    output: &Output,
+) -> Result<i64, Error> {
+    let path = path.as_ref();
+    let mut connection = Connection::open(path).map_err(|err| Error::FileReadError {
+        path: path.to_owned(),
+        reason: err.into(),
+    })?;
+    let transaction = connection.transaction().map_err(serialize_error)?;
+    transaction.execute_batch(SCHEMA).map_err(serialize_error)?;
+    transaction
+        .execute("INSERT INTO runs (label) VALUES (?1)", [label])
+        .map_err(serialize_error)?;
+    let run = transaction.last_insert_rowid();
+
+    {
+        let mut insert = transaction
+            .prepare(
+                "INSERT INTO objects (run_id, name, x, y, width, height, layer, z, rotation, \
+                 shape, properties) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            )
+            .map_err(serialize_error)?;
+        for object in &input.objects {
+            let shape = object
+                .shape
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(serialize_error)?;
+            let properties = serde_json::to_string(&object.properties).map_err(serialize_error)?;
+            insert
+                .execute(params![
+                    run,
+                    object.name,
+                    object.x,
+                    object.y,
+                    object.width,
+                    object.height,
+                    object.layer,
+                    object.z,
+                    object.rotation,
+                    shape,
+                    properties,
+                ])
+                .map_err(serialize_error)?;
+        }
+
+        let mut insert = transaction
+            .prepare(
+                "INSERT INTO intersections (run_id, first, second, x, y, width, height, area, \
+                 iou, first_coverage, second_coverage, properties) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )
+            .map_err(serialize_error)?;
+        for intersection in &output.intersections {
+            let area = &intersection.area;
+            let metrics = intersection.metrics.as_ref();
+            let properties = intersection
+                .properties
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(serialize_error)?;
+            insert
+                .execute(params![
+                    run,
+                    intersection.names.0,
+                    intersection.names.1,
+                    area.from.x,
+                    area.from.y,
+                    area.to.x - area.from.x,
+                    area.to.y - area.from.y,
+                    metrics.map(|metrics| metrics.area),
+                    metrics.map(|metrics| metrics.iou),
+                    metrics.map(|metrics| metrics.coverage.0),
+                    metrics.map(|metrics| metrics.coverage.1),
+                    properties,
+                ])
+                .map_err(serialize_error)?;
+        }
+    }
+    transaction.commit().map_err(serialize_error)?;
+    Ok(run)
+}
+
+// Loads the objects of the run in their stored order.
+fn load_objects(connection: &Connection, run: i64) -> Result<Vec<Object>, Error> {
+    let mut select = connection
+        .prepare(
+            "SELECT name, x, y, width, height, layer, z, rotation, shape, properties \
+             FROM objects WHERE run_id = ?1 ORDER BY rowid",
+        )
+        .map_err(parse_error)?;
+    let rows = select
+        .query_map([run], |row| {
+            Ok((
+                Object {
+                    name: row.get(0)?,
+                    x: row.get(1)?,
+                    y: row.get(2)?,
+                    width: row.get(3)?,
+                    height: row.get(4)?,
+                    layer: row.get(5)?,
+                    z: row.get(6)?,
+                    rotation: row.get(7)?,
+                    shape: None,
+                    properties: Vec::new(),
+                },
+                row.get::<_, Option<String>>(8)?,
+                row.get::<_, String>(9)?,
+            ))
+        })
+        .map_err(parse_error)?;
+
+    let mut objects = Vec::new();
+    for row in rows {
+        let (mut object, shape, properties) = row.map_err(parse_error)?;
+        object.shape = shape
+            .map(|shape| serde_json::from_str(&shape))
+            .transpose()
+            .map_err(parse_error)?;
+        object.properties = serde_json::from_str(&properties).map_err(parse_error)?;
+        objects.push(object);
+    }
+    Ok(objects)
+}
+
+impl Input {
+    /// Loads the scene of the given run stored by [`store_sqlite`], the latest run is loaded if
+    /// no run is given.
+    pub fn from_sqlite<P: AsRef<Path>>(path: P, run: Option<i64>) -> Result<Self, Error> {
+        let connection = open(path.as_ref())?;
+        let run = find_run(&connection, run)?;
+        Ok(Self {
+            objects: load_objects(&connection, run)?,
+        })
+    }
+
+    /// Loads the latest run of the SQLite database like [`Input::from_sqlite`], the database is
+    /// copied into a temporary file first.
+    pub fn from_sqlite_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut file = tempfile::NamedTempFile::new().map_err(parse_error)?;
+        std::io::copy(&mut reader, &mut file).map_err(parse_error)?;
+        Self::from_sqlite(file.path(), None)
+    }
+}
+
+impl Output {
+    /// Loads the results of the given run stored by [`store_sqlite`], the latest run is loaded
+    /// if no run is given. The areas are computed from the stored objects, so they have no
+    /// properties, and the statistics are not stored at all.
+    pub fn from_sqlite<P: AsRef<Path>>(path: P, run: Option<i64>) -> Result<Self, Error> {
+        let connection = open(path.as_ref())?;
+        let run = find_run(&connection, run)?;
+        let areas = load_objects(&connection, run)?
+            .iter()
+            .map(Object::area)
+            .collect();
+        let mut select = connection
+            .prepare(
+                "SELECT first, second, x, y, width, height, area, iou, first_coverage, \
+                 second_coverage, properties FROM intersections WHERE run_id = ?1 ORDER BY rowid",
+            )
+            .map_err(parse_error)?;
+        let rows = select
+            .query_map([run], |row| {
+                let area: Option<f32> = row.get(6)?;
+                let metrics = match (area, row.get(7)?, row.get(8)?, row.get(9)?) {
+                    (Some(area), Some(iou), Some(first), Some(second)) => {
+                        Some(IntersectionMetrics {
+                            area,
+                            iou,
+                            coverage: (first, second),
+                        })
+                    }
+                    _ => None,
+                };
+                Ok((
+                    ObjectIntersection {
+                        names: (row.get(0)?, row.get(1)?),
+                        area: rect(row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?),
+                        metrics,
+                        properties: None,
+                    },
+                    row.get::<_, Option<String>>(10)?,
+                ))
+            })
+            .map_err(parse_error)?;
+
+        let mut intersections = Vec::new();
+        for row in rows {
+            let (mut intersection, properties) = row.map_err(parse_error)?;
+            intersection.properties = properties
+                .map(|properties| serde_json::from_str(&properties))
+                .transpose()
+                .map_err(parse_error)?;
+            intersections.push(intersection);
+        }
+        Ok(Self {
+            page: None,
+            unit: None,
+            areas,
+            intersections,
+            stats: None,
+        })
+    }
+}
+
+#[test]
+fn test_sqlite() {
+    use crate::{analyze, AnyShape, Options};
+
+    let object = |name: &str, rect: [f32; 4]| Object {
+        name: name.to_owned(),
+        x: rect[0],
+        y: rect[1],
+        width: rect[2],
+        height: rect[3],
+        layer: None,
+        z: None,
+        rotation: None,
+        shape: None,
+        properties: Vec::new(),
+    };
+    let first = Input {
+        objects: vec![
+            Object {
+                layer: Some("walls".to_owned()),
+                z: Some(2),
+                properties: vec![serde_json::json!({"name": "color", "value": "red"})],
+                ..object("a", [0.0, 0.0, 4.0, 4.0])
+            },
+            object("b", [3.0, 3.0, 3.0, 2.0]),
+            Object {
+                rotation: Some(45.0),
+                ..Object::from_shape(
+                    "c".to_owned(),
+                    AnyShape::Circle {
+                        center: Point2D::new(10.0, 10.0),
+                        radius: 1.0,
+                    },
+                )
+            },
+        ],
+    };
+    let mut second = first.clone();
+    second.objects[1].width = 10.0;
+    second.objects[1].height = 10.0;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("runs.db");
+    let options = Options {
+        metrics: true,
+        ..Options::default()
+    };
+    let outputs = [
+        analyze(first.clone(), options.clone()),
+        analyze(second.clone(), options),
+    ];
+    assert_eq!(
+        store_sqlite(&path, Some("first"), &first, &outputs[0]).unwrap(),
+        1
+    );
+    assert_eq!(store_sqlite(&path, None, &second, &outputs[1]).unwrap(), 2);
+
+    assert_eq!(Input::from_sqlite(&path, Some(1)).unwrap(), first);
+    assert_eq!(Input::from_sqlite(&path, None).unwrap(), second);
+    let bytes = std::fs::read(&path).unwrap();
+    assert_eq!(Input::from_sqlite_reader(bytes.as_slice()).unwrap(), second);
+    for (run, output) in outputs.iter().enumerate() {
+        let loaded = Output::from_sqlite(&path, Some(run as i64 + 1)).unwrap();
+        assert_eq!(&loaded, output);
+    }
+
+    let connection = Connection::open(&path).unwrap();
+    let added: Vec<(String, String)> = connection
+        .prepare(
+            "SELECT first, second FROM intersections WHERE run_id = 2 \
+             EXCEPT SELECT first, second FROM intersections WHERE run_id = 1",
+        )
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(added, vec![("b".to_owned(), "c".to_owned())]);
+
+    let err = Input::from_sqlite(&path, Some(3)).unwrap_err();
+    assert!(
+        err.to_string().contains("The run 3 is not found"),
+        "{}",
+        err
+    );
+    let empty = dir.path().join("empty.db");
+    Connection::open(&empty)
+        .unwrap()
+        .execute_batch("CREATE TABLE other (x)")
+        .unwrap();
+    let err = Output::from_sqlite(&empty, None).unwrap_err();
+    assert!(err.to_string().contains("no runs"), "{}", err);
+}
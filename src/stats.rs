@@ -0,0 +1,59 @@
+use std::{mem, time::Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BoundingRect, Intersection};
+
+/// Wall time spent in the analysis phase.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PhaseTime {
+    /// Phase name.
+    pub name: String,
+    /// Elapsed time in milliseconds.
+    pub millis: f64,
+}
+
+/// Analysis statistics.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AnalysisStats {
+    /// Number of the processed shapes.
+    pub shapes: usize,
+    /// Number of the shape pairs that have been tested for the intersection.
+    pub candidate_pairs: usize,
+    /// Number of the confirmed intersections.
+    pub intersections: usize,
+    /// Wall time of the analysis phases in the execution order.
+    pub phases: Vec<PhaseTime>,
+    /// Estimated peak memory usage of the analysis data in bytes.
+    pub peak_memory_estimate: usize,
+}
+
+impl AnalysisStats {
+    /// Measures the wall time of the closure and records it as the phase with the given name.
+    pub fn measure<T>(&mut self, name: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let value = f();
+        self.phases.push(PhaseTime {
+            name: name.to_owned(),
+            millis: start.elapsed().as_secs_f64() * 1e3,
+        });
+        value
+    }
+
+    /// Merges the statistics of the nested step into this one.
+    pub(crate) fn merge(&mut self, other: AnalysisStats) {
+        self.shapes = self.shapes.max(other.shapes);
+        self.candidate_pairs += other.candidate_pairs;
+        self.intersections += other.intersections;
+        self.phases.extend(other.phases);
+        self.peak_memory_estimate = self.peak_memory_estimate.max(other.peak_memory_estimate);
+    }
+}
+
+// Estimates the memory used by the search over the shapes.
+pub(crate) fn search_memory_estimate<S>(shapes: usize, intersections: usize) -> usize {
+    shapes * (mem::size_of::<S>() + mem::size_of::<BoundingRect>())
+        + intersections * mem::size_of::<Intersection>()
+}
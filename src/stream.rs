@@ -0,0 +1,68 @@
+use futures::{future, stream, Stream, StreamExt};
+
+use crate::{Intersection, Shape};
+
+/// Searches for intersecting shapes in the asynchronous stream of shapes.
+///
+/// Intersections are yielded as soon as the second shape of the pair arrives, so the
+/// intersections are ordered by the second shape index rather than by the first one
+/// as in [`list_intersections`](crate::list_intersections). Every received shape is kept
+/// until the source stream ends, and each new shape is tested against all of them.
+pub fn stream_intersections<St>(shapes: St) -> impl Stream<Item = Intersection>
+where
+    St: Stream,
+    St::Item: Shape,
+{
+    shapes
+        .enumerate()
+        .scan(Vec::new(), |seen: &mut Vec<St::Item>, (b_idx, shape)| {
+            let b_rect = shape.bounding_rect();
+            let found = seen
+                .iter()
+                .enumerate()
+                .filter_map(|(a_idx, other)| {
+                    let area = other.intersection(&shape)?;
+                    let mtv = other.bounding_rect().mtv(&b_rect)?;
+                    Some(Intersection {
+                        area,
+                        a_idx,
+                        b_idx,
+                        mtv,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            seen.push(shape);
+            future::ready(Some(stream::iter(found)))
+        })
+        .flatten()
+}
+
+#[test]
+fn test_stream_intersections() {
+    use crate::{list_intersections, BoundingRect, Point2D};
+
+    let rect = |x1: f32, y1: f32, x2: f32, y2: f32| {
+        BoundingRect::from_points(Point2D { x: x1, y: y1 }, Point2D { x: x2, y: y2 })
+    };
+    let objects = vec![
+        rect(0.0, 0.0, 4.0, 4.0),
+        rect(10.0, 10.0, 12.0, 12.0),
+        rect(3.0, 3.0, 11.0, 11.0),
+        rect(1.0, 1.0, 2.0, 2.0),
+    ];
+
+    let mut actual = futures::executor::block_on(
+        stream_intersections(stream::iter(objects.clone())).collect::<Vec<_>>(),
+    );
+    assert_eq!(
+        actual
+            .iter()
+            .map(|x| (x.a_idx, x.b_idx))
+            .collect::<Vec<_>>(),
+        vec![(0, 2), (1, 2), (0, 3)]
+    );
+
+    actual.sort_by_key(|x| (x.a_idx, x.b_idx));
+    assert_eq!(actual, list_intersections(&objects));
+}
@@ -0,0 +1,433 @@
+use std::{convert::TryFrom, fmt, io::Read};
+
+use serde_json::Value;
+
+use crate::{decompress, validate::OBJECT_FIELDS, CsvColumns, Error, Input, InputFormat};
+
+/// Field of the input violating the strict schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// Path of the field, e.g. `objects[2].widht`, or the CSV line and column.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Collects the violations of the strict schema.
+#[derive(Default)]
+struct Checker {
+    violations: Vec<SchemaViolation>,
+}
+
+impl Checker {
+    fn report(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.violations.push(SchemaViolation {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Reports the fields of the map not listed in the known ones.
+    fn unknown_fields(&mut self, path: &str, map: &serde_json::Map<String, Value>, known: &[&str]) {
+        for key in map.keys().filter(|key| !known.contains(&key.as_str())) {
+            self.report(join(path, key), "unknown field");
+        }
+    }
+
+    fn map<'a>(
+        &mut self,
+        path: &str,
+        value: &'a Value,
+    ) -> Option<&'a serde_json::Map<String, Value>> {
+        let map = value.as_object();
+        if map.is_none() {
+            self.report(path, format!("expected an object, got {}", kind(value)));
+        }
+        map
+    }
+
+    fn number(&mut self, path: String, value: Option<&Value>) {
+        match value {
+            None => self.report(path, "missing field"),
+            Some(Value::Number(number)) => {
+                if !number.as_f64().is_some_and(|x| (x as f32).is_finite()) {
+                    self.report(path, "the number is out of range");
+                }
+            }
+            Some(value) => self.report(path, format!("expected a number, got {}", kind(value))),
+        }
+    }
+
+    fn point(&mut self, path: String, value: Option<&Value>) {
+        let Some(value) = value else {
+            return self.report(path, "missing field");
+        };
+        if let Some(map) = self.map(&path, value) {
+            self.unknown_fields(&path, map, &["x", "y"]);
+            self.number(join(&path, "x"), map.get("x"));
+            self.number(join(&path, "y"), map.get("y"));
+        }
+    }
+
+    fn object(&mut self, path: &str, value: &Value) {
+        let Some(map) = self.map(path, value) else {
+            return;
+        };
+        self.unknown_fields(path, map, OBJECT_FIELDS);
+
+        match map.get("name") {
+            None => self.report(join(path, "name"), "missing field"),
+            Some(Value::String(_)) => {}
+            Some(value) => self.report(
+                join(path, "name"),
+                format!("expected a string, got {}", kind(value)),
+            ),
+        }
+        if let Some(value) = map.get("layer").filter(|value| !value.is_string()) {
+            let message = format!("expected a string, got {}", kind(value));
+            self.report(join(path, "layer"), message);
+        }
+        if let Some(value) = map
+            .get("z")
+            .filter(|value| value.as_i64().is_none_or(|z| i32::try_from(z).is_err()))
+        {
+            let message = format!("expected an integer level, got {}", kind(value));
+            self.report(join(path, "z"), message);
+        }
+        if map.contains_key("rotation") {
+            self.number(join(path, "rotation"), map.get("rotation"));
+        }
+        if let Some(value) = map.get("properties").filter(|value| !value.is_array()) {
+            let message = format!("expected an array, got {}", kind(value));
+            self.report(join(path, "properties"), message);
+        }
+
+        let shape = match map.get("kind") {
+            None => "rect",
+            Some(Value::String(shape)) => shape.as_str(),
+            Some(value) => {
+                let message = format!("expected a string, got {}", kind(value));
+                return self.report(join(path, "kind"), message);
+            }
+        };
+        let fields: &[&str] = match shape {
+            "rect" => &["from", "to"],
+            "circle" => &["center", "radius"],
+            "polygon" => &["vertices"],
+            "segment" => &["from", "to"],
+            other => {
+                let message = format!(
+                    "unknown kind \"{}\", expected rect, circle, polygon or segment",
+                    other
+                );
+                return self.report(join(path, "kind"), message);
+            }
+        };
+        for field in ["from", "to", "center", "radius", "vertices"] {
+            if map.contains_key(field) && !fields.contains(&field) {
+                let message = format!("is not a field of the {} objects", shape);
+                self.report(join(path, field), message);
+            }
+        }
+
+        match shape {
+            "rect" if map.contains_key("from") || map.contains_key("to") => {
+                for field in ["x", "y", "width", "height"] {
+                    if map.contains_key(field) {
+                        let message = "cannot be combined with the \"from\" and \"to\" corners";
+                        self.report(join(path, field), message);
+                    }
+                }
+                self.point(join(path, "from"), map.get("from"));
+                self.point(join(path, "to"), map.get("to"));
+            }
+            "rect" => {
+                for field in ["x", "y", "width", "height"] {
+                    self.number(join(path, field), map.get(field));
+                }
+            }
+            _ => {
+                // The bounding rect written next to the shape is optional.
+                for field in ["x", "y", "width", "height"] {
+                    if map.contains_key(field) {
+                        self.number(join(path, field), map.get(field));
+                    }
+                }
+                for field in fields {
+                    let field_path = join(path, field);
+                    match (*field, map.get(*field)) {
+                        ("radius", value) => self.number(field_path, value),
+                        ("vertices", Some(Value::Array(vertices))) => {
+                            for (idx, vertex) in vertices.iter().enumerate() {
+                                self.point(format!("{}[{}]", field_path, idx), Some(vertex));
+                            }
+                        }
+                        ("vertices", Some(value)) => {
+                            let message = format!("expected an array, got {}", kind(value));
+                            self.report(field_path, message);
+                        }
+                        (_, value) => self.point(field_path, value),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_owned()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Checks the input parsed into the generic value against the strict schema and reports
+/// every offending field of the objects: the unknown fields, the values of the wrong types,
+/// the numbers that do not fit `f32` and the objects mixing the origin-size and the corners
+/// forms.
+pub fn check_strict(input: &Value) -> Vec<SchemaViolation> {
+    let mut checker = Checker::default();
+    // The other top level fields are the attributes of the container, e.g. the Tiled layer.
+    if let Some(map) = checker.map("", input) {
+        match map.get("objects") {
+            None => checker.report("objects", "missing field"),
+            Some(Value::Array(objects)) => {
+                for (idx, object) in objects.iter().enumerate() {
+                    checker.object(&format!("objects[{}]", idx), object);
+                }
+            }
+            Some(value) => {
+                let message = format!("expected an array, got {}", kind(value));
+                checker.report("objects", message);
+            }
+        }
+    }
+    checker.violations
+}
+
+fn violations_error(violations: Vec<SchemaViolation>) -> Error {
+    let list = violations
+        .iter()
+        .map(|violation| format!("\n  {}", violation))
+        .collect::<String>();
+    Error::ParseError {
+        reason: anyhow::anyhow!(
+            "Found {} strict schema violation(s):{}",
+            violations.len(),
+            list
+        ),
+    }
+}
+
+impl Input {
+    /// Parses the input like [`Input::from_reader_as`], but fails on the unknown fields and the
+    /// values of the wrong types instead of ignoring or coercing them, see [`check_strict`].
+    /// The error lists every offending field with its path.
+    pub fn from_reader_strict<R: Read>(reader: R, format: InputFormat) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let mut reader = decompress::decompress(reader).map_err(|err| parse_error(err.into()))?;
+        let value: Value = match format {
+            InputFormat::Json => {
+                serde_json::from_reader(reader).map_err(|err| parse_error(err.into()))?
+            }
+            InputFormat::Yaml => {
+                serde_yaml::from_reader(reader).map_err(|err| parse_error(err.into()))?
+            }
+            InputFormat::Csv => {
+                let mut bytes = Vec::new();
+                reader
+                    .read_to_end(&mut bytes)
+                    .map_err(|err| parse_error(err.into()))?;
+                return Self::from_csv_bytes_strict(&bytes, &CsvColumns::default());
+            }
+            InputFormat::Toml => {
+                let mut text = String::new();
+                reader
+                    .read_to_string(&mut text)
+                    .map_err(|err| parse_error(err.into()))?;
+                toml::from_str(&text).map_err(|err| parse_error(err.into()))?
+            }
+            InputFormat::Ndjson => {
+                let objects =
+                    serde_json::Deserializer::from_reader(std::io::BufReader::new(reader))
+                        .into_iter::<Value>()
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|err| parse_error(err.into()))?;
+                serde_json::json!({ "objects": objects })
+            }
+            InputFormat::MessagePack => {
+                rmp_serde::from_read(reader).map_err(|err| parse_error(err.into()))?
+            }
+            InputFormat::Cbor => {
+                ciborium::from_reader(reader).map_err(|err| parse_error(err.into()))?
+            }
+            // The annotations and the GeoJSON features are not objects, they are checked by
+            // their own readers.
+            InputFormat::Coco => return Self::from_coco_reader(reader),
+            InputFormat::Yolo => return Self::from_yolo_reader(reader, Default::default()),
+            InputFormat::Voc => return Self::from_voc_reader(reader),
+            InputFormat::GeoJson => return Self::from_geojson_reader(reader),
+            InputFormat::Wkt => return Self::from_wkt_reader(reader),
+            InputFormat::Svg => return Self::from_svg_reader(reader),
+            InputFormat::Tiled => return Self::from_tiled_reader(reader),
+            InputFormat::Shapefile
+            | InputFormat::Dxf
+            | InputFormat::Parquet
+            | InputFormat::Arrow
+            | InputFormat::Sqlite => return Self::from_reader_as(reader, format),
+        };
+
+        let violations = check_strict(&value);
+        if !violations.is_empty() {
+            return Err(violations_error(violations));
+        }
+        serde_json::from_value(value).map_err(|err| parse_error(err.into()))
+    }
+
+    /// Parses the CSV table like [`Input::from_csv_reader`], but fails on the unknown columns
+    /// and on the cells that are not exactly the numbers, e.g. with the padding spaces.
+    pub fn from_csv_reader_strict<R: Read>(reader: R, columns: &CsvColumns) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        decompress::decompress(reader)
+            .and_then(|mut reader| reader.read_to_end(&mut bytes))
+            .map_err(|err| Error::ParseError { reason: err.into() })?;
+        Self::from_csv_bytes_strict(&bytes, columns)
+    }
+
+    // Checks and parses the already decompressed CSV table.
+    fn from_csv_bytes_strict(bytes: &[u8], columns: &CsvColumns) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let mut reader = csv::Reader::from_reader(bytes);
+        let headers = reader
+            .headers()
+            .map_err(|err| parse_error(err.into()))?
+            .clone();
+        let known = [
+            &columns.name,
+            &columns.x,
+            &columns.y,
+            &columns.width,
+            &columns.height,
+            &columns.layer,
+            &columns.z,
+            &columns.rotation,
+            &columns.geometry,
+        ];
+        let mut checker = Checker::default();
+        for header in headers
+            .iter()
+            .filter(|header| !known.contains(&&header.to_string()))
+        {
+            checker.report(format!("column \"{}\"", header), "unknown column");
+        }
+        let numbers = [&columns.x, &columns.y, &columns.width, &columns.height];
+        for record in reader.records() {
+            let record = record.map_err(|err| parse_error(err.into()))?;
+            let line = record.position().map_or(0, |pos| pos.line());
+            // The rects of the shapes are taken from the geometry column.
+            let shape = headers
+                .iter()
+                .position(|header| header == columns.geometry)
+                .and_then(|idx| record.get(idx))
+                .is_some_and(|field| !field.trim().is_empty());
+            for (header, field) in headers.iter().zip(&record) {
+                // The empty rotation cells mean no rotation.
+                let optional = (*header == columns.rotation || shape) && field.trim().is_empty();
+                if (numbers.contains(&&header.to_string()) || *header == columns.rotation)
+                    && !optional
+                    && !field.parse::<f32>().is_ok_and(f32::is_finite)
+                {
+                    let path = format!("line {}, column \"{}\"", line, header);
+                    checker.report(path, format!("expected a number, got \"{}\"", field));
+                }
+                // The empty level cells mean no level.
+                if *header == columns.z && !field.is_empty() && field.parse::<i32>().is_err() {
+                    let path = format!("line {}, column \"{}\"", line, header);
+                    checker.report(path, format!("expected an integer, got \"{}\"", field));
+                }
+            }
+        }
+        if !checker.violations.is_empty() {
+            return Err(violations_error(checker.violations));
+        }
+        Self::from_csv_plain(bytes, columns)
+    }
+}
+
+#[test]
+fn test_check_strict() {
+    let input = serde_json::json!({
+        "objects": [
+            { "name": "A", "x": 0, "y": 0, "width": 1, "height": 1, "layer": "wall" },
+            { "name": "B", "x": 0, "y": 0, "widht": 1, "height": "1" },
+            { "name": 3, "from": { "x": 0, "y": 0, "z": 1 }, "to": { "x": 1 }, "x": 0 },
+            { "name": "D", "x": 1e39, "y": 0, "width": 1, "height": 1, "properties": {} },
+            "E",
+            { "name": "F", "kind": "circle", "center": { "x": 0, "y": 0 }, "vertices": [] },
+            { "name": "G", "kind": "polygon", "vertices": [{ "x": 0, "y": 0 }, { "x": 1 }] },
+            { "name": "H", "kind": "star" },
+            { "name": "I", "x": 0, "y": 0, "width": 1, "height": 1, "z": 1.5 }
+        ],
+        "draworder": "topdown"
+    });
+    let violations = check_strict(&input)
+        .into_iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        violations,
+        vec![
+            "objects[1].widht: unknown field",
+            "objects[1].width: missing field",
+            "objects[1].height: expected a number, got a string",
+            "objects[2].name: expected a string, got a number",
+            "objects[2].x: cannot be combined with the \"from\" and \"to\" corners",
+            "objects[2].from.z: unknown field",
+            "objects[2].to.y: missing field",
+            "objects[3].properties: expected an array, got an object",
+            "objects[3].x: the number is out of range",
+            "objects[4]: expected an object, got a string",
+            "objects[5].vertices: is not a field of the circle objects",
+            "objects[5].radius: missing field",
+            "objects[6].vertices[1].y: missing field",
+            "objects[7].kind: unknown kind \"star\", expected rect, circle, polygon or segment",
+            "objects[8].z: expected an integer level, got a number",
+        ]
+    );
+
+    let valid = std::fs::read("objects/test.json").unwrap();
+    assert_eq!(
+        Input::from_reader_strict(valid.as_slice(), InputFormat::Json).unwrap(),
+        Input::from_reader(valid.as_slice()).unwrap()
+    );
+
+    let yaml = "objects:\n  - { name: A, x: 0, y: 0, widht: 1, height: 1 }\n";
+    let err = Input::from_reader_strict(yaml.as_bytes(), InputFormat::Yaml).unwrap_err();
+    assert!(err.to_string().contains("objects[0].widht: unknown field"));
+
+    let csv = "name,x,y,width,height,depth\nA,0,0, 1,1,3\n";
+    assert!(Input::from_reader_as(csv.as_bytes(), InputFormat::Csv).is_ok());
+    let err = Input::from_reader_strict(csv.as_bytes(), InputFormat::Csv).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Parse error: Found 2 strict schema violation(s):\n  column \"depth\": unknown column\n  \
+         line 2, column \"width\": expected a number, got \" 1\""
+    );
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{io, BoundingRect, Error, Output, OutputFormat};
+
+/// Range of the intersection areas and the number of intersections within it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct HistogramBin {
+    /// Lower bound of the areas, inclusive.
+    pub from: f32,
+    /// Upper bound of the areas, exclusive except for the last bin.
+    pub to: f32,
+    pub count: usize,
+}
+
+/// Summary of the analyzed scene.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SceneSummary {
+    /// Number of the objects.
+    pub objects: usize,
+    pub total_area: f32,
+    pub mean_area: f32,
+    pub median_area: f32,
+    /// Bounding rectangle of all objects, absent if they have no area.
+    pub bounds: Option<BoundingRect>,
+    /// Number of the intersecting object pairs.
+    pub overlaps: usize,
+    /// Sum of the intersection areas, the area covered by several pairs is counted for each.
+    pub overlapped_area: f32,
+    /// Histogram of the intersection areas with the equal width bins.
+    pub histogram: Vec<HistogramBin>,
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0;
+    }
+
+    values.sort_by(f32::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        (values[mid - 1] + values[mid]) / 2.0
+    }
+}
+
+fn histogram(values: &[f32], bins: usize) -> Vec<HistogramBin> {
+    let (min, max) = values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        });
+    if values.is_empty() || bins == 0 {
+        return Vec::new();
+    }
+
+    // All values fall into the single bin if they are equal.
+    let bins = if min < max { bins } else { 1 };
+    let width = (max - min) / bins as f32;
+    let mut histogram = (0..bins)
+        .map(|idx| HistogramBin {
+            from: min + width * idx as f32,
+            to: if idx + 1 == bins {
+                max
+            } else {
+                min + width * (idx + 1) as f32
+            },
+            count: 0,
+        })
+        .collect::<Vec<_>>();
+    for &value in values {
+        let idx = if width > 0.0 {
+            (((value - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        histogram[idx].count += 1;
+    }
+    histogram
+}
+
+/// Summarizes the analysis results, the intersection areas are split into the given number
+/// of histogram bins.
+pub fn summarize(output: &Output, bins: usize) -> SceneSummary {
+    let mut areas = output
+        .areas
+        .iter()
+        .map(|object| object.area.area())
+        .collect::<Vec<_>>();
+    let total_area = areas.iter().sum::<f32>();
+    let bounds = output
+        .areas
+        .iter()
+        .fold(BoundingRect::EMPTY, |bounds, object| {
+            bounds.union(&object.area)
+        });
+
+    let overlaps = output
+        .intersections
+        .iter()
+        .map(|intersection| intersection.area.area())
+        .collect::<Vec<_>>();
+
+    SceneSummary {
+        objects: areas.len(),
+        total_area,
+        mean_area: if areas.is_empty() {
+            0.0
+        } else {
+            total_area / areas.len() as f32
+        },
+        median_area: median(&mut areas),
+        bounds: Some(bounds).filter(|bounds| !bounds.is_empty()),
+        overlaps: overlaps.len(),
+        overlapped_area: overlaps.iter().sum(),
+        histogram: histogram(&overlaps, bins),
+    }
+}
+
+impl SceneSummary {
+    /// Serializes the summary in the given structured format.
+    pub fn to_vec_as(&self, format: OutputFormat) -> Result<Vec<u8>, Error> {
+        io::encode(self, format)
+    }
+}
+
+#[test]
+fn test_summarize() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("B", 3.0, 3.0, 2.0, 2.0)
+        .object("C", 3.0, 0.0, 2.0, 1.0)
+        .object("D", 10.0, -5.0, 1.0, 1.0)
+        .build();
+    let output = crate::analyze(input, crate::Options::default());
+
+    let summary = summarize(&output, 2);
+    assert_eq!(summary.objects, 4);
+    assert_eq!(summary.total_area, 23.0);
+    assert_eq!(summary.mean_area, 5.75);
+    assert_eq!(summary.median_area, 3.0);
+    assert_eq!(
+        summary.bounds,
+        Some(BoundingRect::from_points(
+            crate::Point2D::new(0.0, -5.0),
+            crate::Point2D::new(11.0, 5.0)
+        ))
+    );
+    assert_eq!(summary.overlaps, 2);
+    assert_eq!(summary.overlapped_area, 2.0);
+    assert_eq!(
+        summary.histogram,
+        vec![HistogramBin {
+            from: 1.0,
+            to: 1.0,
+            count: 2
+        }]
+    );
+
+    let empty = summarize(
+        &crate::analyze(crate::Input { objects: vec![] }, Default::default()),
+        10,
+    );
+    assert_eq!(empty.bounds, None);
+    assert!(empty.histogram.is_empty());
+
+    let bins = histogram(&[0.0, 1.0, 2.0, 3.0, 4.0], 2);
+    assert_eq!(
+        bins.iter().map(|bin| bin.count).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert_eq!((bins[0].from, bins[0].to, bins[1].to), (0.0, 2.0, 4.0));
+}
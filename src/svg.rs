@@ -0,0 +1,733 @@
+use std::io::Read;
+
+use roxmltree::{Document, Node};
+
+use crate::{decompress, wkt::ring_shape, AnyShape, BoundingRect, Error, Input, Object, Point2D};
+
+// The elements whose children are not drawn by themselves.
+const HIDDEN_CONTAINERS: &[&str] = &["defs", "clipPath", "mask", "marker", "pattern", "symbol"];
+
+fn parse_error(reason: impl std::fmt::Display) -> Error {
+    Error::ParseError {
+        reason: anyhow::anyhow!("{}", reason),
+    }
+}
+
+// Parses the length attribute, the `px` suffix is skipped and the missing attributes are zero.
+fn length(node: Node, attribute: &str) -> Result<f32, Error> {
+    let Some(value) = node.attribute(attribute) else {
+        return Ok(0.0);
+    };
+    let value = value.trim();
+    value
+        .strip_suffix("px")
+        .unwrap_or(value)
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| {
+            parse_error(format!(
+                "The <{}> has the invalid {} \"{}\", only the plain and the px lengths are supported",
+                node.tag_name().name(),
+                attribute,
+                value
+            ))
+        })
+}
+
+// Splits the path data or the point list into the commands and the numbers.
+fn tokens(data: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = data.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+        } else if c.is_ascii_alphabetic() {
+            tokens.push(Token::Command(c));
+            chars.next();
+        } else {
+            // The number ends on the second sign, the second dot or the next command, e.g.
+            // `1.5.5-2` is `1.5 .5 -2`.
+            let mut end = start;
+            let (mut dot, mut exponent, mut digits) = (false, false, false);
+            while let Some(&(idx, c)) = chars.peek() {
+                let accepted = match c {
+                    '0'..='9' => {
+                        digits = true;
+                        true
+                    }
+                    '+' | '-' => idx == start || data[..idx].ends_with(['e', 'E']),
+                    '.' if !dot && !exponent => {
+                        dot = true;
+                        true
+                    }
+                    'e' | 'E' if digits && !exponent => {
+                        exponent = true;
+                        true
+                    }
+                    _ => false,
+                };
+                if !accepted {
+                    break;
+                }
+                end = idx + c.len_utf8();
+                chars.next();
+            }
+            if end == start {
+                return Err(format!("unexpected \"{}\"", c));
+            }
+            let number = &data[start..end];
+            let value = number
+                .parse::<f32>()
+                .map_err(|_| format!("invalid number \"{}\"", number))?;
+            tokens.push(Token::Number(value));
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+// Affine transform `[a b c d e f]` mapping `(x, y)` to `(a x + c y + e, b x + d y + f)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform([f32; 6]);
+
+impl Transform {
+    const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    // Parses the `transform` attribute, the functions are applied from right to left.
+    fn parse(list: &str) -> Result<Self, String> {
+        let mut transform = Self::IDENTITY;
+        let mut rest = list.trim();
+        while !rest.is_empty() {
+            let (name, tail) = rest
+                .split_once('(')
+                .ok_or_else(|| format!("invalid transform \"{}\"", list))?;
+            let (args, tail) = tail
+                .split_once(')')
+                .ok_or_else(|| format!("invalid transform \"{}\"", list))?;
+            let args = tokens(args)?
+                .into_iter()
+                .map(|token| match token {
+                    Token::Number(value) => Ok(value),
+                    Token::Command(_) => Err(format!("invalid transform \"{}\"", list)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let name = name.trim_matches(|c: char| c.is_whitespace() || c == ',');
+            let function = match (name, &args[..]) {
+                ("matrix", &[a, b, c, d, e, f]) => Self([a, b, c, d, e, f]),
+                ("translate", &[x]) => Self([1.0, 0.0, 0.0, 1.0, x, 0.0]),
+                ("translate", &[x, y]) => Self([1.0, 0.0, 0.0, 1.0, x, y]),
+                ("scale", &[x]) => Self([x, 0.0, 0.0, x, 0.0, 0.0]),
+                ("scale", &[x, y]) => Self([x, 0.0, 0.0, y, 0.0, 0.0]),
+                ("rotate", &[angle]) => Self::rotate(angle),
+                // The rotation about the given point.
+                ("rotate", &[angle, x, y]) => Self([1.0, 0.0, 0.0, 1.0, x, y])
+                    .then(Self::rotate(angle))
+                    .then(Self([1.0, 0.0, 0.0, 1.0, -x, -y])),
+                ("skewX", &[angle]) => Self([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0]),
+                ("skewY", &[angle]) => Self([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+                _ => return Err(format!("unsupported transform \"{}\"", list)),
+            };
+            transform = transform.then(function);
+            rest = tail.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        }
+        Ok(transform)
+    }
+
+    fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Self([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    // Returns the transform applying the given one first and then this one.
+    fn then(self, other: Self) -> Self {
+        let [a1, b1, c1, d1, e1, f1] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+        Self([
+            a1 * a2 + c1 * b2,
+            b1 * a2 + d1 * b2,
+            a1 * c2 + c1 * d2,
+            b1 * c2 + d1 * d2,
+            a1 * e2 + c1 * f2 + e1,
+            b1 * e2 + d1 * f2 + f1,
+        ])
+    }
+
+    fn apply(&self, point: Point2D) -> Point2D {
+        let [a, b, c, d, e, f] = self.0;
+        Point2D::new(a * point.x + c * point.y + e, b * point.x + d * point.y + f)
+    }
+
+    // Applies the transform to the vector, the translation is skipped.
+    fn apply_vector(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let [a, b, c, d, ..] = self.0;
+        (a * x + c * y, b * x + d * y)
+    }
+
+    // Returns the transform of the element combined with the transforms of its ancestors.
+    fn of(node: Node) -> Result<Self, Error> {
+        let mut transforms = node
+            .ancestors()
+            .filter_map(|ancestor| ancestor.attribute("transform"))
+            .map(|list| {
+                Self::parse(list).map_err(|err| {
+                    parse_error(format!("Invalid <{}>: {}", node.tag_name().name(), err))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        transforms.reverse();
+        Ok(transforms.into_iter().fold(Self::IDENTITY, Self::then))
+    }
+}
+
+// Returns the points of the transformed arc bounding it: the end points and the extrema of
+// the transformed ellipse within the arc, see the endpoint to center conversion of the SVG
+// implementation notes.
+#[allow(clippy::too_many_arguments)]
+fn arc_points(
+    from: Point2D,
+    to: Point2D,
+    (rx, ry): (f32, f32),
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    transform: &Transform,
+) -> Vec<Point2D> {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    let mut points = vec![transform.apply(from), transform.apply(to)];
+    // The degenerate arcs are the straight lines.
+    if rx == 0.0 || ry == 0.0 || from == to {
+        return points;
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (dx, dy) = ((from.x - to.x) / 2.0, (from.y - to.y) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+    // The radii too small to reach the end point are scaled up.
+    let scale = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if scale > 1.0 {
+        rx *= scale.sqrt();
+        ry *= scale.sqrt();
+    }
+    let (rx2, ry2) = (rx * rx, ry * ry);
+    let numerator = (rx2 * ry2 - rx2 * y1 * y1 - ry2 * x1 * x1).max(0.0);
+    let mut coefficient = (numerator / (rx2 * y1 * y1 + ry2 * x1 * x1)).sqrt();
+    if large_arc == sweep {
+        coefficient = -coefficient;
+    }
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = Point2D::new(
+        cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0,
+        sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0,
+    );
+    let angle = |x: f32, y: f32| y.atan2(x);
+    let start = angle((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta = angle((-x1 - cx1) / rx, (-y1 - cy1) / ry) - start;
+    if sweep && delta < 0.0 {
+        delta += std::f32::consts::TAU;
+    } else if !sweep && delta > 0.0 {
+        delta -= std::f32::consts::TAU;
+    }
+
+    // The transformed ellipse is `center + u cos t + v sin t`, its coordinates are extreme
+    // where their derivatives are zero.
+    let center = transform.apply(center);
+    let u = transform.apply_vector((rx * cos, rx * sin));
+    let v = transform.apply_vector((-ry * sin, ry * cos));
+    let extrema = [angle(u.0, v.0), angle(u.1, v.1)];
+    for extremum in extrema {
+        for t in [extremum, extremum + std::f32::consts::PI] {
+            let passed = if delta > 0.0 {
+                (t - start).rem_euclid(std::f32::consts::TAU) <= delta
+            } else {
+                (start - t).rem_euclid(std::f32::consts::TAU) <= -delta
+            };
+            if passed {
+                let (sin, cos) = t.sin_cos();
+                points.push(Point2D::new(
+                    center.x + u.0 * cos + v.0 * sin,
+                    center.y + u.1 * cos + v.1 * sin,
+                ));
+            }
+        }
+    }
+    points
+}
+
+// Returns the transformed end points and control points of the path segments, so their
+// bounding rect contains the whole path. The arcs are bounded by their extrema.
+fn path_points(data: &str, transform: &Transform) -> Result<Vec<Point2D>, String> {
+    let tokens = tokens(data)?;
+    let mut points = Vec::new();
+    let (mut current, mut start) = (Point2D::new(0.0, 0.0), Point2D::new(0.0, 0.0));
+    let mut command = None;
+    // Last control point of the previous cubic or the quadratic segment with its command.
+    let mut control: Option<(char, Point2D)> = None;
+    let mut idx = 0;
+    while idx < tokens.len() {
+        if let Token::Command(c) = tokens[idx] {
+            command = Some(c);
+            idx += 1;
+            if c == 'Z' || c == 'z' {
+                current = start;
+                control = None;
+                continue;
+            }
+        }
+        let c = command.ok_or("the path does not start with a command")?;
+        let count = match c.to_ascii_uppercase() {
+            'M' | 'L' | 'T' => 2,
+            'H' | 'V' => 1,
+            'S' | 'Q' => 4,
+            'C' => 6,
+            'A' => 7,
+            'Z' => return Err("the close command has no arguments".to_owned()),
+            other => return Err(format!("unknown path command \"{}\"", other)),
+        };
+        let args = tokens
+            .get(idx..idx + count)
+            .and_then(|args| {
+                args.iter()
+                    .map(|token| match token {
+                        Token::Number(value) => Some(*value),
+                        Token::Command(_) => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| format!("the \"{}\" command expects {} numbers", c, count))?;
+        idx += count;
+
+        let relative = c.is_ascii_lowercase();
+        let point = |x: f32, y: f32| {
+            if relative {
+                Point2D::new(current.x + x, current.y + y)
+            } else {
+                Point2D::new(x, y)
+            }
+        };
+        // The first control point of the smooth segments is the reflection of the last
+        // control point of the previous segment of the same kind or the current point.
+        let reflected = |kind: char| match control {
+            Some((previous, point)) if previous == kind => {
+                Point2D::new(2.0 * current.x - point.x, 2.0 * current.y - point.y)
+            }
+            _ => current,
+        };
+        let upper = c.to_ascii_uppercase();
+        let (end, controls) = match upper {
+            'H' => (
+                Point2D::new(
+                    if relative {
+                        current.x + args[0]
+                    } else {
+                        args[0]
+                    },
+                    current.y,
+                ),
+                vec![],
+            ),
+            'V' => (
+                Point2D::new(
+                    current.x,
+                    if relative {
+                        current.y + args[0]
+                    } else {
+                        args[0]
+                    },
+                ),
+                vec![],
+            ),
+            'A' => {
+                let end = point(args[5], args[6]);
+                points.extend(arc_points(
+                    current,
+                    end,
+                    (args[0], args[1]),
+                    args[2],
+                    args[3] != 0.0,
+                    args[4] != 0.0,
+                    transform,
+                ));
+                (end, vec![])
+            }
+            'S' => (
+                point(args[2], args[3]),
+                vec![reflected('C'), point(args[0], args[1])],
+            ),
+            'T' => (point(args[0], args[1]), vec![reflected('Q')]),
+            _ => (
+                point(args[count - 2], args[count - 1]),
+                args[..count - 2]
+                    .chunks(2)
+                    .map(|pair| point(pair[0], pair[1]))
+                    .collect(),
+            ),
+        };
+        points.extend(controls.iter().map(|&point| transform.apply(point)));
+        points.push(transform.apply(end));
+        control = match upper {
+            'C' | 'S' => controls.last().map(|&point| ('C', point)),
+            'Q' | 'T' => controls.last().map(|&point| ('Q', point)),
+            _ => None,
+        };
+        current = end;
+        if c == 'M' || c == 'm' {
+            start = end;
+            // The next pairs are the line segments.
+            command = Some(if relative { 'l' } else { 'L' });
+        }
+    }
+    Ok(points)
+}
+
+// Returns the bounding rect of the points.
+fn points_rect(points: &[Point2D]) -> Option<BoundingRect> {
+    let first = points.first()?;
+    let (min, max) = points.iter().fold((*first, *first), |(min, max), point| {
+        (
+            Point2D::new(min.x.min(point.x), min.y.min(point.y)),
+            Point2D::new(max.x.max(point.x), max.y.max(point.y)),
+        )
+    });
+    Some(BoundingRect::from_points(min, max))
+}
+
+// Returns the shape of the supported drawing element.
+fn element_shape(node: Node) -> Result<Option<AnyShape>, Error> {
+    let tag = node.tag_name().name();
+    if !matches!(tag, "rect" | "circle" | "ellipse" | "polygon" | "path") {
+        return Ok(None);
+    }
+    let transform = Transform::of(node)?;
+    let [a, b, c, d, ..] = transform.0;
+    // Bounding rect of the transformed ellipse given by its center and its radii.
+    let ellipse_rect = |center: Point2D, rx: f32, ry: f32| {
+        let center = transform.apply(center);
+        let hx = ((a * rx).powi(2) + (c * ry).powi(2)).sqrt();
+        let hy = ((b * rx).powi(2) + (d * ry).powi(2)).sqrt();
+        AnyShape::Rect(BoundingRect::from_points(
+            Point2D::new(center.x - hx, center.y - hy),
+            Point2D::new(center.x + hx, center.y + hy),
+        ))
+    };
+    let shape = match tag {
+        "rect" => {
+            let (x, y) = (length(node, "x")?, length(node, "y")?);
+            let (width, height) = (length(node, "width")?, length(node, "height")?);
+            let corners = [
+                (x, y),
+                (x + width, y),
+                (x + width, y + height),
+                (x, y + height),
+            ];
+            if b == 0.0 && c == 0.0 {
+                let from = transform.apply(Point2D::new(x, y));
+                let to = transform.apply(Point2D::new(x + width, y + height));
+                AnyShape::Rect(BoundingRect::from_points(
+                    Point2D::new(from.x.min(to.x), from.y.min(to.y)),
+                    Point2D::new(from.x.max(to.x), from.y.max(to.y)),
+                ))
+            } else {
+                // The rotated or skewed rects become the polygons.
+                ring_shape(
+                    corners
+                        .iter()
+                        .map(|&(x, y)| transform.apply(Point2D::new(x, y)))
+                        .collect(),
+                )?
+            }
+        }
+        "circle" => {
+            let center = Point2D::new(length(node, "cx")?, length(node, "cy")?);
+            let radius = length(node, "r")?;
+            // Only the similarity transforms keep the circles round.
+            if (a == d && b == -c) || (a == -d && b == c) {
+                AnyShape::Circle {
+                    center: transform.apply(center),
+                    radius: radius * (a * d - b * c).abs().sqrt(),
+                }
+            } else {
+                ellipse_rect(center, radius, radius)
+            }
+        }
+        "ellipse" => {
+            let center = Point2D::new(length(node, "cx")?, length(node, "cy")?);
+            ellipse_rect(center, length(node, "rx")?, length(node, "ry")?)
+        }
+        "polygon" => {
+            let numbers = tokens(node.attribute("points").unwrap_or_default())
+                .ok()
+                .and_then(|tokens| {
+                    tokens
+                        .into_iter()
+                        .map(|token| match token {
+                            Token::Number(value) => Some(value),
+                            Token::Command(_) => None,
+                        })
+                        .collect::<Option<Vec<_>>>()
+                })
+                .filter(|numbers| numbers.len().is_multiple_of(2))
+                .ok_or_else(|| parse_error("The <polygon> has invalid points"))?;
+            let vertices = numbers
+                .chunks(2)
+                .map(|pair| transform.apply(Point2D::new(pair[0], pair[1])))
+                .collect();
+            ring_shape(vertices)?
+        }
+        _ => {
+            let points = path_points(node.attribute("d").unwrap_or_default(), &transform)
+                .map_err(|err| parse_error(format!("Invalid <path>: {}", err)))?;
+            let rect = points_rect(&points)
+                .ok_or_else(|| parse_error("Invalid <path>: the path has no points"))?;
+            AnyShape::Rect(rect)
+        }
+    };
+    Ok(Some(shape))
+}
+
+impl Input {
+    /// Parses the SVG drawing. Every `<rect>`, `<circle>`, `<ellipse>`, `<polygon>` and
+    /// `<path>` becomes an object named by its `id` or by its index, the circles and the
+    /// polygons keep their shapes, the ellipses and the paths are read by their bounding rects.
+    /// The `id` of the closest `<g>` becomes the layer. The `transform` attributes of the
+    /// elements and their ancestors are applied, the rotated or skewed rects become polygons,
+    /// and the elements inside `<defs>` and the like are skipped.
+    pub fn from_svg_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut reader = decompress::decompress(reader).map_err(parse_error)?;
+        let mut text = String::new();
+        reader.read_to_string(&mut text).map_err(parse_error)?;
+        let document = Document::parse(&text).map_err(parse_error)?;
+        let root = document.root_element();
+        if !root.has_tag_name("svg") {
+            return Err(parse_error(format!(
+                "Expected the <svg> root element, got <{}>",
+                root.tag_name().name()
+            )));
+        }
+
+        let mut objects = Vec::new();
+        for node in root.descendants().filter(Node::is_element) {
+            let hidden = node
+                .ancestors()
+                .any(|ancestor| HIDDEN_CONTAINERS.contains(&ancestor.tag_name().name()));
+            if hidden {
+                continue;
+            }
+            let Some(shape) = element_shape(node)? else {
+                continue;
+            };
+            let name = node
+                .attribute("id")
+                .map_or_else(|| objects.len().to_string(), str::to_owned);
+            let layer = node
+                .ancestors()
+                .skip(1)
+                .find(|ancestor| ancestor.has_tag_name("g") && ancestor.has_attribute("id"))
+                .and_then(|group| group.attribute("id"))
+                .map(str::to_owned);
+            objects.push(Object {
+                layer,
+                ..Object::from_shape(name, shape)
+            });
+        }
+        Ok(Self { objects })
+    }
+}
+
+#[test]
+fn test_svg() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100">
+        <defs><rect id="template" width="5" height="5"/></defs>
+        <g id="buttons">
+            <rect id="ok" x="10" y="10" width="40px" height="20"/>
+            <circle cx="40" cy="25" r="10"/>
+        </g>
+        <ellipse id="badge" cx="100" cy="50" rx="20" ry="10"/>
+        <polygon points="0,0 10,0 5,8"/>
+        <path id="arrow" d="M 60 20 l 10 10 h-20 Q 30 50 40 40.5 z"/>
+    </svg>"#;
+    let input = Input::from_svg_reader(svg.as_bytes()).unwrap();
+    let objects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                x.layer.as_deref(),
+                [x.x, x.y, x.width, x.height],
+                x.shape.is_some(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        vec![
+            ("ok", Some("buttons"), [10.0, 10.0, 40.0, 20.0], false),
+            ("1", Some("buttons"), [30.0, 15.0, 20.0, 20.0], true),
+            ("badge", None, [80.0, 40.0, 40.0, 20.0], false),
+            ("3", None, [0.0, 0.0, 10.0, 8.0], true),
+            ("arrow", None, [30.0, 20.0, 40.0, 30.0], false),
+        ]
+    );
+
+    let cases = vec![
+        ("<html/>", "<svg> root", "wrong root"),
+        (
+            r#"<svg><rect width="50%" height="1"/></svg>"#,
+            "invalid width \"50%\"",
+            "percentage",
+        ),
+        (
+            r#"<svg><path d="M 1 1 L 2"/></svg>"#,
+            "expects 2 numbers",
+            "missing coordinate",
+        ),
+        (
+            r#"<svg><path d="M 1 1 X 2 2"/></svg>"#,
+            "unknown path command",
+            "unknown command",
+        ),
+        (r#"<svg><path d=""/></svg>"#, "has no points", "empty path"),
+        (
+            r#"<svg><rect width="1" height="1" transform="perspective(2)"/></svg>"#,
+            "unsupported transform",
+            "unknown transform",
+        ),
+        (
+            r#"<svg><g transform="scale(1 2"><rect width="1" height="1"/></g></svg>"#,
+            "invalid transform",
+            "unclosed transform",
+        ),
+        (
+            r#"<svg><path d="M 1 1 L 2 #"/></svg>"#,
+            "unexpected \"#\"",
+            "garbage",
+        ),
+    ];
+    for case in cases {
+        let err = Input::from_svg_reader(case.0.as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains(case.1),
+            "Test case \"{}\" has been failed: {}",
+            case.2,
+            err
+        );
+    }
+}
+
+#[test]
+fn test_svg_transforms() {
+    let rotate = Transform::parse("rotate(90)").unwrap();
+    let cases = vec![
+        (
+            "M 0 0 A 10 10 0 0 1 20 0",
+            Transform::IDENTITY,
+            [0.0, -10.0, 20.0, 10.0],
+            "arc",
+        ),
+        (
+            "M 0 0 A 10 10 0 0 0 20 0",
+            Transform::IDENTITY,
+            [0.0, 0.0, 20.0, 10.0],
+            "arc back",
+        ),
+        (
+            "M 0 0 A 1 1 0 0 1 20 0",
+            Transform::IDENTITY,
+            [0.0, -10.0, 20.0, 10.0],
+            "small radii",
+        ),
+        (
+            "M 0 0 A 10 0 0 0 1 20 0",
+            Transform::IDENTITY,
+            [0.0, 0.0, 20.0, 0.0],
+            "flat arc",
+        ),
+        (
+            "M 0 0 A 10 10 0 0 1 20 0",
+            rotate,
+            [0.0, 0.0, 10.0, 20.0],
+            "rotated arc",
+        ),
+        (
+            "M 0 0 C 0 10 10 10 10 0 S 20 0 20 0",
+            Transform::IDENTITY,
+            [0.0, -10.0, 20.0, 20.0],
+            "smooth cubic",
+        ),
+        (
+            "M 0 0 Q 5 10 10 0 T 20 0",
+            Transform::IDENTITY,
+            [0.0, -10.0, 20.0, 20.0],
+            "smooth quad",
+        ),
+        (
+            "M 0 0 L 5 5 T 10 0",
+            Transform::IDENTITY,
+            [0.0, 0.0, 10.0, 5.0],
+            "smooth after line",
+        ),
+    ];
+    for case in cases {
+        let rect = points_rect(&path_points(case.0, &case.1).unwrap()).unwrap();
+        let actual = [
+            rect.from.x,
+            rect.from.y,
+            rect.to.x - rect.from.x,
+            rect.to.y - rect.from.y,
+        ];
+        assert!(
+            actual
+                .iter()
+                .zip(&case.2)
+                .all(|(a, b)| (a - b).abs() < 1e-3),
+            "Test case \"{}\" has been failed: {:?}",
+            case.3,
+            actual
+        );
+    }
+
+    let svg = r#"<svg>
+        <g transform="translate(100 0)">
+            <rect id="rotated" width="10" height="10" transform="rotate(45)"/>
+            <circle id="scaled" cx="1" cy="1" r="2" transform="scale(2)"/>
+            <circle id="squeezed" r="2" transform="scale(2, 1)"/>
+        </g>
+        <ellipse id="turned" rx="4" ry="2" transform="rotate(90 10 10)"/>
+    </svg>"#;
+    let input = Input::from_svg_reader(svg.as_bytes()).unwrap();
+    let objects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                [x.x, x.y, x.width, x.height],
+                x.shape.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let approx = |a: [f32; 4], b: [f32; 4]| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-3);
+    let half = 10.0 * std::f32::consts::FRAC_1_SQRT_2;
+    assert_eq!(objects[0].0, "rotated");
+    assert!(matches!(objects[0].2, Some(AnyShape::Polygon(_))));
+    assert!(approx(
+        objects[0].1,
+        [100.0 - half, 0.0, 2.0 * half, 2.0 * half]
+    ));
+    assert_eq!(
+        objects[1].2,
+        Some(AnyShape::Circle {
+            center: Point2D::new(102.0, 2.0),
+            radius: 4.0
+        })
+    );
+    assert_eq!(objects[2].1, [96.0, -2.0, 8.0, 4.0]);
+    assert_eq!(objects[2].2, None);
+    assert!(approx(objects[3].1, [18.0, -4.0, 4.0, 8.0]));
+}
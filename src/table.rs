@@ -0,0 +1,200 @@
+use crate::{Error, ObjectArea, ObjectIntersection, Output, OutputSection};
+
+/// Share of the intersections with the largest areas highlighted in the colored tables.
+const HIGHLIGHTED_SHARE: f32 = 0.1;
+
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// Text table with the left aligned first columns and the right aligned numbers.
+struct Table {
+    title: String,
+    header: Vec<&'static str>,
+    /// Number of the left aligned text columns.
+    text_columns: usize,
+    rows: Vec<(Vec<String>, bool)>,
+}
+
+impl Table {
+    fn render(&self, colored: bool) -> String {
+        let mut widths = self.header.iter().map(|x| x.len()).collect::<Vec<_>>();
+        for (row, _) in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let line = |cells: &[String]| {
+            let cells = cells
+                .iter()
+                .enumerate()
+                .map(|(idx, cell)| {
+                    if idx < self.text_columns {
+                        format!("{:<width$}", cell, width = widths[idx])
+                    } else {
+                        format!("{:>width$}", cell, width = widths[idx])
+                    }
+                })
+                .collect::<Vec<_>>();
+            cells.join("  ").trim_end().to_owned()
+        };
+        let paint = |text: String, style: &str| {
+            if colored {
+                format!("{}{}{}", style, text, RESET)
+            } else {
+                text
+            }
+        };
+
+        let header = self
+            .header
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>();
+        let mut lines = vec![
+            paint(format!("{} ({})", self.title, self.rows.len()), BOLD),
+            paint(line(&header), BOLD),
+        ];
+        for (row, highlighted) in &self.rows {
+            let row = line(row);
+            lines.push(if *highlighted { paint(row, RED) } else { row });
+        }
+        lines.join("\n")
+    }
+}
+
+fn areas_table(areas: &[ObjectArea]) -> Table {
+    let rows = areas
+        .iter()
+        .map(|object| {
+            let (from, to) = (object.area.top_left(), object.area.bottom_right());
+            let row = vec![
+                object.name.clone(),
+                from.x.to_string(),
+                from.y.to_string(),
+                to.x.to_string(),
+                to.y.to_string(),
+                object.area.area().to_string(),
+            ];
+            (row, false)
+        })
+        .collect();
+    Table {
+        title: "Areas".to_owned(),
+        header: vec!["name", "from_x", "from_y", "to_x", "to_y", "area"],
+        text_columns: 1,
+        rows,
+    }
+}
+
+fn intersections_table(intersections: &[ObjectIntersection]) -> Table {
+    // The intersections with the area not smaller than this one are highlighted.
+    let mut areas = intersections
+        .iter()
+        .map(|x| x.area.area())
+        .collect::<Vec<_>>();
+    areas.sort_by(|a, b| b.total_cmp(a));
+    let highlighted = ((areas.len() as f32 * HIGHLIGHTED_SHARE).ceil() as usize).max(1);
+    let threshold = areas.get(highlighted - 1).copied().unwrap_or(f32::INFINITY);
+
+    let metrics = intersections.iter().any(|x| x.metrics.is_some());
+    let mut header = vec![
+        "first", "second", "from_x", "from_y", "to_x", "to_y", "area",
+    ];
+    if metrics {
+        header.extend(["iou", "first_coverage", "second_coverage"]);
+    }
+    let rows = intersections
+        .iter()
+        .map(|intersection| {
+            let (from, to) = (
+                intersection.area.top_left(),
+                intersection.area.bottom_right(),
+            );
+            let area = intersection.area.area();
+            let mut row = vec![
+                intersection.names.0.clone(),
+                intersection.names.1.clone(),
+                from.x.to_string(),
+                from.y.to_string(),
+                to.x.to_string(),
+                to.y.to_string(),
+                area.to_string(),
+            ];
+            if metrics {
+                let metrics = intersection.metrics;
+                let cell = |value: Option<f32>| value.map_or_else(String::new, |x| x.to_string());
+                row.extend([
+                    cell(metrics.map(|x| x.iou)),
+                    cell(metrics.map(|x| x.coverage.0)),
+                    cell(metrics.map(|x| x.coverage.1)),
+                ]);
+            }
+            (row, area >= threshold)
+        })
+        .collect();
+    Table {
+        title: "Intersections".to_owned(),
+        header,
+        text_columns: 2,
+        rows,
+    }
+}
+
+impl Output {
+    /// Formats the areas and the intersections, or only the given section of them, as
+    /// aligned text tables for the terminal. The summary section is not supported.
+    ///
+    /// The colored tables use the ANSI escape codes and highlight the largest intersections.
+    pub fn to_table(&self, section: Option<OutputSection>, colored: bool) -> Result<String, Error> {
+        let areas = || areas_table(&self.areas).render(colored);
+        let intersections = || intersections_table(&self.intersections).render(colored);
+        Ok(match section {
+            None => format!("{}\n\n{}\n", areas(), intersections()),
+            Some(OutputSection::Areas) => format!("{}\n", areas()),
+            Some(OutputSection::Intersections) => format!("{}\n", intersections()),
+            Some(OutputSection::Summary) => {
+                return Err(Error::SerializeError {
+                    reason: anyhow::anyhow!("The summary cannot be written as a table"),
+                })
+            }
+        })
+    }
+}
+
+#[test]
+fn test_output_table() {
+    let input = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 4.0, 4.0)
+        .object("Long name", 3.0, 3.0, 2.0, 2.0)
+        .object("C", 3.0, 0.0, 2.0, 10.0)
+        .build();
+    let output = crate::analyze(input, crate::Options::default());
+
+    assert_eq!(
+        output.to_table(None, false).unwrap(),
+        "\
+Areas (3)
+name       from_x  from_y  to_x  to_y  area
+A               0       0     4     4    16
+Long name       3       3     5     5     4
+C               3       0     5    10    20
+
+Intersections (3)
+first      second     from_x  from_y  to_x  to_y  area
+A          Long name       3       3     4     4     1
+A          C               3       0     4     4     4
+Long name  C               3       3     5     5     4
+"
+    );
+
+    // Both largest intersections are highlighted.
+    let colored = output
+        .to_table(Some(OutputSection::Intersections), true)
+        .unwrap();
+    assert_eq!(colored.matches(RED).count(), 2);
+    assert!(colored.starts_with(BOLD));
+    assert!(output
+        .to_table(Some(OutputSection::Summary), false)
+        .is_err());
+}
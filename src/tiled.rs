@@ -0,0 +1,412 @@
+use std::io::Read;
+
+use roxmltree::{Document, Node};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{decompress, wkt::ring_shape, AnyShape, BoundingRect, Error, Input, Object, Point2D};
+
+fn parse_error(reason: impl std::fmt::Display) -> Error {
+    Error::ParseError {
+        reason: anyhow::anyhow!("{}", reason),
+    }
+}
+
+/// Layer of the Tiled JSON map, the tile and image layers have no objects.
+#[derive(Deserialize)]
+struct Layer {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    objects: Vec<TiledObject>,
+    #[serde(default)]
+    layers: Vec<Layer>,
+}
+
+#[derive(Deserialize)]
+struct Map {
+    layers: Vec<Layer>,
+}
+
+/// Object of the object layer, the same for the TMX and the JSON maps.
+#[derive(Debug, Deserialize)]
+struct TiledObject {
+    #[serde(default)]
+    id: u32,
+    #[serde(default)]
+    name: String,
+    // The class is called the type before Tiled 1.9.
+    #[serde(default, alias = "type")]
+    class: String,
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default)]
+    gid: Option<u32>,
+    #[serde(default)]
+    ellipse: bool,
+    #[serde(default)]
+    point: bool,
+    #[serde(default)]
+    polygon: Option<Vec<Point2D>>,
+    #[serde(default)]
+    polyline: Option<Vec<Point2D>>,
+    #[serde(default)]
+    properties: Vec<Property>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Property {
+    name: String,
+    #[serde(default)]
+    value: Value,
+}
+
+impl TiledObject {
+    // Returns the shape in the map coordinates, the points have no shape.
+    fn shape(&self) -> Result<Option<AnyShape>, Error> {
+        let origin = Point2D::new(self.x, self.y);
+        let offset = |points: &[Point2D]| {
+            points
+                .iter()
+                .map(|point| Point2D::new(origin.x + point.x, origin.y + point.y))
+                .collect::<Vec<_>>()
+        };
+        let shape = if self.point {
+            return Ok(None);
+        } else if let Some(points) = &self.polygon {
+            ring_shape(offset(points)).map_err(|err| {
+                parse_error(format!("The polygon of the object {}: {}", self.id, err))
+            })?
+        } else if let Some(points) = &self.polyline {
+            match offset(points)[..] {
+                [from, to] => AnyShape::Segment { from, to },
+                ref points => {
+                    let Some(first) = points.first() else {
+                        return Err(parse_error(format!(
+                            "The polyline of the object {} has no points",
+                            self.id
+                        )));
+                    };
+                    let (min, max) = points.iter().fold((*first, *first), |(min, max), point| {
+                        (
+                            Point2D::new(min.x.min(point.x), min.y.min(point.y)),
+                            Point2D::new(max.x.max(point.x), max.y.max(point.y)),
+                        )
+                    });
+                    AnyShape::Rect(BoundingRect::from_points(min, max))
+                }
+            }
+        } else {
+            // The tile objects are aligned by their bottom-left corners.
+            let top = if self.gid.is_some() {
+                self.y - self.height
+            } else {
+                self.y
+            };
+            let (from, to) = (
+                Point2D::new(self.x, top),
+                Point2D::new(self.x + self.width, top + self.height),
+            );
+            if self.ellipse && self.width == self.height {
+                AnyShape::Circle {
+                    center: Point2D::new(self.x + self.width / 2.0, top + self.height / 2.0),
+                    radius: self.width / 2.0,
+                }
+            } else {
+                AnyShape::Rect(BoundingRect::from_points(from, to))
+            }
+        };
+        // Tiled rotates the objects about their origins.
+        if self.rotation != 0.0 {
+            Ok(Some(shape.rotated(origin, self.rotation)))
+        } else {
+            Ok(Some(shape))
+        }
+    }
+}
+
+// Parses the points of the TMX polygon, e.g. `0,0 10,0 5,8`.
+fn tmx_points(node: Node) -> Result<Vec<Point2D>, Error> {
+    node.attribute("points")
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(|pair| {
+            pair.split_once(',')
+                .and_then(|(x, y)| Some(Point2D::new(x.parse().ok()?, y.parse().ok()?)))
+                .ok_or_else(|| parse_error(format!("Invalid point \"{}\"", pair)))
+        })
+        .collect()
+}
+
+// Reads the TMX object, the property values are converted by their types.
+fn tmx_object(node: Node) -> Result<TiledObject, Error> {
+    let number = |attribute: &str| -> Result<f32, Error> {
+        node.attribute(attribute).map_or(Ok(0.0), |value| {
+            value.parse().map_err(|_| {
+                parse_error(format!(
+                    "The object has the invalid {} \"{}\"",
+                    attribute, value
+                ))
+            })
+        })
+    };
+    let child = |tag: &str| node.children().find(|child| child.has_tag_name(tag));
+    let properties = child("properties")
+        .into_iter()
+        .flat_map(|properties| properties.children())
+        .filter(|property| property.has_tag_name("property"))
+        .map(|property| {
+            let text = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or_default();
+            let value = match property.attribute("type") {
+                Some("int") => text
+                    .parse::<i64>()
+                    .map_or_else(|_| json!(text), |x| json!(x)),
+                Some("float") => text
+                    .parse::<f64>()
+                    .map_or_else(|_| json!(text), |x| json!(x)),
+                Some("bool") => json!(text == "true"),
+                _ => json!(text),
+            };
+            Property {
+                name: property.attribute("name").unwrap_or_default().to_owned(),
+                value,
+            }
+        })
+        .collect();
+    Ok(TiledObject {
+        id: node
+            .attribute("id")
+            .and_then(|id| id.parse().ok())
+            .unwrap_or_default(),
+        name: node.attribute("name").unwrap_or_default().to_owned(),
+        class: node
+            .attribute("class")
+            .or_else(|| node.attribute("type"))
+            .unwrap_or_default()
+            .to_owned(),
+        x: number("x")?,
+        y: number("y")?,
+        width: number("width")?,
+        height: number("height")?,
+        rotation: number("rotation")?,
+        gid: node.attribute("gid").and_then(|gid| gid.parse().ok()),
+        ellipse: child("ellipse").is_some(),
+        point: child("point").is_some(),
+        polygon: child("polygon").map(tmx_points).transpose()?,
+        polyline: child("polyline").map(tmx_points).transpose()?,
+        properties,
+    })
+}
+
+// Collects the objects of the TMX object groups with their layer names.
+fn tmx_objects(node: Node, objects: &mut Vec<(String, TiledObject)>) -> Result<(), Error> {
+    for child in node.children().filter(Node::is_element) {
+        match child.tag_name().name() {
+            "objectgroup" => {
+                let layer = child.attribute("name").unwrap_or_default();
+                for object in child.children().filter(|x| x.has_tag_name("object")) {
+                    objects.push((layer.to_owned(), tmx_object(object)?));
+                }
+            }
+            "group" => tmx_objects(child, objects)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn json_objects(layers: Vec<Layer>, objects: &mut Vec<(String, TiledObject)>) {
+    for layer in layers {
+        for object in layer.objects {
+            objects.push((layer.name.clone(), object));
+        }
+        json_objects(layer.layers, objects);
+    }
+}
+
+impl Input {
+    /// Parses the object layers of the Tiled map, either the TMX or the JSON one. The objects
+    /// are named by their names or by their ids and get the names of their layers, the
+    /// polygons and the circles keep their shapes, the polylines of two points become the
+    /// segments and the point objects are skipped. The classes and the custom properties are
+    /// kept as the `{ "name": key, "value": value }` properties.
+    pub fn from_tiled_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut reader = decompress::decompress(reader).map_err(parse_error)?;
+        let mut text = String::new();
+        reader.read_to_string(&mut text).map_err(parse_error)?;
+
+        let mut objects = Vec::new();
+        if text.trim_start().starts_with('<') {
+            let document = Document::parse(&text).map_err(parse_error)?;
+            let root = document.root_element();
+            if !root.has_tag_name("map") {
+                return Err(parse_error(format!(
+                    "Expected the <map> root element, got <{}>",
+                    root.tag_name().name()
+                )));
+            }
+            tmx_objects(root, &mut objects)?;
+        } else {
+            let map: Map = serde_json::from_str(&text).map_err(parse_error)?;
+            json_objects(map.layers, &mut objects);
+        }
+
+        let mut input = Vec::new();
+        for (layer, object) in objects {
+            let Some(shape) = object.shape()? else {
+                continue;
+            };
+            let name = if object.name.is_empty() {
+                object.id.to_string()
+            } else {
+                object.name.clone()
+            };
+            let class = Some(object.class)
+                .filter(|class| !class.is_empty())
+                .map(|class| json!({ "name": "class", "value": class }));
+            input.push(Object {
+                layer: Some(layer).filter(|layer| !layer.is_empty()),
+                properties: class
+                    .into_iter()
+                    .chain(
+                        object.properties.into_iter().map(
+                            |property| json!({ "name": property.name, "value": property.value }),
+                        ),
+                    )
+                    .collect(),
+                ..Object::from_shape(name, shape)
+            });
+        }
+        Ok(Self { objects: input })
+    }
+}
+
+#[test]
+fn test_tiled() {
+    let tmx = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <map version="1.10" orientation="orthogonal" width="10" height="10" tilewidth="16" tileheight="16">
+            <layer id="1" name="ground" width="10" height="10"><data encoding="csv">0</data></layer>
+            <objectgroup id="2" name="walls">
+                <object id="1" name="left" class="solid" x="0" y="0" width="16" height="160">
+                    <properties><property name="damage" type="int" value="5"/></properties>
+                </object>
+                <object id="2" x="8" y="8" width="20" height="20"><ellipse/></object>
+                <object id="3" x="40" y="40"><polygon points="0,0 20,0 10,15"/></object>
+                <object id="4" name="spawn" x="50" y="50"><point/></object>
+            </objectgroup>
+            <group id="3" name="props">
+                <objectgroup id="4" name="crates">
+                    <object id="5" gid="7" x="0" y="32" width="16" height="16"/>
+                    <object id="6" x="100" y="0" width="10" height="20" rotation="90"/>
+                </objectgroup>
+            </group>
+        </map>"#;
+    let input = Input::from_tiled_reader(tmx.as_bytes()).unwrap();
+    let objects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                x.layer.as_deref(),
+                // The rotated rects are rounded.
+                [x.x, x.y, x.width, x.height].map(|value| (value * 1000.0).round() / 1000.0),
+                x.shape.is_some(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        objects,
+        vec![
+            ("left", Some("walls"), [0.0, 0.0, 16.0, 160.0], false),
+            ("2", Some("walls"), [8.0, 8.0, 20.0, 20.0], true),
+            ("3", Some("walls"), [40.0, 40.0, 20.0, 15.0], true),
+            ("5", Some("crates"), [0.0, 16.0, 16.0, 16.0], false),
+            ("6", Some("crates"), [80.0, 0.0, 20.0, 10.0], true),
+        ]
+    );
+    assert_eq!(
+        input.objects[0].properties,
+        vec![
+            json!({ "name": "class", "value": "solid" }),
+            json!({ "name": "damage", "value": 5 }),
+        ]
+    );
+
+    let map = r#"{
+        "type": "map",
+        "layers": [
+            { "type": "tilelayer", "name": "ground", "data": [0] },
+            {
+                "type": "objectgroup",
+                "name": "walls",
+                "objects": [
+                    {
+                        "id": 1, "name": "left", "type": "solid", "x": 0, "y": 0,
+                        "width": 16, "height": 160,
+                        "properties": [{ "name": "damage", "type": "int", "value": 5 }]
+                    },
+                    { "id": 2, "name": "", "x": 8, "y": 8, "width": 20, "height": 20, "ellipse": true },
+                    { "id": 3, "x": 40, "y": 40, "polygon": [{ "x": 0, "y": 0 }, { "x": 20, "y": 0 }, { "x": 10, "y": 15 }] },
+                    { "id": 4, "name": "spawn", "x": 50, "y": 50, "point": true }
+                ]
+            }
+        ]
+    }"#;
+    let json = Input::from_tiled_reader(map.as_bytes()).unwrap();
+    assert_eq!(
+        json.objects
+            .iter()
+            .map(|x| (x.name.as_str(), x.layer.as_deref()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("left", Some("walls")),
+            ("2", Some("walls")),
+            ("3", Some("walls"))
+        ]
+    );
+    assert_eq!(json.objects[1..], input.objects[1..3]);
+    assert_eq!(
+        json.objects[0].properties[1],
+        json!({ "name": "damage", "value": 5 })
+    );
+
+    let cases = vec![
+        ("<tileset/>", "<map> root", "wrong root"),
+        (
+            r#"<map><objectgroup><object x="a"/></objectgroup></map>"#,
+            "invalid x \"a\"",
+            "not a number",
+        ),
+        (
+            r#"<map><objectgroup><object><polygon points="0,0 1"/></object></objectgroup></map>"#,
+            "Invalid point \"1\"",
+            "invalid point",
+        ),
+        (
+            r#"{ "type": "map" }"#,
+            "missing field `layers`",
+            "no layers",
+        ),
+    ];
+    for case in cases {
+        let err = Input::from_tiled_reader(case.0.as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains(case.1),
+            "Test case \"{}\" has been failed: {}",
+            case.2,
+            err
+        );
+    }
+}
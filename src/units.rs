@@ -0,0 +1,153 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AnyShape, Input, Object, Point2D};
+
+/// Length unit of the coordinates, written into the output to tell the consumers how to read
+/// them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    Mm,
+    Cm,
+    M,
+    Px,
+}
+
+impl Unit {
+    /// Names of all units.
+    pub const VARIANTS: &'static [&'static str] = &["mm", "cm", "m", "px"];
+
+    /// Length of the unit in meters, the pixels have no physical length.
+    pub fn meters(self) -> Option<f32> {
+        match self {
+            Self::Mm => Some(0.001),
+            Self::Cm => Some(0.01),
+            Self::M => Some(1.0),
+            Self::Px => None,
+        }
+    }
+
+    /// Returns the factor converting the coordinates in this unit into the other one, if the
+    /// units are convertible.
+    pub fn factor_to(self, other: Unit) -> Option<f32> {
+        if self == other {
+            return Some(1.0);
+        }
+        Some(self.meters()? / other.meters()?)
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mm" => Ok(Self::Mm),
+            "cm" => Ok(Self::Cm),
+            "m" => Ok(Self::M),
+            "px" => Ok(Self::Px),
+            other => Err(format!("Unknown unit \"{}\"", other)),
+        }
+    }
+}
+
+impl AnyShape {
+    /// Returns the shape with all coordinates multiplied by the factor.
+    pub fn scaled(&self, factor: f32) -> AnyShape {
+        self.map(
+            |p| Point2D::new(p.x * factor, p.y * factor),
+            |length| length * factor,
+        )
+    }
+}
+
+impl Object {
+    /// Multiplies the coordinates and the sizes of the object by the positive factor, the
+    /// rotation is kept.
+    pub fn scale(&mut self, factor: f32) {
+        self.x *= factor;
+        self.y *= factor;
+        self.width *= factor;
+        self.height *= factor;
+        self.shape = self.shape.as_ref().map(|shape| shape.scaled(factor));
+    }
+}
+
+impl Input {
+    /// Multiplies the coordinates and the sizes of all objects by the positive factor, e.g.
+    /// `0.001` to read the millimeters as meters. The rotations are kept.
+    pub fn scale(&mut self, factor: f32) {
+        for object in &mut self.objects {
+            object.scale(factor);
+        }
+    }
+}
+
+#[test]
+fn test_input_scale() {
+    let mut input = crate::SceneBuilder::new()
+        .object("A", 1000.0, 2000.0, 500.0, 250.0)
+        .shape(
+            "B",
+            AnyShape::Circle {
+                center: Point2D::new(100.0, 100.0),
+                radius: 50.0,
+            },
+        )
+        .build();
+    input.scale(0.001);
+
+    let a = &input.objects[0];
+    let rect = (a.x, a.y, a.width, a.height);
+    let expected = (1.0, 2.0, 0.5, 0.25);
+    assert!(
+        (rect.0 - expected.0).abs() < 1e-6
+            && (rect.1 - expected.1).abs() < 1e-6
+            && (rect.2 - expected.2).abs() < 1e-6
+            && (rect.3 - expected.3).abs() < 1e-6,
+        "{:?}",
+        rect
+    );
+    match &input.objects[1].shape {
+        Some(AnyShape::Circle { center, radius }) => {
+            assert!((center.x - 0.1).abs() < 1e-6 && (center.y - 0.1).abs() < 1e-6);
+            assert!((radius - 0.05).abs() < 1e-6);
+        }
+        other => panic!("Unexpected shape {:?}", other),
+    }
+    assert!((input.objects[1].width - 0.1).abs() < 1e-6);
+
+    let cases = vec![
+        (Unit::Mm, Unit::M, Some(0.001)),
+        (Unit::M, Unit::Cm, Some(100.0)),
+        (Unit::Cm, Unit::Mm, Some(10.0)),
+        (Unit::Px, Unit::Px, Some(1.0)),
+        (Unit::Px, Unit::M, None),
+        (Unit::Mm, Unit::Px, None),
+    ];
+    for case in cases {
+        let factor = case.0.factor_to(case.1);
+        assert!(
+            match (factor, case.2) {
+                (Some(actual), Some(expected)) => (actual - expected).abs() < 1e-6 * expected,
+                (actual, expected) => actual == expected,
+            },
+            "Test case \"{:?} to {:?}\" has been failed",
+            case.0,
+            case.1
+        );
+    }
+
+    let cases = vec![("mm", Ok(Unit::Mm)), ("m", Ok(Unit::M)), ("inch", Err(()))];
+    for case in cases {
+        assert_eq!(
+            case.0.parse::<Unit>().map_err(drop),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.0
+        );
+    }
+}
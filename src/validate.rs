@@ -0,0 +1,293 @@
+use std::{collections::BTreeMap, collections::HashMap, ops::Range};
+
+use serde::Deserialize;
+use serde_json::value::RawValue;
+
+use crate::{Input, Object};
+
+/// Severity of the found problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Problem found in the input file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Byte range of the problem in the source text, if known.
+    pub span: Option<Range<usize>>,
+    /// Byte range of the related location, e.g. the first object with the same name.
+    pub related: Option<Range<usize>>,
+}
+
+impl Diagnostic {
+    fn error(message: impl Into<String>, span: Option<Range<usize>>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+            related: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>, span: Option<Range<usize>>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(message, span)
+        }
+    }
+}
+
+pub(crate) const OBJECT_FIELDS: &[&str] = &[
+    "name",
+    "width",
+    "height",
+    "x",
+    "y",
+    "from",
+    "to",
+    "layer",
+    "z",
+    "rotation",
+    "kind",
+    "center",
+    "radius",
+    "vertices",
+    "properties",
+];
+
+#[derive(Deserialize)]
+struct RawInput<'a> {
+    #[serde(borrow)]
+    objects: Vec<&'a RawValue>,
+}
+
+/// Returns the byte offset of the 1-based line and column reported by `serde_json`.
+fn offset_of(text: &str, line: usize, column: usize) -> usize {
+    let line_start = text
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum::<usize>();
+    (line_start + column.saturating_sub(1)).min(text.len())
+}
+
+/// Returns the error message without the position, which is reported by the span.
+fn message_of(err: &serde_json::Error) -> String {
+    let message = err.to_string();
+    match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_owned(),
+        None => message,
+    }
+}
+
+/// Returns the byte range of the slice borrowed from the text.
+fn span_of(text: &str, part: &str) -> Range<usize> {
+    let start = part.as_ptr() as usize - text.as_ptr() as usize;
+    start..start + part.len()
+}
+
+/// Checks the JSON input text and reports every found problem with its location: syntax and
+/// schema errors, invalid object dimensions and duplicate names.
+pub fn validate_json(text: &str) -> Vec<Diagnostic> {
+    let raw: RawInput = match serde_json::from_str(text) {
+        Ok(raw) => raw,
+        Err(err) => {
+            let offset = offset_of(text, err.line(), err.column());
+            return vec![Diagnostic::error(message_of(&err), Some(offset..offset))];
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    let mut names: HashMap<String, Range<usize>> = HashMap::new();
+    for raw_object in raw.objects {
+        let object_span = span_of(text, raw_object.get());
+        let fields: BTreeMap<&str, &RawValue> = match serde_json::from_str(raw_object.get()) {
+            Ok(fields) => fields,
+            Err(_) => {
+                diagnostics.push(Diagnostic::error("Object expected", Some(object_span)));
+                continue;
+            }
+        };
+        let field_span = |name: &str| {
+            fields
+                .get(name)
+                .map(|value| span_of(text, value.get()))
+                .or_else(|| Some(object_span.clone()))
+        };
+
+        for name in fields.keys().filter(|name| !OBJECT_FIELDS.contains(name)) {
+            diagnostics.push(Diagnostic::warning(
+                format!("Unknown field \"{}\" is ignored", name),
+                field_span(name),
+            ));
+        }
+
+        let object: Object = match serde_json::from_str(raw_object.get()) {
+            Ok(object) => object,
+            Err(err) => {
+                let offset =
+                    object_span.start + offset_of(raw_object.get(), err.line(), err.column());
+                let span = OBJECT_FIELDS
+                    .iter()
+                    .filter_map(|name| field_span(name))
+                    .find(|span| span.start <= offset && offset <= span.end)
+                    .unwrap_or(offset..offset);
+                diagnostics.push(Diagnostic::error(message_of(&err), Some(span)));
+                continue;
+            }
+        };
+
+        for problem in object_problems(&object) {
+            diagnostics.push(Diagnostic {
+                span: field_span(problem.field),
+                ..problem.diagnostic
+            });
+        }
+
+        let name_span = field_span("name").unwrap();
+        if let Some(first) = names.get(&object.name) {
+            diagnostics.push(Diagnostic {
+                related: Some(first.clone()),
+                ..Diagnostic::error(
+                    format!("Duplicate object name \"{}\"", object.name),
+                    Some(name_span),
+                )
+            });
+        } else {
+            names.insert(object.name, name_span);
+        }
+    }
+    diagnostics
+}
+
+/// Checks the already parsed input, the found problems have no location.
+pub fn validate_input(input: &Input) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut names = HashMap::new();
+    for (idx, object) in input.objects.iter().enumerate() {
+        diagnostics.extend(object_problems(object).map(|problem| Diagnostic {
+            message: format!("Object #{}: {}", idx, problem.diagnostic.message),
+            ..problem.diagnostic
+        }));
+        match names.get(&object.name) {
+            Some(first) => diagnostics.push(Diagnostic::error(
+                format!(
+                    "Object #{}: duplicate object name \"{}\", first used by object #{}",
+                    idx, object.name, first
+                ),
+                None,
+            )),
+            None => {
+                names.insert(&object.name, idx);
+            }
+        }
+    }
+    diagnostics
+}
+
+struct ObjectProblem {
+    field: &'static str,
+    diagnostic: Diagnostic,
+}
+
+fn object_problems(object: &Object) -> impl Iterator<Item = ObjectProblem> + '_ {
+    let values = [
+        ("width", object.width),
+        ("height", object.height),
+        ("x", object.x),
+        ("y", object.y),
+    ];
+    let rotation = object.rotation.map(|rotation| ("rotation", rotation));
+    IntoIterator::into_iter(values)
+        .chain(rotation)
+        .filter_map(|(field, value)| {
+            let message = if !value.is_finite() {
+                format!("\"{}\" must be a finite number", field)
+            } else if matches!(field, "width" | "height") && value < 0.0 {
+                format!("\"{}\" cannot be negative", field)
+            } else {
+                return None;
+            };
+            Some(ObjectProblem {
+                field,
+                diagnostic: Diagnostic::error(message, None),
+            })
+        })
+}
+
+#[test]
+fn test_validate_json() {
+    let text = r#"{
+    "objects": [
+        { "name": "A", "width": -2.0, "height": 3.0, "x": 1.0, "y": 1e39 },
+        { "name": "B", "width": 1.0, "height": "1", "x": 0.0, "y": 0.0 },
+        { "name": "A", "width": 1.0, "height": 1.0, "x": 0.0, "y": 0.0, "depth": 1 }
+    ]
+}"#;
+    let snippet = |span: &Option<Range<usize>>| &text[span.clone().unwrap()];
+
+    let diagnostics = validate_json(text);
+    let messages = diagnostics
+        .iter()
+        .map(|d| (d.severity, d.message.as_str(), snippet(&d.span)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages[0],
+        (Severity::Error, "\"width\" cannot be negative", "-2.0")
+    );
+    assert_eq!(
+        messages[1],
+        (Severity::Error, "\"y\" must be a finite number", "1e39")
+    );
+    assert_eq!(
+        messages[2],
+        (
+            Severity::Error,
+            "invalid type: string \"1\", expected f32",
+            "\"1\""
+        )
+    );
+    assert_eq!(
+        messages[3],
+        (Severity::Warning, "Unknown field \"depth\" is ignored", "1")
+    );
+    assert_eq!(
+        messages[4],
+        (Severity::Error, "Duplicate object name \"A\"", "\"A\"")
+    );
+    assert_eq!(snippet(&diagnostics[4].related), "\"A\"");
+    assert_eq!(diagnostics[4].related.as_ref().unwrap().start, 37);
+    assert_eq!(messages.len(), 5);
+
+    let text = "{\n  \"objects\": [\n    { \"name\": }\n  ]\n}";
+    let diagnostics = validate_json(text);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].span, Some(31..31));
+    assert_eq!(&text[31..32], "}");
+
+    assert!(validate_json(&std::fs::read_to_string("objects/test.json").unwrap()).is_empty());
+}
+
+#[test]
+fn test_validate_input() {
+    let mut input = Input::from_path("objects/test.json").unwrap();
+    assert!(validate_input(&input).is_empty());
+
+    input.objects[1].width = f32::NAN;
+    input.objects[2].name = input.objects[0].name.clone();
+    let messages = validate_input(&input)
+        .into_iter()
+        .map(|d| d.message)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "Object #1: \"width\" must be a finite number".to_owned(),
+            "Object #2: duplicate object name \"A\", first used by object #0".to_owned(),
+        ]
+    );
+}
@@ -0,0 +1,165 @@
+use std::io::Read;
+
+use roxmltree::{Document, Node};
+use serde_json::json;
+
+use crate::{decompress, Error, Input, Object};
+
+// Returns the text of the child element with the given tag.
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name(tag))
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+impl Input {
+    /// Parses the Pascal VOC annotation file. Every `<object>` becomes an object named by its
+    /// index with the `<bndbox>` corners as the rect and the class `<name>` as the layer, the
+    /// `difficult` and `truncated` flags are kept as the properties.
+    pub fn from_voc_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let mut reader = decompress::decompress(reader).map_err(|err| parse_error(err.into()))?;
+        let mut text = String::new();
+        reader
+            .read_to_string(&mut text)
+            .map_err(|err| parse_error(err.into()))?;
+        let document = Document::parse(&text).map_err(|err| parse_error(err.into()))?;
+        let root = document.root_element();
+        if !root.has_tag_name("annotation") {
+            return Err(parse_error(anyhow::anyhow!(
+                "Expected the <annotation> root element, got <{}>",
+                root.tag_name().name()
+            )));
+        }
+
+        let objects = root
+            .children()
+            .filter(|node| node.has_tag_name("object"))
+            .enumerate()
+            .map(|(idx, node)| {
+                let name = idx.to_string();
+                let bndbox = node
+                    .children()
+                    .find(|child| child.has_tag_name("bndbox"))
+                    .ok_or_else(|| {
+                        parse_error(anyhow::anyhow!("The object {} has no <bndbox>", name))
+                    })?;
+                let coordinate = |tag: &str| {
+                    child_text(bndbox, tag)
+                        .and_then(|text| text.parse::<f32>().ok())
+                        .filter(|value| value.is_finite())
+                        .ok_or_else(|| {
+                            parse_error(anyhow::anyhow!(
+                                "The object {} has no numeric <{}>",
+                                name,
+                                tag
+                            ))
+                        })
+                };
+                let (xmin, ymin) = (coordinate("xmin")?, coordinate("ymin")?);
+                let (xmax, ymax) = (coordinate("xmax")?, coordinate("ymax")?);
+                if xmax < xmin || ymax < ymin {
+                    return Err(parse_error(anyhow::anyhow!(
+                        "The <bndbox> of the object {} has the max corner before the min one",
+                        name
+                    )));
+                }
+
+                let properties = ["difficult", "truncated"]
+                    .iter()
+                    .filter_map(|&flag| {
+                        let value = child_text(node, flag)?.parse::<i64>().ok()?;
+                        Some(json!({ "name": flag, "value": value }))
+                    })
+                    .collect();
+                Ok(Object {
+                    name,
+                    width: xmax - xmin,
+                    height: ymax - ymin,
+                    x: xmin,
+                    y: ymin,
+                    layer: child_text(node, "name").map(str::to_owned),
+                    z: None,
+                    rotation: None,
+                    shape: None,
+                    properties,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { objects })
+    }
+}
+
+#[test]
+fn test_voc() {
+    let annotation = r#"<annotation>
+        <filename>000001.jpg</filename>
+        <size><width>353</width><height>500</height><depth>3</depth></size>
+        <object>
+            <name>dog</name>
+            <truncated>1</truncated>
+            <difficult>0</difficult>
+            <bndbox><xmin>48</xmin><ymin>240</ymin><xmax>195</xmax><ymax>371</ymax></bndbox>
+        </object>
+        <object>
+            <name>person</name>
+            <bndbox><xmin>8.5</xmin><ymin>12</ymin><xmax>352</xmax><ymax>498</ymax></bndbox>
+        </object>
+    </annotation>"#;
+    let input = Input::from_voc_reader(annotation.as_bytes()).unwrap();
+    let rects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                x.layer.as_deref(),
+                [x.x, x.y, x.width, x.height],
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rects,
+        vec![
+            ("0", Some("dog"), [48.0, 240.0, 147.0, 131.0]),
+            ("1", Some("person"), [8.5, 12.0, 343.5, 486.0]),
+        ]
+    );
+    assert_eq!(
+        input.objects[0].properties,
+        vec![
+            json!({ "name": "difficult", "value": 0 }),
+            json!({ "name": "truncated", "value": 1 }),
+        ]
+    );
+
+    let cases = vec![
+        ("<scene/>", "<annotation> root", "wrong root"),
+        (
+            "<annotation><object><name>a</name></object></annotation>",
+            "has no <bndbox>",
+            "no bndbox",
+        ),
+        (
+            "<annotation><object><bndbox><xmin>1</xmin><ymin>1</ymin><xmax>x</xmax><ymax>2</ymax></bndbox></object></annotation>",
+            "no numeric <xmax>",
+            "not a number",
+        ),
+        (
+            "<annotation><object><bndbox><xmin>5</xmin><ymin>1</ymin><xmax>2</xmax><ymax>2</ymax></bndbox></object></annotation>",
+            "max corner before",
+            "inverted",
+        ),
+        ("<annotation>", "", "not an xml"),
+    ];
+    for case in cases {
+        let err = Input::from_voc_reader(case.0.as_bytes()).unwrap_err();
+        assert!(
+            err.to_string().contains(case.1),
+            "Test case \"{}\" has been failed: {}",
+            case.2,
+            err
+        );
+    }
+}
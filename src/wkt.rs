@@ -0,0 +1,447 @@
+use std::{
+    convert::TryInto,
+    io::{BufRead, BufReader, Read},
+};
+
+use crate::{decompress, AnyShape, Error, Input, Object, Output, Point2D, Polygon};
+
+const WKB_LINE_STRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+// The EWKB flags of PostGIS.
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_M: u32 = 0x4000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+fn geometry_error(reason: impl std::fmt::Display) -> Error {
+    Error::ParseError {
+        reason: anyhow::anyhow!("Invalid geometry: {}", reason),
+    }
+}
+
+// Returns the shape of the exterior ring, the closing vertex is dropped and the rectangular
+// rings are read as the rects.
+pub(crate) fn ring_shape(mut vertices: Vec<Point2D>) -> Result<AnyShape, Error> {
+    if vertices.len() > 1 && vertices.first() == vertices.last() {
+        vertices.pop();
+    }
+    let polygon = Polygon::new(vertices);
+    if polygon.vertices.len() < 3 {
+        Err(geometry_error("the polygon has less than three vertices"))
+    } else if polygon.is_rect() {
+        Ok(AnyShape::Rect(crate::Shape::bounding_rect(&polygon)))
+    } else {
+        Ok(AnyShape::Polygon(polygon))
+    }
+}
+
+fn line_shape(points: Vec<Point2D>) -> Result<AnyShape, Error> {
+    match points[..] {
+        [from, to] => Ok(AnyShape::Segment { from, to }),
+        _ => Err(geometry_error(
+            "only the line strings of two points are supported",
+        )),
+    }
+}
+
+// Parses the comma separated points, the coordinates besides x and y are ignored.
+fn wkt_points(text: &str) -> Result<Vec<Point2D>, Error> {
+    text.split(',')
+        .map(|point| {
+            let coordinates = point
+                .split_whitespace()
+                .map(|value| value.parse::<f32>().map_err(geometry_error))
+                .collect::<Result<Vec<_>, _>>()?;
+            match coordinates[..] {
+                [x, y, ..] => Ok(Point2D::new(x, y)),
+                _ => Err(geometry_error(format!(
+                    "expected \"x y\", got \"{}\"",
+                    point
+                ))),
+            }
+        })
+        .collect()
+}
+
+// Returns the contents of the outer parentheses.
+fn unwrap_parens(text: &str) -> Result<&str, Error> {
+    text.trim()
+        .strip_prefix('(')
+        .and_then(|text| text.strip_suffix(')'))
+        .ok_or_else(|| geometry_error(format!("expected \"(...)\", got \"{}\"", text)))
+}
+
+// Reads the WKB numbers in the byte order of the geometry.
+struct WkbReader<'a> {
+    bytes: &'a [u8],
+    little_endian: bool,
+}
+
+impl WkbReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        if self.bytes.len() < N {
+            return Err(geometry_error("the WKB is truncated"));
+        }
+        let (head, tail) = self.bytes.split_at(N);
+        self.bytes = tail;
+        Ok(head.try_into().expect("the head has N bytes"))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let bytes = self.take::<4>()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn f64(&mut self) -> Result<f64, Error> {
+        let bytes = self.take::<8>()?;
+        Ok(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn points(&mut self, dimensions: usize) -> Result<Vec<Point2D>, Error> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(dimensions * 8) > self.bytes.len() {
+            return Err(geometry_error("the WKB is truncated"));
+        }
+        (0..count)
+            .map(|_| {
+                let point = Point2D::new(self.f64()? as f32, self.f64()? as f32);
+                for _ in 2..dimensions {
+                    self.f64()?;
+                }
+                Ok(point)
+            })
+            .collect()
+    }
+}
+
+impl AnyShape {
+    /// Parses the `POLYGON` or the two-point `LINESTRING` WKT, e.g. the PostGIS `ST_AsText`.
+    /// The holes, the `SRID=...;` prefix and the coordinates besides x and y are ignored,
+    /// the rectangular polygons are read as the rects.
+    pub fn from_wkt(text: &str) -> Result<AnyShape, Error> {
+        let text = text.trim();
+        let text = match text.split_once(';') {
+            Some((srid, rest)) if srid.trim_start().to_ascii_uppercase().starts_with("SRID=") => {
+                rest.trim()
+            }
+            _ => text,
+        };
+        let open = text
+            .find('(')
+            .ok_or_else(|| geometry_error(format!("expected a polygon, got \"{}\"", text)))?;
+        let (tag, body) = text.split_at(open);
+        let kind = tag.split_whitespace().next().unwrap_or_default();
+        match kind.to_ascii_uppercase().as_str() {
+            "POLYGON" => {
+                let rings = unwrap_parens(body)?;
+                let exterior = rings
+                    .split(')')
+                    .next()
+                    .and_then(|ring| ring.trim().strip_prefix('('))
+                    .ok_or_else(|| geometry_error("expected the exterior ring"))?;
+                ring_shape(wkt_points(exterior)?)
+            }
+            "LINESTRING" => line_shape(wkt_points(unwrap_parens(body)?)?),
+            other => Err(geometry_error(format!(
+                "unsupported geometry type \"{}\"",
+                other
+            ))),
+        }
+    }
+
+    /// Writes the shape as WKT, the rects and the circles are written as the polygons and the
+    /// segments as the line strings.
+    pub fn to_wkt(&self) -> String {
+        let points = |points: &mut dyn Iterator<Item = &Point2D>| {
+            points
+                .map(|point| format!("{} {}", point.x, point.y))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Self::Segment { from, to } => {
+                format!("LINESTRING ({})", points(&mut [*from, *to].iter()))
+            }
+            shape => {
+                let outline = shape.outline();
+                let ring = &mut outline.vertices.iter().chain(outline.vertices.first());
+                format!("POLYGON (({}))", points(ring))
+            }
+        }
+    }
+
+    /// Parses the `POLYGON` or the two-point `LINESTRING` WKB in any byte order, e.g. the
+    /// PostGIS `ST_AsBinary`. The EWKB of PostGIS is supported too, the SRID and the
+    /// coordinates besides x and y are ignored.
+    pub fn from_wkb(bytes: &[u8]) -> Result<AnyShape, Error> {
+        let mut reader = WkbReader {
+            bytes,
+            little_endian: true,
+        };
+        reader.little_endian = match reader.take::<1>()? {
+            [0] => false,
+            [1] => true,
+            [other] => return Err(geometry_error(format!("unknown byte order {}", other))),
+        };
+        let code = reader.u32()?;
+        if code & EWKB_SRID != 0 {
+            reader.u32()?;
+        }
+        // The ISO WKB adds thousands to the type code for the extra dimensions.
+        let (kind, iso_dimensions) = match (code & 0xFFFF) / 1000 {
+            0 => (code & 0xFFFF, 2),
+            1 | 2 => ((code & 0xFFFF) % 1000, 3),
+            _ => ((code & 0xFFFF) % 1000, 4),
+        };
+        let ewkb_dimensions = 2 + (code & EWKB_Z != 0) as usize + (code & EWKB_M != 0) as usize;
+        let dimensions = iso_dimensions.max(ewkb_dimensions);
+        match kind {
+            WKB_POLYGON => {
+                let rings = reader.u32()?;
+                if rings == 0 {
+                    return Err(geometry_error("the polygon is empty"));
+                }
+                ring_shape(reader.points(dimensions)?)
+            }
+            WKB_LINE_STRING => line_shape(reader.points(dimensions)?),
+            other => Err(geometry_error(format!(
+                "unsupported WKB geometry type {}",
+                other
+            ))),
+        }
+    }
+
+    /// Writes the shape as the little endian WKB like [`AnyShape::to_wkt`].
+    pub fn to_wkb(&self) -> Vec<u8> {
+        let (kind, points) = match self {
+            Self::Segment { from, to } => (WKB_LINE_STRING, vec![*from, *to]),
+            shape => {
+                let mut vertices = shape.outline().vertices;
+                vertices.extend(vertices.first().copied());
+                (WKB_POLYGON, vertices)
+            }
+        };
+        let mut bytes = vec![1];
+        bytes.extend(kind.to_le_bytes());
+        if kind == WKB_POLYGON {
+            bytes.extend(1_u32.to_le_bytes());
+        }
+        bytes.extend((points.len() as u32).to_le_bytes());
+        for point in points {
+            bytes.extend((point.x as f64).to_le_bytes());
+            bytes.extend((point.y as f64).to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// Parses the WKT or the hex encoded WKB geometry of a table cell, the PostGIS exports
+/// write the geometry columns as the hex EWKB.
+pub(crate) fn parse_geometry(text: &str) -> Result<AnyShape, Error> {
+    let text = text.trim();
+    if text.len().is_multiple_of(2) && text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        let bytes = (0..text.len())
+            .step_by(2)
+            .map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).map_err(geometry_error))
+            .collect::<Result<Vec<_>, _>>()?;
+        AnyShape::from_wkb(&bytes)
+    } else {
+        AnyShape::from_wkt(text)
+    }
+}
+
+impl Input {
+    /// Parses one WKT geometry per line, optionally followed by the tab separated name like
+    /// the written ones. The objects without the names are named by their numbers starting
+    /// from zero. The empty lines are skipped.
+    pub fn from_wkt_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let reader = decompress::decompress(reader).map_err(|err| parse_error(err.into()))?;
+        let mut objects = Vec::new();
+        for (idx, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|err| parse_error(err.into()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut columns = line.split('\t');
+            let geometry = columns.next().unwrap_or_default();
+            let shape = AnyShape::from_wkt(geometry)
+                .map_err(|err| parse_error(anyhow::anyhow!("{} on line {}", err, idx + 1)))?;
+            let name = match columns.next() {
+                Some(name) if !name.is_empty() => name.to_owned(),
+                _ => objects.len().to_string(),
+            };
+            objects.push(Object::from_shape(name, shape));
+        }
+        Ok(Self { objects })
+    }
+
+    /// Writes the exact shapes of the objects as WKT with their names, one per line.
+    pub(crate) fn to_wkt(&self) -> Vec<u8> {
+        wkt_lines(
+            self.objects
+                .iter()
+                .map(|object| (object.area().exact_shape(), vec![object.name.as_str()])),
+        )
+    }
+}
+
+impl Output {
+    /// Writes the rects of the intersections as WKT with the names of both objects, one per
+    /// line.
+    pub(crate) fn intersections_to_wkt(&self) -> Vec<u8> {
+        wkt_lines(self.intersections.iter().map(|intersection| {
+            (
+                AnyShape::Rect(intersection.area),
+                vec![intersection.names.0.as_str(), intersection.names.1.as_str()],
+            )
+        }))
+    }
+
+    /// Writes the exact shapes of the areas as WKT with their names, one per line.
+    pub(crate) fn areas_to_wkt(&self) -> Vec<u8> {
+        wkt_lines(
+            self.areas
+                .iter()
+                .map(|area| (area.exact_shape(), vec![area.name.as_str()])),
+        )
+    }
+}
+
+// Writes every shape with its tab separated names, the tabs and the line breaks of the names
+// are replaced by the spaces to keep the columns.
+fn wkt_lines<'a>(rows: impl Iterator<Item = (AnyShape, Vec<&'a str>)>) -> Vec<u8> {
+    let mut text = String::new();
+    for (shape, names) in rows {
+        text += &shape.to_wkt();
+        for name in names {
+            text.push('\t');
+            text.extend(name.chars().map(|c| match c {
+                '\t' | '\n' | '\r' => ' ',
+                c => c,
+            }));
+        }
+        text.push('\n');
+    }
+    text.into_bytes()
+}
+
+#[test]
+fn test_wkt() {
+    use crate::BoundingRect;
+
+    let rect = AnyShape::Rect(BoundingRect::from_points(
+        Point2D::new(0.0, 0.0),
+        Point2D::new(2.0, 1.0),
+    ));
+    let triangle = AnyShape::Polygon(Polygon::new(vec![
+        Point2D::new(0.0, 0.0),
+        Point2D::new(4.0, 0.0),
+        Point2D::new(2.0, 3.5),
+    ]));
+    let segment = AnyShape::Segment {
+        from: Point2D::new(1.0, 1.0),
+        to: Point2D::new(2.0, 3.0),
+    };
+    let cases = vec![
+        ("POLYGON ((0 0, 2 0, 2 1, 0 1, 0 0))", &rect, "rect"),
+        (
+            "SRID=4326;polygon((0 0 5,4 0 5,2 3.5 5,0 0 5),(1 1,2 1,2 2,1 1))",
+            &triangle,
+            "ewkt with holes",
+        ),
+        ("LINESTRING (1 1, 2 3)", &segment, "segment"),
+    ];
+    for case in cases {
+        assert_eq!(
+            &AnyShape::from_wkt(case.0).unwrap(),
+            case.1,
+            "Test case \"{}\" has been failed",
+            case.2
+        );
+        let written = case.1.to_wkt();
+        assert_eq!(&AnyShape::from_wkt(&written).unwrap(), case.1);
+        assert_eq!(&AnyShape::from_wkb(&case.1.to_wkb()).unwrap(), case.1);
+    }
+    assert_eq!(rect.to_wkt(), "POLYGON ((0 0, 2 0, 2 1, 0 1, 0 0))");
+
+    // The hex EWKB of `SRID=4326;LINESTRING(1 1,2 3)` written by PostGIS.
+    let ewkb = "0102000020E610000002000000000000000000F03F000000000000F03F00000000000000400000000000000840";
+    assert_eq!(parse_geometry(ewkb).unwrap(), segment);
+    let big_endian =
+        "0000000002000000023FF00000000000003FF000000000000040000000000000004008000000000000";
+    assert_eq!(parse_geometry(big_endian).unwrap(), segment);
+
+    // The geometry column replaces the rect columns.
+    let csv = format!(
+        "name,geometry,score\nA,\"POLYGON ((0 0, 4 0, 2 3.5, 0 0))\",1\nB,{},2\n",
+        ewkb
+    );
+    let input = Input::from_csv_reader(csv.as_bytes(), &Default::default()).unwrap();
+    let a = &input.objects[0];
+    assert_eq!(
+        (a.shape.as_ref(), [a.x, a.y, a.width, a.height]),
+        (Some(&triangle), [0.0, 0.0, 4.0, 3.5])
+    );
+    assert_eq!(input.objects[1].shape, Some(segment));
+    let written = input
+        .to_vec_as(crate::OutputFormat::Csv, Default::default())
+        .unwrap();
+    assert_eq!(
+        Input::from_csv_reader(written.as_slice(), &Default::default()).unwrap(),
+        input
+    );
+
+    // The names are written after the geometries and read back.
+    let wkt = "POLYGON ((0 0, 2 0, 2 1, 0 1, 0 0))\tA\n\nLINESTRING (1 1, 2 3)\n";
+    let input = Input::from_wkt_reader(wkt.as_bytes()).unwrap();
+    let names = input
+        .objects
+        .iter()
+        .map(|object| object.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["A", "1"]);
+    let mut named = input.clone();
+    named.objects[1].name = "B\tline".to_owned();
+    assert_eq!(
+        String::from_utf8(named.to_wkt()).unwrap(),
+        "POLYGON ((0 0, 2 0, 2 1, 0 1, 0 0))\tA\nLINESTRING (1 1, 2 3)\tB line\n"
+    );
+    assert_eq!(
+        Input::from_wkt_reader(input.to_wkt().as_slice()).unwrap(),
+        input
+    );
+    let scene = crate::SceneBuilder::new()
+        .object("A", 0.0, 0.0, 2.0, 2.0)
+        .object("B", 1.0, 1.0, 2.0, 2.0)
+        .build();
+    let output = crate::analyze(scene, Default::default());
+    assert_eq!(
+        String::from_utf8(output.intersections_to_wkt()).unwrap(),
+        "POLYGON ((1 1, 2 1, 2 2, 1 2, 1 1))\tA\tB\n"
+    );
+
+    let errors = vec![
+        ("POINT (1 2)", "unsupported geometry type \"POINT\""),
+        ("POLYGON ((0 0, 1 1))", "less than three vertices"),
+        ("LINESTRING (0 0, 1 1, 2 0)", "two points"),
+        ("POLYGON ((0 0, 1 x, 1 1, 0 0))", "invalid float"),
+        ("0102000000", "truncated"),
+    ];
+    for (text, expected) in errors {
+        let err = parse_geometry(text).unwrap_err().to_string();
+        assert!(
+            err.contains(expected),
+            "Test case \"{}\" has been failed: {}",
+            text,
+            err
+        );
+    }
+}
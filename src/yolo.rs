@@ -0,0 +1,322 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    str::FromStr,
+};
+
+use serde_json::json;
+
+use crate::{decompress, Error, Input, Object};
+
+/// Size of the image the YOLO labels are normalized by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// The unit size keeps the normalized coordinates.
+impl Default for ImageSize {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+impl ImageSize {
+    /// Converts the normalized center and size of the label into the origin and size of the
+    /// rect in the image coordinates.
+    pub fn denormalize(&self, [cx, cy, w, h]: [f32; 4]) -> [f32; 4] {
+        let (width, height) = (w * self.width, h * self.height);
+        [
+            cx * self.width - width / 2.0,
+            cy * self.height - height / 2.0,
+            width,
+            height,
+        ]
+    }
+
+    /// Converts the origin and size of the rect in the image coordinates into the normalized
+    /// center and size of the label.
+    pub fn normalize(&self, [x, y, width, height]: [f32; 4]) -> [f32; 4] {
+        [
+            (x + width / 2.0) / self.width,
+            (y + height / 2.0) / self.height,
+            width / self.width,
+            height / self.height,
+        ]
+    }
+
+    /// Reads the size from the header of the PNG or JPEG image, the pixels are not decoded.
+    pub fn from_image_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let mut reader = BufReader::new(reader);
+        let mut read = |count: usize| {
+            let mut bytes = vec![0; count];
+            reader
+                .read_exact(&mut bytes)
+                .map(|_| bytes)
+                .map_err(|err| parse_error(err.into()))
+        };
+        let be = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .fold(0_u32, |value, &x| value << 8 | u32::from(x))
+        };
+        let size = |width: u32, height: u32| {
+            if width == 0 || height == 0 {
+                return Err(parse_error(anyhow::anyhow!("The image has no pixels")));
+            }
+            Ok(Self {
+                width: width as f32,
+                height: height as f32,
+            })
+        };
+
+        let signature = read(2)?;
+        if signature == b"\x89P" {
+            // The signature is followed by the `IHDR` chunk starting with the width and height.
+            let header = read(22)?;
+            if &header[..6] != b"NG\r\n\x1a\n" || &header[10..14] != b"IHDR" {
+                return Err(parse_error(anyhow::anyhow!("Invalid PNG header")));
+            }
+            return size(be(&header[14..18]), be(&header[18..22]));
+        }
+        if signature != b"\xff\xd8" {
+            return Err(parse_error(anyhow::anyhow!(
+                "Unknown image format, expected PNG or JPEG"
+            )));
+        }
+        // The JPEG segments are skipped up to the start of the frame holding the size.
+        loop {
+            if read(1)? != [0xff] {
+                return Err(parse_error(anyhow::anyhow!("Invalid JPEG segment")));
+            }
+            // The marker may be preceded by the fill bytes.
+            let mut marker = read(1)?[0];
+            while marker == 0xff {
+                marker = read(1)?[0];
+            }
+            // The segments without the length.
+            if matches!(marker, 0x01 | 0xd0..=0xd9) {
+                continue;
+            }
+            let length = be(&read(2)?) as usize;
+            if length < 2 {
+                return Err(parse_error(anyhow::anyhow!("Invalid JPEG segment")));
+            }
+            let segment = read(length - 2)?;
+            let is_frame = matches!(marker, 0xc0..=0xcf) && ![0xc4, 0xc8, 0xcc].contains(&marker);
+            if is_frame {
+                if segment.len() < 5 {
+                    return Err(parse_error(anyhow::anyhow!("Invalid JPEG frame")));
+                }
+                return size(be(&segment[3..5]), be(&segment[1..3]));
+            }
+        }
+    }
+}
+
+/// Parses the `WIDTHxHEIGHT` size, e.g. `640x480`.
+impl FromStr for ImageSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Expected \"WIDTHxHEIGHT\", got \"{}\"", s);
+        let (width, height) = s.split_once('x').ok_or_else(error)?;
+        let size = |value: &str| {
+            value
+                .trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite() && *value > 0.0)
+                .ok_or_else(error)
+        };
+        Ok(Self {
+            width: size(width)?,
+            height: size(height)?,
+        })
+    }
+}
+
+impl Input {
+    /// Parses the YOLO label file, one `class cx cy w h` line per object with the optional
+    /// score at the end. The center and the size are normalized by the image size. The
+    /// objects are named by their indices starting from zero, the empty lines are skipped and
+    /// not counted. They get the class id as the layer and the score as the `score` property.
+    pub fn from_yolo_reader<R: Read>(reader: R, size: ImageSize) -> Result<Self, Error> {
+        let parse_error = |reason: anyhow::Error| Error::ParseError { reason };
+        let reader = decompress::decompress(reader).map_err(|err| parse_error(err.into()))?;
+        let mut objects = Vec::new();
+        for (idx, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|err| parse_error(err.into()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid = || {
+                parse_error(anyhow::anyhow!(
+                    "Invalid YOLO label \"{}\" on line {}, expected \"class cx cy w h [score]\"",
+                    line,
+                    idx + 1
+                ))
+            };
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if !(5..=6).contains(&fields.len()) {
+                return Err(invalid());
+            }
+            let class = fields[0].parse::<u32>().map_err(|_| invalid())?;
+            let numbers = fields[1..5]
+                .iter()
+                .map(|field| field.parse::<f32>().map_err(|_| invalid()))
+                .collect::<Result<Vec<_>, _>>()?;
+            // The score is kept as it is written.
+            let score = fields
+                .get(5)
+                .map(|field| field.parse::<f64>().map_err(|_| invalid()))
+                .transpose()?;
+
+            let [x, y, width, height] =
+                size.denormalize([numbers[0], numbers[1], numbers[2], numbers[3]]);
+            let properties = score
+                .map(|score| json!({ "name": "score", "value": score }))
+                .into_iter()
+                .collect();
+            objects.push(Object {
+                name: objects.len().to_string(),
+                width,
+                height,
+                x,
+                y,
+                layer: Some(class.to_string()),
+                z: None,
+                rotation: None,
+                shape: None,
+                properties,
+            });
+        }
+        Ok(Self { objects })
+    }
+
+    /// Writes the objects as the YOLO label file normalized by the image size, the bounding
+    /// rects of the rotated and non-rectangular objects are written. The layers must be the
+    /// class ids, the numeric `score` property is written as the last column.
+    pub fn to_yolo_labels(&self, size: ImageSize) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::new();
+        for object in &self.objects {
+            let class = object
+                .layer
+                .as_deref()
+                .and_then(|layer| layer.parse::<u32>().ok())
+                .ok_or_else(|| Error::SerializeError {
+                    reason: anyhow::anyhow!(
+                        "The layer of the object \"{}\" is not a YOLO class id",
+                        object.name
+                    ),
+                })?;
+            let (from, to) = {
+                let area = object.area().area;
+                (area.top_left(), area.bottom_right())
+            };
+            let label = size.normalize([from.x, from.y, to.x - from.x, to.y - from.y]);
+            let mut line = class.to_string();
+            for value in label {
+                line.push(' ');
+                line.push_str(&value.to_string());
+            }
+            if let Some(score) = object
+                .property_values("score")
+                .find_map(serde_json::Value::as_f64)
+            {
+                line.push(' ');
+                line.push_str(&score.to_string());
+            }
+            line.push('\n');
+            bytes.extend(line.into_bytes());
+        }
+        Ok(bytes)
+    }
+}
+
+#[test]
+fn test_yolo_labels() {
+    let size = ImageSize::from_str("640x480").unwrap();
+    let labels = "0 0.5 0.5 0.25 0.5\n\n2 0.125 0.25 0.25 0.5 0.75\n";
+    let input = Input::from_yolo_reader(labels.as_bytes(), size).unwrap();
+    let rects = input
+        .objects
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                x.layer.as_deref(),
+                [x.x, x.y, x.width, x.height],
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rects,
+        vec![
+            ("0", Some("0"), [240.0, 120.0, 160.0, 240.0]),
+            ("1", Some("2"), [0.0, 0.0, 160.0, 240.0]),
+        ]
+    );
+    let written = String::from_utf8(input.to_yolo_labels(size).unwrap()).unwrap();
+    assert_eq!(written, "0 0.5 0.5 0.25 0.5\n2 0.125 0.25 0.25 0.5 0.75\n");
+
+    let cases = vec![
+        ("0 0.5 0.5 0.25", "on line 1", "missing field"),
+        ("cat 0.5 0.5 0.25 0.5", "Invalid YOLO label", "class name"),
+        (
+            "0 0.5 0.5 0.25 0.5\n1 0.5 x 0.25 0.5",
+            "on line 2",
+            "not a number",
+        ),
+    ];
+    for case in cases {
+        let err = Input::from_yolo_reader(case.0.as_bytes(), size).unwrap_err();
+        assert!(
+            err.to_string().contains(case.1),
+            "Test case \"{}\" has been failed: {}",
+            case.2,
+            err
+        );
+    }
+
+    let mut named = input;
+    named.objects[0].layer = Some("cat".to_owned());
+    assert!(named.to_yolo_labels(size).is_err());
+    assert!(ImageSize::from_str("640").is_err() && ImageSize::from_str("0x480").is_err());
+}
+
+#[test]
+fn test_image_size() {
+    let png = [
+        b"\x89PNG\r\n\x1a\n".as_ref(),
+        &[0, 0, 0, 13],
+        b"IHDR",
+        &[0, 0, 2, 128, 0, 0, 1, 224],
+        &[8, 6, 0, 0, 0],
+    ]
+    .concat();
+    let jpeg = [
+        [0xff, 0xd8].as_ref(),
+        // The APP0 segment before the frame.
+        &[0xff, 0xe0, 0, 4, 0, 0],
+        &[0xff, 0xff, 0xc0, 0, 8, 8, 1, 224, 2, 128, 3],
+    ]
+    .concat();
+    let cases = vec![
+        (png.clone(), Some((640.0, 480.0)), "png"),
+        (jpeg.clone(), Some((640.0, 480.0)), "jpeg"),
+        (png[..20].to_vec(), None, "truncated png"),
+        (jpeg[..8].to_vec(), None, "truncated jpeg"),
+        (b"GIF89a".to_vec(), None, "gif"),
+    ];
+    for case in cases {
+        let size = ImageSize::from_image_reader(case.0.as_slice())
+            .ok()
+            .map(|size| (size.width, size.height));
+        assert_eq!(size, case.1, "Test case \"{}\" has been failed", case.2);
+    }
+}